@@ -28,6 +28,7 @@ extern crate simplelog;
 mod banned_set;
 mod jwt;
 mod password;
+mod refresh_token;
 mod secret;
 
 pub use banned_set::BannedSet;
@@ -39,6 +40,10 @@ pub use password::{
     hash_password,
     verify_hash,
 };
+pub use refresh_token::{
+    generate_refresh_secret,
+    hash_refresh_secret,
+};
 pub use secret::Secret;
 
 #[cfg(test)]
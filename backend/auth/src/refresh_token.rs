@@ -0,0 +1,36 @@
+//! Refresh tokens let a client get new JWTs without logging in again.
+//!
+//! Unlike JWTs, they are checked against the database every time they are used, so they can be revoked.
+//! Only a hash of each token is stored. Because the tokens are long and random, a fast hash is enough to protect them.
+
+use crypto::{
+    digest::Digest,
+    sha2::Sha256,
+};
+use rand::{
+    OsRng,
+    Rng,
+};
+
+/// The number of random bytes in a refresh token.
+const REFRESH_SECRET_BYTES: usize = 32;
+
+/// Generates the random part of a refresh token, hex encoded.
+pub fn generate_refresh_secret() -> String {
+    let mut bytes = [0u8; REFRESH_SECRET_BYTES];
+    OsRng::new()
+        .expect("The OS random number generator should be available")
+        .fill_bytes(&mut bytes);
+    encode_hex(&bytes)
+}
+
+/// Hashes the random part of a refresh token, so it can be stored and looked up.
+pub fn hash_refresh_secret(refresh_secret: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.input_str(refresh_secret);
+    hasher.result_str()
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
@@ -65,3 +65,12 @@ fn jwt_tampering_detected() {
         panic!("Should not be able to decode this modified jwt.");
     }
 }
+
+#[test]
+fn refresh_secret() {
+    let refresh_secret: String = generate_refresh_secret();
+    assert_eq!(refresh_secret.len(), 64);
+    assert_ne!(refresh_secret, generate_refresh_secret());
+    assert_eq!(hash_refresh_secret(&refresh_secret), hash_refresh_secret(&refresh_secret));
+    assert_ne!(hash_refresh_secret(&refresh_secret), refresh_secret);
+}
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE reputation_thresholds;
+ALTER TABLE users DROP COLUMN reputation;
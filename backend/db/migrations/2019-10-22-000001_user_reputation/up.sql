@@ -0,0 +1,22 @@
+-- Reputation is kept on the user row and adjusted whenever a vote on one of their posts changes.
+ALTER TABLE users ADD COLUMN reputation INTEGER NOT NULL DEFAULT 0;
+
+-- Credit the votes that were cast before reputation was tracked.
+UPDATE users SET reputation =
+    10 * (
+        SELECT COUNT(*) FROM post_upvotes
+        INNER JOIN posts ON posts.uuid = post_upvotes.post_uuid
+        WHERE posts.author_uuid = users.uuid AND post_upvotes.user_uuid != users.uuid
+    )
+    - 2 * (
+        SELECT COUNT(*) FROM post_downvotes
+        INNER JOIN posts ON posts.uuid = post_downvotes.post_uuid
+        WHERE posts.author_uuid = users.uuid AND post_downvotes.user_uuid != users.uuid
+    );
+
+-- Minimum reputation required to perform a given action.
+CREATE TABLE reputation_thresholds (
+    uuid UUID PRIMARY KEY NOT NULL Default gen_random_uuid(),
+    action INTEGER UNIQUE NOT NULL,
+    minimum_reputation INTEGER NOT NULL
+);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE poll_votes;
+DROP TABLE poll_options;
+DROP TABLE polls;
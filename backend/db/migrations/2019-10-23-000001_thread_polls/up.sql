@@ -0,0 +1,24 @@
+CREATE TABLE polls (
+    uuid UUID PRIMARY KEY NOT NULL Default gen_random_uuid(),
+    -- A thread may have at most one poll.
+    thread_uuid UUID UNIQUE NOT NULL REFERENCES threads(uuid) ON DELETE CASCADE,
+    question VARCHAR NOT NULL,
+    multiple_choice BOOLEAN NOT NULL,
+    closes_at TIMESTAMP,
+    hide_results_until_closed BOOLEAN NOT NULL
+);
+
+CREATE TABLE poll_options (
+    uuid UUID PRIMARY KEY NOT NULL Default gen_random_uuid(),
+    poll_uuid UUID NOT NULL REFERENCES polls(uuid) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    content VARCHAR NOT NULL
+);
+
+CREATE TABLE poll_votes (
+    uuid UUID PRIMARY KEY NOT NULL Default gen_random_uuid(),
+    poll_uuid UUID NOT NULL REFERENCES polls(uuid) ON DELETE CASCADE,
+    option_uuid UUID NOT NULL REFERENCES poll_options(uuid) ON DELETE CASCADE,
+    user_uuid UUID NOT NULL REFERENCES users(uuid) ON DELETE CASCADE,
+    UNIQUE (option_uuid, user_uuid)
+);
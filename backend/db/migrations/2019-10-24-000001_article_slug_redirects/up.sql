@@ -0,0 +1,6 @@
+-- When an article's title changes, its previous slug is kept here so that old links still resolve.
+CREATE TABLE article_slug_redirects (
+    uuid UUID PRIMARY KEY NOT NULL Default gen_random_uuid(),
+    article_uuid UUID NOT NULL REFERENCES articles(uuid) ON DELETE CASCADE,
+    slug VARCHAR UNIQUE NOT NULL
+);
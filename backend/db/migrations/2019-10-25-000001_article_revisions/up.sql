@@ -0,0 +1,15 @@
+CREATE TABLE article_revisions (
+    uuid UUID PRIMARY KEY NOT NULL Default gen_random_uuid(),
+    article_uuid UUID NOT NULL REFERENCES articles(uuid) ON DELETE CASCADE,
+    editor_uuid UUID NOT NULL REFERENCES users(uuid) ON DELETE CASCADE,
+    revision_number INTEGER NOT NULL,
+    title VARCHAR NOT NULL,
+    body TEXT NOT NULL,
+    created_date TIMESTAMP NOT NULL,
+    UNIQUE (article_uuid, revision_number)
+);
+
+-- Existing articles start their history with their current content.
+INSERT INTO article_revisions (article_uuid, editor_uuid, revision_number, title, body, created_date)
+SELECT uuid, author_uuid, 1, title, body, NOW()
+FROM articles;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE article_tags;
+ALTER TABLE articles DROP COLUMN category_uuid;
+DROP TABLE categories;
@@ -0,0 +1,16 @@
+CREATE TABLE categories (
+    uuid UUID PRIMARY KEY NOT NULL Default gen_random_uuid(),
+    name VARCHAR UNIQUE NOT NULL
+);
+
+-- Deleting a category leaves its articles uncategorized.
+ALTER TABLE articles ADD COLUMN category_uuid UUID REFERENCES categories(uuid) ON DELETE SET NULL;
+
+CREATE TABLE article_tags (
+    uuid UUID PRIMARY KEY NOT NULL Default gen_random_uuid(),
+    article_uuid UUID NOT NULL REFERENCES articles(uuid) ON DELETE CASCADE,
+    tag VARCHAR NOT NULL,
+    UNIQUE (article_uuid, tag)
+);
+
+CREATE INDEX article_tags_tag_idx ON article_tags (tag);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX articles_comment_thread_uuid_idx;
+ALTER TABLE articles DROP COLUMN comment_thread_uuid;
@@ -0,0 +1,4 @@
+-- The forum thread where readers discuss the article. It is created when the article is first published.
+ALTER TABLE articles ADD COLUMN comment_thread_uuid UUID REFERENCES threads(uuid) ON DELETE SET NULL;
+-- Each thread belongs to at most one article.
+CREATE UNIQUE INDEX articles_comment_thread_uuid_idx ON articles (comment_thread_uuid);
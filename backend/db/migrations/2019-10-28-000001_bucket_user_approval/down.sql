@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX junction_bucket_users_bucket_user_idx;
+ALTER TABLE junction_bucket_users DROP COLUMN approved;
@@ -0,0 +1,20 @@
+-- Users must be approved by an owner of a bucket before they can participate in it.
+-- Everyone already in a bucket was let in before approval existed, so they start out approved.
+ALTER TABLE junction_bucket_users ADD COLUMN approved BOOLEAN NOT NULL DEFAULT TRUE;
+ALTER TABLE junction_bucket_users ALTER COLUMN approved SET DEFAULT FALSE;
+
+-- A user is in a bucket at most once.
+-- Existing duplicates are merged into one row that keeps the most access any of them had.
+UPDATE junction_bucket_users j
+SET owner = d.owner, approved = d.approved
+FROM (
+    SELECT bucket_uuid, user_uuid, bool_or(owner) AS owner, bool_or(approved) AS approved
+    FROM junction_bucket_users
+    GROUP BY bucket_uuid, user_uuid
+    HAVING count(*) > 1
+) d
+WHERE j.bucket_uuid = d.bucket_uuid AND j.user_uuid = d.user_uuid;
+DELETE FROM junction_bucket_users a
+USING junction_bucket_users b
+WHERE a.bucket_uuid = b.bucket_uuid AND a.user_uuid = b.user_uuid AND a.uuid > b.uuid;
+CREATE UNIQUE INDEX junction_bucket_users_bucket_user_idx ON junction_bucket_users (bucket_uuid, user_uuid);
@@ -0,0 +1,2 @@
+-- The question every participant of the bucket is currently looking at.
+ALTER TABLE buckets ADD COLUMN current_question_uuid UUID REFERENCES questions(uuid) ON DELETE SET NULL;
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE buckets DROP COLUMN turn_ends_at;
+ALTER TABLE buckets DROP COLUMN hot_seat_user_uuid;
+ALTER TABLE buckets DROP COLUMN turn_user_uuid;
+ALTER TABLE buckets DROP COLUMN turn_seconds;
+ALTER TABLE buckets DROP COLUMN turn_mode;
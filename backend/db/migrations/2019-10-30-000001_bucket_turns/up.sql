@@ -0,0 +1,10 @@
+-- How participants take turns in the bucket's session. 0 is free for all, 1 is round robin, and 2 is hot seat.
+ALTER TABLE buckets ADD COLUMN turn_mode INTEGER NOT NULL DEFAULT 0;
+-- How long each turn lasts. Turns are untimed if this is null.
+ALTER TABLE buckets ADD COLUMN turn_seconds INTEGER;
+-- The participant whose turn it is to draw.
+ALTER TABLE buckets ADD COLUMN turn_user_uuid UUID REFERENCES users(uuid) ON DELETE SET NULL;
+-- In hot seat mode, the participant chosen to answer the current question.
+ALTER TABLE buckets ADD COLUMN hot_seat_user_uuid UUID REFERENCES users(uuid) ON DELETE SET NULL;
+-- When the current turn is skipped if it hasn't finished.
+ALTER TABLE buckets ADD COLUMN turn_ends_at TIMESTAMP;
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE buckets DROP COLUMN deck_started_at;
+ALTER TABLE buckets DROP COLUMN draw_mode;
+DROP INDEX questions_bucket_last_drawn_at_idx;
+ALTER TABLE questions DROP COLUMN last_drawn_at;
+DROP TABLE question_draws;
@@ -0,0 +1,19 @@
+-- Every time a question is drawn in a bucket's session.
+CREATE TABLE question_draws (
+    uuid UUID PRIMARY KEY NOT NULL Default gen_random_uuid(),
+    bucket_uuid UUID NOT NULL REFERENCES buckets(uuid) ON DELETE CASCADE,
+    question_uuid UUID NOT NULL REFERENCES questions(uuid) ON DELETE CASCADE,
+    drawn_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE INDEX question_draws_bucket_drawn_at_idx ON question_draws (bucket_uuid, drawn_at);
+
+-- When the question was last drawn, kept alongside the question so draws don't need to search the history.
+ALTER TABLE questions ADD COLUMN last_drawn_at TIMESTAMP;
+
+CREATE INDEX questions_bucket_last_drawn_at_idx ON questions (bucket_uuid, on_floor, last_drawn_at NULLS FIRST);
+
+-- How questions are drawn. 0 favours questions that haven't been seen recently, and 1 is a shuffled deck.
+ALTER TABLE buckets ADD COLUMN draw_mode INTEGER NOT NULL DEFAULT 0;
+-- When the current deck was shuffled. Questions that haven't been drawn since then are still in the deck.
+ALTER TABLE buckets ADD COLUMN deck_started_at TIMESTAMP;
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX answers_question_uuid_idx;
+ALTER TABLE answers DROP COLUMN created_at;
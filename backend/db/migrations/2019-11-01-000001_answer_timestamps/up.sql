@@ -0,0 +1,5 @@
+-- When the answer was given. Answers that predate this column were given at an unknown time, so they are left null.
+ALTER TABLE answers ADD COLUMN created_at TIMESTAMP;
+ALTER TABLE answers ALTER COLUMN created_at SET DEFAULT now();
+
+CREATE INDEX answers_question_uuid_idx ON answers (question_uuid);
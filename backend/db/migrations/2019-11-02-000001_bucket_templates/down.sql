@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX buckets_template_idx;
+ALTER TABLE buckets DROP COLUMN is_template;
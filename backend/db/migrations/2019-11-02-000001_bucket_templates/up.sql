@@ -0,0 +1,4 @@
+-- Templates can be browsed and cloned by anyone, not just the bucket's owners.
+ALTER TABLE buckets ADD COLUMN is_template BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE INDEX buckets_template_idx ON buckets (bucket_name) WHERE is_template;
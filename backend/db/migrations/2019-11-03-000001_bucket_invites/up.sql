@@ -0,0 +1,16 @@
+-- Links that add whoever holds them to a bucket, without waiting for approval.
+CREATE TABLE bucket_invites (
+    uuid UUID PRIMARY KEY NOT NULL Default gen_random_uuid(),
+    bucket_uuid UUID NOT NULL REFERENCES buckets(uuid) ON DELETE CASCADE,
+    creator_uuid UUID NOT NULL REFERENCES users(uuid) ON DELETE CASCADE,
+    -- Only a hash of the random part of the invite's token is kept, so the table can't be used to join buckets.
+    token_hash VARCHAR NOT NULL,
+    -- The invite can be used any number of times if this is null.
+    max_uses INTEGER,
+    uses INTEGER NOT NULL DEFAULT 0,
+    -- The invite never expires if this is null.
+    expires_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE INDEX bucket_invites_bucket_uuid_idx ON bucket_invites (bucket_uuid);
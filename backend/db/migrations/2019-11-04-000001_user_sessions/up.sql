@@ -0,0 +1,13 @@
+-- Each login starts a session, which hands out short lived access tokens for as long as its refresh token is valid.
+CREATE TABLE user_sessions (
+    uuid UUID PRIMARY KEY NOT NULL Default gen_random_uuid(),
+    user_uuid UUID NOT NULL REFERENCES users(uuid) ON DELETE CASCADE,
+    -- Only a hash of the refresh token is kept, and it changes every time the token is used.
+    refresh_token_hash VARCHAR NOT NULL,
+    user_agent VARCHAR,
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    last_used_at TIMESTAMP NOT NULL DEFAULT now(),
+    expires_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX user_sessions_user_uuid_idx ON user_sessions (user_uuid);
@@ -0,0 +1,15 @@
+-- Bans are kept here so they survive restarts. A user has at most one ban at a time.
+CREATE TABLE user_bans (
+    uuid UUID PRIMARY KEY NOT NULL Default gen_random_uuid(),
+    user_uuid UUID NOT NULL UNIQUE REFERENCES users(uuid) ON DELETE CASCADE,
+    -- The admin who issued the ban. The ban stays in place if the admin's account is deleted.
+    admin_uuid UUID REFERENCES users(uuid) ON DELETE SET NULL,
+    reason VARCHAR NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    -- The ban is permanent if this is null.
+    expires_at TIMESTAMP
+);
+
+-- Users who were banned before bans were recorded stay banned.
+INSERT INTO user_bans (user_uuid, reason)
+SELECT uuid, 'Banned before ban reasons were recorded' FROM users WHERE banned;
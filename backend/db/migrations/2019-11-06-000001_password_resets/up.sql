@@ -0,0 +1,14 @@
+-- Password reset tokens are mailed here. Users who haven't set an email can't reset their password.
+ALTER TABLE users ADD COLUMN email VARCHAR;
+
+-- Each reset token can be used once before it expires.
+CREATE TABLE password_resets (
+    uuid UUID PRIMARY KEY NOT NULL Default gen_random_uuid(),
+    user_uuid UUID NOT NULL REFERENCES users(uuid) ON DELETE CASCADE,
+    -- Only a hash of the token is kept, so the table can't be used to reset passwords.
+    token_hash VARCHAR NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    expires_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX password_resets_user_uuid_idx ON password_resets (user_uuid);
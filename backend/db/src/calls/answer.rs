@@ -4,13 +4,23 @@ use crate::{
     schema::{
         self,
         answers,
+        junction_bucket_users,
+        questions,
     },
     user::User,
 };
 //use error::JoeResult;
-use diesel::pg::PgConnection;
+use chrono::NaiveDateTime;
+use diesel::{
+    self,
+    pg::PgConnection,
+    prelude::*,
+};
 use error::BackendResult;
-use identifiers::answer::AnswerUuid;
+use identifiers::{
+    answer::AnswerUuid,
+    user::UserUuid,
+};
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Identifiable, Queryable, Associations, TypeName)]
@@ -24,6 +34,9 @@ pub struct Answer {
     pub question_uuid: Uuid,
     pub author_uuid: Option<Uuid>,
     pub answer_text: Option<String>,
+    /// When the answer was given.
+    /// This is unknown for answers given before answers were timestamped.
+    pub created_at: Option<NaiveDateTime>,
 }
 
 #[derive(Insertable, Debug)]
@@ -40,6 +53,14 @@ pub struct AnswerData {
     pub user: Option<User>,
 }
 
+/// An answer given by a particular user, along with the question it answers.
+#[derive(Debug)]
+pub struct UserAnswerData {
+    pub answer: Answer,
+    pub question: Question,
+    pub user: User,
+}
+
 impl Answer {
     pub fn get_answer(uuid: AnswerUuid, conn: &PgConnection) -> BackendResult<Answer> {
         get_row::<Answer, _>(schema::answers::table, uuid.0, conn)
@@ -50,4 +71,48 @@ impl Answer {
     pub fn create_answer(new: NewAnswer, conn: &PgConnection) -> BackendResult<Answer> {
         create_row::<Answer, NewAnswer, _>(schema::answers::table, new, conn)
     }
+
+    /// Replaces the text of the answer.
+    pub fn edit_answer(uuid: AnswerUuid, answer_text: Option<String>, conn: &PgConnection) -> BackendResult<Answer> {
+        diesel::update(answers::table.filter(answers::uuid.eq(uuid.0)))
+            .set(answers::answer_text.eq(answer_text))
+            .get_result(conn)
+            .map_err(handle_err::<Answer>)
+    }
+
+    /// Gets the answers the author has given, newest first.
+    ///
+    /// Unless the viewer is the author, only answers in buckets the viewer participates in are included.
+    /// Anonymous answers have no author, so they are never included.
+    pub fn get_answers_by_user(
+        author_uuid: UserUuid,
+        viewer_uuid: UserUuid,
+        conn: &PgConnection,
+    ) -> BackendResult<Vec<UserAnswerData>> {
+        let author: User = User::get_user(author_uuid, conn)?;
+
+        let mut query = answers::table
+            .filter(answers::author_uuid.eq(author_uuid.0))
+            .inner_join(questions::table)
+            // Answers without a timestamp are the oldest, so they go last.
+            .order((answers::created_at.is_null(), answers::created_at.desc()))
+            .into_boxed();
+        if author_uuid != viewer_uuid {
+            let visible_buckets = junction_bucket_users::table
+                .filter(junction_bucket_users::user_uuid.eq(viewer_uuid.0))
+                .filter(junction_bucket_users::approved.eq(true))
+                .select(junction_bucket_users::bucket_uuid);
+            query = query.filter(questions::bucket_uuid.eq_any(visible_buckets));
+        }
+        let answers_and_questions: Vec<(Answer, Question)> = query.load(conn).map_err(handle_err::<Answer>)?;
+
+        Ok(answers_and_questions
+            .into_iter()
+            .map(|(answer, question)| UserAnswerData {
+                answer,
+                question,
+                user: author.clone(),
+            })
+            .collect())
+    }
 }
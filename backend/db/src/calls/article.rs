@@ -3,28 +3,52 @@ use chrono::{
     Utc,
 };
 use crate::{
+    article_revision::ArticleRevision,
     calls::prelude::*,
+    diesel_extensions::pagination::PaginatedData,
+    forum::{
+        Forum,
+        NewForum,
+    },
     schema::{
         self,
+        article_slug_redirects,
+        article_tags,
         articles,
+        threads,
+    },
+    thread::{
+        NewThread,
+        Thread,
     },
     user::User,
 };
 use diesel::{
     self,
+    result::Error as DieselError,
     BelongingToDsl,
+    BoolExpressionMethods,
+    Connection,
     ExpressionMethods,
+    OptionalExtension,
     PgConnection,
     QueryDsl,
     RunQueryDsl,
 };
-use error::BackendResult;
+use error::{
+    BackendResult,
+    Error,
+};
 use identifiers::{
     article::ArticleUuid,
+    category::CategoryUuid,
+    thread::ThreadUuid,
     user::UserUuid,
 };
 use uuid::Uuid;
 
+use std::collections::HashMap;
+
 /// The database's representation of an article
 #[derive(Clone, Queryable, Identifiable, Associations, Debug, PartialEq, TypeName)]
 #[primary_key(uuid)]
@@ -43,7 +67,12 @@ pub struct Article {
     pub body: String,
     /// The presence of a publish date will idicate the article's published status,
     /// and will be used in ordering sets of the most recent articles.
+    /// A publish date in the future indicates that the article is scheduled to be published at that time.
     pub publish_date: Option<NaiveDateTime>,
+    /// The category the article is filed under, if any.
+    pub category_uuid: Option<Uuid>,
+    /// The forum thread where the article is discussed. This is created when the article is published.
+    pub comment_thread_uuid: Option<Uuid>,
 }
 
 /// Specifies the attributes that can be changed for an article.
@@ -54,6 +83,8 @@ pub struct ArticleChangeset {
     pub uuid: Uuid,
     pub title: Option<String>,
     pub body: Option<String>,
+    /// Derived from the title when it changes, and never set directly by requests.
+    pub slug: Option<String>,
 }
 
 /// Represents an article that will be inserted into the database.
@@ -66,23 +97,121 @@ pub struct NewArticle {
     pub author_uuid: Uuid,
 }
 
+/// A slug that an article used to be reachable by, before its title was changed.
+#[derive(Clone, Queryable, Identifiable, Associations, Debug, PartialEq, TypeName)]
+#[primary_key(uuid)]
+#[belongs_to(Article, foreign_key = "article_uuid")]
+#[table_name = "article_slug_redirects"]
+pub struct ArticleSlugRedirect {
+    pub uuid: Uuid,
+    pub article_uuid: Uuid,
+    pub slug: String,
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[table_name = "article_slug_redirects"]
+pub struct NewArticleSlugRedirect {
+    pub article_uuid: Uuid,
+    pub slug: String,
+}
+
+/// A tag attached to an article.
+/// Tags are normalized into slugs so that differently capitalized or punctuated tags are considered the same.
+#[derive(Clone, Queryable, Identifiable, Associations, Debug, PartialEq, TypeName)]
+#[primary_key(uuid)]
+#[belongs_to(Article, foreign_key = "article_uuid")]
+#[table_name = "article_tags"]
+pub struct ArticleTag {
+    pub uuid: Uuid,
+    pub article_uuid: Uuid,
+    pub tag: String,
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[table_name = "article_tags"]
+pub struct NewArticleTag {
+    pub article_uuid: Uuid,
+    pub tag: String,
+}
+
 pub struct ArticleData {
     pub article: Article,
     pub user: User,
+    pub tags: Vec<String>,
+    /// The number of replies in the article's comment thread.
+    pub comment_count: i64,
+}
+
+/// The title of the forum that article comment threads are created in, unless configured otherwise.
+pub const DEFAULT_ARTICLE_COMMENTS_FORUM: &str = "Article Comments";
+
+/// The advisory lock held while an article's slug is being chosen.
+const SLUG_LOCK_KEY: i64 = 0x736c_7567;
+
+/// Restricts which published articles are returned when listing articles.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ArticleFilter {
+    All,
+    Tag(String),
+    Category(CategoryUuid),
 }
 
 impl Article {
+    /// The article is published if it has a publish date that isn't in the future.
+    pub fn is_published(&self) -> bool {
+        match self.publish_date {
+            Some(date) => date <= Utc::now().naive_utc(),
+            None => false,
+        }
+    }
+
+    /// Published articles can be read by anyone,
+    /// but unpublished and scheduled articles can only be read by their author.
+    pub fn is_visible_to(&self, user_uuid: Option<UserUuid>) -> bool {
+        self.is_published() || user_uuid.map(|user_uuid| user_uuid.0) == Some(self.author_uuid)
+    }
+
     pub fn get_article(uuid: ArticleUuid, conn: &PgConnection) -> BackendResult<Article> {
         get_row::<Article, _>(schema::articles::table, uuid.0, conn)
     }
     pub fn delete_article(uuid: ArticleUuid, conn: &PgConnection) -> BackendResult<Article> {
         delete_row::<Article, _>(schema::articles::table, uuid.0, conn)
     }
+    /// Creates the article, making its slug unique among all current and previous slugs.
+    /// If the title doesn't produce a slug, the article's uuid is used as its slug instead.
+    /// The initial content is recorded as the article's first revision.
     pub fn create_article(new: NewArticle, conn: &PgConnection) -> BackendResult<Article> {
-        create_row::<Article, NewArticle, _>(schema::articles::table, new, conn)
+        conn.transaction::<_, DieselError, _>(|| {
+            Article::lock_slugs(conn)?;
+            let article: Article = if new.slug.is_empty() {
+                // The uuid isn't known until the article is inserted, so a placeholder slug is used until then.
+                let new = NewArticle {
+                    slug: Uuid::new_v4().to_string(),
+                    ..new
+                };
+                let article: Article = diesel::insert_into(articles::table)
+                    .values(&new)
+                    .get_result(conn)?;
+                diesel::update(articles::table.find(article.uuid))
+                    .set(articles::slug.eq(article.uuid.to_string()))
+                    .get_result(conn)?
+            } else {
+                let slug = Article::unique_slug(&new.slug, None, conn)?;
+                let new = NewArticle { slug, ..new };
+                diesel::insert_into(articles::table)
+                    .values(&new)
+                    .get_result(conn)?
+            };
+            ArticleRevision::record_revision(&article, UserUuid(article.author_uuid), conn)?;
+            Ok(article)
+        })
+        .map_err(handle_err::<Article>)
     }
+    /// Applies the changeset without recording a revision.
+    /// The slug is kept in step with the title the same way as in `update_article`.
     pub fn update_article_2(changeset: ArticleChangeset, conn: &PgConnection) -> BackendResult<Article> {
-        update_row::<Article, ArticleChangeset, _>(schema::articles::table, changeset, conn)
+        conn.transaction::<_, DieselError, _>(|| Article::apply_changeset(changeset, conn))
+            .map_err(handle_err::<Article>)
     }
 
     // /// Gets the n most recent articles, where n is specified by the number_of_articles parameter.
@@ -104,39 +233,315 @@ impl Article {
     pub fn get_article_data(article_uuid: ArticleUuid, conn: &PgConnection) -> BackendResult<ArticleData> {
         let article = Article::get_article(article_uuid, conn)?;
         let user = User::get_user(UserUuid(article.author_uuid), conn)?;
-        Ok(ArticleData { article, user })
+        Article::assemble_article_data(vec![(article, user)], conn)?
+            .pop()
+            .ok_or(Error::InternalServerError)
+    }
+
+    /// Gets the article that currently has the slug, or that had the slug before its title was changed.
+    pub fn get_article_data_by_slug(requested_slug: &str, conn: &PgConnection) -> BackendResult<ArticleData> {
+        let article: Option<Article> = articles::table
+            .filter(articles::slug.eq(requested_slug))
+            .first::<Article>(conn)
+            .optional()
+            .map_err(handle_err::<Article>)?;
+
+        let article: Article = match article {
+            Some(article) => article,
+            None => {
+                let redirect: ArticleSlugRedirect = article_slug_redirects::table
+                    .filter(article_slug_redirects::slug.eq(requested_slug))
+                    .first::<ArticleSlugRedirect>(conn)
+                    .map_err(handle_err::<Article>)?;
+                Article::get_article(ArticleUuid(redirect.article_uuid), conn)?
+            }
+        };
+
+        let user = User::get_user(UserUuid(article.author_uuid), conn)?;
+        Article::assemble_article_data(vec![(article, user)], conn)?
+            .pop()
+            .ok_or(Error::InternalServerError)
     }
 
-    pub fn get_paginated(page_index: i32, page_size: i32, conn: &PgConnection) -> BackendResult<Vec<ArticleData>> {
+    /// Finds a slug based on the desired one that isn't used by any other article, either as its
+    /// current slug or as a redirect.
+    /// Conflicting slugs are disambiguated by appending an incrementing number.
+    ///
+    /// Slugs belonging to the `owner` article are not considered conflicts,
+    /// allowing an article to reclaim one of its old slugs.
+    ///
+    /// This must be called in a transaction after `lock_slugs`, so the slug can't be taken before it is used.
+    fn unique_slug(desired_slug: &str, owner: Option<ArticleUuid>, conn: &PgConnection) -> Result<String, DieselError> {
+        let owner_uuid: Option<Uuid> = owner.map(|owner| owner.0);
+        let pattern = format!("{}%", desired_slug);
+
+        let mut taken: Vec<String> = articles::table
+            .filter(articles::slug.like(&pattern))
+            .select((articles::uuid, articles::slug))
+            .load::<(Uuid, String)>(conn)?
+            .into_iter()
+            .filter(|(article_uuid, _)| Some(*article_uuid) != owner_uuid)
+            .map(|(_, taken_slug)| taken_slug)
+            .collect();
+
+        let redirects: Vec<String> = article_slug_redirects::table
+            .filter(article_slug_redirects::slug.like(&pattern))
+            .select((article_slug_redirects::article_uuid, article_slug_redirects::slug))
+            .load::<(Uuid, String)>(conn)?
+            .into_iter()
+            .filter(|(article_uuid, _)| Some(*article_uuid) != owner_uuid)
+            .map(|(_, taken_slug)| taken_slug)
+            .collect();
+        taken.extend(redirects);
+
+        if !taken.iter().any(|taken_slug| taken_slug == desired_slug) {
+            return Ok(desired_slug.to_string());
+        }
+
+        let mut suffix: usize = 2;
+        loop {
+            let candidate = format!("{}-{}", desired_slug, suffix);
+            if !taken.iter().any(|taken_slug| taken_slug == &candidate) {
+                return Ok(candidate);
+            }
+            suffix += 1;
+        }
+    }
+
+    /// Picks the slug for the article when its title changes.
+    /// If the title doesn't produce a slug, the article's uuid is used as its slug instead.
+    fn slug_for_title(title: &str, article_uuid: Uuid, conn: &PgConnection) -> Result<String, DieselError> {
+        let desired_slug = slug::slugify(title);
+        if desired_slug.is_empty() {
+            Ok(article_uuid.to_string())
+        } else {
+            Article::unique_slug(&desired_slug, Some(ArticleUuid(article_uuid)), conn)
+        }
+    }
+
+    /// Prevents other transactions from assigning slugs until this transaction ends.
+    ///
+    /// Slugs must be unique across both articles and their redirects,
+    /// which the unique constraints on each table can't enforce on their own.
+    fn lock_slugs(conn: &PgConnection) -> Result<(), DieselError> {
+        diesel::sql_query(format!("SELECT pg_advisory_xact_lock({})", SLUG_LOCK_KEY))
+            .execute(conn)
+            .map(|_| ())
+    }
+
+    /// Gets published articles based on page size and index, optionally restricted to a tag or category.
+    pub fn get_paginated(
+        page_index: i32,
+        page_size: i32,
+        filter: ArticleFilter,
+        conn: &PgConnection,
+    ) -> BackendResult<PaginatedData<ArticleData>> {
         use crate::{
             diesel_extensions::pagination::*,
-            schema::{
-                articles::dsl::*,
-                users,
-            },
+            schema::users,
         };
 
-        let (articles_and_users, _count) = articles
+        let now = Utc::now().naive_utc();
+        let mut query = articles::table
             .inner_join(users::table)
-            .filter(publish_date.is_not_null())
-            .order(publish_date)
+            .filter(articles::publish_date.le(now))
+            .order(articles::publish_date)
+            .into_boxed();
+
+        query = match filter {
+            ArticleFilter::All => query,
+            ArticleFilter::Tag(tag) => {
+                let tagged = article_tags::table
+                    .filter(article_tags::tag.eq(slug::slugify(tag)))
+                    .select(article_tags::article_uuid);
+                query.filter(articles::uuid.eq_any(tagged))
+            }
+            ArticleFilter::Category(category) => query.filter(articles::category_uuid.eq(category.0)),
+        };
+
+        let mut page: PaginatedData<(Article, User)> = query
             .paginate(page_index.into())
             .per_page(page_size.into())
-            .load_and_count_pages::<(Article, User)>(conn)
+            .load_page::<(Article, User)>(conn)
+            .map_err(handle_err::<Article>)?;
+
+        let articles_and_users = std::mem::replace(&mut page.items, vec![]);
+        let article_data = Article::assemble_article_data(articles_and_users, conn)?;
+        Ok(page.with_items(article_data))
+    }
+
+    /// Gets every published article, newest first.
+    pub fn get_all_published(conn: &PgConnection) -> BackendResult<Vec<ArticleData>> {
+        use crate::schema::users;
+
+        let now = Utc::now().naive_utc();
+        let articles_and_users: Vec<(Article, User)> = articles::table
+            .inner_join(users::table)
+            .filter(articles::publish_date.le(now))
+            .order(articles::publish_date.desc())
+            .load::<(Article, User)>(conn)
             .map_err(handle_err::<Article>)?;
 
-        let article_data = articles_and_users
+        Article::assemble_article_data(articles_and_users, conn)
+    }
+
+    /// Gets published articles that share tags with the given article,
+    /// ranked by the number of tags they have in common.
+    ///
+    /// The given article is reported as missing if the user isn't allowed to read it,
+    /// so its tags can't be inferred before it is published.
+    pub fn get_related_articles(
+        article_uuid: ArticleUuid,
+        user_uuid: Option<UserUuid>,
+        limit: usize,
+        conn: &PgConnection,
+    ) -> BackendResult<Vec<ArticleData>> {
+        use crate::schema::users;
+
+        let article: Article = Article::get_article(article_uuid, conn)?;
+        if !article.is_visible_to(user_uuid) {
+            return Err(Error::NotFound {
+                type_name: "Article".to_string(),
+            });
+        }
+        let tags: Vec<String> = Article::get_tags(&article, conn)?;
+
+        let shared_tags: Vec<Uuid> = article_tags::table
+            .filter(article_tags::tag.eq_any(&tags))
+            .filter(article_tags::article_uuid.ne(article.uuid))
+            .select(article_tags::article_uuid)
+            .load::<Uuid>(conn)
+            .map_err(handle_err::<ArticleTag>)?;
+
+        let mut shared_counts: HashMap<Uuid, usize> = HashMap::new();
+        for related_uuid in shared_tags {
+            *shared_counts.entry(related_uuid).or_insert(0) += 1;
+        }
+        let related_uuids: Vec<Uuid> = shared_counts.keys().cloned().collect();
+
+        let now = Utc::now().naive_utc();
+        let mut related: Vec<(Article, User)> = articles::table
+            .inner_join(users::table)
+            .filter(articles::uuid.eq_any(related_uuids))
+            .filter(articles::publish_date.le(now))
+            .load::<(Article, User)>(conn)
+            .map_err(handle_err::<Article>)?;
+
+        // Most shared tags first, with the most recently published articles breaking ties.
+        related.sort_by(|a, b| {
+            shared_counts[&b.0.uuid]
+                .cmp(&shared_counts[&a.0.uuid])
+                .then(b.0.publish_date.cmp(&a.0.publish_date))
+        });
+        related.truncate(limit);
+
+        Article::assemble_article_data(related, conn)
+    }
+
+    /// Gets the tags attached to an article.
+    pub fn get_tags(article: &Article, conn: &PgConnection) -> BackendResult<Vec<String>> {
+        ArticleTag::belonging_to(article)
+            .select(article_tags::tag)
+            .order(article_tags::tag)
+            .load::<String>(conn)
+            .map_err(handle_err::<ArticleTag>)
+    }
+
+    /// Replaces the tags attached to an article.
+    /// Tags are normalized, and duplicates are discarded.
+    /// The old tags are only removed if the new ones are attached.
+    pub fn set_tags(article_uuid: ArticleUuid, tags: Vec<String>, conn: &PgConnection) -> BackendResult<ArticleData> {
+        let mut tags: Vec<String> = tags
+            .into_iter()
+            .map(slug::slugify)
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        tags.sort();
+        tags.dedup();
+
+        let new_tags: Vec<NewArticleTag> = tags
             .into_iter()
-            .map(|x| ArticleData {
-                article: x.0,
-                user: x.1,
+            .map(|tag| NewArticleTag {
+                article_uuid: article_uuid.0,
+                tag,
+            })
+            .collect();
+
+        conn.transaction::<_, DieselError, _>(|| {
+            diesel::delete(article_tags::table.filter(article_tags::article_uuid.eq(article_uuid.0))).execute(conn)?;
+            diesel::insert_into(article_tags::table)
+                .values(&new_tags)
+                .execute(conn)
+        })
+        .map_err(handle_err::<ArticleTag>)?;
+
+        Article::get_article_data(article_uuid, conn)
+    }
+
+    /// Files the article under a category, or removes it from its category if None is provided.
+    pub fn set_category(
+        article_uuid: ArticleUuid,
+        category: Option<CategoryUuid>,
+        conn: &PgConnection,
+    ) -> BackendResult<Article> {
+        diesel::update(articles::table.find(article_uuid.0))
+            .set(articles::category_uuid.eq(category.map(|category| category.0)))
+            .get_result(conn)
+            .map_err(handle_err::<Article>)
+    }
+
+    /// Loads the tags and comment counts for each of the articles,
+    /// and bundles them together with the articles and their authors.
+    fn assemble_article_data(
+        articles_and_users: Vec<(Article, User)>,
+        conn: &PgConnection,
+    ) -> BackendResult<Vec<ArticleData>> {
+        use crate::schema::posts;
+        use diesel::GroupedBy;
+
+        let (articles, users): (Vec<Article>, Vec<User>) = articles_and_users.into_iter().unzip();
+        let tags: Vec<Vec<ArticleTag>> = ArticleTag::belonging_to(&articles)
+            .order(article_tags::tag)
+            .load::<ArticleTag>(conn)
+            .map_err(handle_err::<ArticleTag>)?
+            .grouped_by(&articles);
+
+        // Every post in a comment thread except the root post is a comment.
+        let comment_thread_uuids: Vec<Uuid> = articles.iter().filter_map(|article| article.comment_thread_uuid).collect();
+        let comment_threads: Vec<Uuid> = posts::table
+            .filter(posts::thread_uuid.eq_any(comment_thread_uuids))
+            .filter(posts::parent_uuid.is_not_null())
+            .select(posts::thread_uuid)
+            .load::<Uuid>(conn)
+            .map_err(handle_err::<Article>)?;
+        let mut comment_counts: HashMap<Uuid, i64> = HashMap::new();
+        for thread_uuid in comment_threads {
+            *comment_counts.entry(thread_uuid).or_insert(0) += 1;
+        }
+
+        let article_data = articles
+            .into_iter()
+            .zip(users.into_iter())
+            .zip(tags.into_iter())
+            .map(|((article, user), tags)| {
+                let comment_count = article
+                    .comment_thread_uuid
+                    .and_then(|thread_uuid| comment_counts.get(&thread_uuid).cloned())
+                    .unwrap_or(0);
+                ArticleData {
+                    article,
+                    user,
+                    tags: tags.into_iter().map(|tag| tag.tag).collect(),
+                    comment_count,
+                }
             })
             .collect();
 
         Ok(article_data)
     }
 
-    /// Gets the unpublished articles for a given user
+    /// Gets the unpublished articles for a given user.
+    /// Articles that are scheduled to be published in the future are included.
     pub fn get_unpublished_articles_for_user(user_uuid: UserUuid, conn: &PgConnection) -> BackendResult<Vec<Article>> {
         use crate::schema::{
             articles::dsl::*,
@@ -149,8 +554,9 @@ impl Article {
             .get_result::<User>(conn)
             .map_err(handle_err::<User>)?;
 
+        let now = Utc::now().naive_utc();
         Article::belonging_to(&user)
-            .filter(publish_date.is_null())
+            .filter(publish_date.is_null().or(publish_date.gt(now)))
             .order(publish_date)
             .load::<Article>(conn)
             .map_err(handle_err::<Article>)
@@ -159,27 +565,178 @@ impl Article {
     /// Sets the date for the article's publish date.
     /// If true, it will set the publish datetime to the current time, indicating it is published.
     /// If false, it will set the publish column to Null, indicating that it has not been published.
-    pub fn set_publish_status(article_uuid: ArticleUuid, publish: bool, conn: &PgConnection) -> BackendResult<Article> {
-        use crate::schema::articles::{
-            self,
-            dsl::*,
+    ///
+    /// Publishing the article for the first time creates a thread for its comments in the `comments_forum`.
+    /// Unpublishing the article archives its comment thread until it is published again.
+    pub fn set_publish_status(
+        article_uuid: ArticleUuid,
+        publish: bool,
+        comments_forum: &str,
+        conn: &PgConnection,
+    ) -> BackendResult<Article> {
+        let publish_value: Option<NaiveDateTime> = if publish { Some(Utc::now().naive_utc()) } else { None };
+        Article::set_publish_date(article_uuid, publish_value, comments_forum, conn)
+    }
+
+    /// Schedules the article to be published at the given time.
+    /// The article will be hidden from non-authors until that time arrives.
+    ///
+    /// Its comment thread is archived, or not created yet, until the article is first read after that time.
+    pub fn schedule_publish(
+        article_uuid: ArticleUuid,
+        publish_at: NaiveDateTime,
+        comments_forum: &str,
+        conn: &PgConnection,
+    ) -> BackendResult<Article> {
+        if publish_at <= Utc::now().naive_utc() {
+            return Err(Error::BadRequest);
+        }
+        Article::set_publish_date(article_uuid, Some(publish_at), comments_forum, conn)
+    }
+
+    /// Sets the publish date, and brings the comment thread in line with it in the same transaction.
+    ///
+    /// Updating the article locks its row until the transaction ends,
+    /// so when it is published by concurrent requests, the later ones see the thread made by the first.
+    fn set_publish_date(
+        article_uuid: ArticleUuid,
+        publish_value: Option<NaiveDateTime>,
+        comments_forum: &str,
+        conn: &PgConnection,
+    ) -> BackendResult<Article> {
+        conn.transaction::<_, DieselError, _>(|| {
+            let article: Article = diesel::update(articles::table.find(article_uuid.0))
+                .set(articles::publish_date.eq(publish_value))
+                .get_result(conn)?;
+            Article::sync_comment_thread(article, comments_forum, conn)
+        })
+        .map_err(handle_err::<Article>)
+    }
+
+    /// Opens the comment thread of a published article that doesn't have an open one yet.
+    ///
+    /// Nothing runs when a scheduled article's publish date arrives,
+    /// so its thread is created or unarchived by the first read after that time.
+    /// Articles that aren't published, or whose thread is already open, are returned as they are.
+    pub fn open_comment_thread(article: Article, comments_forum: &str, conn: &PgConnection) -> BackendResult<Article> {
+        if !article.is_published() {
+            return Ok(article);
+        }
+        if let Some(thread_uuid) = article.comment_thread_uuid {
+            let thread: Thread = Thread::get_thread(ThreadUuid(thread_uuid), conn)?;
+            if !thread.archived {
+                return Ok(article);
+            }
+        }
+
+        conn.transaction::<_, DieselError, _>(|| {
+            // Concurrent readers wait here, and then find the thread opened by the first.
+            let article: Article = articles::table
+                .find(article.uuid)
+                .for_update()
+                .get_result(conn)?;
+            Article::sync_comment_thread(article, comments_forum, conn)
+        })
+        .map_err(handle_err::<Article>)
+    }
+
+    /// Creates or unarchives the comment thread if the article is published, and archives it otherwise.
+    /// The article's row must already be locked.
+    /// This is meant to be part of a larger transaction.
+    fn sync_comment_thread(article: Article, comments_forum: &str, conn: &PgConnection) -> Result<Article, DieselError> {
+        match article.comment_thread_uuid {
+            None if article.is_published() => Article::create_comment_thread(article, comments_forum, conn),
+            None => Ok(article),
+            Some(thread_uuid) => {
+                diesel::update(threads::table.find(thread_uuid))
+                    .set(threads::archived.eq(!article.is_published()))
+                    .execute(conn)?;
+                Ok(article)
+            }
+        }
+    }
+
+    /// Creates the thread that the article is discussed in.
+    /// The thread is created in the forum with the given title, which is created if it doesn't exist yet.
+    fn create_comment_thread(article: Article, comments_forum: &str, conn: &PgConnection) -> Result<Article, DieselError> {
+        let new_forum = NewForum {
+            title: comments_forum.to_string(),
+            description: "Discussion of published articles.".to_string(),
         };
+        let forum: Forum = Forum::get_or_create_forum_by_title(new_forum, conn)?;
 
-        let publish_value: Option<NaiveDateTime> = if publish { Some(Utc::now().naive_utc()) } else { None };
+        let new_thread = NewThread {
+            forum_uuid: forum.uuid,
+            author_uuid: article.author_uuid,
+            created_date: Utc::now().naive_utc(),
+            locked: false,
+            archived: false,
+            title: article.title.clone(),
+        };
+        let post_content = format!("Comments for the article \"{}\".", article.title);
+        let (thread, _) = Thread::insert_thread_with_initial_post(new_thread, post_content, conn)?;
 
-        diesel::update(articles::table)
-            .filter(articles::uuid.eq(article_uuid.0))
-            .set(publish_date.eq(publish_value))
+        diesel::update(articles::table.find(article.uuid))
+            .set(articles::comment_thread_uuid.eq(Some(thread.uuid)))
             .get_result(conn)
-            .map_err(handle_err::<Article>)
     }
 
     /// Applies the changeset to its corresponding article.
-    pub fn update_article(changeset: ArticleChangeset, conn: &PgConnection) -> BackendResult<Article> {
-        use crate::schema::articles;
-        diesel::update(articles::table)
+    ///
+    /// If the title changes, the article gets a new slug, and its old slug is kept as a redirect.
+    /// The resulting content is recorded as a new revision, attributed to the editor, in the same transaction.
+    pub fn update_article(
+        changeset: ArticleChangeset,
+        editor_uuid: UserUuid,
+        conn: &PgConnection,
+    ) -> BackendResult<Article> {
+        conn.transaction::<_, DieselError, _>(|| {
+            let article: Article = Article::apply_changeset(changeset, conn)?;
+            ArticleRevision::record_revision(&article, editor_uuid, conn)?;
+            Ok(article)
+        })
+        .map_err(handle_err::<Article>)
+    }
+
+    /// Updates the article, giving it a new slug if its title changed.
+    /// This is meant to be part of a larger transaction.
+    fn apply_changeset(changeset: ArticleChangeset, conn: &PgConnection) -> Result<Article, DieselError> {
+        let existing: Article = articles::table.find(changeset.uuid).get_result(conn)?;
+
+        let new_slug: Option<String> = match changeset.title {
+            Some(ref new_title) if new_title != &existing.title => {
+                Article::lock_slugs(conn)?;
+                Some(Article::slug_for_title(new_title, existing.uuid, conn)?)
+            }
+            _ => None,
+        };
+
+        if let Some(ref new_slug) = new_slug {
+            if new_slug != &existing.slug {
+                // The article may be reclaiming one of its own previous slugs.
+                diesel::delete(
+                    article_slug_redirects::table
+                        .filter(article_slug_redirects::article_uuid.eq(existing.uuid))
+                        .filter(article_slug_redirects::slug.eq(new_slug)),
+                )
+                .execute(conn)?;
+
+                let redirect = NewArticleSlugRedirect {
+                    article_uuid: existing.uuid,
+                    slug: existing.slug.clone(),
+                };
+                diesel::insert_into(article_slug_redirects::table)
+                    .values(&redirect)
+                    .execute(conn)?;
+            }
+        }
+
+        let changeset = ArticleChangeset {
+            slug: new_slug,
+            ..changeset
+        };
+        diesel::update(articles::table.find(changeset.uuid))
             .set(&changeset)
             .get_result(conn)
-            .map_err(handle_err::<Article>)
     }
 }
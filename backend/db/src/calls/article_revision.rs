@@ -0,0 +1,281 @@
+use chrono::{
+    NaiveDateTime,
+    Utc,
+};
+use crate::{
+    article::{
+        Article,
+        ArticleChangeset,
+    },
+    calls::prelude::*,
+    schema::{
+        article_revisions,
+        users,
+    },
+    user::User,
+};
+use diesel::{
+    self,
+    dsl::max,
+    result::Error as DieselError,
+    BelongingToDsl,
+    ExpressionMethods,
+    PgConnection,
+    QueryDsl,
+    RunQueryDsl,
+};
+use error::BackendResult;
+use identifiers::{
+    article::ArticleUuid,
+    user::UserUuid,
+};
+use uuid::Uuid;
+use wire::article::DiffLine;
+
+/// A snapshot of an article, taken every time it is created or updated.
+#[derive(Clone, Queryable, Identifiable, Associations, Debug, PartialEq, TypeName)]
+#[primary_key(uuid)]
+#[belongs_to(Article, foreign_key = "article_uuid")]
+#[belongs_to(User, foreign_key = "editor_uuid")]
+#[table_name = "article_revisions"]
+pub struct ArticleRevision {
+    /// Primary Key
+    pub uuid: Uuid,
+    /// The article this is a revision of.
+    pub article_uuid: Uuid,
+    /// The user who made the change that produced this revision.
+    pub editor_uuid: Uuid,
+    /// Starts at 1 for the article's initial content, and increases by one with every update.
+    pub revision_number: i32,
+    pub title: String,
+    pub body: String,
+    pub created_date: NaiveDateTime,
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[table_name = "article_revisions"]
+struct NewArticleRevision {
+    pub article_uuid: Uuid,
+    pub editor_uuid: Uuid,
+    pub revision_number: i32,
+    pub title: String,
+    pub body: String,
+    pub created_date: NaiveDateTime,
+}
+
+pub struct ArticleRevisionData {
+    pub revision: ArticleRevision,
+    pub editor: User,
+}
+
+pub struct ArticleDiffData {
+    pub article_uuid: Uuid,
+    pub from: ArticleRevision,
+    pub to: ArticleRevision,
+    pub lines: Vec<DiffLine>,
+}
+
+impl ArticleRevision {
+    /// Records the current state of the article as its newest revision.
+    ///
+    /// This is meant to be part of the transaction that created or updated the article.
+    /// The article's row stays locked until that transaction ends,
+    /// so concurrent edits can't both claim the next revision number.
+    pub(crate) fn record_revision(
+        article: &Article,
+        editor_uuid: UserUuid,
+        conn: &PgConnection,
+    ) -> Result<ArticleRevision, DieselError> {
+        let latest: Option<i32> = ArticleRevision::belonging_to(article)
+            .select(max(article_revisions::revision_number))
+            .first::<Option<i32>>(conn)?;
+
+        let new_revision = NewArticleRevision {
+            article_uuid: article.uuid,
+            editor_uuid: editor_uuid.0,
+            revision_number: latest.unwrap_or(0) + 1,
+            title: article.title.clone(),
+            body: article.body.clone(),
+            created_date: Utc::now().naive_utc(),
+        };
+
+        diesel::insert_into(article_revisions::table)
+            .values(&new_revision)
+            .get_result(conn)
+    }
+
+    /// Gets all of the revisions for an article, oldest first.
+    pub fn get_revisions_for_article(
+        article_uuid: ArticleUuid,
+        conn: &PgConnection,
+    ) -> BackendResult<Vec<ArticleRevisionData>> {
+        let article: Article = Article::get_article(article_uuid, conn)?;
+
+        let revisions_and_editors: Vec<(ArticleRevision, User)> = ArticleRevision::belonging_to(&article)
+            .inner_join(users::table)
+            .order(article_revisions::revision_number)
+            .load::<(ArticleRevision, User)>(conn)
+            .map_err(handle_err::<ArticleRevision>)?;
+
+        Ok(revisions_and_editors
+            .into_iter()
+            .map(|(revision, editor)| ArticleRevisionData { revision, editor })
+            .collect())
+    }
+
+    /// Gets a single revision of an article.
+    pub fn get_revision(
+        article_uuid: ArticleUuid,
+        revision_number: i32,
+        conn: &PgConnection,
+    ) -> BackendResult<ArticleRevisionData> {
+        let (revision, editor) = article_revisions::table
+            .inner_join(users::table)
+            .filter(article_revisions::article_uuid.eq(article_uuid.0))
+            .filter(article_revisions::revision_number.eq(revision_number))
+            .first::<(ArticleRevision, User)>(conn)
+            .map_err(handle_err::<ArticleRevision>)?;
+        Ok(ArticleRevisionData { revision, editor })
+    }
+
+    /// Gets the line-based difference between the bodies of two revisions.
+    pub fn diff_revisions(
+        article_uuid: ArticleUuid,
+        from_revision: i32,
+        to_revision: i32,
+        conn: &PgConnection,
+    ) -> BackendResult<ArticleDiffData> {
+        let from = ArticleRevision::get_revision(article_uuid, from_revision, conn)?.revision;
+        let to = ArticleRevision::get_revision(article_uuid, to_revision, conn)?.revision;
+        let lines = diff_lines(&from.body, &to.body);
+        Ok(ArticleDiffData {
+            article_uuid: article_uuid.0,
+            from,
+            to,
+            lines,
+        })
+    }
+
+    /// Restores the article to the content it had at an earlier revision.
+    /// The rollback is itself recorded as a new revision, so no history is lost.
+    pub fn rollback(
+        article_uuid: ArticleUuid,
+        revision_number: i32,
+        editor_uuid: UserUuid,
+        conn: &PgConnection,
+    ) -> BackendResult<Article> {
+        let revision = ArticleRevision::get_revision(article_uuid, revision_number, conn)?.revision;
+        let changeset = ArticleChangeset {
+            uuid: article_uuid.0,
+            title: Some(revision.title),
+            body: Some(revision.body),
+            slug: None,
+        };
+        Article::update_article(changeset, editor_uuid, conn)
+    }
+}
+
+/// The most lines that can differ between two bodies before the diff stops looking for lines they share.
+/// Finding shared lines takes memory proportional to the product of the numbers of differing lines.
+const MAX_DIFF_LINES: usize = 2000;
+
+/// Computes a line-based diff using the longest common subsequence of lines.
+///
+/// Lines shared at the start and end of both bodies are matched up first.
+/// If too many lines remain between them, they are all shown as removed and then added,
+/// instead of finding the lines they have in common.
+fn diff_lines(from: &str, to: &str) -> Vec<DiffLine> {
+    let from: Vec<&str> = from.lines().collect();
+    let to: Vec<&str> = to.lines().collect();
+
+    let prefix: usize = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let suffix: usize = from[prefix..]
+        .iter()
+        .rev()
+        .zip(to[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut lines = Vec::with_capacity(from.len().max(to.len()));
+    lines.extend(from[..prefix].iter().map(|line| DiffLine::Unchanged(line.to_string())));
+    let from_middle: &[&str] = &from[prefix..from.len() - suffix];
+    let to_middle: &[&str] = &to[prefix..to.len() - suffix];
+    if from_middle.len() > MAX_DIFF_LINES || to_middle.len() > MAX_DIFF_LINES {
+        lines.extend(from_middle.iter().map(|line| DiffLine::Removed(line.to_string())));
+        lines.extend(to_middle.iter().map(|line| DiffLine::Added(line.to_string())));
+    } else {
+        lines.extend(diff_common_subsequence(from_middle, to_middle));
+    }
+    lines.extend(from[from.len() - suffix..].iter().map(|line| DiffLine::Unchanged(line.to_string())));
+    lines
+}
+
+/// Diffs the lines by finding their longest common subsequence.
+fn diff_common_subsequence(from: &[&str], to: &[&str]) -> Vec<DiffLine> {
+    // lcs[i][j] holds the length of the longest common subsequence of from[i..] and to[j..].
+    let mut lcs = vec![vec![0u32; to.len() + 1]; from.len() + 1];
+    for i in (0..from.len()).rev() {
+        for j in (0..to.len()).rev() {
+            lcs[i][j] = if from[i] == to[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::with_capacity(from.len().max(to.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < from.len() && j < to.len() {
+        if from[i] == to[j] {
+            lines.push(DiffLine::Unchanged(from[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            lines.push(DiffLine::Removed(from[i].to_string()));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(to[j].to_string()));
+            j += 1;
+        }
+    }
+    lines.extend(from[i..].iter().map(|line| DiffLine::Removed(line.to_string())));
+    lines.extend(to[j..].iter().map(|line| DiffLine::Added(line.to_string())));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_finds_changed_lines() {
+        let lines = diff_lines("a\nb\nc\nd", "a\nc\ne\nd");
+        assert_eq!(
+            lines,
+            vec![
+                DiffLine::Unchanged("a".to_string()),
+                DiffLine::Removed("b".to_string()),
+                DiffLine::Unchanged("c".to_string()),
+                DiffLine::Added("e".to_string()),
+                DiffLine::Unchanged("d".to_string()),
+            ]
+        );
+    }
+
+    /// Bodies too large to search for common lines are still diffed around their shared start and end.
+    #[test]
+    fn diff_of_large_bodies_is_bounded() {
+        let from: String = (0..MAX_DIFF_LINES + 1).map(|n| format!("from {}\n", n)).collect();
+        let to: String = (0..MAX_DIFF_LINES + 1).map(|n| format!("to {}\n", n)).collect();
+        let from = format!("start\n{}end", from);
+        let to = format!("start\n{}end", to);
+
+        let lines = diff_lines(&from, &to);
+        assert_eq!(lines.len(), 2 * (MAX_DIFF_LINES + 1) + 2);
+        assert_eq!(lines[0], DiffLine::Unchanged("start".to_string()));
+        assert_eq!(lines[1], DiffLine::Removed("from 0".to_string()));
+        assert_eq!(lines[MAX_DIFF_LINES + 2], DiffLine::Added("to 0".to_string()));
+        assert_eq!(lines[lines.len() - 1], DiffLine::Unchanged("end".to_string()));
+    }
+}
@@ -5,26 +5,58 @@ use chrono::{
 };
 use crate::{
     auth_lib::{
+        generate_refresh_secret,
+        hash_password,
+        hash_refresh_secret,
         verify_hash,
         Secret,
         ServerJwt,
     },
+    password_reset::{
+        NewPasswordReset,
+        PasswordReset,
+    },
     user::User,
+    user_ban::UserBan,
+    user_session::{
+        NewUserSession,
+        UserSession,
+    },
+};
+use diesel::{
+    Connection,
+    PgConnection,
 };
-use diesel::PgConnection;
-use error::{
-    LoginError,
-    LoginResult,
+use error::LoginError;
+use identifiers::{
+    password_reset::PasswordResetUuid,
+    user::UserUuid,
+    user_session::UserSessionUuid,
 };
-use identifiers::user::UserUuid;
 use wire::{
-    login::LoginRequest,
+    login::{
+        LoginRequest,
+        TokensResponse,
+    },
     user::Jwt,
 };
 
 use log::info;
 
-pub fn login(login_request: LoginRequest, secret: &Secret, conn: &PgConnection) -> LoginResult {
+/// JWTs are short lived, so a session that has been ended stops working soon after.
+pub const JWT_MINUTES: i64 = 15;
+/// A session ends if its refresh token goes unused for this long.
+pub const REFRESH_TOKEN_DAYS: i64 = 30;
+/// Password reset tokens have to be used soon after they are mailed.
+pub const PASSWORD_RESET_MINUTES: i64 = 60;
+
+/// Logs the user in, starting a new session.
+pub fn login(
+    login_request: LoginRequest,
+    user_agent: Option<String>,
+    secret: &Secret,
+    conn: &PgConnection,
+) -> Result<TokensResponse, LoginError> {
     info!("Logging in for user: {}", &login_request.user_name);
 
     let user: User =
@@ -61,47 +93,201 @@ pub fn login(login_request: LoginRequest, secret: &Secret, conn: &PgConnection)
         Err(e) => return Err(LoginError::PasswordHashingError(e)),
     }
 
-    // generate token
-    info!("Generating JWT Expiry Date");
-    let duration: Duration = Duration::days(7); // Expire after a week
-    let new_expire_date: NaiveDateTime = match Utc::now().checked_add_signed(duration) {
-        Some(ndt) => ndt.naive_utc(),
-        None => return Err(LoginError::OtherError("Could not calculate offset for token expiry")),
+    ensure_not_banned(user_uuid, conn)?;
+
+    info!("Starting session");
+    let refresh_secret: String = generate_refresh_secret();
+    let new_session = NewUserSession {
+        user_uuid: user.uuid,
+        refresh_token_hash: hash_refresh_secret(&refresh_secret),
+        user_agent,
+        expires_at: offset_from_now(Duration::days(REFRESH_TOKEN_DAYS))?,
+    };
+    let session: UserSession =
+        UserSession::create_session(new_session, conn).map_err(|_| LoginError::OtherError("Could not start session"))?;
+
+    Ok(TokensResponse {
+        jwt: create_jwt_string(&user, secret)?,
+        refresh_token: format_refresh_token(UserSessionUuid(session.uuid), &refresh_secret),
+    })
+}
+
+/// Exchanges a refresh token for a new JWT and a new refresh token.
+///
+/// Each refresh token can only be used once.
+/// If one is used again, it has probably been stolen, so the session it belongs to is ended.
+pub fn refresh(refresh_token: &str, secret: &Secret, conn: &PgConnection) -> Result<TokensResponse, LoginError> {
+    let (session_uuid, refresh_secret) = parse_refresh_token(refresh_token)?;
+
+    let new_refresh_secret: String = generate_refresh_secret();
+    let session: Option<UserSession> = UserSession::rotate_refresh_token(
+        session_uuid,
+        &hash_refresh_secret(refresh_secret),
+        hash_refresh_secret(&new_refresh_secret),
+        offset_from_now(Duration::days(REFRESH_TOKEN_DAYS))?,
+        conn,
+    )
+    .map_err(|_| LoginError::OtherError("DB error"))?;
+
+    let session: UserSession = match session {
+        Some(session) => session,
+        None => {
+            info!("Refresh token was reused or expired, ending session: {}", session_uuid);
+            let _ = UserSession::end_session(session_uuid, conn);
+            return Err(LoginError::InvalidRefreshToken);
+        }
+    };
+
+    let user: User = User::get_user(UserUuid(session.user_uuid), conn).map_err(|_| LoginError::UsernameDoesNotExist)?;
+    ensure_not_banned(UserUuid(user.uuid), conn)?;
+    Ok(TokensResponse {
+        jwt: create_jwt_string(&user, secret)?,
+        refresh_token: format_refresh_token(session_uuid, &new_refresh_secret),
+    })
+}
+
+/// Ends the session the refresh token belongs to.
+pub fn logout(refresh_token: &str, conn: &PgConnection) -> Result<(), LoginError> {
+    let (session_uuid, refresh_secret) = parse_refresh_token(refresh_token)?;
+    let session: UserSession = UserSession::get_session(session_uuid, conn).map_err(|_| LoginError::InvalidRefreshToken)?;
+    if session.refresh_token_hash != hash_refresh_secret(refresh_secret) {
+        return Err(LoginError::InvalidRefreshToken);
+    }
+    UserSession::end_session(session_uuid, conn)
+        .map(|_| ())
+        .map_err(|_| LoginError::OtherError("DB error"))
+}
+
+/// Sets a new password for the user, as long as they know their current one.
+pub fn change_password(
+    user_uuid: UserUuid,
+    current_password: &str,
+    new_password: &str,
+    conn: &PgConnection,
+) -> Result<(), LoginError> {
+    let user: User = User::get_user(user_uuid, conn).map_err(|_| LoginError::UsernameDoesNotExist)?;
+    if !verify_hash(current_password, &user.password_hash).map_err(LoginError::PasswordHashingError)? {
+        info!("Wrong current password entered while changing password for user: {}", &user.user_name);
+        return Err(LoginError::IncorrectPassword);
+    }
+    set_password(user_uuid, new_password, conn)
+}
+
+/// Creates a password reset token for the user.
+///
+/// Returns the email the token should be sent to along with the token,
+/// or None if the user doesn't exist or has no email to send it to.
+pub fn request_password_reset(user_name: &str, conn: &PgConnection) -> Result<Option<(String, String)>, LoginError> {
+    let user: User = match User::get_user_by_user_name(user_name, conn) {
+        Ok(user) => user,
+        Err(_) => return Ok(None),
     };
+    let email: String = match user.email {
+        Some(email) => email,
+        None => {
+            info!("Password reset requested for user without an email: {}", user_name);
+            return Ok(None);
+        }
+    };
+
+    let reset_secret: String = generate_refresh_secret();
+    let new_reset = NewPasswordReset {
+        user_uuid: user.uuid,
+        token_hash: hash_refresh_secret(&reset_secret),
+        expires_at: offset_from_now(Duration::minutes(PASSWORD_RESET_MINUTES))?,
+    };
+    let reset: PasswordReset =
+        PasswordReset::create_reset(new_reset, conn).map_err(|_| LoginError::OtherError("Could not create reset token"))?;
+    Ok(Some((email, format!("{}.{}", reset.uuid, reset_secret))))
+}
+
+/// Uses up the reset token to set a new password.
+///
+/// All of the user's sessions are ended, in case the password was reset because someone else had it.
+pub fn reset_password(reset_token: &str, new_password: &str, conn: &PgConnection) -> Result<(), LoginError> {
+    let (reset_uuid, reset_secret) = parse_reset_token(reset_token)?;
+    // The password is hashed before the transaction, so the database isn't kept waiting on it.
+    let password_hash: String =
+        hash_password(new_password).map_err(|_| LoginError::PasswordHashingError("Could not hash password"))?;
 
+    // The token is only used up if the password is actually changed.
+    conn.transaction::<_, LoginError, _>(|| {
+        let reset: PasswordReset = PasswordReset::redeem_reset(reset_uuid, &hash_refresh_secret(reset_secret), conn)
+            .map_err(|_| LoginError::OtherError("DB error"))?
+            .ok_or(LoginError::InvalidResetToken)?;
+
+        let user_uuid = UserUuid(reset.user_uuid);
+        User::update_password(user_uuid, password_hash, conn)
+            .map_err(|_| LoginError::OtherError("Could not update password"))?;
+        // Failed logins made before the reset shouldn't count against the new password.
+        User::reset_login_failure_count(user_uuid, conn).map_err(|_| LoginError::OtherError("DB error"))?;
+        UserSession::end_all_sessions(user_uuid, conn)
+            .map(|_| ())
+            .map_err(|_| LoginError::OtherError("Could not end sessions"))
+    })
+}
+
+fn set_password(user_uuid: UserUuid, new_password: &str, conn: &PgConnection) -> Result<(), LoginError> {
+    let password_hash: String =
+        hash_password(new_password).map_err(|_| LoginError::PasswordHashingError("Could not hash password"))?;
+    User::update_password(user_uuid, password_hash, conn)
+        .map(|_| ())
+        .map_err(|_| LoginError::OtherError("Could not update password"))
+}
+
+fn ensure_not_banned(user_uuid: UserUuid, conn: &PgConnection) -> Result<(), LoginError> {
+    match UserBan::get_active_ban(user_uuid, conn).map_err(|_| LoginError::OtherError("DB error"))? {
+        Some(ban) => {
+            info!("Account banned.");
+            Err(LoginError::AccountBanned {
+                reason: ban.reason,
+                banned_until: ban.expires_at,
+            })
+        }
+        None => Ok(()),
+    }
+}
+
+fn create_jwt_string(user: &User, secret: &Secret) -> Result<String, LoginError> {
     info!("Creating JWT");
     let jwt = Jwt {
-        //        user_name: user.user_name.clone(),
         sub: UserUuid(user.uuid),
         user_roles: user.roles.iter().map(|role_id| (*role_id).into()).collect(),
-        exp: new_expire_date,
+        exp: offset_from_now(Duration::minutes(JWT_MINUTES))?,
         iat: Utc::now().naive_utc(),
     };
-    let jwt = ServerJwt(jwt);
-    let jwt_string: String = match jwt.encode_jwt_string(&secret) {
-        Ok(s) => s,
-        Err(e) => return Err(LoginError::JwtError(e)),
-    };
+    ServerJwt(jwt).encode_jwt_string(secret).map_err(LoginError::JwtError)
+}
 
-    Ok(jwt_string)
+fn offset_from_now(duration: Duration) -> Result<NaiveDateTime, LoginError> {
+    Utc::now()
+        .checked_add_signed(duration)
+        .map(|date| date.naive_utc())
+        .ok_or(LoginError::OtherError("Could not calculate offset for token expiry"))
 }
 
-pub fn reauth(jwt: ServerJwt, secret: &Secret) -> LoginResult {
-    let mut jwt = jwt.0;
-    info!("Generating JWT Expiry Date");
-    let duration: Duration = Duration::days(7); // Expire after a week
-    let new_expire_date: NaiveDateTime = match Utc::now().checked_add_signed(duration) {
-        Some(ndt) => ndt.naive_utc(),
-        None => return Err(LoginError::OtherError("Could not calculate offset for token expiry")),
-    };
-    jwt.exp = new_expire_date;
-    jwt.iat = Utc::now().naive_utc();
+/// Refresh tokens are the session's uuid followed by the random secret, which is only stored as a hash.
+fn format_refresh_token(session_uuid: UserSessionUuid, refresh_secret: &str) -> String {
+    format!("{}.{}", session_uuid, refresh_secret)
+}
 
-    let jwt = ServerJwt(jwt);
-    let jwt_string: String = match jwt.encode_jwt_string(&secret) {
-        Ok(s) => s,
-        Err(e) => return Err(LoginError::JwtError(e)),
-    };
+fn parse_refresh_token(refresh_token: &str) -> Result<(UserSessionUuid, &str), LoginError> {
+    let mut parts = refresh_token.splitn(2, '.');
+    match (parts.next(), parts.next()) {
+        (Some(uuid), Some(refresh_secret)) => UserSessionUuid::parse_str(uuid)
+            .map(|session_uuid| (session_uuid, refresh_secret))
+            .map_err(|_| LoginError::InvalidRefreshToken),
+        _ => Err(LoginError::InvalidRefreshToken),
+    }
+}
 
-    Ok(jwt_string)
+/// Reset tokens are built the same way as refresh tokens: the reset's uuid followed by the random secret.
+fn parse_reset_token(reset_token: &str) -> Result<(PasswordResetUuid, &str), LoginError> {
+    let mut parts = reset_token.splitn(2, '.');
+    match (parts.next(), parts.next()) {
+        (Some(uuid), Some(reset_secret)) => PasswordResetUuid::parse_str(uuid)
+            .map(|reset_uuid| (reset_uuid, reset_secret))
+            .map_err(|_| LoginError::InvalidResetToken),
+        _ => Err(LoginError::InvalidResetToken),
+    }
 }
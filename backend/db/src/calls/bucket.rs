@@ -1,10 +1,19 @@
 use chrono::{
-//    Duration,
+    Duration,
     NaiveDateTime,
-//    Utc,
+    Utc,
 };
 use crate::{
+    answer::{
+        Answer,
+        NewAnswer,
+    },
     calls::prelude::*,
+    question::{
+        NewQuestion,
+        Question,
+        QuestionData,
+    },
     schema::{
         self,
         buckets,
@@ -15,13 +24,23 @@ use crate::{
 use diesel::{
     self,
     prelude::*,
+    result::Error as DieselError,
+};
+use error::{
+    BackendResult,
+    Error,
 };
-use error::BackendResult;
 use identifiers::{
     bucket::BucketUuid,
+    question::QuestionUuid,
     user::UserUuid,
 };
+use std::collections::HashMap;
 use uuid::Uuid;
+use wire::bucket::{
+    DrawMode,
+    TurnMode,
+};
 
 #[derive(Debug, Clone, Identifiable, Queryable, TypeName)]
 #[primary_key(uuid)]
@@ -34,6 +53,24 @@ pub struct Bucket {
     /// The is public field indicates if the bucket will allow other users to request to join
     /// A None variant indicates that it is private, if it is a Some with a time in the future, it is public.
     pub is_public_until: Option<NaiveDateTime>,
+    /// The question every participant in the bucket's session is currently looking at.
+    pub current_question_uuid: Option<Uuid>,
+    /// How participants take turns, stored as the integer representation of a `TurnMode`.
+    pub turn_mode: i32,
+    /// How long each turn lasts, if turns are timed.
+    pub turn_seconds: Option<i32>,
+    /// The participant whose turn it is to draw.
+    pub turn_user_uuid: Option<Uuid>,
+    /// In hot seat mode, the participant chosen to answer the current question.
+    pub hot_seat_user_uuid: Option<Uuid>,
+    /// When the current turn is skipped if it hasn't finished.
+    pub turn_ends_at: Option<NaiveDateTime>,
+    /// How questions are drawn, stored as the integer representation of a `DrawMode`.
+    pub draw_mode: i32,
+    /// When the deck was last shuffled in the shuffle deck draw mode.
+    pub deck_started_at: Option<NaiveDateTime>,
+    /// Templates can be browsed and cloned by anyone.
+    pub is_template: bool,
 }
 
 #[derive(Insertable, Debug, Clone)]
@@ -53,7 +90,7 @@ pub struct BucketUser {
     pub bucket_id: Uuid,
     pub user_id: Uuid,
     pub owner: bool,
-//    pub approved: bool,
+    pub approved: bool,
 }
 
 /// A new entry into the bucket <-> user junction table.
@@ -63,7 +100,7 @@ pub struct NewBucketUser {
     pub bucket_uuid: Uuid,
     pub user_uuid: Uuid,
     pub owner: bool,
-//    pub approved: bool,
+    pub approved: bool,
 }
 
 #[derive(AsChangeset, Clone, Debug, PartialEq)]
@@ -72,7 +109,7 @@ pub struct NewBucketUser {
 pub struct BucketUserChangeset {
     pub uuid: Uuid,
     pub owner: bool,
-//    pub approved: bool,
+    pub approved: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -81,10 +118,92 @@ pub struct UsersInBucketData {
     pub users: Vec<User>,
 }
 
+/// The state of the game being played in the bucket, which is shared by all of its participants.
+#[derive(Debug)]
+pub struct BucketSessionData {
+    pub bucket: Bucket,
+    pub current_question: Option<QuestionData>,
+    /// The number of questions that are still available to be drawn.
+    pub questions_in_bucket: i64,
+    pub turn_user: Option<User>,
+    pub hot_seat_user: Option<User>,
+}
+
+/// How long a bucket can go without questions being drawn or answered before the next one starts a new session.
+const SESSION_GAP_MINUTES: i64 = 60;
+/// The number of questions listed in a bucket's most favorited questions.
+const MOST_FAVORITED_LENGTH: usize = 5;
+
+/// Statistics about how a bucket's questions have been used.
+#[derive(Debug)]
+pub struct BucketStatsData {
+    pub bucket: Bucket,
+    pub total_questions: i64,
+    pub questions_on_floor: i64,
+    pub questions_in_bucket: i64,
+    /// Participants who have answered questions, most answers first.
+    pub participants: Vec<ParticipantStatsData>,
+    /// Answers given without an author, which are never attributed to anyone.
+    pub anonymous_answers: i64,
+    pub most_favorited: Vec<FavoritedQuestionData>,
+    /// The bucket's past sessions, oldest first.
+    pub sessions: Vec<SessionSummary>,
+}
+
+#[derive(Debug)]
+pub struct ParticipantStatsData {
+    pub user: User,
+    pub answers: i64,
+}
+
+#[derive(Debug)]
+pub struct FavoritedQuestionData {
+    pub question: Question,
+    pub favorites: i64,
+}
+
+/// A stretch of activity in a bucket, where each draw or answer came within `SESSION_GAP_MINUTES` of the last.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionSummary {
+    pub started_at: NaiveDateTime,
+    pub ended_at: NaiveDateTime,
+    pub draws: i64,
+    pub answers: i64,
+}
+
+/// Something a participant does in a bucket's session, which may be reserved for a particular participant when taking turns.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SessionAction {
+    Draw,
+    Discard,
+    ChooseAnswerer,
+    Answer(QuestionUuid),
+}
+
 impl Bucket {
+    /// Returns true if the bucket is currently open to the public.
+    pub fn is_public(&self) -> bool {
+        match self.is_public_until {
+            Some(until) => Utc::now().naive_utc() < until,
+            None => false,
+        }
+    }
+
+    /// How participants take turns in the bucket's session.
+    pub fn turn_mode(&self) -> TurnMode {
+        TurnMode::from(self.turn_mode)
+    }
+
+    /// How questions are drawn from the bucket.
+    pub fn draw_mode(&self) -> DrawMode {
+        DrawMode::from(self.draw_mode)
+    }
+
     pub fn get_bucket(uuid: BucketUuid, conn: &PgConnection) -> BackendResult<Bucket> {
         get_row::<Bucket, _>(schema::buckets::table, uuid.0, conn)
     }
+    /// Deletes the bucket.
+    /// Its questions, their answers, and its participants are removed along with it.
     pub fn delete_bucket(uuid: BucketUuid, conn: &PgConnection) -> BackendResult<Bucket> {
         delete_row::<Bucket, _>(schema::buckets::table, uuid.0, conn)
     }
@@ -92,6 +211,62 @@ impl Bucket {
         create_row::<Bucket, NewBucket, _>(schema::buckets::table, new, conn)
     }
 
+    /// Creates the bucket and records the user who created it as its owner.
+    /// Both happen in the same transaction, so a bucket is never left without an owner.
+    pub fn create_bucket_with_owner(new: NewBucket, owner_uuid: UserUuid, conn: &PgConnection) -> BackendResult<Bucket> {
+        conn.transaction::<_, DieselError, _>(|| {
+            let bucket: Bucket = diesel::insert_into(buckets::table)
+                .values(&new)
+                .get_result(conn)?;
+            Self::set_owner(owner_uuid, BucketUuid(bucket.uuid), true, conn)?;
+            Ok(bucket)
+        })
+        .map_err(handle_err::<Bucket>)
+    }
+
+    /// Makes the user an owner of the bucket, alongside any existing owners.
+    pub fn add_owner(user_uuid: UserUuid, bucket_uuid: BucketUuid, conn: &PgConnection) -> BackendResult<()> {
+        // Reports a missing user as not found, instead of as a foreign key violation.
+        User::get_user(user_uuid, conn)?;
+        Self::set_owner(user_uuid, bucket_uuid, true, conn).map_err(handle_err::<Bucket>)
+    }
+
+    /// Hands the current owner's ownership of the bucket over to another user.
+    /// The previous owner remains an approved participant.
+    pub fn transfer_ownership(
+        current_owner_uuid: UserUuid,
+        new_owner_uuid: UserUuid,
+        bucket_uuid: BucketUuid,
+        conn: &PgConnection,
+    ) -> BackendResult<()> {
+        User::get_user(new_owner_uuid, conn)?;
+        conn.transaction::<_, DieselError, _>(|| {
+            Self::set_owner(new_owner_uuid, bucket_uuid, true, conn)?;
+            Self::set_owner(current_owner_uuid, bucket_uuid, false, conn)
+        })
+        .map_err(handle_err::<Bucket>)
+    }
+
+    /// Sets whether the user owns the bucket, adding them to the bucket if they aren't already in it.
+    /// Either way, the user is left approved to participate in the bucket.
+    fn set_owner(user_uuid: UserUuid, bucket_uuid: BucketUuid, owner: bool, conn: &PgConnection) -> Result<(), DieselError> {
+        use crate::schema::junction_bucket_users as junctions;
+
+        let new_bucket_user = NewBucketUser {
+            bucket_uuid: bucket_uuid.0,
+            user_uuid: user_uuid.0,
+            owner,
+            approved: true,
+        };
+        diesel::insert_into(junctions::table)
+            .values(&new_bucket_user)
+            .on_conflict((junctions::bucket_uuid, junctions::user_uuid))
+            .do_update()
+            .set((junctions::owner.eq(owner), junctions::approved.eq(true)))
+            .execute(conn)?;
+        Ok(())
+    }
+
     pub fn get_bucket_by_name(bucket_name: String, conn: &PgConnection) -> BackendResult<Bucket> {
         use crate::schema::buckets::dsl::{
             bucket_name as table_bucket_name,
@@ -131,45 +306,105 @@ impl Bucket {
             .map_err(handle_err::<Bucket>)
     }
 
-//    /// Get buckets that are public, but the user is not a member of
-//    #[deprecated]
-//    pub fn get_public_buckets(user_uuid: UserUuid, conn: &PgConnection) -> BackendResult<Vec<Bucket>> {
-//        use crate::schema::{
-//            buckets::{
-//                self,
-//                dsl::*,
-//            },
-//            junction_bucket_users as junctions,
-//            junction_bucket_users::dsl::junction_bucket_users,
-//        };
-//
-//        // Don't return any buckets with these ids
-//        let bucket_uuids_in_which_the_user_is_already_a_member_or_has_requested_to_join: Vec<Uuid> =
-//            junction_bucket_users
-//                .filter(junctions::user_uuid.eq(user_uuid.0))
-//                .select(junctions::bucket_uuid)
-//                .load::<Uuid>(conn)
-//                .map_err(handle_err::<User>)?;
-//
-//        buckets
-//            .filter(is_public_until.gt(Utc::now().naive_utc())) // Get buckets with an expiry date in the future.
-//            .filter(buckets::uuid.ne_all(bucket_uuids_in_which_the_user_is_already_a_member_or_has_requested_to_join)) // If buckets are in this set, don't return them
-//            .select(buckets::all_columns)
-//            .load::<Bucket>(conn)
-//            .map_err(handle_err::<User>)
-//    }
+    /// Get buckets that are public, but the user is not a member of.
+    /// Buckets the user has a pending request to join are included, as joining them no longer needs approval.
+    pub fn get_public_buckets(user_uuid: UserUuid, conn: &PgConnection) -> BackendResult<Vec<Bucket>> {
+        use crate::schema::{
+            buckets::{
+                self,
+                dsl::*,
+            },
+            junction_bucket_users as junctions,
+            junction_bucket_users::dsl::junction_bucket_users,
+        };
+
+        // Don't return any buckets with these ids
+        let bucket_uuids_in_which_the_user_is_already_a_member: Vec<Uuid> =
+            junction_bucket_users
+                .filter(junctions::user_uuid.eq(user_uuid.0))
+                .filter(junctions::approved.eq(true))
+                .select(junctions::bucket_uuid)
+                .load::<Uuid>(conn)
+                .map_err(handle_err::<User>)?;
+
+        buckets
+            .filter(is_public_until.gt(Utc::now().naive_utc())) // Get buckets with an expiry date in the future.
+            .filter(buckets::uuid.ne_all(bucket_uuids_in_which_the_user_is_already_a_member)) // If buckets are in this set, don't return them
+            .select(buckets::all_columns)
+            .load::<Bucket>(conn)
+            .map_err(handle_err::<User>)
+    }
 
     /// If a user joins a bucket, then they will be added to the bucket.
+    /// Adding a user that is already in the bucket leaves them as they were.
     pub fn add_user_to_bucket(new_bucket_user: NewBucketUser, conn: &PgConnection) -> BackendResult<()> {
         use crate::schema::junction_bucket_users;
 
         diesel::insert_into(junction_bucket_users::table)
             .values(&new_bucket_user)
+            .on_conflict((junction_bucket_users::bucket_uuid, junction_bucket_users::user_uuid))
+            .do_nothing()
             .execute(conn)
             .map_err(handle_err::<Bucket>)?;
         Ok(())
     }
 
+    /// Approves the user to participate in the bucket, adding them to the bucket if they aren't already in it.
+    /// This is meant to be part of a larger transaction.
+    pub(crate) fn join_approved(user_uuid: UserUuid, bucket_uuid: BucketUuid, conn: &PgConnection) -> Result<(), DieselError> {
+        use crate::schema::junction_bucket_users as junctions;
+
+        let new_bucket_user = NewBucketUser {
+            bucket_uuid: bucket_uuid.0,
+            user_uuid: user_uuid.0,
+            owner: false,
+            approved: true,
+        };
+        diesel::insert_into(junctions::table)
+            .values(&new_bucket_user)
+            .on_conflict((junctions::bucket_uuid, junctions::user_uuid))
+            .do_update()
+            .set(junctions::approved.eq(true))
+            .execute(conn)?;
+        Ok(())
+    }
+
+    /// Records a request by the user to join the bucket.
+    /// While the bucket is public, the user is approved immediately.
+    /// Otherwise, the user won't be able to participate until an owner approves the request.
+    ///
+    /// Returns whether the user has been approved.
+    pub fn request_to_join_bucket(user_uuid: UserUuid, bucket_uuid: BucketUuid, conn: &PgConnection) -> BackendResult<bool> {
+        use crate::schema::junction_bucket_users as junctions;
+
+        let bucket: Bucket = Self::get_bucket(bucket_uuid, conn)?;
+        let is_public = bucket.is_public();
+
+        let existing_approval: Option<bool> = junctions::table
+            .filter(junctions::bucket_uuid.eq(bucket_uuid.0))
+            .filter(junctions::user_uuid.eq(user_uuid.0))
+            .select(junctions::approved)
+            .first::<bool>(conn)
+            .optional()
+            .map_err(handle_err::<Bucket>)?;
+
+        match existing_approval {
+            Some(true) => Ok(true),
+            // A pending request made while the bucket was private is let through once it opens.
+            Some(false) if is_public => Self::set_user_approval(user_uuid, bucket_uuid, true, conn).map(|_| true),
+            Some(false) => Ok(false),
+            None => {
+                let new_bucket_user = NewBucketUser {
+                    bucket_uuid: bucket_uuid.0,
+                    user_uuid: user_uuid.0,
+                    owner: false,
+                    approved: is_public,
+                };
+                Self::add_user_to_bucket(new_bucket_user, conn).map(|_| is_public)
+            }
+        }
+    }
+
     /// If a user has visited a bucket before, the bucket name is saved for easy access via results returned from this call.
     pub fn get_buckets_user_belongs_to(user_uuid: UserUuid, conn: &PgConnection) -> BackendResult<Vec<Bucket>> {
         use crate::schema::{
@@ -185,85 +420,81 @@ impl Bucket {
             .map_err(handle_err::<User>)
     }
 
-//    /// Helper function.
-//    /// Gets users depending on the approval column.
-//    /// It will exclude the user making the request.
-//    #[deprecated]
-//    fn get_users_approval(
-//        bucket_uuid: BucketUuid,
-//        user_uuid: UserUuid,
-//        approval: bool,
-//        conn: &PgConnection,
-//    ) -> BackendResult<Vec<User>> {
-//        use crate::schema::{
-//            junction_bucket_users as junctions,
-//            junction_bucket_users::dsl::junction_bucket_users,
-//            users,
-//        };
-//
-//        junction_bucket_users
-//            .filter(junctions::bucket_uuid.eq(bucket_uuid.0))
-//            .filter(junctions::approved.eq(approval))
-//            .filter(junctions::user_uuid.ne(user_uuid.0))
-//            .inner_join(users::table)
-//            .select(users::all_columns)
-//            .load::<User>(conn)
-//            .map_err(handle_err::<Bucket>)
-//    }
-
-//    /// This function gets all players that are part of the bucket,
-//    /// excluding the active user
-//    #[deprecated]
-//    pub fn get_users_with_approval(
-//        bucket_uuid: BucketUuid,
-//        user_uuid: UserUuid,
-//        conn: &PgConnection,
-//    ) -> BackendResult<Vec<User>> {
-//        Self::get_users_approval(bucket_uuid, user_uuid, true, conn)
-//    }
-
-//    #[deprecated]
-//    fn get_users_requiring_approval(
-//        bucket_uuid: BucketUuid,
-//        user_uuid: UserUuid,
-//        conn: &PgConnection,
-//    ) -> BackendResult<Vec<User>> {
-//        Self::get_users_approval(bucket_uuid, user_uuid, false, conn)
-//    }
-//
-//    #[deprecated]
-//    pub fn get_users_requiring_approval_for_owned_buckets(
-//        bucket_owner_uuid: UserUuid,
-//        conn: &PgConnection,
-//    ) -> BackendResult<Vec<UsersInBucketData>> {
-//        use crate::schema::{
-//            buckets,
-//            junction_bucket_users as junctions,
-//            junction_bucket_users::dsl::*,
-//        };
-//
-//        let buckets: Vec<Bucket> = junction_bucket_users
-//            .filter(junctions::user_uuid.eq(bucket_owner_uuid.0))
-//            .filter(owner.eq(true))
-//            .inner_join(buckets::table)
-//            .select(buckets::all_columns)
-//            .load::<Bucket>(conn)
-//            .map_err(handle_err::<Bucket>)?;
-//
-//        // This is an ineffecient query. Its time will scale linearly (with a high constant) with the number of buckets the user owns.
-//        let bucket_users = buckets
-//            .into_iter()
-//            .filter_map(|bucket| {
-//                if let Ok(users) = Self::get_users_requiring_approval(BucketUuid(bucket.uuid), bucket_owner_uuid, conn)
-//                {
-//                    Some(UsersInBucketData { bucket, users })
-//                } else {
-//                    None
-//                }
-//            })
-//            .collect();
-//        Ok(bucket_users)
-//    }
+    /// Helper function.
+    /// Gets users depending on the approval column.
+    /// It will exclude the user making the request.
+    fn get_users_approval(
+        bucket_uuid: BucketUuid,
+        user_uuid: UserUuid,
+        approval: bool,
+        conn: &PgConnection,
+    ) -> BackendResult<Vec<User>> {
+        use crate::schema::{
+            junction_bucket_users as junctions,
+            junction_bucket_users::dsl::junction_bucket_users,
+            users,
+        };
+
+        junction_bucket_users
+            .filter(junctions::bucket_uuid.eq(bucket_uuid.0))
+            .filter(junctions::approved.eq(approval))
+            .filter(junctions::user_uuid.ne(user_uuid.0))
+            .inner_join(users::table)
+            .select(users::all_columns)
+            .load::<User>(conn)
+            .map_err(handle_err::<Bucket>)
+    }
+
+    /// This function gets all players that are part of the bucket,
+    /// excluding the active user
+    pub fn get_users_with_approval(
+        bucket_uuid: BucketUuid,
+        user_uuid: UserUuid,
+        conn: &PgConnection,
+    ) -> BackendResult<Vec<User>> {
+        Self::get_users_approval(bucket_uuid, user_uuid, true, conn)
+    }
+
+    fn get_users_requiring_approval(
+        bucket_uuid: BucketUuid,
+        user_uuid: UserUuid,
+        conn: &PgConnection,
+    ) -> BackendResult<Vec<User>> {
+        Self::get_users_approval(bucket_uuid, user_uuid, false, conn)
+    }
+
+    pub fn get_users_requiring_approval_for_owned_buckets(
+        bucket_owner_uuid: UserUuid,
+        conn: &PgConnection,
+    ) -> BackendResult<Vec<UsersInBucketData>> {
+        use crate::schema::{
+            buckets,
+            junction_bucket_users as junctions,
+            junction_bucket_users::dsl::*,
+        };
+
+        let buckets: Vec<Bucket> = junction_bucket_users
+            .filter(junctions::user_uuid.eq(bucket_owner_uuid.0))
+            .filter(owner.eq(true))
+            .inner_join(buckets::table)
+            .select(buckets::all_columns)
+            .load::<Bucket>(conn)
+            .map_err(handle_err::<Bucket>)?;
+
+        // This is an ineffecient query. Its time will scale linearly (with a high constant) with the number of buckets the user owns.
+        let bucket_users = buckets
+            .into_iter()
+            .filter_map(|bucket| {
+                if let Ok(users) = Self::get_users_requiring_approval(BucketUuid(bucket.uuid), bucket_owner_uuid, conn)
+                {
+                    Some(UsersInBucketData { bucket, users })
+                } else {
+                    None
+                }
+            })
+            .collect();
+        Ok(bucket_users)
+    }
 
     /// Is the user the owner of the bucket
     pub fn is_user_owner(user_uuid: UserUuid, bucket_uuid: BucketUuid, conn: &PgConnection) -> bool {
@@ -281,21 +512,21 @@ impl Bucket {
         //            .map_err(Bucket::handle_error)
     }
 
-//
-//    #[deprecated]
-//    pub fn is_user_approved(user_uuid: UserUuid, bucket_uuid: BucketUuid, conn: &PgConnection) -> bool {
-//        use crate::schema::{
-//            junction_bucket_users as junctions,
-//            junction_bucket_users::dsl::junction_bucket_users,
-//        };
-//
-//        junction_bucket_users
-//            .filter(junctions::user_uuid.eq(user_uuid.0))
-//            .filter(junctions::bucket_uuid.eq(bucket_uuid.0))
-//            .select(junctions::approved)
-//            .first::<bool>(conn)
-//            .unwrap_or(false)
-//    }
+    /// Is the user allowed to participate in the bucket.
+    /// Users who have requested to join, but have not yet been approved by an owner, are not.
+    pub fn is_user_approved(user_uuid: UserUuid, bucket_uuid: BucketUuid, conn: &PgConnection) -> bool {
+        use crate::schema::{
+            junction_bucket_users as junctions,
+            junction_bucket_users::dsl::junction_bucket_users,
+        };
+
+        junction_bucket_users
+            .filter(junctions::user_uuid.eq(user_uuid.0))
+            .filter(junctions::bucket_uuid.eq(bucket_uuid.0))
+            .select(junctions::approved)
+            .first::<bool>(conn)
+            .unwrap_or(false)
+    }
 
     #[deprecated]
     pub fn apply_changeset(changeset: BucketUserChangeset, conn: &PgConnection) -> BackendResult<BucketUser> {
@@ -307,57 +538,128 @@ impl Bucket {
             .map_err(handle_err::<Bucket>)
     }
 
-//    #[deprecated]
-//    pub fn set_bucket_publicity(bucket_uuid: BucketUuid, publicity: bool, conn: &PgConnection) -> BackendResult<()> {
-//        use crate::schema::buckets::{
-//            self,
-//            dsl::*,
-//        };
-//
-//        let target = buckets.filter(buckets::uuid.eq(bucket_uuid.0));
-//
-//        let expire_time: Option<NaiveDateTime> = if publicity {
-//            Some(Utc::now().naive_utc() + Duration::days(1))
-//        } else {
-//            None
-//        };
-//
-//        diesel::update(target)
-//            .set(buckets::is_public_until.eq(expire_time))
-//            .execute(conn)
-//            .map_err(handle_err::<Bucket>)?;
-//
-//        Ok(())
-//    }
-
-//    #[deprecated]
-//    pub fn set_user_approval(
-//        user_uuid: UserUuid,
-//        bucket_uuid: BucketUuid,
-//        approval: bool,
-//        conn: &PgConnection,
-//    ) -> BackendResult<()> {
-//        use crate::schema::{
-//            junction_bucket_users as junctions,
-//            junction_bucket_users::dsl::junction_bucket_users,
-//        };
-//
-//        let target = junction_bucket_users
-//            .filter(junctions::user_uuid.eq(user_uuid.0))
-//            .filter(junctions::bucket_uuid.eq(bucket_uuid.0));
-//
-//        diesel::update(target)
-//            .set(junctions::approved.eq(approval))
-//            .execute(conn)
-//            .map_err(handle_err::<Bucket>)?;
-//
-//        Ok(())
-//    }
+    /// Opens the bucket to the public until the given time, or makes it private if None.
+    pub fn set_bucket_publicity(
+        bucket_uuid: BucketUuid,
+        public_until: Option<NaiveDateTime>,
+        conn: &PgConnection,
+    ) -> BackendResult<Bucket> {
+        use crate::schema::buckets::{
+            self,
+            dsl::*,
+        };
+
+        let target = buckets.filter(buckets::uuid.eq(bucket_uuid.0));
+
+        diesel::update(target)
+            .set(buckets::is_public_until.eq(public_until))
+            .get_result(conn)
+            .map_err(handle_err::<Bucket>)
+    }
+
+    /// Publishes the bucket as a template, or withdraws it.
+    pub fn set_template(bucket_uuid: BucketUuid, is_template: bool, conn: &PgConnection) -> BackendResult<Bucket> {
+        diesel::update(buckets::table.filter(buckets::uuid.eq(bucket_uuid.0)))
+            .set(buckets::is_template.eq(is_template))
+            .get_result(conn)
+            .map_err(handle_err::<Bucket>)
+    }
+
+    /// Gets every bucket that has been published as a template, by name.
+    pub fn get_templates(conn: &PgConnection) -> BackendResult<Vec<Bucket>> {
+        buckets::table
+            .filter(buckets::is_template.eq(true))
+            .order(buckets::bucket_name.asc())
+            .load::<Bucket>(conn)
+            .map_err(handle_err::<Bucket>)
+    }
+
+    /// Creates a private bucket with the same questions as the source bucket, owned by the user who cloned it.
+    /// Every question starts out in the new bucket, even if it was on the floor in the source bucket.
+    /// If `include_answers` is set, each question's answers are copied along with it, keeping their authors.
+    pub fn clone_bucket(
+        source_uuid: BucketUuid,
+        bucket_name: String,
+        owner_uuid: UserUuid,
+        include_answers: bool,
+        conn: &PgConnection,
+    ) -> BackendResult<Bucket> {
+        use crate::schema::{
+            answers,
+            questions,
+        };
+
+        let source_questions: Vec<QuestionData> = Question::get_questions_for_bucket(source_uuid, conn)?;
+
+        conn.transaction::<_, DieselError, _>(|| {
+            let new_bucket = NewBucket {
+                bucket_name,
+                is_public_until: None,
+            };
+            let bucket: Bucket = diesel::insert_into(buckets::table)
+                .values(&new_bucket)
+                .get_result(conn)?;
+            Self::set_owner(owner_uuid, BucketUuid(bucket.uuid), true, conn)?;
+
+            for question_data in source_questions {
+                let new_question = NewQuestion {
+                    bucket_uuid: bucket.uuid,
+                    author_uuid: question_data.question.author_uuid,
+                    question_text: question_data.question.question_text,
+                    on_floor: false,
+                };
+                let question: Question = diesel::insert_into(questions::table)
+                    .values(&new_question)
+                    .get_result(conn)?;
+
+                if include_answers && !question_data.answers.is_empty() {
+                    let new_answers: Vec<NewAnswer> = question_data
+                        .answers
+                        .into_iter()
+                        .map(|answer_data| NewAnswer {
+                            author_uuid: answer_data.answer.author_uuid,
+                            question_uuid: question.uuid,
+                            answer_text: answer_data.answer.answer_text,
+                        })
+                        .collect();
+                    diesel::insert_into(answers::table)
+                        .values(&new_answers)
+                        .execute(conn)?;
+                }
+            }
+            Ok(bucket)
+        })
+        .map_err(handle_err::<Bucket>)
+    }
+
+    /// Approves or revokes the user's access to the bucket.
+    pub fn set_user_approval(
+        user_uuid: UserUuid,
+        bucket_uuid: BucketUuid,
+        approval: bool,
+        conn: &PgConnection,
+    ) -> BackendResult<()> {
+        use crate::schema::{
+            junction_bucket_users as junctions,
+            junction_bucket_users::dsl::junction_bucket_users,
+        };
+
+        let target = junction_bucket_users
+            .filter(junctions::user_uuid.eq(user_uuid.0))
+            .filter(junctions::bucket_uuid.eq(bucket_uuid.0));
+
+        diesel::update(target)
+            .set(junctions::approved.eq(approval))
+            .execute(conn)
+            .map_err(handle_err::<Bucket>)?;
+
+        Ok(())
+    }
 
     /// Removes the user from the junction table for the given bucket.
     /// This has the effect of denying any request to join the bucket, as well as kicking a user out of the bucket.
-
-    #[deprecated]
+    ///
+    /// If it was the user's turn, the turn passes to whoever came after them, so the session isn't left waiting on them.
     pub fn remove_user_from_bucket(
         user_uuid: UserUuid,
         bucket_uuid: BucketUuid,
@@ -365,11 +667,454 @@ impl Bucket {
     ) -> BackendResult<()> {
         use crate::schema::junction_bucket_users as junctions;
 
-        diesel::delete(junctions::table)
+        let bucket: Bucket = Self::get_bucket(bucket_uuid, conn)?;
+        let turn_order: Vec<Uuid> = Self::get_turn_order(bucket_uuid, conn)?;
+
+        conn.transaction::<_, DieselError, _>(|| {
+            diesel::delete(junctions::table)
+                .filter(junctions::bucket_uuid.eq(bucket_uuid.0))
+                .filter(junctions::user_uuid.eq(user_uuid.0))
+                .execute(conn)?;
+
+            if bucket.turn_mode() != TurnMode::FreeForAll && bucket.turn_user_uuid == Some(user_uuid.0) {
+                let next_user_uuid: Option<Uuid> =
+                    next_in_turn_order(&turn_order, user_uuid.0).filter(|next| *next != user_uuid.0);
+                Self::start_turn(&bucket, next_user_uuid, conn)?;
+            }
+            Ok(())
+        })
+        .map_err(handle_err::<Bucket>)
+    }
+
+    /// Gets the state of the bucket's session.
+    pub fn get_session(bucket_uuid: BucketUuid, conn: &PgConnection) -> BackendResult<BucketSessionData> {
+        let bucket: Bucket = Self::get_bucket(bucket_uuid, conn)?;
+        let current_question: Option<QuestionData> = bucket
+            .current_question_uuid
+            .map(|question_uuid| Question::get_full_question(QuestionUuid(question_uuid), conn))
+            .transpose()?;
+        let questions_in_bucket: i64 = Question::get_number_of_questions_in_bucket(bucket_uuid, conn)?;
+        let turn_user: Option<User> = bucket
+            .turn_user_uuid
+            .map(|user_uuid| User::get_user(UserUuid(user_uuid), conn))
+            .transpose()?;
+        let hot_seat_user: Option<User> = bucket
+            .hot_seat_user_uuid
+            .map(|user_uuid| User::get_user(UserUuid(user_uuid), conn))
+            .transpose()?;
+        Ok(BucketSessionData {
+            bucket,
+            current_question,
+            questions_in_bucket,
+            turn_user,
+            hot_seat_user,
+        })
+    }
+
+    /// Draws a random question from the bucket, making it the current question for every participant.
+    /// In hot seat mode, whoever was chosen to answer the previous question is cleared.
+    pub fn draw_question(bucket_uuid: BucketUuid, conn: &PgConnection) -> BackendResult<BucketSessionData> {
+        use crate::schema::buckets::{
+            self,
+            dsl::*,
+        };
+
+        let question: QuestionData = Question::get_random_question(bucket_uuid, conn)?;
+        diesel::update(buckets.filter(buckets::uuid.eq(bucket_uuid.0)))
+            .set((
+                current_question_uuid.eq(Some(question.question.uuid)),
+                hot_seat_user_uuid.eq(None::<Uuid>),
+            ))
+            .execute(conn)
+            .map_err(handle_err::<Bucket>)?;
+        Self::get_session(bucket_uuid, conn)
+    }
+
+    /// Deletes the current question, leaving the session without one.
+    pub fn discard_current_question(bucket_uuid: BucketUuid, conn: &PgConnection) -> BackendResult<BucketSessionData> {
+        let bucket: Bucket = Self::get_bucket(bucket_uuid, conn)?;
+        if let Some(question_uuid) = bucket.current_question_uuid {
+            // Deleting the question clears it from the bucket as well.
+            Question::delete_question(QuestionUuid(question_uuid), conn)?;
+        }
+        Self::get_session(bucket_uuid, conn)
+    }
+
+    /// Once the current question has been answered, the session no longer has a current question.
+    /// When taking turns, this ends the turn.
+    /// Does nothing if the question isn't the current question of its bucket.
+    pub fn finish_current_question(question_uuid: QuestionUuid, conn: &PgConnection) -> BackendResult<()> {
+        use crate::schema::buckets::dsl::*;
+
+        let bucket: Option<Bucket> = buckets
+            .filter(current_question_uuid.eq(question_uuid.0))
+            .first::<Bucket>(conn)
+            .optional()
+            .map_err(handle_err::<Bucket>)?;
+
+        match bucket {
+            Some(ref bucket) if bucket.turn_mode() != TurnMode::FreeForAll => {
+                Self::advance_turn(bucket, conn)
+            }
+            Some(bucket) => Self::set_current_question(BucketUuid(bucket.uuid), None, conn),
+            None => Ok(()),
+        }
+    }
+
+    /// Changes how participants take turns in the bucket's session.
+    /// The current question is cleared, and turns start over from the first participant.
+    pub fn set_turn_mode(
+        bucket_uuid: BucketUuid,
+        mode: TurnMode,
+        seconds: Option<i32>,
+        conn: &PgConnection,
+    ) -> BackendResult<BucketSessionData> {
+        use crate::schema::buckets::{
+            self,
+            dsl::*,
+        };
+
+        let (first_user_uuid, seconds) = match mode {
+            TurnMode::FreeForAll => (None, None),
+            TurnMode::RoundRobin | TurnMode::HotSeat => {
+                let participants: Vec<Uuid> = Self::get_turn_order(bucket_uuid, conn)?;
+                (participants.first().cloned(), seconds)
+            }
+        };
+
+        diesel::update(buckets.filter(buckets::uuid.eq(bucket_uuid.0)))
+            .set((
+                turn_mode.eq(i32::from(mode)),
+                turn_seconds.eq(seconds),
+                turn_user_uuid.eq(first_user_uuid),
+                hot_seat_user_uuid.eq(None::<Uuid>),
+                turn_ends_at.eq(Self::turn_end(seconds)),
+                current_question_uuid.eq(None::<Uuid>),
+            ))
+            .execute(conn)
+            .map_err(handle_err::<Bucket>)?;
+        Self::get_session(bucket_uuid, conn)
+    }
+
+    /// Changes how questions are drawn from the bucket.
+    /// Choosing the shuffle deck mode starts a new deck, containing every question in the bucket.
+    pub fn set_draw_mode(bucket_uuid: BucketUuid, mode: DrawMode, conn: &PgConnection) -> BackendResult<BucketSessionData> {
+        use crate::schema::buckets::{
+            self,
+            dsl::*,
+        };
+
+        let target = buckets.filter(buckets::uuid.eq(bucket_uuid.0));
+        let updated = match mode {
+            DrawMode::Fair => diesel::update(target)
+                .set(draw_mode.eq(i32::from(mode)))
+                .execute(conn),
+            DrawMode::ShuffleDeck => diesel::update(target)
+                .set((
+                    draw_mode.eq(i32::from(mode)),
+                    deck_started_at.eq(Some(Utc::now().naive_utc())),
+                ))
+                .execute(conn),
+        };
+        updated.map_err(handle_err::<Bucket>)?;
+        Self::get_session(bucket_uuid, conn)
+    }
+
+    /// In hot seat mode, records who the drawer chose to answer the current question.
+    /// The chosen user must be a participant in the bucket.
+    pub fn choose_answerer(
+        bucket_uuid: BucketUuid,
+        answerer_uuid: UserUuid,
+        conn: &PgConnection,
+    ) -> BackendResult<BucketSessionData> {
+        use crate::schema::buckets::{
+            self,
+            dsl::*,
+        };
+
+        let bucket: Bucket = Self::get_bucket(bucket_uuid, conn)?;
+        if bucket.current_question_uuid.is_none() || !Self::is_user_approved(answerer_uuid, bucket_uuid, conn) {
+            return Err(Error::BadRequest);
+        }
+
+        diesel::update(buckets.filter(buckets::uuid.eq(bucket_uuid.0)))
+            .set(hot_seat_user_uuid.eq(Some(answerer_uuid.0)))
+            .execute(conn)
+            .map_err(handle_err::<Bucket>)?;
+        Self::get_session(bucket_uuid, conn)
+    }
+
+    /// Checks that the action can be taken by the user right now.
+    ///
+    /// When the session is free for all, anyone can do anything.
+    /// Otherwise, only the participant whose turn it is can draw or discard questions.
+    /// In round robin mode they answer the question themselves,
+    /// while in hot seat mode they choose someone to answer it and only that participant can.
+    pub fn ensure_turn(
+        user_uuid: UserUuid,
+        bucket_uuid: BucketUuid,
+        action: SessionAction,
+        conn: &PgConnection,
+    ) -> BackendResult<()> {
+        let bucket: Bucket = Self::get_bucket(bucket_uuid, conn)?;
+        let is_turn_user = bucket.turn_user_uuid == Some(user_uuid.0);
+
+        match (bucket.turn_mode(), action) {
+            (TurnMode::FreeForAll, SessionAction::ChooseAnswerer) => Err(Error::BadRequest),
+            (TurnMode::FreeForAll, _) => Ok(()),
+            (TurnMode::RoundRobin, SessionAction::ChooseAnswerer) => Err(Error::BadRequest),
+            (_, SessionAction::Answer(question_uuid)) if bucket.current_question_uuid != Some(question_uuid.0) => {
+                Err(Error::OutOfTurn {
+                    reason: "Only the current question can be answered while taking turns",
+                })
+            }
+            (TurnMode::HotSeat, SessionAction::Answer(_)) => {
+                if bucket.hot_seat_user_uuid.is_none() {
+                    Err(Error::OutOfTurn {
+                        reason: "The drawer hasn't chosen who answers this question yet",
+                    })
+                } else if bucket.hot_seat_user_uuid == Some(user_uuid.0) {
+                    Ok(())
+                } else {
+                    Err(Error::OutOfTurn {
+                        reason: "Only the participant in the hot seat can answer this question",
+                    })
+                }
+            }
+            (_, _) if is_turn_user => Ok(()),
+            (_, _) => Err(Error::OutOfTurn {
+                reason: "Another participant is taking their turn",
+            }),
+        }
+    }
+
+    /// If the current turn has run out of time, it is skipped and the next participant's turn begins.
+    /// The question being answered is left in the bucket.
+    ///
+    /// Returns true if the turn was skipped.
+    pub fn skip_expired_turn(bucket_uuid: BucketUuid, conn: &PgConnection) -> BackendResult<bool> {
+        let bucket: Bucket = Self::get_bucket(bucket_uuid, conn)?;
+        match bucket.turn_ends_at {
+            Some(ends_at) if bucket.turn_mode() != TurnMode::FreeForAll && ends_at < Utc::now().naive_utc() => {
+                Self::advance_turn(&bucket, conn).map(|_| true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Skips every timed turn that has run out of time, in any bucket.
+    ///
+    /// Returns the buckets whose turns were skipped, so their participants can be told.
+    pub fn skip_expired_turns(conn: &PgConnection) -> BackendResult<Vec<BucketUuid>> {
+        use crate::schema::buckets::dsl::*;
+
+        let expired: Vec<Bucket> = buckets
+            .filter(turn_mode.ne(i32::from(TurnMode::FreeForAll)))
+            .filter(turn_ends_at.lt(Utc::now().naive_utc()))
+            .load::<Bucket>(conn)
+            .map_err(handle_err::<Bucket>)?;
+
+        expired
+            .into_iter()
+            .map(|bucket| Self::advance_turn(&bucket, conn).map(|_| BucketUuid(bucket.uuid)))
+            .collect()
+    }
+
+    /// Gathers statistics about the bucket's questions, answers, and past sessions.
+    pub fn get_stats(bucket_uuid: BucketUuid, conn: &PgConnection) -> BackendResult<BucketStatsData> {
+        use crate::schema::{
+            answers,
+            junction_favorite_questions_users as favorites,
+            question_draws,
+            questions,
+            users,
+        };
+
+        let bucket: Bucket = Self::get_bucket(bucket_uuid, conn)?;
+
+        let total_questions: i64 = Question::belonging_to(&bucket)
+            .count()
+            .get_result(conn)
+            .map_err(handle_err::<Question>)?;
+        let questions_in_bucket: i64 = Question::get_number_of_questions_in_bucket(bucket_uuid, conn)?;
+
+        let answers: Vec<(Option<Uuid>, Option<NaiveDateTime>)> = answers::table
+            .inner_join(questions::table)
+            .filter(questions::bucket_uuid.eq(bucket.uuid))
+            .select((answers::author_uuid, answers::created_at))
+            .load(conn)
+            .map_err(handle_err::<Answer>)?;
+
+        let mut answer_counts: HashMap<Uuid, i64> = HashMap::new();
+        let mut anonymous_answers: i64 = 0;
+        for (author_uuid, _) in &answers {
+            match author_uuid {
+                Some(author_uuid) => *answer_counts.entry(*author_uuid).or_insert(0) += 1,
+                None => anonymous_answers += 1,
+            }
+        }
+        let authors: Vec<Uuid> = answer_counts.keys().cloned().collect();
+        let mut participants: Vec<ParticipantStatsData> = users::table
+            .filter(users::uuid.eq_any(authors))
+            .load::<User>(conn)
+            .map_err(handle_err::<User>)?
+            .into_iter()
+            .map(|user| ParticipantStatsData {
+                answers: answer_counts[&user.uuid],
+                user,
+            })
+            .collect();
+        participants.sort_by(|a, b| b.answers.cmp(&a.answers).then_with(|| a.user.display_name.cmp(&b.user.display_name)));
+
+        let favorited: Vec<Uuid> = favorites::table
+            .inner_join(questions::table)
+            .filter(questions::bucket_uuid.eq(bucket.uuid))
+            .select(favorites::question_uuid)
+            .load(conn)
+            .map_err(handle_err::<Question>)?;
+        let mut favorite_counts: HashMap<Uuid, i64> = HashMap::new();
+        for question_uuid in favorited {
+            *favorite_counts.entry(question_uuid).or_insert(0) += 1;
+        }
+        let favorited_uuids: Vec<Uuid> = favorite_counts.keys().cloned().collect();
+        let mut most_favorited: Vec<FavoritedQuestionData> = questions::table
+            .filter(questions::uuid.eq_any(favorited_uuids))
+            .load::<Question>(conn)
+            .map_err(handle_err::<Question>)?
+            .into_iter()
+            .map(|question| FavoritedQuestionData {
+                favorites: favorite_counts[&question.uuid],
+                question,
+            })
+            .collect();
+        most_favorited.sort_by(|a, b| b.favorites.cmp(&a.favorites).then_with(|| a.question.question_text.cmp(&b.question.question_text)));
+        most_favorited.truncate(MOST_FAVORITED_LENGTH);
+
+        let draws: Vec<NaiveDateTime> = question_draws::table
+            .filter(question_draws::bucket_uuid.eq(bucket.uuid))
+            .select(question_draws::drawn_at)
+            .load(conn)
+            .map_err(handle_err::<Question>)?;
+        // Answers given before answers were timestamped can't be placed in a session.
+        let answer_times: Vec<NaiveDateTime> = answers.into_iter().filter_map(|(_, created_at)| created_at).collect();
+        let sessions: Vec<SessionSummary> = summarize_sessions(draws, answer_times);
+
+        Ok(BucketStatsData {
+            bucket,
+            total_questions,
+            questions_on_floor: total_questions - questions_in_bucket,
+            questions_in_bucket,
+            participants,
+            anonymous_answers,
+            most_favorited,
+            sessions,
+        })
+    }
+
+    /// Gives the turn to the next participant, clearing the current question.
+    fn advance_turn(bucket: &Bucket, conn: &PgConnection) -> BackendResult<()> {
+        let participants: Vec<Uuid> = Self::get_turn_order(BucketUuid(bucket.uuid), conn)?;
+        let next_user_uuid: Option<Uuid> = bucket
+            .turn_user_uuid
+            .and_then(|current| next_in_turn_order(&participants, current))
+            .or_else(|| participants.first().cloned());
+
+        Self::start_turn(bucket, next_user_uuid, conn).map_err(handle_err::<Bucket>)
+    }
+
+    /// Gives the turn to the user, clearing anything left over from the previous turn.
+    fn start_turn(bucket: &Bucket, next_user_uuid: Option<Uuid>, conn: &PgConnection) -> Result<(), DieselError> {
+        use crate::schema::buckets::{
+            self,
+            dsl::*,
+        };
+
+        diesel::update(buckets.filter(buckets::uuid.eq(bucket.uuid)))
+            .set((
+                turn_user_uuid.eq(next_user_uuid),
+                hot_seat_user_uuid.eq(None::<Uuid>),
+                turn_ends_at.eq(Self::turn_end(bucket.turn_seconds)),
+                current_question_uuid.eq(None::<Uuid>),
+            ))
+            .execute(conn)?;
+        Ok(())
+    }
+
+    /// The order participants take turns in, which is by user name.
+    fn get_turn_order(bucket_uuid: BucketUuid, conn: &PgConnection) -> BackendResult<Vec<Uuid>> {
+        use crate::schema::{
+            junction_bucket_users as junctions,
+            users,
+        };
+
+        junctions::table
             .filter(junctions::bucket_uuid.eq(bucket_uuid.0))
-            .filter(junctions::user_uuid.eq(user_uuid.0))
+            .filter(junctions::approved.eq(true))
+            .inner_join(users::table)
+            .order(users::user_name.asc())
+            .select(users::uuid)
+            .load::<Uuid>(conn)
+            .map_err(handle_err::<Bucket>)
+    }
+
+    /// When a turn starting now ends, if turns are timed.
+    fn turn_end(seconds: Option<i32>) -> Option<NaiveDateTime> {
+        seconds.map(|seconds| Utc::now().naive_utc() + Duration::seconds(i64::from(seconds)))
+    }
+
+    fn set_current_question(bucket_uuid: BucketUuid, question_uuid: Option<Uuid>, conn: &PgConnection) -> BackendResult<()> {
+        use crate::schema::buckets::{
+            self,
+            dsl::*,
+        };
+
+        diesel::update(buckets.filter(buckets::uuid.eq(bucket_uuid.0)))
+            .set(current_question_uuid.eq(question_uuid))
             .execute(conn)
             .map_err(handle_err::<Bucket>)?;
         Ok(())
     }
 }
+
+/// The participant who comes after the given one in the turn order, wrapping around to the first.
+fn next_in_turn_order(participants: &[Uuid], current: Uuid) -> Option<Uuid> {
+    participants
+        .iter()
+        .position(|participant| *participant == current)
+        .map(|index| participants[(index + 1) % participants.len()])
+}
+
+/// Splits the bucket's draws and answers into sessions, wherever there was a gap of more than `SESSION_GAP_MINUTES`.
+fn summarize_sessions(draws: Vec<NaiveDateTime>, answers: Vec<NaiveDateTime>) -> Vec<SessionSummary> {
+    let mut activity: Vec<(NaiveDateTime, bool)> = draws
+        .into_iter()
+        .map(|drawn_at| (drawn_at, true))
+        .chain(answers.into_iter().map(|answered_at| (answered_at, false)))
+        .collect();
+    activity.sort();
+
+    let gap = Duration::minutes(SESSION_GAP_MINUTES);
+    let mut sessions: Vec<SessionSummary> = vec![];
+    for (time, is_draw) in activity {
+        let continues_session = sessions
+            .last()
+            .map(|session| time - session.ended_at <= gap)
+            .unwrap_or(false);
+        if !continues_session {
+            sessions.push(SessionSummary {
+                started_at: time,
+                ended_at: time,
+                draws: 0,
+                answers: 0,
+            });
+        }
+        let session = sessions.last_mut().expect("A session was just pushed if there wasn't one");
+        session.ended_at = time;
+        if is_draw {
+            session.draws += 1;
+        } else {
+            session.answers += 1;
+        }
+    }
+    sessions
+}
@@ -0,0 +1,145 @@
+use chrono::{
+    NaiveDateTime,
+    Utc,
+};
+use crate::{
+    bucket::Bucket,
+    calls::prelude::*,
+    schema::{
+        self,
+        bucket_invites,
+    },
+    user::User,
+};
+use diesel::{
+    self,
+    dsl::sql,
+    expression::SqlLiteral,
+    prelude::*,
+    result::Error as DieselError,
+    sql_types::Bool,
+};
+use error::{
+    BackendResult,
+    Error,
+};
+use identifiers::{
+    bucket::BucketUuid,
+    bucket_invite::BucketInviteUuid,
+    user::UserUuid,
+};
+use uuid::Uuid;
+
+/// A link that adds whoever holds it to a bucket, skipping the approval step.
+#[derive(Debug, Clone, Identifiable, Queryable, Associations, TypeName)]
+#[primary_key(uuid)]
+#[table_name = "bucket_invites"]
+#[belongs_to(Bucket, foreign_key = "bucket_uuid")]
+#[belongs_to(User, foreign_key = "creator_uuid")]
+pub struct BucketInvite {
+    /// Primary Key.
+    pub uuid: Uuid,
+    pub bucket_uuid: Uuid,
+    /// The owner who created the invite.
+    pub creator_uuid: Uuid,
+    /// The hash of the random part of the invite's token.
+    pub token_hash: String,
+    /// How many times the invite can be used. It can be used any number of times if None.
+    pub max_uses: Option<i32>,
+    /// How many users have joined the bucket with the invite.
+    pub uses: i32,
+    /// When the invite stops working. It never expires if None.
+    pub expires_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[table_name = "bucket_invites"]
+pub struct NewBucketInvite {
+    pub bucket_uuid: Uuid,
+    pub creator_uuid: Uuid,
+    pub token_hash: String,
+    pub max_uses: Option<i32>,
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+impl BucketInvite {
+    pub fn get_invite(uuid: BucketInviteUuid, conn: &PgConnection) -> BackendResult<BucketInvite> {
+        get_row::<BucketInvite, _>(schema::bucket_invites::table, uuid.0, conn)
+    }
+    pub fn create_invite(new: NewBucketInvite, conn: &PgConnection) -> BackendResult<BucketInvite> {
+        create_row::<BucketInvite, NewBucketInvite, _>(schema::bucket_invites::table, new, conn)
+    }
+    /// Revoking an invite deletes it, so the links made from it stop working.
+    pub fn revoke_invite(uuid: BucketInviteUuid, conn: &PgConnection) -> BackendResult<BucketInvite> {
+        delete_row::<BucketInvite, _>(schema::bucket_invites::table, uuid.0, conn)
+    }
+
+    /// Gets the bucket's invites that haven't expired or been used up, newest first.
+    pub fn get_active_invites(bucket_uuid: BucketUuid, conn: &PgConnection) -> BackendResult<Vec<BucketInvite>> {
+        let now: NaiveDateTime = Utc::now().naive_utc();
+        bucket_invites::table
+            .filter(bucket_invites::bucket_uuid.eq(bucket_uuid.0))
+            .filter(has_uses_left())
+            .filter(bucket_invites::expires_at.is_null().or(bucket_invites::expires_at.gt(now)))
+            .order(bucket_invites::created_at.desc())
+            .load::<BucketInvite>(conn)
+            .map_err(handle_err::<BucketInvite>)
+    }
+
+    /// Adds the user to the invite's bucket as an approved participant.
+    ///
+    /// If the user had already asked to join, their request is approved.
+    /// Users who are already participants don't use up the invite.
+    /// The use is only counted if the user is added to the bucket.
+    pub fn redeem_invite(
+        uuid: BucketInviteUuid,
+        token_hash: &str,
+        user_uuid: UserUuid,
+        conn: &PgConnection,
+    ) -> BackendResult<Bucket> {
+        let invite: BucketInvite = Self::get_invite(uuid, conn)?;
+        if invite.token_hash != token_hash {
+            return Err(Error::NotAuthorized {
+                reason: "The invite link is not valid",
+            });
+        }
+        let bucket_uuid = BucketUuid(invite.bucket_uuid);
+        if Bucket::is_user_approved(user_uuid, bucket_uuid, conn) {
+            return Bucket::get_bucket(bucket_uuid, conn);
+        }
+
+        let redeemed: bool = conn
+            .transaction::<_, DieselError, _>(|| {
+                // The use is counted in the same statement that checks the invite is still active,
+                // so concurrent redemptions can't exceed its maximum uses.
+                let now: NaiveDateTime = Utc::now().naive_utc();
+                let target = bucket_invites::table
+                    .filter(bucket_invites::uuid.eq(uuid.0))
+                    .filter(has_uses_left())
+                    .filter(bucket_invites::expires_at.is_null().or(bucket_invites::expires_at.gt(now)));
+                let counted: usize = diesel::update(target)
+                    .set(bucket_invites::uses.eq(bucket_invites::uses + 1))
+                    .execute(conn)?;
+                if counted == 0 {
+                    return Ok(false);
+                }
+
+                Bucket::join_approved(user_uuid, bucket_uuid, conn)?;
+                Ok(true)
+            })
+            .map_err(handle_err::<BucketInvite>)?;
+        if !redeemed {
+            return Err(Error::NotAuthorized {
+                reason: "The invite has expired or has been used up",
+            });
+        }
+
+        Bucket::get_bucket(bucket_uuid, conn)
+    }
+}
+
+/// Invites without a maximum number of uses never run out.
+fn has_uses_left() -> SqlLiteral<Bool> {
+    sql::<Bool>("(bucket_invites.max_uses IS NULL OR bucket_invites.uses < bucket_invites.max_uses)")
+}
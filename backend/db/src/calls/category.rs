@@ -0,0 +1,43 @@
+use crate::{
+    calls::prelude::*,
+    schema::{
+        self,
+        categories,
+    },
+};
+use diesel::pg::PgConnection;
+use error::BackendResult;
+use identifiers::category::CategoryUuid;
+use uuid::Uuid;
+
+/// A category that articles can be filed under.
+#[derive(Debug, Clone, Identifiable, Queryable, TypeName)]
+#[primary_key(uuid)]
+#[table_name = "categories"]
+pub struct Category {
+    /// Primary Key.
+    pub uuid: Uuid,
+    /// Displayed name of the category.
+    pub name: String,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "categories"]
+pub struct NewCategory {
+    pub name: String,
+}
+
+impl Category {
+    pub fn get_category(uuid: CategoryUuid, conn: &PgConnection) -> BackendResult<Category> {
+        get_row::<Category, _>(schema::categories::table, uuid.0, conn)
+    }
+    pub fn get_categories(conn: &PgConnection) -> BackendResult<Vec<Category>> {
+        get_rows::<Category, _>(schema::categories::table, conn)
+    }
+    pub fn delete_category(uuid: CategoryUuid, conn: &PgConnection) -> BackendResult<Category> {
+        delete_row::<Category, _>(schema::categories::table, uuid.0, conn)
+    }
+    pub fn create_category(new: NewCategory, conn: &PgConnection) -> BackendResult<Category> {
+        create_row::<Category, NewCategory, _>(schema::categories::table, new, conn)
+    }
+}
@@ -5,7 +5,15 @@ use crate::{
         forums,
     },
 };
-use diesel::pg::PgConnection;
+use diesel::{
+    self,
+    pg::PgConnection,
+    result::Error as DieselError,
+    ExpressionMethods,
+    OptionalExtension,
+    QueryDsl,
+    RunQueryDsl,
+};
 use error::BackendResult;
 use identifiers::forum::ForumUuid;
 use uuid::Uuid;
@@ -42,4 +50,20 @@ impl Forum {
     pub fn create_forum(new: NewForum, conn: &PgConnection) -> BackendResult<Forum> {
         create_row::<Forum, NewForum, _>(schema::forums::table, new, conn)
     }
+
+    /// Gets the forum with the given title, creating it if it doesn't exist yet.
+    /// This is meant to be part of a larger transaction.
+    pub(crate) fn get_or_create_forum_by_title(new: NewForum, conn: &PgConnection) -> Result<Forum, DieselError> {
+        use crate::schema::forums::dsl::*;
+
+        let existing: Option<Forum> = forums
+            .filter(title.eq(&new.title))
+            .first::<Forum>(conn)
+            .optional()?;
+
+        match existing {
+            Some(forum) => Ok(forum),
+            None => diesel::insert_into(forums).values(&new).get_result(conn),
+        }
+    }
 }
@@ -146,7 +146,7 @@ impl Message {
         page_index: i32,
         page_size: i32,
         conn: &PgConnection,
-    ) -> BackendResult<Vec<MessageData>> {
+    ) -> BackendResult<PaginatedData<MessageData>> {
         //        use schema::messages::dsl::*;
         use crate::schema::{
             messages,
@@ -156,15 +156,16 @@ impl Message {
 
         //        let m_chat_id: Uuid = m_chat_id.0;
 
-        let (messages_and_users, _count): (Vec<(Message, User)>, i64) = messages::table
+        let mut page: PaginatedData<(Message, User)> = messages::table
             .inner_join(users::table)
             .order(messages::create_date)
             .filter(messages::chat_uuid.eq(chat_uuid.0))
             .select((messages::all_columns, users::all_columns))
             .paginate(page_index.into())
             .per_page(page_size.into())
-            .load_and_count_pages::<(Message, User)>(conn) // Apparently just `load` doesn't work, so we use this instead and throw away the count.
+            .load_page::<(Message, User)>(conn)
             .map_err(handle_err::<Message>)?;
+        let messages_and_users: Vec<(Message, User)> = std::mem::replace(&mut page.items, vec![]);
 
         let collected_messages: Vec<Message> = messages_and_users.iter().map(|x| x.0.clone()).collect();
 
@@ -193,6 +194,6 @@ impl Message {
             })
             .collect::<Vec<MessageData>>();
 
-        Ok(message_data)
+        Ok(page.with_items(message_data))
     }
 }
@@ -0,0 +1,75 @@
+use chrono::{
+    NaiveDateTime,
+    Utc,
+};
+use crate::{
+    calls::prelude::*,
+    schema::password_resets,
+    user::User,
+};
+use diesel::{
+    self,
+    prelude::*,
+    result::Error as DieselError,
+};
+use error::BackendResult;
+use identifiers::password_reset::PasswordResetUuid;
+use uuid::Uuid;
+
+/// A token that lets the user set a new password without knowing their current one.
+#[derive(Debug, Clone, Identifiable, Queryable, Associations, TypeName)]
+#[primary_key(uuid)]
+#[table_name = "password_resets"]
+#[belongs_to(User, foreign_key = "user_uuid")]
+pub struct PasswordReset {
+    /// Primary Key.
+    pub uuid: Uuid,
+    pub user_uuid: Uuid,
+    /// The hash of the token that was mailed to the user.
+    pub token_hash: String,
+    pub created_at: NaiveDateTime,
+    /// The token stops working at this time.
+    pub expires_at: NaiveDateTime,
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[table_name = "password_resets"]
+pub struct NewPasswordReset {
+    pub user_uuid: Uuid,
+    pub token_hash: String,
+    pub expires_at: NaiveDateTime,
+}
+
+impl PasswordReset {
+    /// Creates a reset token, replacing any the user had already been sent,
+    /// so only the most recent email can be used.
+    pub fn create_reset(new: NewPasswordReset, conn: &PgConnection) -> BackendResult<PasswordReset> {
+        conn.transaction::<_, DieselError, _>(|| {
+            diesel::delete(password_resets::table.filter(password_resets::user_uuid.eq(new.user_uuid))).execute(conn)?;
+            diesel::insert_into(password_resets::table)
+                .values(&new)
+                .get_result::<PasswordReset>(conn)
+        })
+        .map_err(handle_err::<PasswordReset>)
+    }
+
+    /// Uses up the reset token, as long as it hasn't already been used or expired.
+    ///
+    /// The token is deleted in the same statement that checks it, so it can't be used twice.
+    /// Returns None if the token was already used, was replaced, or expired.
+    pub fn redeem_reset(
+        uuid: PasswordResetUuid,
+        token_hash: &str,
+        conn: &PgConnection,
+    ) -> BackendResult<Option<PasswordReset>> {
+        let now: NaiveDateTime = Utc::now().naive_utc();
+        let target = password_resets::table
+            .filter(password_resets::uuid.eq(uuid.0))
+            .filter(password_resets::token_hash.eq(token_hash))
+            .filter(password_resets::expires_at.gt(now));
+        diesel::delete(target)
+            .get_result::<PasswordReset>(conn)
+            .optional()
+            .map_err(handle_err::<PasswordReset>)
+    }
+}
@@ -0,0 +1,308 @@
+use chrono::{
+    NaiveDateTime,
+    Utc,
+};
+use crate::{
+    calls::prelude::*,
+    schema::{
+        self,
+        poll_options,
+        poll_votes,
+        polls,
+    },
+    thread::Thread,
+    user::User,
+};
+use diesel::{
+    self,
+    result::Error as DieselError,
+    BelongingToDsl,
+    Connection,
+    ExpressionMethods,
+    OptionalExtension,
+    PgConnection,
+    QueryDsl,
+    RunQueryDsl,
+};
+use error::{
+    BackendResult,
+    Error,
+};
+use identifiers::{
+    poll::PollUuid,
+    poll_option::PollOptionUuid,
+    thread::ThreadUuid,
+    user::UserUuid,
+};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Identifiable, Associations, Queryable, TypeName)]
+#[primary_key(uuid)]
+#[belongs_to(Thread, foreign_key = "thread_uuid")]
+#[table_name = "polls"]
+pub struct Poll {
+    /// Primary Key
+    pub uuid: Uuid,
+    /// The Foreign Key of the thread the poll is attached to.
+    pub thread_uuid: Uuid,
+    /// The question being asked.
+    pub question: String,
+    /// If true, users can vote for more than one option.
+    pub multiple_choice: bool,
+    /// If present, votes are not accepted after this time.
+    pub closes_at: Option<NaiveDateTime>,
+    /// If true, the tallies are not shown until the poll is closed.
+    pub hide_results_until_closed: bool,
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[table_name = "polls"]
+pub struct NewPoll {
+    pub thread_uuid: Uuid,
+    pub question: String,
+    pub multiple_choice: bool,
+    pub closes_at: Option<NaiveDateTime>,
+    pub hide_results_until_closed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Identifiable, Associations, Queryable, TypeName)]
+#[primary_key(uuid)]
+#[belongs_to(Poll, foreign_key = "poll_uuid")]
+#[table_name = "poll_options"]
+pub struct PollOption {
+    /// Primary Key
+    pub uuid: Uuid,
+    /// The Foreign Key of the poll the option belongs to.
+    pub poll_uuid: Uuid,
+    /// The order in which the option is displayed.
+    pub position: i32,
+    /// The text of the option.
+    pub content: String,
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[table_name = "poll_options"]
+pub struct NewPollOption {
+    pub poll_uuid: Uuid,
+    pub position: i32,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Identifiable, Associations, Queryable, TypeName)]
+#[primary_key(uuid)]
+#[belongs_to(User, foreign_key = "user_uuid")]
+#[belongs_to(Poll, foreign_key = "poll_uuid")]
+#[belongs_to(PollOption, foreign_key = "option_uuid")]
+#[table_name = "poll_votes"]
+pub struct PollVote {
+    /// Primary Key
+    pub uuid: Uuid,
+    /// Foreign Key
+    pub poll_uuid: Uuid,
+    /// Foreign Key
+    pub option_uuid: Uuid,
+    /// Foreign Key
+    pub user_uuid: Uuid,
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[table_name = "poll_votes"]
+struct NewPollVote {
+    pub poll_uuid: Uuid,
+    pub option_uuid: Uuid,
+    pub user_uuid: Uuid,
+}
+
+/// The poll, its options alongside their tallies, and the options the requesting user voted for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PollData {
+    pub poll: Poll,
+    pub options: Vec<(PollOption, i64)>,
+    pub user_votes: Vec<Uuid>,
+}
+
+impl Poll {
+    /// Returns true if the poll is no longer accepting votes.
+    pub fn is_closed(&self) -> bool {
+        match self.closes_at {
+            Some(closes_at) => closes_at <= Utc::now().naive_utc(),
+            None => false,
+        }
+    }
+
+    pub fn get_poll(uuid: PollUuid, conn: &PgConnection) -> BackendResult<Poll> {
+        get_row::<Poll, _>(schema::polls::table, uuid.0, conn)
+    }
+
+    /// Checks that a poll with these settings can be created.
+    ///
+    /// It needs at least two options, and can't close before it is created.
+    /// Results can only be hidden until the poll closes if it does close.
+    pub(crate) fn validate_new_poll(
+        option_count: usize,
+        closes_at: Option<NaiveDateTime>,
+        hide_results_until_closed: bool,
+    ) -> Result<(), Error> {
+        if option_count < 2 {
+            return Err(Error::BadRequest);
+        }
+        match closes_at {
+            Some(closes_at) if closes_at <= Utc::now().naive_utc() => Err(Error::BadRequest),
+            None if hide_results_until_closed => Err(Error::BadRequest),
+            _ => Ok(()),
+        }
+    }
+
+    /// Creates a poll along with its options.
+    /// The options are displayed in the order they are provided.
+    pub fn create_poll(new_poll: NewPoll, options: Vec<String>, conn: &PgConnection) -> BackendResult<PollData> {
+        Poll::validate_new_poll(options.len(), new_poll.closes_at, new_poll.hide_results_until_closed)?;
+
+        let (poll, options) = conn
+            .transaction::<_, DieselError, _>(|| Poll::insert_poll(new_poll, options, conn))
+            .map_err(handle_err::<Poll>)?;
+
+        Ok(PollData {
+            poll,
+            options: options.into_iter().map(|option| (option, 0)).collect(),
+            user_votes: vec![],
+        })
+    }
+
+    /// Inserts the poll and its options, so it can be part of a larger transaction.
+    /// Callers are responsible for validating the poll with `validate_new_poll`.
+    pub(crate) fn insert_poll(
+        new_poll: NewPoll,
+        options: Vec<String>,
+        conn: &PgConnection,
+    ) -> Result<(Poll, Vec<PollOption>), DieselError> {
+        let poll: Poll = diesel::insert_into(polls::table)
+            .values(&new_poll)
+            .get_result(conn)?;
+
+        let new_options: Vec<NewPollOption> = options
+            .into_iter()
+            .enumerate()
+            .map(|(position, content)| NewPollOption {
+                poll_uuid: poll.uuid,
+                position: position as i32,
+                content,
+            })
+            .collect();
+
+        let options: Vec<PollOption> = diesel::insert_into(poll_options::table)
+            .values(&new_options)
+            .get_results(conn)?;
+        Ok((poll, options))
+    }
+
+    /// Gets the poll attached to the thread, if there is one.
+    pub fn get_poll_data_for_thread(
+        thread_uuid: ThreadUuid,
+        user_uuid: Option<UserUuid>,
+        conn: &PgConnection,
+    ) -> BackendResult<Option<PollData>> {
+        polls::table
+            .filter(polls::thread_uuid.eq(thread_uuid.0))
+            .first::<Poll>(conn)
+            .optional()
+            .map_err(handle_err::<Poll>)?
+            .map(|poll| Poll::get_poll_data(poll, user_uuid, conn))
+            .transpose()
+    }
+
+    /// Gets the options and tallies for the poll.
+    fn get_poll_data(poll: Poll, user_uuid: Option<UserUuid>, conn: &PgConnection) -> BackendResult<PollData> {
+        let options: Vec<PollOption> = PollOption::belonging_to(&poll)
+            .order(poll_options::position)
+            .load::<PollOption>(conn)
+            .map_err(handle_err::<PollOption>)?;
+
+        let votes: Vec<PollVote> = PollVote::belonging_to(&poll)
+            .load::<PollVote>(conn)
+            .map_err(handle_err::<PollVote>)?;
+
+        let user_votes: Vec<Uuid> = match user_uuid {
+            Some(user_uuid) => votes
+                .iter()
+                .filter(|vote| vote.user_uuid == user_uuid.0)
+                .map(|vote| vote.option_uuid)
+                .collect(),
+            None => vec![],
+        };
+
+        let options: Vec<(PollOption, i64)> = options
+            .into_iter()
+            .map(|option| {
+                let count = votes.iter().filter(|vote| vote.option_uuid == option.uuid).count() as i64;
+                (option, count)
+            })
+            .collect();
+
+        Ok(PollData {
+            poll,
+            options,
+            user_votes,
+        })
+    }
+
+    /// Records the user's vote, replacing any vote they had already cast on the poll.
+    ///
+    /// Votes are rejected if the poll has closed, or if the thread it belongs to is locked or archived.
+    pub fn vote(
+        poll_uuid: PollUuid,
+        user_uuid: UserUuid,
+        option_uuids: Vec<PollOptionUuid>,
+        conn: &PgConnection,
+    ) -> BackendResult<PollData> {
+        let poll: Poll = Poll::get_poll(poll_uuid, conn)?;
+
+        let thread: Thread = Thread::get_thread(ThreadUuid(poll.thread_uuid), conn)?;
+        if thread.locked || thread.archived {
+            return Err(Error::ThreadImmutable);
+        }
+        if poll.is_closed() {
+            return Err(Error::BadRequest);
+        }
+
+        if option_uuids.is_empty() || (!poll.multiple_choice && option_uuids.len() > 1) {
+            return Err(Error::BadRequest);
+        }
+
+        let options: Vec<PollOption> = PollOption::belonging_to(&poll)
+            .load::<PollOption>(conn)
+            .map_err(handle_err::<PollOption>)?;
+        let all_options_belong_to_poll = option_uuids
+            .iter()
+            .all(|option_uuid| options.iter().any(|option| option.uuid == option_uuid.0));
+        if !all_options_belong_to_poll {
+            return Err(Error::BadRequest);
+        }
+
+        let mut option_uuids: Vec<Uuid> = option_uuids.into_iter().map(|option_uuid| option_uuid.0).collect();
+        option_uuids.sort();
+        option_uuids.dedup();
+
+        let new_votes: Vec<NewPollVote> = option_uuids
+            .into_iter()
+            .map(|option_uuid| NewPollVote {
+                poll_uuid: poll.uuid,
+                option_uuid,
+                user_uuid: user_uuid.0,
+            })
+            .collect();
+        // The previous vote is replaced in the same transaction, so a failure can't leave the user with no vote or two.
+        conn.transaction::<_, DieselError, _>(|| {
+            let previous_votes = poll_votes::table
+                .filter(poll_votes::poll_uuid.eq(poll.uuid))
+                .filter(poll_votes::user_uuid.eq(user_uuid.0));
+            diesel::delete(previous_votes).execute(conn)?;
+            diesel::insert_into(poll_votes::table)
+                .values(&new_votes)
+                .execute(conn)
+        })
+        .map_err(handle_err::<PollVote>)?;
+
+        Poll::get_poll_data(poll, Some(user_uuid), conn)
+    }
+}
@@ -4,6 +4,10 @@ use chrono::{
 };
 use crate::{
     calls::prelude::*,
+    reputation::{
+        DOWNVOTE_REPUTATION,
+        UPVOTE_REPUTATION,
+    },
     schema::{
         self,
         post_downvotes,
@@ -15,7 +19,9 @@ use crate::{
 };
 use diesel::{
     self,
+    result::Error as DieselError,
     BelongingToDsl,
+    Connection,
     ExpressionMethods,
     PgConnection,
     QueryDsl,
@@ -508,6 +514,9 @@ impl Post {
     }
 
     /// Add a vote record to a post.
+    ///
+    /// Any opposing vote by the user is removed, and the author's reputation is adjusted,
+    /// in the same transaction as the vote is recorded.
     pub fn vote(vote: PostVote, conn: &PgConnection) -> BackendResult<()> {
         use crate::schema::{
             post_downvotes,
@@ -523,90 +532,115 @@ impl Post {
             PostVote::Down(ref vote) => vote,
         };
 
-        let upvote_exists: bool = select(exists(
-            post_upvotes::table
-                .filter(post_upvotes::user_uuid.eq(v.user_uuid.0))
-                .filter(post_upvotes::post_uuid.eq(v.post_uuid.0)),
-        ))
-        .get_result(conn)
-        .map_err(handle_err::<Post>)?;
-
-        let downvote_exists: bool = select(exists(
-            post_downvotes::table
-                .filter(post_downvotes::user_uuid.eq(v.user_uuid.0))
-                .filter(post_downvotes::post_uuid.eq(v.post_uuid.0)),
-        ))
-        .get_result(conn)
-        .map_err(handle_err::<Post>)?;
+        let vote_cast: bool = conn
+            .transaction::<_, DieselError, _>(|| {
+                let upvote_exists: bool = select(exists(
+                    post_upvotes::table
+                        .filter(post_upvotes::user_uuid.eq(v.user_uuid.0))
+                        .filter(post_upvotes::post_uuid.eq(v.post_uuid.0)),
+                ))
+                .get_result(conn)?;
+
+                let downvote_exists: bool = select(exists(
+                    post_downvotes::table
+                        .filter(post_downvotes::user_uuid.eq(v.user_uuid.0))
+                        .filter(post_downvotes::post_uuid.eq(v.post_uuid.0)),
+                ))
+                .get_result(conn)?;
+
+                match vote {
+                    PostVote::Up(vote) => {
+                        if upvote_exists {
+                            return Ok(false);
+                        }
+                        if downvote_exists {
+                            Post::remove_downvote(vote.user_uuid, vote.post_uuid, conn)?;
+                        }
+                        // Insert the new upvote
+                        let upvote: NewUpvote = vote.into();
+                        diesel::insert_into(post_upvotes::table)
+                            .values(upvote)
+                            .execute(conn)?;
+                        Post::adjust_author_reputation(vote.user_uuid, vote.post_uuid, UPVOTE_REPUTATION, conn)?;
+                    }
+                    PostVote::Down(vote) => {
+                        if downvote_exists {
+                            return Ok(false);
+                        }
+                        if upvote_exists {
+                            Post::remove_upvote(vote.user_uuid, vote.post_uuid, conn)?;
+                        }
+                        // Add the new downvote
+                        let downvote: NewDownvote = vote.into();
+                        diesel::insert_into(post_downvotes::table)
+                            .values(downvote)
+                            .execute(conn)?;
+                        Post::adjust_author_reputation(vote.user_uuid, vote.post_uuid, DOWNVOTE_REPUTATION, conn)?;
+                    }
+                };
+                Ok(true)
+            })
+            .map_err(handle_err::<Post>)?;
 
-        match vote {
-            PostVote::Up(vote) => {
-                if downvote_exists {
-                    Post::remove_downvote(vote.user_uuid, vote.post_uuid, conn)?
-                }
-                if !upvote_exists {
-                    // Insert the new upvote
-                    let upvote: NewUpvote = vote.into();
-                    return diesel::insert_into(post_upvotes::table)
-                        .values(upvote)
-                        .execute(conn)
-                        .map_err(handle_err::<Post>)
-                        .map(|_| ());
-                } else {
-                    return Err(Error::BadRequest);
-                }
-            }
-            PostVote::Down(vote) => {
-                if upvote_exists {
-                    Post::remove_upvote(vote.user_uuid, vote.post_uuid, conn)?
-                }
-                if !downvote_exists {
-                    // Add the new downvote
-                    let downvote: NewDownvote = vote.into();
-                    return diesel::insert_into(post_downvotes::table)
-                        .values(downvote)
-                        .execute(conn)
-                        .map_err(handle_err::<Post>)
-                        .map(|_| ());
-                } else {
-                    return Err(Error::BadRequest);
-                }
-            }
-        };
+        if vote_cast {
+            Ok(())
+        } else {
+            Err(Error::BadRequest)
+        }
     }
 
     /// Removes an upvote from a post.
-    fn remove_upvote(user_uuid: UserUuid, post_uuid: PostUuid, conn: &PgConnection) -> BackendResult<()> {
+    fn remove_upvote(user_uuid: UserUuid, post_uuid: PostUuid, conn: &PgConnection) -> Result<(), DieselError> {
         use crate::schema::post_upvotes;
 
         let target = post_upvotes::table
             .filter(post_upvotes::user_uuid.eq(user_uuid.0))
             .filter(post_upvotes::post_uuid.eq(post_uuid.0));
-        diesel::delete(target)
-            .execute(conn)
-            .map_err(handle_err::<Post>)
-            .map(|_| ())
+        let removed: usize = diesel::delete(target).execute(conn)?;
+        if removed > 0 {
+            Post::adjust_author_reputation(user_uuid, post_uuid, -UPVOTE_REPUTATION, conn)?;
+        }
+        Ok(())
     }
     /// Removes a downvote from a post.
-    fn remove_downvote(user_uuid: UserUuid, post_uuid: PostUuid, conn: &PgConnection) -> BackendResult<()> {
+    fn remove_downvote(user_uuid: UserUuid, post_uuid: PostUuid, conn: &PgConnection) -> Result<(), DieselError> {
         use crate::schema::post_downvotes;
 
         let target = post_downvotes::table
             .filter(post_downvotes::user_uuid.eq(user_uuid.0))
             .filter(post_downvotes::post_uuid.eq(post_uuid.0));
-        diesel::delete(target)
-            .execute(conn)
-            .map_err(handle_err::<Post>)
-            .map(|_| ())
+        let removed: usize = diesel::delete(target).execute(conn)?;
+        if removed > 0 {
+            Post::adjust_author_reputation(user_uuid, post_uuid, -DOWNVOTE_REPUTATION, conn)?;
+        }
+        Ok(())
+    }
+
+    /// Applies a reputation change to the author of the post as a result of a vote.
+    /// Users can't change their own reputation by voting on their own posts.
+    fn adjust_author_reputation(
+        voter_uuid: UserUuid,
+        post_uuid: PostUuid,
+        delta: i32,
+        conn: &PgConnection,
+    ) -> Result<(), DieselError> {
+        let author_uuid: Uuid = posts::table
+            .find(post_uuid.0)
+            .select(posts::author_uuid)
+            .get_result(conn)?;
+        if author_uuid == voter_uuid.0 {
+            return Ok(());
+        }
+        User::adjust_reputation(UserUuid(author_uuid), delta, conn).map(|_| ())
     }
 
     /// Remove any vote for the post
     pub fn revoke_vote(user_uuid: UserUuid, post_uuid: PostUuid, conn: &PgConnection) -> BackendResult<()> {
-        let x = Post::remove_upvote(user_uuid, post_uuid, conn);
-        let y = Post::remove_downvote(user_uuid, post_uuid, conn);
-
-        x?;
-        y
+        conn.transaction::<_, DieselError, _>(|| {
+            Post::remove_upvote(user_uuid, post_uuid, conn)?;
+            Post::remove_downvote(user_uuid, post_uuid, conn)
+        })
+        .map_err(handle_err::<Post>)
     }
 
     /// Gets the vote counts for a single post.
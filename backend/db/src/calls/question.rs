@@ -2,26 +2,47 @@ use crate::{
     answer::{
         Answer,
         AnswerData,
+        NewAnswer,
     },
     bucket::Bucket,
     calls::prelude::*,
     schema::{
         self,
         questions,
+        question_draws,
         junction_favorite_questions_users
     },
     user::User,
 };
+use chrono::{
+    NaiveDateTime,
+    Utc,
+};
 use diesel::{
     self,
+    dsl::sql,
+    result::Error as DieselError,
+    sql_types::Nullable,
+    sql_types::Timestamp,
     BelongingToDsl,
+    BoolExpressionMethods,
+    Connection,
     ExpressionMethods,
     GroupedBy,
+    OptionalExtension,
     PgConnection,
     QueryDsl,
     RunQueryDsl,
 };
-use error::BackendResult;
+use error::{
+    BackendResult,
+    Error,
+};
+use rand::{
+    self,
+    Rng,
+};
+use std::collections::HashMap;
 use identifiers::{
     bucket::BucketUuid,
     question::QuestionUuid,
@@ -29,6 +50,10 @@ use identifiers::{
 };
 use uuid::Uuid;
 use diesel::pg::expression::array_comparison::any;
+use wire::{
+    bucket::DrawMode,
+    question::QuestionFileEntry,
+};
 
 #[derive(Debug, Clone, Identifiable, Queryable, Associations, TypeName)]
 #[primary_key(uuid)]
@@ -42,6 +67,8 @@ pub struct Question {
     pub author_uuid: Option<Uuid>,
     pub question_text: String,
     pub on_floor: bool,
+    /// When the question was last drawn in its bucket's session.
+    pub last_drawn_at: Option<NaiveDateTime>,
 }
 
 #[derive(Insertable, Debug)]
@@ -60,6 +87,32 @@ pub struct QuestionData {
     pub answers: Vec<AnswerData>,
 }
 
+/// A record of a question having been drawn in its bucket's session.
+#[derive(Debug, Clone, Identifiable, Queryable, Associations)]
+#[primary_key(uuid)]
+#[table_name = "question_draws"]
+#[belongs_to(Question, foreign_key = "question_uuid")]
+pub struct QuestionDraw {
+    pub uuid: Uuid,
+    pub bucket_uuid: Uuid,
+    pub question_uuid: Uuid,
+    pub drawn_at: NaiveDateTime,
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[table_name = "question_draws"]
+pub struct NewQuestionDraw {
+    pub bucket_uuid: Uuid,
+    pub question_uuid: Uuid,
+    pub drawn_at: NaiveDateTime,
+}
+
+#[derive(Debug)]
+pub struct QuestionDrawData {
+    pub draw: QuestionDraw,
+    pub question: Question,
+}
+
 #[derive(Debug, Clone, Identifiable, Queryable)]
 #[table_name = "junction_favorite_questions_users"]
 #[primary_key(uuid)]
@@ -105,6 +158,73 @@ impl Question {
         })
     }
 
+    /// Creates a question in the bucket for each of the file entries, along with its answers.
+    /// The entries are expected to have been validated and deduplicated already.
+    ///
+    /// Files only name the authors of answers, so imported answers are anonymous.
+    pub fn create_questions(
+        bucket_uuid: BucketUuid,
+        author_uuid: Option<UserUuid>,
+        entries: Vec<QuestionFileEntry>,
+        conn: &PgConnection,
+    ) -> BackendResult<Vec<QuestionData>> {
+        if entries.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let user: Option<User> = author_uuid
+            .map(|author_uuid| User::get_user(author_uuid, conn))
+            .transpose()?;
+
+        conn.transaction::<_, DieselError, _>(|| {
+            let new_questions: Vec<NewQuestion> = entries
+                .iter()
+                .map(|entry| NewQuestion {
+                    bucket_uuid: bucket_uuid.0,
+                    author_uuid: author_uuid.map(|author_uuid| author_uuid.0),
+                    question_text: entry.question_text.clone(),
+                    on_floor: false,
+                })
+                .collect();
+
+            // Rows are returned in the order they were inserted in.
+            let questions: Vec<Question> = diesel::insert_into(schema::questions::table)
+                .values(&new_questions)
+                .get_results(conn)?;
+
+            let new_answers: Vec<NewAnswer> = questions
+                .iter()
+                .zip(entries.into_iter())
+                .flat_map(|(question, entry)| {
+                    let question_uuid = question.uuid;
+                    entry.answers.into_iter().map(move |answer| NewAnswer {
+                        author_uuid: None,
+                        question_uuid,
+                        answer_text: answer.answer_text,
+                    })
+                })
+                .collect();
+            let answers: Vec<Answer> = diesel::insert_into(schema::answers::table)
+                .values(&new_answers)
+                .get_results(conn)?;
+
+            let answers: Vec<Vec<Answer>> = answers.grouped_by(&questions);
+            Ok(questions
+                .into_iter()
+                .zip(answers.into_iter())
+                .map(|(question, answers)| QuestionData {
+                    question,
+                    user: user.clone(),
+                    answers: answers
+                        .into_iter()
+                        .map(|answer| AnswerData { answer, user: None })
+                        .collect(),
+                })
+                .collect())
+        })
+        .map_err(handle_err::<Question>)
+    }
+
     /// Gets a list of all questions across all buckets.
     pub fn get_questions(conn: &PgConnection) -> BackendResult<Vec<QuestionData>> {
         use crate::schema::{
@@ -129,50 +249,186 @@ impl Question {
         Ok(question_data)
     }
 
-    /// Gets a random question that may have already been answered
+    /// Draws a question from the bucket according to its draw mode, and records the draw in the bucket's history.
+    ///
+    /// Neither draw mode sorts the whole bucket randomly.
+    /// Fair draws only consider the questions that have gone the longest without being drawn,
+    /// while shuffled decks take the question that follows a random uuid.
     pub fn get_random_question(bucket_uuid: BucketUuid, conn: &PgConnection) -> BackendResult<QuestionData> {
-        use crate::schema::users::dsl::*;
+        let bucket = Bucket::get_bucket(bucket_uuid, &conn)?;
+
+        let question: Question = match bucket.draw_mode() {
+            DrawMode::Fair => Self::draw_fairly(&bucket, conn)?,
+            DrawMode::ShuffleDeck => Self::draw_from_deck(&bucket, conn)?,
+        };
+        Self::record_draw(&question, conn)?;
 
-        use crate::schema::questions::columns::on_floor;
+        Self::get_full_question(QuestionUuid(question.uuid), conn)
+    }
 
-        // Get the bucket from which questions will be retrieved.
-        let bucket = Bucket::get_bucket(bucket_uuid, &conn)?;
+    /// The number of questions considered when drawing fairly.
+    const FAIR_DRAW_CANDIDATES: i64 = 16;
 
-        no_arg_sql_function!(RANDOM, (), "Represents the sql RANDOM() function");
+    /// Picks among the questions that have gone the longest without being drawn.
+    /// The less recently a question was drawn, and the fewer answers it has, the more likely it is to be picked.
+    /// The question currently being looked at is never picked if there is any alternative.
+    fn draw_fairly(bucket: &Bucket, conn: &PgConnection) -> BackendResult<Question> {
+        use crate::schema::answers;
 
-        // Get a random question belonging to the bucket.
-        let question: Question = Question::belonging_to(&bucket)
-            .order(RANDOM)
-            .filter(on_floor.eq(false)) // Only get a question if it is not on the "floor" (and therefore in the bucket)
-            .first::<Question>(conn)
+        let mut candidates: Vec<Question> = Question::belonging_to(bucket)
+            .filter(questions::on_floor.eq(false)) // Only get a question if it is not on the "floor" (and therefore in the bucket)
+            .order(sql::<Nullable<Timestamp>>("last_drawn_at ASC NULLS FIRST"))
+            .limit(Self::FAIR_DRAW_CANDIDATES)
+            .load::<Question>(conn)
             .map_err(handle_err::<Question>)?;
-        // Get the answers associated with the question.
-        let answers_and_users: Vec<(Answer, Option<User>)> = Answer::belonging_to(&question)
-            .left_join(users)
-            .load::<(Answer, Option<User>)>(conn)
-            .map_err(handle_err::<Answer>)?;
-        // Get the author of the question.
-        let user: Option<User> = if let Some(author_uuid) = question.author_uuid {
-            let author = users
-                .find(author_uuid)
-                .first::<User>(conn)
-                .map_err(handle_err::<User>)?;
-            Some(author)
-        } else {
-            None
+
+        if candidates.len() > 1 {
+            candidates.retain(|question| Some(question.uuid) != bucket.current_question_uuid);
+        }
+        if candidates.is_empty() {
+            return Err(Error::NotFound {
+                type_name: "Question".to_string(),
+            });
+        }
+
+        let candidate_uuids: Vec<Uuid> = candidates.iter().map(|question| question.uuid).collect();
+        let mut answer_counts: HashMap<Uuid, usize> = HashMap::new();
+        answers::table
+            .filter(answers::question_uuid.eq_any(candidate_uuids))
+            .select(answers::question_uuid)
+            .load::<Uuid>(conn)
+            .map_err(handle_err::<Answer>)?
+            .into_iter()
+            .for_each(|question_uuid| *answer_counts.entry(question_uuid).or_insert(0) += 1);
+
+        let number_of_candidates = candidates.len();
+        let weights: Vec<f64> = candidates
+            .iter()
+            .enumerate()
+            .map(|(rank, question)| {
+                let answers = answer_counts.get(&question.uuid).cloned().unwrap_or(0);
+                (number_of_candidates - rank) as f64 / (1 + answers) as f64
+            })
+            .collect();
+
+        let mut remaining: f64 = rand::thread_rng().gen::<f64>() * weights.iter().sum::<f64>();
+        let index: usize = weights
+            .iter()
+            .position(|weight| {
+                remaining -= weight;
+                remaining < 0.0
+            })
+            .unwrap_or(number_of_candidates - 1);
+        Ok(candidates.swap_remove(index))
+    }
+
+    /// Picks a question that hasn't been drawn since the deck was shuffled.
+    /// Once every question has been drawn, the deck is shuffled again.
+    /// The question currently being looked at is never the first pick of a new deck if there is any alternative.
+    fn draw_from_deck(bucket: &Bucket, conn: &PgConnection) -> BackendResult<Question> {
+        if let Some(question) = Self::pick_from_deck(bucket, bucket.deck_started_at, None, conn)? {
+            return Ok(question);
+        }
+
+        let deck_started_at: NaiveDateTime = Utc::now().naive_utc();
+        {
+            use crate::schema::buckets;
+            diesel::update(buckets::table.filter(buckets::uuid.eq(bucket.uuid)))
+                .set(buckets::deck_started_at.eq(Some(deck_started_at)))
+                .execute(conn)
+                .map_err(handle_err::<Bucket>)?;
+        }
+
+        let question: Option<Question> =
+            match Self::pick_from_deck(bucket, Some(deck_started_at), bucket.current_question_uuid, conn)? {
+                Some(question) => Some(question),
+                None => Self::pick_from_deck(bucket, Some(deck_started_at), None, conn)?,
+            };
+        question.ok_or_else(|| Error::NotFound {
+            type_name: "Question".to_string(),
+        })
+    }
+
+    /// Gets the question in the deck whose uuid follows a random one.
+    /// Because uuids are random, this is close to picking uniformly, while only needing an index lookup.
+    fn pick_from_deck(
+        bucket: &Bucket,
+        deck_started_at: Option<NaiveDateTime>,
+        excluded: Option<Uuid>,
+        conn: &PgConnection,
+    ) -> BackendResult<Option<Question>> {
+        let deck = || {
+            let mut query = Question::belonging_to(bucket)
+                .filter(questions::on_floor.eq(false))
+                .into_boxed();
+            if let Some(excluded) = excluded {
+                query = query.filter(questions::uuid.ne(excluded));
+            }
+            match deck_started_at {
+                Some(deck_started_at) => query.filter(
+                    questions::last_drawn_at
+                        .is_null()
+                        .or(questions::last_drawn_at.lt(deck_started_at)),
+                ),
+                None => query.filter(questions::last_drawn_at.is_null()),
+            }
         };
 
+        let pivot: Uuid = Uuid::new_v4();
+        let question: Option<Question> = deck()
+            .filter(questions::uuid.ge(pivot))
+            .order(questions::uuid.asc())
+            .first::<Question>(conn)
+            .optional()
+            .map_err(handle_err::<Question>)?;
 
-        // Get them all together.
+        match question {
+            Some(question) => Ok(Some(question)),
+            // Wrap around to the start of the deck.
+            None => deck()
+                .order(questions::uuid.asc())
+                .first::<Question>(conn)
+                .optional()
+                .map_err(handle_err::<Question>),
+        }
+    }
 
-        Ok(QuestionData {
-            question,
-            user,
-            answers: answers_and_users
-                .into_iter()
-                .map(|x| AnswerData { answer: x.0, user: x.1 })
-                .collect(),
+    /// Adds the draw to the bucket's history.
+    fn record_draw(question: &Question, conn: &PgConnection) -> BackendResult<()> {
+        // The draw is timestamped here rather than by the database, so it can be compared against `deck_started_at`.
+        let new_draw = NewQuestionDraw {
+            bucket_uuid: question.bucket_uuid,
+            question_uuid: question.uuid,
+            drawn_at: Utc::now().naive_utc(),
+        };
+
+        conn.transaction::<_, DieselError, _>(|| {
+            let draw: QuestionDraw = diesel::insert_into(question_draws::table)
+                .values(&new_draw)
+                .get_result(conn)?;
+            diesel::update(questions::table.filter(questions::uuid.eq(question.uuid)))
+                .set(questions::last_drawn_at.eq(Some(draw.drawn_at)))
+                .execute(conn)?;
+            Ok(())
         })
+        .map_err(handle_err::<Question>)
+    }
+
+    /// Gets the most recent draws in the bucket, newest first.
+    pub fn get_draw_history(bucket_uuid: BucketUuid, limit: i64, conn: &PgConnection) -> BackendResult<Vec<QuestionDrawData>> {
+        question_draws::table
+            .filter(question_draws::bucket_uuid.eq(bucket_uuid.0))
+            .inner_join(questions::table)
+            .order(question_draws::drawn_at.desc())
+            .limit(limit)
+            .load::<(QuestionDraw, Question)>(conn)
+            .map(|draws| {
+                draws
+                    .into_iter()
+                    .map(|(draw, question)| QuestionDrawData { draw, question })
+                    .collect()
+            })
+            .map_err(handle_err::<Question>)
     }
 
     /// Helper function that gets answers for a set of questions and properly spaces them so that they may be joined together.
@@ -368,4 +624,77 @@ impl Question {
             .map_err(handle_err::<Question>)?;
         Ok(question_uuid)
     }
+
+    /// Merges questions into another question in the same bucket, then deletes them.
+    ///
+    /// Their answers and draws are moved to the kept question, and anyone who favorited them has the kept question favorited instead.
+    /// If one of them is the bucket's current question, the kept question becomes the current question.
+    pub fn merge_questions(keep: QuestionUuid, merge: Vec<QuestionUuid>, conn: &PgConnection) -> BackendResult<QuestionData> {
+        use crate::schema::{
+            answers,
+            buckets,
+            junction_favorite_questions_users as favorites,
+        };
+
+        let kept_question: Question = Self::get_question(keep, conn)?;
+        let mut merge_uuids: Vec<Uuid> = merge
+            .into_iter()
+            .map(|question_uuid| question_uuid.0)
+            .filter(|question_uuid| *question_uuid != keep.0)
+            .collect();
+        merge_uuids.sort();
+        merge_uuids.dedup();
+
+        let in_same_bucket: i64 = questions::table
+            .filter(questions::uuid.eq_any(&merge_uuids))
+            .filter(questions::bucket_uuid.eq(kept_question.bucket_uuid))
+            .count()
+            .get_result(conn)
+            .map_err(handle_err::<Question>)?;
+        if merge_uuids.is_empty() || in_same_bucket != merge_uuids.len() as i64 {
+            return Err(Error::BadRequest);
+        }
+
+        conn.transaction::<_, DieselError, _>(|| {
+            diesel::update(answers::table.filter(answers::question_uuid.eq_any(&merge_uuids)))
+                .set(answers::question_uuid.eq(keep.0))
+                .execute(conn)?;
+            diesel::update(question_draws::table.filter(question_draws::question_uuid.eq_any(&merge_uuids)))
+                .set(question_draws::question_uuid.eq(keep.0))
+                .execute(conn)?;
+            diesel::update(buckets::table.filter(buckets::current_question_uuid.eq_any(&merge_uuids)))
+                .set(buckets::current_question_uuid.eq(Some(keep.0)))
+                .execute(conn)?;
+
+            let already_favorited: Vec<Uuid> = favorites::table
+                .filter(favorites::question_uuid.eq(keep.0))
+                .select(favorites::user_uuid)
+                .load(conn)?;
+            let mut newly_favorited: Vec<Uuid> = favorites::table
+                .filter(favorites::question_uuid.eq_any(&merge_uuids))
+                .select(favorites::user_uuid)
+                .load::<Uuid>(conn)?
+                .into_iter()
+                .filter(|user_uuid| !already_favorited.contains(user_uuid))
+                .collect();
+            newly_favorited.sort();
+            newly_favorited.dedup();
+            let new_favorites: Vec<NewFavoriteQuestionJunction> = newly_favorited
+                .into_iter()
+                .map(|user_uuid| NewFavoriteQuestionJunction {
+                    question_uuid: keep.0,
+                    user_uuid,
+                })
+                .collect();
+            diesel::insert_into(favorites::table)
+                .values(&new_favorites)
+                .execute(conn)?;
+
+            diesel::delete(questions::table.filter(questions::uuid.eq_any(&merge_uuids))).execute(conn)?;
+            Ok(())
+        })
+        .map_err(handle_err::<Question>)?;
+
+        Self::get_full_question(keep, conn)
+    }
 }
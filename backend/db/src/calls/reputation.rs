@@ -0,0 +1,95 @@
+use crate::{
+    calls::prelude::*,
+    schema::{
+        self,
+        reputation_thresholds,
+    },
+    user::User,
+};
+use diesel::{
+    self,
+    ExpressionMethods,
+    OptionalExtension,
+    PgConnection,
+    QueryDsl,
+    RunQueryDsl,
+};
+use error::BackendResult;
+use identifiers::user::UserUuid;
+use uuid::Uuid;
+use wire::reputation::ReputationAction;
+
+/// The reputation a user gains when another user upvotes one of their posts.
+pub const UPVOTE_REPUTATION: i32 = 10;
+/// The reputation a user gains (loses) when another user downvotes one of their posts.
+pub const DOWNVOTE_REPUTATION: i32 = -2;
+
+#[derive(Debug, Clone, Identifiable, Queryable, TypeName)]
+#[primary_key(uuid)]
+#[table_name = "reputation_thresholds"]
+pub struct ReputationThreshold {
+    /// Primary Key.
+    pub uuid: Uuid,
+    /// The action being gated, stored as the integer representation of a ReputationAction.
+    pub action: i32,
+    /// The reputation a user must have before they can perform the action.
+    pub minimum_reputation: i32,
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[table_name = "reputation_thresholds"]
+pub struct NewReputationThreshold {
+    pub action: i32,
+    pub minimum_reputation: i32,
+}
+
+impl ReputationThreshold {
+    /// Gets all of the thresholds that have been set.
+    pub fn get_thresholds(conn: &PgConnection) -> BackendResult<Vec<ReputationThreshold>> {
+        get_rows::<ReputationThreshold, _>(schema::reputation_thresholds::table, conn)
+    }
+
+    /// Sets the threshold for an action, replacing any existing threshold for that action.
+    pub fn set_threshold(new: NewReputationThreshold, conn: &PgConnection) -> BackendResult<ReputationThreshold> {
+        use crate::schema::reputation_thresholds::dsl::*;
+
+        diesel::insert_into(reputation_thresholds)
+            .values(&new)
+            .on_conflict(action)
+            .do_update()
+            .set(minimum_reputation.eq(new.minimum_reputation))
+            .get_result(conn)
+            .map_err(handle_err::<ReputationThreshold>)
+    }
+
+    /// Gets the threshold for a given action, if one has been set.
+    pub fn get_threshold_for_action(
+        reputation_action: ReputationAction,
+        conn: &PgConnection,
+    ) -> BackendResult<Option<ReputationThreshold>> {
+        use crate::schema::reputation_thresholds::dsl::*;
+
+        let action_number: i32 = reputation_action.into();
+        reputation_thresholds
+            .filter(action.eq(action_number))
+            .first::<ReputationThreshold>(conn)
+            .optional()
+            .map_err(handle_err::<ReputationThreshold>)
+    }
+
+    /// Checks if the user has enough reputation to perform the action.
+    /// Actions without a threshold are open to everyone.
+    pub fn user_meets_threshold(
+        user_uuid: UserUuid,
+        reputation_action: ReputationAction,
+        conn: &PgConnection,
+    ) -> BackendResult<bool> {
+        match ReputationThreshold::get_threshold_for_action(reputation_action, conn)? {
+            Some(threshold) => {
+                let user: User = User::get_user(user_uuid, conn)?;
+                Ok(user.reputation >= threshold.minimum_reputation)
+            }
+            None => Ok(true),
+        }
+    }
+}
@@ -1,12 +1,15 @@
 use chrono::NaiveDateTime;
 use crate::{
+    diesel_extensions::pagination::PaginatedData,
     forum::Forum,
     schema::threads,
     user::User,
 };
 use diesel::{
     self,
+    result::Error as DieselError,
     BelongingToDsl,
+    Connection,
     ExpressionMethods,
     PgConnection,
     QueryDsl,
@@ -19,16 +22,25 @@ use identifiers::{
     user::UserUuid,
 };
 use uuid::Uuid;
+use wire::poll::NewPollRequest;
 
 use crate::{
     calls::prelude::*,
+    poll::{
+        NewPoll,
+        Poll,
+        PollData,
+    },
     post::{
         ChildlessPostData,
         NewPost,
         Post,
         PostData,
     },
-    schema,
+    schema::{
+        self,
+        posts,
+    },
 };
 
 #[derive(Debug, Clone, Identifiable, Associations, Queryable, TypeName)]
@@ -68,6 +80,7 @@ pub struct ThreadData {
     pub thread: Thread,
     pub post: PostData,
     pub user: User,
+    pub poll: Option<PollData>,
 }
 
 pub struct MinimalThreadData {
@@ -171,7 +184,7 @@ impl Thread {
         page_index: i32,
         page_size: i32,
         conn: &PgConnection,
-    ) -> BackendResult<Vec<MinimalThreadData>> {
+    ) -> BackendResult<PaginatedData<MinimalThreadData>> {
         use crate::{
             diesel_extensions::pagination::*,
             forum::Forum,
@@ -183,42 +196,84 @@ impl Thread {
 
         let forum: Forum = Forum::get_forum(requested_forum_uuid, conn)?;
 
-        let (thread_users, _count) = Thread::belonging_to(&forum)
+        let thread_users: PaginatedData<(Thread, User)> = Thread::belonging_to(&forum)
             .inner_join(users::table)
             .order(created_date)
             .filter(archived.eq(false))
             .paginate(page_index.into())
             .per_page(page_size.into())
-            .load_and_count_pages::<(Thread, User)>(conn)
+            .load_page::<(Thread, User)>(conn)
             .map_err(handle_err::<Thread>)?;
 
-        let minimal_threads = thread_users
-            .into_iter()
-            .map(|x| MinimalThreadData { thread: x.0, user: x.1 })
-            .collect();
+        let minimal_threads = thread_users.map_items(|x| MinimalThreadData { thread: x.0, user: x.1 });
 
         Ok(minimal_threads)
     }
 
-    /// Creates a thread with an initial post.
+    /// Creates a thread with an initial post, and optionally a poll.
+    /// Everything is created in one transaction, so a thread is never left without its post.
     pub fn create_thread_with_initial_post(
         new_thread: NewThread,
         post_content: String,
+        new_poll: Option<NewPollRequest>,
         conn: &PgConnection,
     ) -> BackendResult<ThreadData> {
-        let thread: Thread = Thread::create_thread(new_thread, conn)?;
+        if let Some(ref request) = new_poll {
+            Poll::validate_new_poll(request.options.len(), request.closes_at, request.hide_results_until_closed)?;
+        }
 
-        let new_post: NewPost = NewPost::from((thread.clone(), post_content));
+        let (thread, post, poll) = conn
+            .transaction::<_, DieselError, _>(|| {
+                let (thread, post) = Thread::insert_thread_with_initial_post(new_thread, post_content, conn)?;
+                let poll = match new_poll {
+                    Some(request) => {
+                        let options: Vec<String> = request.options.clone();
+                        let new_poll: NewPoll = NewPoll::from((thread.clone(), request));
+                        Some(Poll::insert_poll(new_poll, options, conn)?)
+                    }
+                    None => None,
+                };
+                Ok((thread, post, poll))
+            })
+            .map_err(handle_err::<Thread>)?;
+
+        let user: User = User::get_user(UserUuid(thread.author_uuid), conn)?;
+        let votes = Post::get_vote_counts(&post, UserUuid(post.author_uuid), conn)?;
+        let post_data = ChildlessPostData {
+            post,
+            user: user.clone(),
+            votes,
+        };
+        let poll: Option<PollData> = poll.map(|(poll, options)| PollData {
+            poll,
+            options: options.into_iter().map(|option| (option, 0)).collect(),
+            user_votes: vec![],
+        });
 
-        let post_data: ChildlessPostData = Post::create_and_get_user(new_post, conn)?;
-        let user: User = post_data.user.clone();
         Ok(ThreadData {
             thread,
             post: PostData::from(post_data),
             user,
+            poll,
         })
     }
 
+    /// Inserts the thread and its first post, so they can be part of a larger transaction.
+    pub(crate) fn insert_thread_with_initial_post(
+        new_thread: NewThread,
+        post_content: String,
+        conn: &PgConnection,
+    ) -> Result<(Thread, Post), DieselError> {
+        let thread: Thread = diesel::insert_into(threads::table)
+            .values(&new_thread)
+            .get_result(conn)?;
+        let new_post: NewPost = NewPost::from((thread.clone(), post_content));
+        let post: Post = diesel::insert_into(posts::table)
+            .values(&new_post)
+            .get_result(conn)?;
+        Ok((thread, post))
+    }
+
     /// Gets every bit of data related to a thread.
     pub fn get_full_thread(
         thread_uuid: ThreadUuid,
@@ -229,6 +284,12 @@ impl Thread {
         let post: PostData = Post::get_posts_in_thread(thread_uuid, user_uuid, conn)?;
         let author_uuid = UserUuid(thread.author_uuid);
         let user = User::get_user(author_uuid, conn)?;
-        Ok(ThreadData { thread, post, user })
+        let poll: Option<PollData> = Poll::get_poll_data_for_thread(thread_uuid, user_uuid, conn)?;
+        Ok(ThreadData {
+            thread,
+            post,
+            user,
+            poll,
+        })
     }
 }
@@ -5,6 +5,7 @@ use chrono::{
 };
 use crate::{
     calls::prelude::*,
+    diesel_extensions::pagination::PaginatedData,
     schema::{
         self,
         users,
@@ -12,6 +13,7 @@ use crate::{
 };
 use diesel::{
     self,
+    result::Error as DieselError,
     ExpressionMethods,
     PgConnection,
     QueryDsl,
@@ -48,6 +50,10 @@ pub struct User {
     pub banned: bool,
     /// The roles of the user.
     pub roles: Vec<i32>, // currently this is stored as an int. It would be better to store it as an enum, if diesel-enum serialization can be made to work.
+    /// Accumulated from the votes that other users have cast on this user's posts.
+    pub reputation: i32,
+    /// Where password reset tokens are sent. Users without one can't reset their password.
+    pub email: Option<String>,
 }
 
 #[derive(Insertable, Debug, Clone)]
@@ -229,8 +235,23 @@ impl User {
         }
     }
 
+    /// Adds the delta to the user's reputation.
+    /// The addition is performed by the database, so concurrent votes won't clobber each other.
+    /// This is meant to be part of a larger transaction.
+    pub(crate) fn adjust_reputation(user_uuid: UserUuid, delta: i32, conn: &PgConnection) -> Result<User, DieselError> {
+        use crate::schema::users::{
+            self,
+            dsl::*,
+        };
+
+        let target = users.filter(users::uuid.eq(user_uuid.0));
+        diesel::update(target)
+            .set(reputation.eq(reputation + delta))
+            .get_result(conn)
+    }
+
     /// Gets a number of users at specified offsets.
-    pub fn get_paginated(page_index: i32, page_size: i32, conn: &PgConnection) -> BackendResult<(Vec<User>, i64)> {
+    pub fn get_paginated(page_index: i32, page_size: i32, conn: &PgConnection) -> BackendResult<PaginatedData<User>> {
         use crate::{
             diesel_extensions::pagination::Paginate,
             schema::users,
@@ -240,7 +261,7 @@ impl User {
             .order(users::user_name)
             .paginate(page_index.into())
             .per_page(page_size.into())
-            .load_and_count_pages::<User>(conn)
+            .load_page::<User>(conn)
             .map_err(handle_err::<User>)
     }
 
@@ -278,6 +299,31 @@ impl User {
             .map_err(handle_err::<User>)
     }
 
+    /// Replaces the user's password hash.
+    pub fn update_password(user_uuid: UserUuid, new_password_hash: String, conn: &PgConnection) -> BackendResult<User> {
+        use crate::schema::users::dsl::*;
+
+        let target = users.filter(uuid.eq(user_uuid.0));
+
+        info!("Updating user password");
+        diesel::update(target)
+            .set(password_hash.eq(new_password_hash))
+            .get_result(conn)
+            .map_err(handle_err::<User>)
+    }
+
+    /// Sets or clears the email that password reset tokens are sent to.
+    pub fn update_email(user_uuid: UserUuid, new_email: Option<String>, conn: &PgConnection) -> BackendResult<User> {
+        use crate::schema::users::dsl::*;
+
+        let target = users.filter(uuid.eq(user_uuid.0));
+
+        diesel::update(target)
+            .set(email.eq(new_email))
+            .get_result(conn)
+            .map_err(handle_err::<User>)
+    }
+
     /// Deletes the user by their name.
     pub fn delete_user_by_name(name: String, conn: &PgConnection) -> BackendResult<User> {
         use crate::schema::users::dsl::*;
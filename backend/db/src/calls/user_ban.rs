@@ -0,0 +1,113 @@
+use chrono::{
+    NaiveDateTime,
+    Utc,
+};
+use crate::{
+    calls::prelude::*,
+    schema::{
+        user_bans,
+        users,
+    },
+    user::User,
+};
+use diesel::{
+    self,
+    prelude::*,
+    result::Error as DieselError,
+};
+use error::BackendResult;
+use identifiers::user::UserUuid;
+use uuid::Uuid;
+
+/// Stops a user from logging in or using their outstanding JWTs, until it is lifted or expires.
+#[derive(Debug, Clone, Identifiable, Queryable, Associations, TypeName)]
+#[primary_key(uuid)]
+#[table_name = "user_bans"]
+#[belongs_to(User, foreign_key = "user_uuid")]
+pub struct UserBan {
+    /// Primary Key.
+    pub uuid: Uuid,
+    /// The banned user.
+    pub user_uuid: Uuid,
+    /// The admin who issued the ban. None if their account has since been deleted.
+    pub admin_uuid: Option<Uuid>,
+    pub reason: String,
+    pub created_at: NaiveDateTime,
+    /// The ban is lifted at this time. It is permanent if None.
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[table_name = "user_bans"]
+pub struct NewUserBan {
+    pub user_uuid: Uuid,
+    pub admin_uuid: Option<Uuid>,
+    pub reason: String,
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+impl UserBan {
+    /// Bans the user, replacing any ban they already had.
+    /// The user's `banned` flag is kept in step with their ban.
+    pub fn ban_user(new_ban: NewUserBan, conn: &PgConnection) -> BackendResult<UserBan> {
+        conn.transaction::<_, DieselError, _>(|| {
+            diesel::delete(user_bans::table.filter(user_bans::user_uuid.eq(new_ban.user_uuid))).execute(conn)?;
+            diesel::update(users::table.find(new_ban.user_uuid))
+                .set(users::banned.eq(true))
+                .execute(conn)?;
+            diesel::insert_into(user_bans::table)
+                .values(&new_ban)
+                .get_result::<UserBan>(conn)
+        })
+        .map_err(handle_err::<UserBan>)
+    }
+
+    /// Lifts the user's ban, if they have one.
+    pub fn unban_user(user_uuid: UserUuid, conn: &PgConnection) -> BackendResult<()> {
+        conn.transaction::<_, DieselError, _>(|| {
+            diesel::delete(user_bans::table.filter(user_bans::user_uuid.eq(user_uuid.0))).execute(conn)?;
+            diesel::update(users::table.find(user_uuid.0))
+                .set(users::banned.eq(false))
+                .execute(conn)?;
+            Ok(())
+        })
+        .map_err(handle_err::<UserBan>)
+    }
+
+    /// Gets the user's ban, unless they aren't banned or their ban has expired.
+    pub fn get_active_ban(user_uuid: UserUuid, conn: &PgConnection) -> BackendResult<Option<UserBan>> {
+        let now: NaiveDateTime = Utc::now().naive_utc();
+        user_bans::table
+            .filter(user_bans::user_uuid.eq(user_uuid.0))
+            .filter(user_bans::expires_at.is_null().or(user_bans::expires_at.gt(now)))
+            .first::<UserBan>(conn)
+            .optional()
+            .map_err(handle_err::<UserBan>)
+    }
+
+    /// Gets every ban that hasn't expired, newest first.
+    pub fn get_active_bans(conn: &PgConnection) -> BackendResult<Vec<UserBan>> {
+        let now: NaiveDateTime = Utc::now().naive_utc();
+        user_bans::table
+            .filter(user_bans::expires_at.is_null().or(user_bans::expires_at.gt(now)))
+            .order(user_bans::created_at.desc())
+            .load::<UserBan>(conn)
+            .map_err(handle_err::<UserBan>)
+    }
+
+    /// Removes the bans that have expired, clearing the `banned` flag of the users they applied to.
+    /// Returns the users whose bans were lifted.
+    pub fn lift_expired_bans(conn: &PgConnection) -> BackendResult<Vec<UserUuid>> {
+        let now: NaiveDateTime = Utc::now().naive_utc();
+        conn.transaction::<_, DieselError, _>(|| {
+            let lifted: Vec<Uuid> = diesel::delete(user_bans::table.filter(user_bans::expires_at.le(now)))
+                .returning(user_bans::user_uuid)
+                .get_results::<Uuid>(conn)?;
+            diesel::update(users::table.filter(users::uuid.eq_any(lifted.clone())))
+                .set(users::banned.eq(false))
+                .execute(conn)?;
+            Ok(lifted.into_iter().map(UserUuid).collect())
+        })
+        .map_err(handle_err::<UserBan>)
+    }
+}
@@ -0,0 +1,109 @@
+use chrono::{
+    NaiveDateTime,
+    Utc,
+};
+use crate::{
+    calls::prelude::*,
+    schema::{
+        self,
+        user_sessions,
+    },
+    user::User,
+};
+use diesel::{
+    self,
+    prelude::*,
+};
+use error::BackendResult;
+use identifiers::{
+    user::UserUuid,
+    user_session::UserSessionUuid,
+};
+use uuid::Uuid;
+
+/// A login on one of the user's devices or browsers.
+#[derive(Debug, Clone, Identifiable, Queryable, Associations, TypeName)]
+#[primary_key(uuid)]
+#[table_name = "user_sessions"]
+#[belongs_to(User, foreign_key = "user_uuid")]
+pub struct UserSession {
+    /// Primary Key.
+    pub uuid: Uuid,
+    pub user_uuid: Uuid,
+    /// The hash of the session's current refresh token.
+    pub refresh_token_hash: String,
+    pub user_agent: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub last_used_at: NaiveDateTime,
+    /// The refresh token stops working at this time, ending the session.
+    pub expires_at: NaiveDateTime,
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[table_name = "user_sessions"]
+pub struct NewUserSession {
+    pub user_uuid: Uuid,
+    pub refresh_token_hash: String,
+    pub user_agent: Option<String>,
+    pub expires_at: NaiveDateTime,
+}
+
+impl UserSession {
+    pub fn get_session(uuid: UserSessionUuid, conn: &PgConnection) -> BackendResult<UserSession> {
+        get_row::<UserSession, _>(schema::user_sessions::table, uuid.0, conn)
+    }
+    pub fn create_session(new: NewUserSession, conn: &PgConnection) -> BackendResult<UserSession> {
+        create_row::<UserSession, NewUserSession, _>(schema::user_sessions::table, new, conn)
+    }
+    /// Ending a session revokes its refresh token.
+    /// JWTs that were already handed out keep working until they expire.
+    pub fn end_session(uuid: UserSessionUuid, conn: &PgConnection) -> BackendResult<UserSession> {
+        delete_row::<UserSession, _>(schema::user_sessions::table, uuid.0, conn)
+    }
+
+    /// Ends every one of the user's sessions, logging them out everywhere.
+    pub fn end_all_sessions(user_uuid: UserUuid, conn: &PgConnection) -> BackendResult<usize> {
+        diesel::delete(user_sessions::table.filter(user_sessions::user_uuid.eq(user_uuid.0)))
+            .execute(conn)
+            .map_err(handle_err::<UserSession>)
+    }
+
+    /// Gets the user's sessions that haven't expired, most recently used first.
+    pub fn get_active_sessions(user_uuid: UserUuid, conn: &PgConnection) -> BackendResult<Vec<UserSession>> {
+        let now: NaiveDateTime = Utc::now().naive_utc();
+        user_sessions::table
+            .filter(user_sessions::user_uuid.eq(user_uuid.0))
+            .filter(user_sessions::expires_at.gt(now))
+            .order(user_sessions::last_used_at.desc())
+            .load::<UserSession>(conn)
+            .map_err(handle_err::<UserSession>)
+    }
+
+    /// Replaces the session's refresh token, as long as it hasn't already been replaced or expired.
+    ///
+    /// The old hash is checked in the same statement that replaces it,
+    /// so a refresh token can't be used twice, even by concurrent requests.
+    /// Returns None if the token had already been used or the session expired.
+    pub fn rotate_refresh_token(
+        uuid: UserSessionUuid,
+        old_hash: &str,
+        new_hash: String,
+        expires_at: NaiveDateTime,
+        conn: &PgConnection,
+    ) -> BackendResult<Option<UserSession>> {
+        let now: NaiveDateTime = Utc::now().naive_utc();
+        let target = user_sessions::table
+            .filter(user_sessions::uuid.eq(uuid.0))
+            .filter(user_sessions::refresh_token_hash.eq(old_hash))
+            .filter(user_sessions::expires_at.gt(now));
+        diesel::update(target)
+            .set((
+                user_sessions::refresh_token_hash.eq(new_hash),
+                user_sessions::last_used_at.eq(now),
+                user_sessions::expires_at.eq(expires_at),
+            ))
+            .get_result::<UserSession>(conn)
+            .optional()
+            .map_err(handle_err::<UserSession>)
+    }
+}
@@ -1,6 +1,8 @@
 use crate::answer::*;
 use identifiers::{
     answer::AnswerUuid,
+    bucket::BucketUuid,
+    question::QuestionUuid,
     user::UserUuid,
 };
 use wire::answer::*;
@@ -16,6 +18,20 @@ impl From<AnswerData> for AnswerResponse {
     }
 }
 
+impl From<UserAnswerData> for UserAnswerResponse {
+    fn from(data: UserAnswerData) -> UserAnswerResponse {
+        UserAnswerResponse {
+            answer: AnswerResponse::from(AnswerData {
+                answer: data.answer,
+                user: Some(data.user),
+            }),
+            question_uuid: QuestionUuid(data.question.uuid),
+            question_text: data.question.question_text,
+            bucket_uuid: BucketUuid(data.question.bucket_uuid),
+        }
+    }
+}
+
 //impl From<NewAnswerRequest> for NewAnswer {
 //    fn from(request: NewAnswerRequest) -> NewAnswer {
 //        NewAnswer {
@@ -3,12 +3,10 @@ use wire::article::*;
 
 use identifiers::{
     article::ArticleUuid,
+    category::CategoryUuid,
+    thread::ThreadUuid,
     user::UserUuid,
 };
-use rand::{
-    self,
-    Rng,
-};
 use slug;
 
 impl From<Article> for MinimalArticleResponse {
@@ -17,8 +15,10 @@ impl From<Article> for MinimalArticleResponse {
             uuid: ArticleUuid(article.uuid),
             author_uuid: UserUuid(article.author_uuid),
             title: article.title,
+            slug: article.slug,
             body: article.body,
             publish_date: article.publish_date,
+            category_uuid: article.category_uuid.map(CategoryUuid),
         }
     }
 }
@@ -29,8 +29,13 @@ impl From<ArticleData> for FullArticleResponse {
             id: ArticleUuid(data.article.uuid),
             author: data.user.into(),
             title: data.article.title,
+            slug: data.article.slug,
             body: data.article.body,
             publish_date: data.article.publish_date,
+            tags: data.tags,
+            category_uuid: data.article.category_uuid.map(CategoryUuid),
+            comment_thread_uuid: data.article.comment_thread_uuid.map(ThreadUuid),
+            comment_count: data.comment_count,
         }
     }
 }
@@ -41,7 +46,10 @@ impl From<ArticleData> for ArticlePreviewResponse {
             uuid: ArticleUuid(data.article.uuid),
             author: data.user.into(),
             title: data.article.title,
+            slug: data.article.slug,
             publish_date: data.article.publish_date,
+            tags: data.tags,
+            category_uuid: data.article.category_uuid.map(CategoryUuid),
         }
     }
 }
@@ -52,6 +60,7 @@ impl From<UpdateArticleRequest> for ArticleChangeset {
             uuid: request.uuid.0,
             title: request.title,
             body: request.body,
+            slug: None,
         }
     }
 }
@@ -60,23 +69,9 @@ impl From<NewArticleRequest> for NewArticle {
     fn from(new_article_request: NewArticleRequest) -> NewArticle {
         NewArticle {
             title: new_article_request.title.clone(),
-            slug: slugify(&new_article_request.title),
+            slug: slug::slugify(&new_article_request.title),
             body: new_article_request.body,
             author_uuid: new_article_request.author_id.0,
         }
     }
 }
-
-const SUFFIX_LEN: usize = 6;
-
-fn slugify(title: &str) -> String {
-    // if cfg!(feature = "random_suffix") {
-    format!("{}-{}", slug::slugify(title), generate_suffix(SUFFIX_LEN))
-    // } else {
-    // slug::slugify(title)
-    // }
-}
-
-fn generate_suffix(len: usize) -> String {
-    rand::thread_rng().gen_ascii_chars().take(len).collect::<String>()
-}
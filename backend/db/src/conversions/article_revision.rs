@@ -0,0 +1,29 @@
+use crate::article_revision::*;
+use identifiers::article::ArticleUuid;
+use wire::article::*;
+
+impl From<ArticleRevisionData> for ArticleRevisionResponse {
+    fn from(data: ArticleRevisionData) -> ArticleRevisionResponse {
+        ArticleRevisionResponse {
+            article_uuid: ArticleUuid(data.revision.article_uuid),
+            revision_number: data.revision.revision_number,
+            editor: data.editor.into(),
+            title: data.revision.title,
+            body: data.revision.body,
+            created_date: data.revision.created_date,
+        }
+    }
+}
+
+impl From<ArticleDiffData> for ArticleDiffResponse {
+    fn from(data: ArticleDiffData) -> ArticleDiffResponse {
+        ArticleDiffResponse {
+            article_uuid: ArticleUuid(data.article_uuid),
+            from_revision: data.from.revision_number,
+            to_revision: data.to.revision_number,
+            from_title: data.from.title,
+            to_title: data.to.title,
+            lines: data.lines,
+        }
+    }
+}
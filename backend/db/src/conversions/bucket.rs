@@ -1,7 +1,11 @@
 use crate::bucket::*;
-use identifiers::bucket::BucketUuid;
+use identifiers::{
+    bucket::BucketUuid,
+    question::QuestionUuid,
+};
 use wire::{
     bucket::*,
+    question::QuestionResponse,
     user::*,
 };
 
@@ -13,16 +17,13 @@ use chrono::{
 
 impl From<Bucket> for BucketResponse {
     fn from(bucket: Bucket) -> BucketResponse {
-        let is_public: bool = if let Some(until) = bucket.is_public_until {
-            Utc::now().naive_utc() < until // If the current time is less than the expiry time on publicity, then the bucket is public.
-        } else {
-            false
-        };
-
+        let is_public: bool = bucket.is_public();
         BucketResponse {
             uuid: BucketUuid(bucket.uuid),
             bucket_name: bucket.bucket_name,
             is_public,
+            public_until: if is_public { bucket.is_public_until } else { None },
+            is_template: bucket.is_template,
         }
     }
 }
@@ -50,3 +51,58 @@ impl From<UsersInBucketData> for BucketUsersResponse {
         }
     }
 }
+
+impl From<BucketSessionData> for BucketSessionResponse {
+    fn from(data: BucketSessionData) -> BucketSessionResponse {
+        BucketSessionResponse {
+            bucket_uuid: BucketUuid(data.bucket.uuid),
+            current_question: data.current_question.map(QuestionResponse::from),
+            questions_in_bucket: data.questions_in_bucket,
+            turn_mode: data.bucket.turn_mode(),
+            turn_seconds: data.bucket.turn_seconds,
+            turn_user: data.turn_user.map(UserResponse::from),
+            hot_seat_user: data.hot_seat_user.map(UserResponse::from),
+            turn_ends_at: data.bucket.turn_ends_at,
+            draw_mode: data.bucket.draw_mode(),
+        }
+    }
+}
+
+impl From<BucketStatsData> for BucketStatsResponse {
+    fn from(data: BucketStatsData) -> BucketStatsResponse {
+        BucketStatsResponse {
+            bucket: BucketResponse::from(data.bucket),
+            total_questions: data.total_questions,
+            questions_on_floor: data.questions_on_floor,
+            questions_in_bucket: data.questions_in_bucket,
+            participants: data
+                .participants
+                .into_iter()
+                .map(|participant| ParticipantStatsResponse {
+                    user: UserResponse::from(participant.user),
+                    answers: participant.answers,
+                })
+                .collect(),
+            anonymous_answers: data.anonymous_answers,
+            most_favorited: data
+                .most_favorited
+                .into_iter()
+                .map(|favorited| FavoritedQuestionResponse {
+                    question_uuid: QuestionUuid(favorited.question.uuid),
+                    question_text: favorited.question.question_text,
+                    favorites: favorited.favorites,
+                })
+                .collect(),
+            sessions: data
+                .sessions
+                .into_iter()
+                .map(|session| SessionSummaryResponse {
+                    started_at: session.started_at,
+                    ended_at: session.ended_at,
+                    draws: session.draws,
+                    answers: session.answers,
+                })
+                .collect(),
+        }
+    }
+}
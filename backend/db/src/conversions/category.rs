@@ -0,0 +1,18 @@
+use crate::category::*;
+use identifiers::category::CategoryUuid;
+use wire::article::*;
+
+impl From<Category> for CategoryResponse {
+    fn from(category: Category) -> CategoryResponse {
+        CategoryResponse {
+            uuid: CategoryUuid(category.uuid),
+            name: category.name,
+        }
+    }
+}
+
+impl From<NewCategoryRequest> for NewCategory {
+    fn from(request: NewCategoryRequest) -> NewCategory {
+        NewCategory { name: request.name }
+    }
+}
@@ -6,11 +6,17 @@
 
 pub mod answer;
 pub mod article;
+pub mod article_revision;
 pub mod bucket;
+pub mod category;
 pub mod chat;
 pub mod forum;
 pub mod message;
+pub mod poll;
 pub mod post;
 pub mod question;
+pub mod reputation;
 pub mod thread;
 pub mod user;
+pub mod user_ban;
+pub mod user_session;
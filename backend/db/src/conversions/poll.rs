@@ -0,0 +1,46 @@
+use crate::{
+    poll::*,
+    thread::Thread,
+};
+use identifiers::{
+    poll::PollUuid,
+    poll_option::PollOptionUuid,
+};
+use wire::poll::*;
+
+impl From<(Thread, NewPollRequest)> for NewPoll {
+    fn from(content: (Thread, NewPollRequest)) -> NewPoll {
+        let (thread, request) = content;
+        NewPoll {
+            thread_uuid: thread.uuid,
+            question: request.question,
+            multiple_choice: request.multiple_choice,
+            closes_at: request.closes_at,
+            hide_results_until_closed: request.hide_results_until_closed,
+        }
+    }
+}
+
+impl From<PollData> for PollResponse {
+    fn from(data: PollData) -> PollResponse {
+        let closed = data.poll.is_closed();
+        let results_hidden = data.poll.hide_results_until_closed && !closed;
+        PollResponse {
+            uuid: PollUuid(data.poll.uuid),
+            question: data.poll.question,
+            multiple_choice: data.poll.multiple_choice,
+            closes_at: data.poll.closes_at,
+            closed,
+            options: data
+                .options
+                .into_iter()
+                .map(|(option, votes)| PollOptionResponse {
+                    uuid: PollOptionUuid(option.uuid),
+                    content: option.content,
+                    votes: if results_hidden { None } else { Some(votes) },
+                })
+                .collect(),
+            user_votes: data.user_votes.into_iter().map(PollOptionUuid).collect(),
+        }
+    }
+}
@@ -23,6 +23,16 @@ impl From<QuestionData> for QuestionResponse {
     }
 }
 
+impl From<QuestionDrawData> for QuestionDrawResponse {
+    fn from(data: QuestionDrawData) -> QuestionDrawResponse {
+        QuestionDrawResponse {
+            question_uuid: QuestionUuid(data.question.uuid),
+            question_text: data.question.question_text,
+            drawn_at: data.draw.drawn_at,
+        }
+    }
+}
+
 impl NewQuestion {
     pub fn attach_user_id(request: NewQuestionRequest, user_id: Option<UserUuid>) -> NewQuestion {
         NewQuestion {
@@ -0,0 +1,20 @@
+use crate::reputation::*;
+use wire::reputation::*;
+
+impl From<ReputationThreshold> for ReputationThresholdResponse {
+    fn from(threshold: ReputationThreshold) -> ReputationThresholdResponse {
+        ReputationThresholdResponse {
+            action: threshold.action.into(),
+            minimum_reputation: threshold.minimum_reputation,
+        }
+    }
+}
+
+impl From<ReputationThresholdRequest> for NewReputationThreshold {
+    fn from(request: ReputationThresholdRequest) -> NewReputationThreshold {
+        NewReputationThreshold {
+            action: request.action.into(),
+            minimum_reputation: request.minimum_reputation,
+        }
+    }
+}
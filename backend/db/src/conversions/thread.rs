@@ -4,7 +4,10 @@ use identifiers::{
     forum::ForumUuid,
     thread::ThreadUuid,
 };
-use wire::thread::*;
+use wire::{
+    poll::PollResponse,
+    thread::*,
+};
 
 impl From<NewThreadRequest> for NewThread {
     fn from(request: NewThreadRequest) -> NewThread {
@@ -29,6 +32,7 @@ impl From<ThreadData> for ThreadResponse {
             posts: data.post.into(),
             created_date: data.thread.created_date,
             locked: data.thread.locked,
+            poll: data.poll.map(PollResponse::from),
         }
     }
 }
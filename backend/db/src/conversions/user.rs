@@ -10,6 +10,7 @@ impl From<User> for UserResponse {
             user_name: user.user_name,
             display_name: user.display_name,
             uuid: UserUuid(user.uuid),
+            reputation: user.reputation,
         }
     }
 }
@@ -33,6 +34,7 @@ impl From<User> for FullUserResponse {
             user_name: user.user_name,
             display_name: user.display_name,
             uuid: UserUuid(user.uuid),
+            reputation: user.reputation,
             banned: user.banned,
             locked: user.locked.is_some(),
         }
@@ -0,0 +1,15 @@
+use crate::user_ban::UserBan;
+use identifiers::user::UserUuid;
+use wire::user::UserBanResponse;
+
+impl From<UserBan> for UserBanResponse {
+    fn from(ban: UserBan) -> UserBanResponse {
+        UserBanResponse {
+            user_uuid: UserUuid(ban.user_uuid),
+            admin_uuid: ban.admin_uuid.map(UserUuid),
+            reason: ban.reason,
+            created_at: ban.created_at,
+            expires_at: ban.expires_at,
+        }
+    }
+}
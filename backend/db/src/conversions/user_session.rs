@@ -0,0 +1,15 @@
+use crate::user_session::UserSession;
+use identifiers::user_session::UserSessionUuid;
+use wire::login::UserSessionResponse;
+
+impl From<UserSession> for UserSessionResponse {
+    fn from(session: UserSession) -> UserSessionResponse {
+        UserSessionResponse {
+            uuid: UserSessionUuid(session.uuid),
+            user_agent: session.user_agent,
+            created_at: session.created_at,
+            last_used_at: session.last_used_at,
+            expires_at: session.expires_at,
+        }
+    }
+}
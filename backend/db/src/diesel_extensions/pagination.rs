@@ -5,17 +5,19 @@ use diesel::{
     query_dsl::methods::LoadQuery,
     sql_types::BigInt,
 };
+use wire::page::MAX_PAGE_SIZE;
 
 pub trait Paginate: Sized {
     fn paginate(self, page: i64) -> Paginated<Self>;
 }
 
 impl<T> Paginate for T {
+    /// Pages start at 1. Lower page indices are treated as the first page.
     fn paginate(self, page: i64) -> Paginated<Self> {
         Paginated {
             query: self,
             per_page: PAGINATION_PER_PAGE_DEFAULT,
-            page,
+            page: page.max(1),
         }
     }
 }
@@ -23,6 +25,43 @@ impl<T> Paginate for T {
 /// By default, a page will contain 10 entries
 pub const PAGINATION_PER_PAGE_DEFAULT: i64 = 10;
 
+/// A page of results, along with the totals across every page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaginatedData<T> {
+    pub items: Vec<T>,
+    pub page: i64,
+    pub page_size: i64,
+    pub total_items: i64,
+    pub total_pages: i64,
+}
+
+impl<T> PaginatedData<T> {
+    /// Transforms the items on the page, keeping the totals.
+    pub fn map_items<U, F>(self, f: F) -> PaginatedData<U>
+    where
+        F: FnMut(T) -> U,
+    {
+        PaginatedData {
+            items: self.items.into_iter().map(f).collect(),
+            page: self.page,
+            page_size: self.page_size,
+            total_items: self.total_items,
+            total_pages: self.total_pages,
+        }
+    }
+
+    /// Replaces the items on the page, keeping the totals.
+    pub fn with_items<U>(self, items: Vec<U>) -> PaginatedData<U> {
+        PaginatedData {
+            items,
+            page: self.page,
+            page_size: self.page_size,
+            total_items: self.total_items,
+            total_pages: self.total_pages,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, QueryId)]
 pub struct Paginated<T> {
     query: T,
@@ -31,20 +70,33 @@ pub struct Paginated<T> {
 }
 
 impl<T> Paginated<T> {
+    /// Sets the size of the page, which is kept between 1 and the maximum page size that clients can request.
     pub fn per_page(self, per_page: i64) -> Self {
+        let per_page = per_page.max(1).min(i64::from(MAX_PAGE_SIZE));
         Paginated { per_page, ..self }
     }
 
-    pub fn load_and_count_pages<U>(self, conn: &PgConnection) -> QueryResult<(Vec<U>, i64)>
+    /// Loads the page, and counts the items and pages across the whole query.
+    ///
+    /// The totals are counted by a separate query,
+    /// so they are still reported when requesting a page past the end.
+    pub fn load_page<U>(self, conn: &PgConnection) -> QueryResult<PaginatedData<U>>
     where
-        Self: LoadQuery<PgConnection, (U, i64)>,
+        T: QueryFragment<Pg>,
+        Self: LoadQuery<PgConnection, U>,
     {
+        let total: i64 = CountQuery { query: &self.query }.get_result(conn)?;
+        let page = self.page;
         let per_page = self.per_page;
-        let results = self.load::<(U, i64)>(conn)?;
-        let total = results.get(0).map(|x| x.1).unwrap_or(0);
-        let records = results.into_iter().map(|x| x.0).collect();
+        let records = self.load::<U>(conn)?;
         let total_pages = (total as f64 / per_page as f64).ceil() as i64;
-        Ok((records, total_pages))
+        Ok(PaginatedData {
+            items: records,
+            page,
+            page_size: per_page,
+            total_items: total,
+            total_pages,
+        })
     }
 
     // TODO test if this actually works. It may be better to structure it like the above query and just not return the total pages value.
@@ -59,7 +111,7 @@ impl<T> Paginated<T> {
 }
 
 impl<T: Query> Query for Paginated<T> {
-    type SqlType = (T::SqlType, BigInt);
+    type SqlType = T::SqlType;
 }
 
 impl<T> RunQueryDsl<PgConnection> for Paginated<T> {}
@@ -69,7 +121,7 @@ where
     T: QueryFragment<Pg>,
 {
     fn walk_ast(&self, mut out: AstPass<Pg>) -> QueryResult<()> {
-        out.push_sql("SELECT *, COUNT(*) OVER () FROM (");
+        out.push_sql("SELECT * FROM (");
         self.query.walk_ast(out.reborrow())?;
         out.push_sql(") t LIMIT ");
         out.push_bind_param::<BigInt, _>(&self.per_page)?;
@@ -79,3 +131,31 @@ where
         Ok(())
     }
 }
+
+/// Counts the rows returned by the query being paginated.
+struct CountQuery<'a, T: 'a> {
+    query: &'a T,
+}
+
+impl<'a, T> QueryId for CountQuery<'a, T> {
+    type QueryId = ();
+    const HAS_STATIC_QUERY_ID: bool = false;
+}
+
+impl<'a, T> Query for CountQuery<'a, T> {
+    type SqlType = BigInt;
+}
+
+impl<'a, T> RunQueryDsl<PgConnection> for CountQuery<'a, T> {}
+
+impl<'a, T> QueryFragment<Pg> for CountQuery<'a, T>
+where
+    T: QueryFragment<Pg>,
+{
+    fn walk_ast(&self, mut out: AstPass<Pg>) -> QueryResult<()> {
+        out.push_sql("SELECT COUNT(*) FROM (");
+        self.query.walk_ast(out.reborrow())?;
+        out.push_sql(") t");
+        Ok(())
+    }
+}
@@ -45,6 +45,7 @@ pub mod schema;
 
 pub use crate::{
     answer::Answer,
+    diesel_extensions::pagination::PaginatedData,
     article::Article,
     bucket::Bucket,
     chat::Chat,
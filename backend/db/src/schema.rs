@@ -4,6 +4,35 @@ table! {
         question_uuid -> Uuid,
         author_uuid -> Nullable<Uuid>,
         answer_text -> Nullable<Varchar>,
+        created_at -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    article_revisions (uuid) {
+        uuid -> Uuid,
+        article_uuid -> Uuid,
+        editor_uuid -> Uuid,
+        revision_number -> Int4,
+        title -> Varchar,
+        body -> Text,
+        created_date -> Timestamp,
+    }
+}
+
+table! {
+    article_slug_redirects (uuid) {
+        uuid -> Uuid,
+        article_uuid -> Uuid,
+        slug -> Varchar,
+    }
+}
+
+table! {
+    article_tags (uuid) {
+        uuid -> Uuid,
+        article_uuid -> Uuid,
+        tag -> Varchar,
     }
 }
 
@@ -15,6 +44,21 @@ table! {
         slug -> Varchar,
         body -> Text,
         publish_date -> Nullable<Timestamp>,
+        category_uuid -> Nullable<Uuid>,
+        comment_thread_uuid -> Nullable<Uuid>,
+    }
+}
+
+table! {
+    bucket_invites (uuid) {
+        uuid -> Uuid,
+        bucket_uuid -> Uuid,
+        creator_uuid -> Uuid,
+        token_hash -> Varchar,
+        max_uses -> Nullable<Int4>,
+        uses -> Int4,
+        expires_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
     }
 }
 
@@ -23,6 +67,22 @@ table! {
         uuid -> Uuid,
         bucket_name -> Varchar,
         is_public_until -> Nullable<Timestamp>,
+        current_question_uuid -> Nullable<Uuid>,
+        turn_mode -> Int4,
+        turn_seconds -> Nullable<Int4>,
+        turn_user_uuid -> Nullable<Uuid>,
+        hot_seat_user_uuid -> Nullable<Uuid>,
+        turn_ends_at -> Nullable<Timestamp>,
+        draw_mode -> Int4,
+        deck_started_at -> Nullable<Timestamp>,
+        is_template -> Bool,
+    }
+}
+
+table! {
+    categories (uuid) {
+        uuid -> Uuid,
+        name -> Varchar,
     }
 }
 
@@ -48,6 +108,7 @@ table! {
         bucket_uuid -> Uuid,
         user_uuid -> Uuid,
         owner -> Bool,
+        approved -> Bool,
     }
 }
 
@@ -79,6 +140,45 @@ table! {
     }
 }
 
+table! {
+    password_resets (uuid) {
+        uuid -> Uuid,
+        user_uuid -> Uuid,
+        token_hash -> Varchar,
+        created_at -> Timestamp,
+        expires_at -> Timestamp,
+    }
+}
+
+table! {
+    poll_options (uuid) {
+        uuid -> Uuid,
+        poll_uuid -> Uuid,
+        position -> Int4,
+        content -> Varchar,
+    }
+}
+
+table! {
+    poll_votes (uuid) {
+        uuid -> Uuid,
+        poll_uuid -> Uuid,
+        option_uuid -> Uuid,
+        user_uuid -> Uuid,
+    }
+}
+
+table! {
+    polls (uuid) {
+        uuid -> Uuid,
+        thread_uuid -> Uuid,
+        question -> Varchar,
+        multiple_choice -> Bool,
+        closes_at -> Nullable<Timestamp>,
+        hide_results_until_closed -> Bool,
+    }
+}
+
 table! {
     post_downvotes (uuid) {
         uuid -> Uuid,
@@ -108,6 +208,15 @@ table! {
     }
 }
 
+table! {
+    question_draws (uuid) {
+        uuid -> Uuid,
+        bucket_uuid -> Uuid,
+        question_uuid -> Uuid,
+        drawn_at -> Timestamp,
+    }
+}
+
 table! {
     questions (uuid) {
         uuid -> Uuid,
@@ -115,6 +224,15 @@ table! {
         author_uuid -> Nullable<Uuid>,
         question_text -> Varchar,
         on_floor -> Bool,
+        last_drawn_at -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    reputation_thresholds (uuid) {
+        uuid -> Uuid,
+        action -> Int4,
+        minimum_reputation -> Int4,
     }
 }
 
@@ -130,6 +248,29 @@ table! {
     }
 }
 
+table! {
+    user_bans (uuid) {
+        uuid -> Uuid,
+        user_uuid -> Uuid,
+        admin_uuid -> Nullable<Uuid>,
+        reason -> Varchar,
+        created_at -> Timestamp,
+        expires_at -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    user_sessions (uuid) {
+        uuid -> Uuid,
+        user_uuid -> Uuid,
+        refresh_token_hash -> Varchar,
+        user_agent -> Nullable<Varchar>,
+        created_at -> Timestamp,
+        last_used_at -> Timestamp,
+        expires_at -> Timestamp,
+    }
+}
+
 table! {
     users (uuid) {
         uuid -> Uuid,
@@ -140,12 +281,22 @@ table! {
         failed_login_count -> Int4,
         banned -> Bool,
         roles -> Array<Int4>,
+        reputation -> Int4,
+        email -> Nullable<Varchar>,
     }
 }
 
 joinable!(answers -> questions (question_uuid));
 joinable!(answers -> users (author_uuid));
+joinable!(article_revisions -> articles (article_uuid));
+joinable!(article_revisions -> users (editor_uuid));
+joinable!(article_slug_redirects -> articles (article_uuid));
+joinable!(article_tags -> articles (article_uuid));
+joinable!(articles -> categories (category_uuid));
+joinable!(articles -> threads (comment_thread_uuid));
 joinable!(articles -> users (author_uuid));
+joinable!(bucket_invites -> buckets (bucket_uuid));
+joinable!(bucket_invites -> users (creator_uuid));
 joinable!(chats -> users (leader_uuid));
 joinable!(junction_bucket_users -> buckets (bucket_uuid));
 joinable!(junction_bucket_users -> users (user_uuid));
@@ -155,31 +306,53 @@ joinable!(junction_favorite_questions_users -> questions (question_uuid));
 joinable!(junction_favorite_questions_users -> users (user_uuid));
 joinable!(messages -> chats (chat_uuid));
 joinable!(messages -> users (author_uuid));
+joinable!(poll_options -> polls (poll_uuid));
+joinable!(password_resets -> users (user_uuid));
+joinable!(poll_votes -> poll_options (option_uuid));
+joinable!(poll_votes -> polls (poll_uuid));
+joinable!(poll_votes -> users (user_uuid));
+joinable!(polls -> threads (thread_uuid));
 joinable!(post_downvotes -> posts (post_uuid));
 joinable!(post_downvotes -> users (user_uuid));
 joinable!(post_upvotes -> posts (post_uuid));
 joinable!(post_upvotes -> users (user_uuid));
 joinable!(posts -> threads (thread_uuid));
 joinable!(posts -> users (author_uuid));
+joinable!(question_draws -> buckets (bucket_uuid));
+joinable!(question_draws -> questions (question_uuid));
 joinable!(questions -> buckets (bucket_uuid));
 joinable!(questions -> users (author_uuid));
 joinable!(threads -> forums (forum_uuid));
 joinable!(threads -> users (author_uuid));
+joinable!(user_sessions -> users (user_uuid));
 
 allow_tables_to_appear_in_same_query!(
     answers,
+    article_revisions,
+    article_slug_redirects,
+    article_tags,
     articles,
+    bucket_invites,
     buckets,
+    categories,
     chats,
     forums,
     junction_bucket_users,
     junction_chat_users,
     junction_favorite_questions_users,
     messages,
+    password_resets,
+    poll_options,
+    poll_votes,
+    polls,
     post_downvotes,
     posts,
     post_upvotes,
+    question_draws,
     questions,
+    reputation_thresholds,
     threads,
+    user_bans,
+    user_sessions,
     users,
 );
@@ -0,0 +1,78 @@
+use db::answer::{Answer, NewAnswer};
+use db::bucket::{Bucket, NewBucketUser};
+
+use common::setup::*;
+use diesel::PgConnection;
+use identifiers::answer::AnswerUuid;
+use identifiers::bucket::BucketUuid;
+use identifiers::user::UserUuid;
+
+use testing_fixtures::fixtures::bucket::BucketFixture;
+
+
+/// Answers the fixture's first question, in the private bucket, as the normal user.
+fn answer_question_1(fixture: &BucketFixture, conn: &PgConnection) -> Answer {
+    let new_answer = NewAnswer {
+        author_uuid: Some(fixture.user_fixture.normal_user.uuid),
+        question_uuid: fixture.question_1.uuid,
+        answer_text: Some("An answer".to_string()),
+    };
+    Answer::create_answer(new_answer, conn).expect("create answer")
+}
+
+#[test]
+fn edit_answer() {
+    setup(|fixture: &BucketFixture, conn: &PgConnection| {
+        let answer = answer_question_1(fixture, conn);
+        let answer_uuid = AnswerUuid(answer.uuid);
+
+        let edited = Answer::edit_answer(answer_uuid, Some("A better answer".to_string()), conn).expect("edit answer");
+        assert_eq!(edited.answer_text, Some("A better answer".to_string()));
+        assert_eq!(edited.author_uuid, answer.author_uuid);
+        assert_eq!(edited.created_at, answer.created_at);
+
+        let read = Answer::get_answer(answer_uuid, conn).expect("get answer");
+        assert_eq!(read.answer_text, Some("A better answer".to_string()));
+    });
+}
+
+#[test]
+fn answers_by_user_are_visible_to_the_author() {
+    setup(|fixture: &BucketFixture, conn: &PgConnection| {
+        let answer = answer_question_1(fixture, conn);
+        let normal_user_uuid = UserUuid(fixture.user_fixture.normal_user.uuid);
+
+        let answers = Answer::get_answers_by_user(normal_user_uuid, normal_user_uuid, conn).expect("get answers");
+        assert_eq!(answers.len(), 1);
+        assert_eq!(answers[0].answer.uuid, answer.uuid);
+        assert_eq!(answers[0].question.uuid, fixture.question_1.uuid);
+    });
+}
+
+/// Other users only see answers given in buckets they have been approved to participate in.
+#[test]
+fn answers_by_user_are_hidden_outside_approved_buckets() {
+    setup(|fixture: &BucketFixture, conn: &PgConnection| {
+        answer_question_1(fixture, conn);
+        let normal_user_uuid = UserUuid(fixture.user_fixture.normal_user.uuid);
+        let admin_user_uuid = UserUuid(fixture.user_fixture.admin_user.uuid);
+        let bucket_uuid = BucketUuid(fixture.private_bucket.uuid);
+
+        let answers = Answer::get_answers_by_user(normal_user_uuid, admin_user_uuid, conn).expect("get answers");
+        assert_eq!(answers.len(), 0);
+
+        let new_bucket_user = NewBucketUser {
+            bucket_uuid: bucket_uuid.0,
+            user_uuid: admin_user_uuid.0,
+            owner: false,
+            approved: false,
+        };
+        Bucket::add_user_to_bucket(new_bucket_user, conn).expect("add user to bucket");
+        let answers = Answer::get_answers_by_user(normal_user_uuid, admin_user_uuid, conn).expect("get answers");
+        assert_eq!(answers.len(), 0);
+
+        Bucket::set_user_approval(admin_user_uuid, bucket_uuid, true, conn).expect("approve user");
+        let answers = Answer::get_answers_by_user(normal_user_uuid, admin_user_uuid, conn).expect("get answers");
+        assert_eq!(answers.len(), 1);
+    });
+}
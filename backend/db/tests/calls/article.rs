@@ -0,0 +1,271 @@
+use common::setup::*;
+use chrono::{Duration, Utc};
+use diesel::{self, ExpressionMethods, PgConnection, QueryDsl, RunQueryDsl};
+use db::article::{Article, ArticleChangeset, ArticleFilter, NewArticle, DEFAULT_ARTICLE_COMMENTS_FORUM};
+use db::article_revision::ArticleRevision;
+use db::forum::Forum;
+use db::schema::articles;
+use db::thread::Thread;
+use identifiers::article::ArticleUuid;
+use identifiers::forum::ForumUuid;
+use identifiers::user::UserUuid;
+use testing_fixtures::fixtures::user::UserFixture;
+
+
+fn create_article(title: &str, fixture: &UserFixture, conn: &PgConnection) -> Article {
+    let new_article = NewArticle {
+        title: title.to_string(),
+        slug: slug::slugify(title),
+        body: "Article body".to_string(),
+        author_uuid: fixture.normal_user.uuid,
+    };
+    Article::create_article(new_article, conn).expect("create article")
+}
+
+/// The titles of all the threads that can be seen in the forums.
+fn listed_thread_titles(conn: &PgConnection) -> Vec<String> {
+    Forum::get_forums(conn)
+        .expect("get forums")
+        .into_iter()
+        .flat_map(|forum| {
+            Thread::get_paginated(ForumUuid(forum.uuid), 1, 100, conn)
+                .expect("get threads")
+                .items
+        })
+        .map(|data| data.thread.title)
+        .collect()
+}
+
+
+#[test]
+fn publishing_creates_one_comment_thread() {
+    setup(|fixture: &UserFixture, conn: &PgConnection| {
+        let article = create_article("Article Title", fixture, conn);
+        assert_eq!(article.comment_thread_uuid, None);
+
+        let article_uuid = ArticleUuid(article.uuid);
+        let published = Article::set_publish_status(article_uuid, true, DEFAULT_ARTICLE_COMMENTS_FORUM, conn)
+            .expect("publish article");
+        let thread_uuid = published.comment_thread_uuid.expect("thread created on publish");
+
+        Article::set_publish_status(article_uuid, false, DEFAULT_ARTICLE_COMMENTS_FORUM, conn)
+            .expect("unpublish article");
+        let republished = Article::set_publish_status(article_uuid, true, DEFAULT_ARTICLE_COMMENTS_FORUM, conn)
+            .expect("republish article");
+        assert_eq!(republished.comment_thread_uuid, Some(thread_uuid));
+
+        let read = Article::get_article(article_uuid, conn).expect("get article");
+        assert_eq!(read.comment_thread_uuid, Some(thread_uuid));
+    })
+}
+
+/// Until a scheduled article is published, nothing in the forums should reveal it.
+#[test]
+fn scheduled_articles_are_hidden_from_forums() {
+    setup(|fixture: &UserFixture, conn: &PgConnection| {
+        let article = create_article("Upcoming Announcement", fixture, conn);
+        let article_uuid = ArticleUuid(article.uuid);
+        let publish_at = Utc::now().naive_utc() + Duration::days(1);
+
+        let scheduled = Article::schedule_publish(article_uuid, publish_at, DEFAULT_ARTICLE_COMMENTS_FORUM, conn)
+            .expect("schedule article");
+        assert_eq!(scheduled.comment_thread_uuid, None);
+        let opened = Article::open_comment_thread(scheduled, DEFAULT_ARTICLE_COMMENTS_FORUM, conn)
+            .expect("open comment thread");
+        assert_eq!(opened.comment_thread_uuid, None);
+        assert!(!listed_thread_titles(conn).contains(&article.title));
+
+        // Rescheduling a published article archives its thread again.
+        Article::set_publish_status(article_uuid, true, DEFAULT_ARTICLE_COMMENTS_FORUM, conn)
+            .expect("publish article");
+        assert!(listed_thread_titles(conn).contains(&article.title));
+        Article::schedule_publish(article_uuid, publish_at, DEFAULT_ARTICLE_COMMENTS_FORUM, conn)
+            .expect("reschedule article");
+        assert!(!listed_thread_titles(conn).contains(&article.title));
+    })
+}
+
+#[test]
+fn scheduled_articles_are_only_listed_for_their_author() {
+    setup(|fixture: &UserFixture, conn: &PgConnection| {
+        let article = create_article("Coming Soon", fixture, conn);
+        let article_uuid = ArticleUuid(article.uuid);
+        let publish_at = Utc::now().naive_utc() + Duration::days(1);
+        let scheduled = Article::schedule_publish(article_uuid, publish_at, DEFAULT_ARTICLE_COMMENTS_FORUM, conn)
+            .expect("schedule article");
+
+        assert!(!scheduled.is_visible_to(None));
+        assert!(!scheduled.is_visible_to(Some(UserUuid(fixture.admin_user.uuid))));
+        assert!(scheduled.is_visible_to(Some(UserUuid(fixture.normal_user.uuid))));
+
+        let page = Article::get_paginated(1, 25, ArticleFilter::All, conn).expect("get articles");
+        assert!(page.items.iter().all(|data| data.article.uuid != article.uuid));
+        let published = Article::get_all_published(conn).expect("get published articles");
+        assert!(published.iter().all(|data| data.article.uuid != article.uuid));
+
+        let owned = Article::get_unpublished_articles_for_user(UserUuid(fixture.normal_user.uuid), conn)
+            .expect("get unpublished articles");
+        assert!(owned.iter().any(|owned| owned.uuid == article.uuid));
+        let others = Article::get_unpublished_articles_for_user(UserUuid(fixture.admin_user.uuid), conn)
+            .expect("get unpublished articles");
+        assert!(others.iter().all(|other| other.uuid != article.uuid));
+    })
+}
+
+#[test]
+fn unpublished_articles_are_hidden_from_forums() {
+    setup(|fixture: &UserFixture, conn: &PgConnection| {
+        let article = create_article("Retracted", fixture, conn);
+        let article_uuid = ArticleUuid(article.uuid);
+
+        Article::set_publish_status(article_uuid, true, DEFAULT_ARTICLE_COMMENTS_FORUM, conn)
+            .expect("publish article");
+        assert!(listed_thread_titles(conn).contains(&article.title));
+
+        Article::set_publish_status(article_uuid, false, DEFAULT_ARTICLE_COMMENTS_FORUM, conn)
+            .expect("unpublish article");
+        assert!(!listed_thread_titles(conn).contains(&article.title));
+    })
+}
+
+/// Nothing runs when the publish date arrives, so the first read afterwards opens the comment thread.
+#[test]
+fn scheduled_articles_open_comment_thread_once_published() {
+    setup(|fixture: &UserFixture, conn: &PgConnection| {
+        let article = create_article("Scheduled", fixture, conn);
+        let article_uuid = ArticleUuid(article.uuid);
+        let publish_at = Utc::now().naive_utc() + Duration::days(1);
+        Article::schedule_publish(article_uuid, publish_at, DEFAULT_ARTICLE_COMMENTS_FORUM, conn)
+            .expect("schedule article");
+
+        // Let the publish date pass.
+        let published: Article = diesel::update(articles::table.find(article.uuid))
+            .set(articles::publish_date.eq(Some(Utc::now().naive_utc() - Duration::minutes(1))))
+            .get_result(conn)
+            .expect("move publish date");
+
+        let opened = Article::open_comment_thread(published, DEFAULT_ARTICLE_COMMENTS_FORUM, conn)
+            .expect("open comment thread");
+        let thread_uuid = opened.comment_thread_uuid.expect("thread created on first read");
+        assert!(listed_thread_titles(conn).contains(&article.title));
+
+        let reopened = Article::open_comment_thread(opened, DEFAULT_ARTICLE_COMMENTS_FORUM, conn)
+            .expect("open comment thread");
+        assert_eq!(reopened.comment_thread_uuid, Some(thread_uuid));
+    })
+}
+
+
+#[test]
+fn colliding_slugs_are_suffixed() {
+    setup(|fixture: &UserFixture, conn: &PgConnection| {
+        let first = create_article("Hello World", fixture, conn);
+        let second = create_article("Hello, World!", fixture, conn);
+        let third = create_article("hello world?", fixture, conn);
+
+        assert_eq!(first.slug, "hello-world");
+        assert_eq!(second.slug, "hello-world-2");
+        assert_eq!(third.slug, "hello-world-3");
+    })
+}
+
+/// Titles without letters or numbers don't produce a slug, so the uuid is used instead.
+#[test]
+fn empty_slugs_fall_back_to_uuid() {
+    setup(|fixture: &UserFixture, conn: &PgConnection| {
+        let first = create_article("!!!", fixture, conn);
+        let second = create_article("???", fixture, conn);
+
+        assert_eq!(first.slug, first.uuid.to_string());
+        assert_eq!(second.slug, second.uuid.to_string());
+
+        let found = Article::get_article_data_by_slug(&first.slug, conn).expect("get article by slug");
+        assert_eq!(found.article.uuid, first.uuid);
+    })
+}
+
+#[test]
+fn renaming_keeps_old_slug_as_redirect() {
+    setup(|fixture: &UserFixture, conn: &PgConnection| {
+        let original = create_article("Original Title", fixture, conn);
+        create_article("New Title", fixture, conn);
+
+        let changeset = ArticleChangeset {
+            uuid: original.uuid,
+            title: Some("New Title!".to_string()),
+            body: None,
+            slug: None,
+        };
+        let renamed = Article::update_article_2(changeset, conn).expect("update article");
+        assert_eq!(renamed.slug, "new-title-2");
+
+        let found = Article::get_article_data_by_slug("original-title", conn).expect("get article by old slug");
+        assert_eq!(found.article.uuid, original.uuid);
+
+        let changeset = ArticleChangeset {
+            uuid: original.uuid,
+            title: Some("...".to_string()),
+            body: None,
+            slug: None,
+        };
+        let renamed = Article::update_article_2(changeset, conn).expect("update article");
+        assert_eq!(renamed.slug, original.uuid.to_string());
+
+        let found = Article::get_article_data_by_slug("new-title-2", conn).expect("get article by old slug");
+        assert_eq!(found.article.uuid, original.uuid);
+    })
+}
+
+/// The tags of an unpublished article shouldn't be discoverable through its related articles.
+#[test]
+fn related_articles_require_visible_article() {
+    setup(|fixture: &UserFixture, conn: &PgConnection| {
+        let draft = create_article("Draft", fixture, conn);
+        let published = create_article("Published", fixture, conn);
+        let draft_uuid = ArticleUuid(draft.uuid);
+        let published_uuid = ArticleUuid(published.uuid);
+
+        Article::set_tags(draft_uuid, vec!["Rust".to_string()], conn).expect("set tags");
+        let data = Article::set_tags(published_uuid, vec!["rust".to_string(), "Rust!".to_string()], conn)
+            .expect("set tags");
+        assert_eq!(data.tags, vec!["rust".to_string()]);
+        Article::set_publish_status(published_uuid, true, DEFAULT_ARTICLE_COMMENTS_FORUM, conn)
+            .expect("publish article");
+
+        let author = Some(UserUuid(fixture.normal_user.uuid));
+        let other = Some(UserUuid(fixture.admin_user.uuid));
+
+        Article::get_related_articles(draft_uuid, other, 5, conn).expect_err("draft should be hidden");
+        Article::get_related_articles(draft_uuid, None, 5, conn).expect_err("draft should be hidden");
+
+        let related = Article::get_related_articles(draft_uuid, author, 5, conn).expect("author can see draft");
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].article.uuid, published.uuid);
+    })
+}
+
+/// Every edit is recorded as the next revision, including rollbacks.
+#[test]
+fn edits_are_recorded_as_revisions() {
+    setup(|fixture: &UserFixture, conn: &PgConnection| {
+        let article = create_article("Revised", fixture, conn);
+        let article_uuid = ArticleUuid(article.uuid);
+        let editor_uuid = UserUuid(fixture.normal_user.uuid);
+
+        let changeset = ArticleChangeset {
+            uuid: article.uuid,
+            title: None,
+            body: Some("Second body".to_string()),
+            slug: None,
+        };
+        Article::update_article(changeset, editor_uuid, conn).expect("update article");
+        let rolled_back = ArticleRevision::rollback(article_uuid, 1, editor_uuid, conn).expect("roll back article");
+        assert_eq!(rolled_back.body, article.body);
+
+        let revisions = ArticleRevision::get_revisions_for_article(article_uuid, conn).expect("get revisions");
+        let numbers: Vec<i32> = revisions.iter().map(|data| data.revision.revision_number).collect();
+        assert_eq!(numbers, vec![1, 2, 3]);
+        assert_eq!(revisions[1].revision.body, "Second body");
+        assert_eq!(revisions[2].revision.body, article.body);
+    })
+}
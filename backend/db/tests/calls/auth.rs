@@ -5,7 +5,10 @@ use common::setup::*;
 use diesel::PgConnection;
 //use db::user::{User, NewUser};
 use db::auth;
-use wire::login::LoginRequest;
+use db::user::User;
+use db::user_session::UserSession;
+use identifiers::user::UserUuid;
+use wire::login::{LoginRequest, TokensResponse};
 use auth_lib::ServerJwt;
 
 
@@ -16,7 +19,7 @@ fn fail_login_invalid_password() {
             user_name: fixture.admin_user.user_name.clone(),
             password: "Invalid Password".to_string(),
         };
-        auth::login(bad_login, &fixture.secret, conn)
+        auth::login(bad_login, None, &fixture.secret, conn)
             .expect_err("Should have failed to log the user in");
     })
 }
@@ -28,7 +31,7 @@ fn fail_login_invalid_username() {
             user_name: "Non-existent username".to_string(),
             password: "Inconsequential password".to_string(),
         };
-        auth::login(bad_login, &fixture.secret, conn)
+        auth::login(bad_login, None, &fixture.secret, conn)
             .expect_err("Should have failed to log the user in");
     })
 }
@@ -41,10 +44,10 @@ fn successful_login() {
             user_name: fixture.admin_user.user_name.clone(),
             password: PASSWORD.to_string()
         };
-        let jwt_string: String = auth::login(login_request, &fixture.secret, conn)
+        let tokens: TokensResponse = auth::login(login_request, None, &fixture.secret, conn)
             .expect("Should have logged the user in");
 
-        let jwt =  ServerJwt::decode_jwt_string(jwt_string.as_str(), &fixture.secret )
+        let jwt =  ServerJwt::decode_jwt_string(tokens.jwt.as_str(), &fixture.secret )
             .expect("Decoded jwt token");
         assert_eq!(jwt.0.sub.0, fixture.admin_user.uuid);
         let expected_roles: Vec<UserRole> = fixture.admin_user.roles.clone().into_iter().map(UserRole::from).collect();
@@ -53,18 +56,77 @@ fn successful_login() {
 }
 
 #[test]
-fn successful_reauth() {
+fn successful_refresh() {
     setup(|fixture: &UserFixture, conn: &PgConnection| {
         let login_request = LoginRequest {
             user_name: fixture.admin_user.user_name.clone(),
             password: PASSWORD.to_string()
         };
-        let jwt_string: String = auth::login(login_request, &fixture.secret, conn)
+        let tokens: TokensResponse = auth::login(login_request, Some("Test Browser".to_string()), &fixture.secret, conn)
             .expect("Should have logged the user in");
 
-        let jwt =  ServerJwt::decode_jwt_string(jwt_string.as_str(), &fixture.secret )
+        let new_tokens: TokensResponse = auth::refresh(&tokens.refresh_token, &fixture.secret, conn)
+            .expect("New tokens should be provided");
+        ServerJwt::decode_jwt_string(new_tokens.jwt.as_str(), &fixture.secret )
             .expect("Decoded jwt token");
 
-        auth::reauth(jwt, &fixture.secret).expect("New JWT should be provided");
+        let sessions = UserSession::get_active_sessions(UserUuid(fixture.admin_user.uuid), conn).expect("get sessions");
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].user_agent, Some("Test Browser".to_string()));
+
+        auth::logout(&new_tokens.refresh_token, conn).expect("Should have logged the user out");
+        auth::refresh(&new_tokens.refresh_token, &fixture.secret, conn)
+            .expect_err("The session should have ended");
+    })
+}
+
+#[test]
+fn reused_refresh_token_ends_session() {
+    setup(|fixture: &UserFixture, conn: &PgConnection| {
+        let login_request = LoginRequest {
+            user_name: fixture.admin_user.user_name.clone(),
+            password: PASSWORD.to_string()
+        };
+        let tokens: TokensResponse = auth::login(login_request, None, &fixture.secret, conn)
+            .expect("Should have logged the user in");
+
+        let new_tokens: TokensResponse = auth::refresh(&tokens.refresh_token, &fixture.secret, conn)
+            .expect("New tokens should be provided");
+        auth::refresh(&tokens.refresh_token, &fixture.secret, conn)
+            .expect_err("A refresh token should only work once");
+        auth::refresh(&new_tokens.refresh_token, &fixture.secret, conn)
+            .expect_err("Reusing a refresh token should end its session");
+    })
+}
+
+#[test]
+fn requesting_a_reset_replaces_earlier_tokens() {
+    setup(|fixture: &UserFixture, conn: &PgConnection| {
+        let user_uuid = UserUuid(fixture.normal_user.uuid);
+        let user_name: &str = &fixture.normal_user.user_name;
+        assert!(auth::request_password_reset(user_name, conn).expect("request reset").is_none(), "There is no email to send a token to");
+
+        User::update_email(user_uuid, Some("user@example.com".to_string()), conn).expect("set email");
+        let (email, first_token) = auth::request_password_reset(user_name, conn)
+            .expect("request reset")
+            .expect("The token should be sent to the user's email");
+        assert_eq!(email, "user@example.com");
+        let (_, second_token) = auth::request_password_reset(user_name, conn)
+            .expect("request reset")
+            .expect("The token should be sent to the user's email");
+
+        auth::reset_password(&first_token, "new password", conn)
+            .expect_err("Only the most recent token should work");
+        auth::reset_password(&second_token, "new password", conn)
+            .expect("Should have reset the password");
+        auth::reset_password(&second_token, "another password", conn)
+            .expect_err("A reset token should only work once");
+
+        let login_request = LoginRequest {
+            user_name: user_name.to_string(),
+            password: "new password".to_string()
+        };
+        auth::login(login_request, None, &fixture.secret, conn)
+            .expect("Should have logged in with the new password");
     })
 }
@@ -1,7 +1,10 @@
-use db::bucket::{Bucket, NewBucket, NewBucketUser};
+use db::bucket::{Bucket, NewBucket, NewBucketUser, SessionAction};
+use db::bucket_invite::{BucketInvite, NewBucketInvite};
+use error::Error;
+use wire::bucket::{DrawMode, TurnMode};
 use db::question::{Question, NewQuestion};
 use db::answer::{Answer, NewAnswer, AnswerData};
-use db::user::User;
+use db::user::{User, NewUser};
 
 use common::setup::*;
 use diesel::PgConnection;
@@ -9,7 +12,9 @@ use chrono::Utc;
 use chrono::Duration;
 use identifiers::user::UserUuid;
 use identifiers::bucket::BucketUuid;
+use identifiers::bucket_invite::BucketInviteUuid;
 use identifiers::question::QuestionUuid;
+use uuid::Uuid;
 
 
 use testing_fixtures::fixtures::bucket::BucketFixture;
@@ -52,12 +57,30 @@ fn set_publicity() {
     setup(|fixture: &BucketFixture, conn: &PgConnection| {
         let bucket_uuid: BucketUuid = BucketUuid(fixture.private_bucket.uuid);
         let user_uuid: UserUuid = UserUuid(fixture.user_fixture.admin_user.uuid);
-        Bucket::set_bucket_publicity(bucket_uuid, true, conn).expect("set publicity");
+        Bucket::set_bucket_publicity(bucket_uuid, Some(Utc::now().naive_utc() + Duration::days(1)), conn).expect("set publicity");
         let buckets = Bucket::get_public_buckets(user_uuid, conn).expect("get public buckets");
         assert_eq!(buckets.len(), 2); // Both buckets should be public now.
     });
 }
 
+#[test]
+fn join_public_bucket_without_approval() {
+    setup(|fixture: &BucketFixture, conn: &PgConnection| {
+        let user_uuid: UserUuid = UserUuid(fixture.user_fixture.admin_user.uuid);
+
+        let bucket_uuid: BucketUuid = BucketUuid(fixture.joinable_bucket.uuid);
+        let approved = Bucket::request_to_join_bucket(user_uuid, bucket_uuid, conn).expect("join public bucket");
+        assert!(approved);
+        assert!(Bucket::is_user_approved(user_uuid, bucket_uuid, conn));
+
+        // Private buckets still need an owner to approve the request.
+        let bucket_uuid: BucketUuid = BucketUuid(fixture.private_bucket.uuid);
+        let approved = Bucket::request_to_join_bucket(user_uuid, bucket_uuid, conn).expect("request to join private bucket");
+        assert!(!approved);
+        assert!(!Bucket::is_user_approved(user_uuid, bucket_uuid, conn));
+    });
+}
+
 #[test]
 fn get_users_buckets() {
     setup(|fixture: &BucketFixture, conn: &PgConnection| {
@@ -87,6 +110,250 @@ fn is_user_bucket_owner() {
     });
 }
 
+#[test]
+fn create_bucket_with_owner() {
+    setup(|fixture: &BucketFixture, conn: &PgConnection| {
+        let user_uuid: UserUuid = UserUuid(fixture.user_fixture.admin_user.uuid);
+        let new_bucket = NewBucket {
+            bucket_name: "Owned Bucket".to_string(),
+            is_public_until: None,
+        };
+        let bucket = Bucket::create_bucket_with_owner(new_bucket, user_uuid, conn).expect("create bucket");
+        let bucket_uuid = BucketUuid(bucket.uuid);
+
+        assert!(Bucket::is_user_owner(user_uuid, bucket_uuid, conn));
+        assert!(Bucket::is_user_approved(user_uuid, bucket_uuid, conn));
+        let owned = Bucket::get_buckets_user_owns(user_uuid, conn).expect("get owned buckets");
+        assert_eq!(owned.len(), 1);
+    });
+}
+
+#[test]
+fn transfer_ownership() {
+    setup(|fixture: &BucketFixture, conn: &PgConnection| {
+        let owner_uuid: UserUuid = UserUuid(fixture.user_fixture.normal_user.uuid);
+        let new_owner_uuid: UserUuid = UserUuid(fixture.user_fixture.admin_user.uuid);
+        let bucket_uuid: BucketUuid = BucketUuid(fixture.private_bucket.uuid);
+
+        Bucket::transfer_ownership(owner_uuid, new_owner_uuid, bucket_uuid, conn).expect("transfer ownership");
+        assert!(Bucket::is_user_owner(new_owner_uuid, bucket_uuid, conn));
+        assert!(!Bucket::is_user_owner(owner_uuid, bucket_uuid, conn));
+        // The previous owner can still participate.
+        assert!(Bucket::is_user_approved(owner_uuid, bucket_uuid, conn));
+    });
+}
+
+#[test]
+fn ownership_requires_existing_user() {
+    setup(|fixture: &BucketFixture, conn: &PgConnection| {
+        let owner_uuid: UserUuid = UserUuid(fixture.user_fixture.normal_user.uuid);
+        let missing_uuid: UserUuid = UserUuid(Uuid::new_v4());
+        let bucket_uuid: BucketUuid = BucketUuid(fixture.private_bucket.uuid);
+
+        match Bucket::add_owner(missing_uuid, bucket_uuid, conn) {
+            Err(Error::NotFound { .. }) => {}
+            other => panic!("Expected the user to be missing, got {:?}", other),
+        }
+        match Bucket::transfer_ownership(owner_uuid, missing_uuid, bucket_uuid, conn) {
+            Err(Error::NotFound { .. }) => {}
+            other => panic!("Expected the user to be missing, got {:?}", other),
+        }
+        assert!(Bucket::is_user_owner(owner_uuid, bucket_uuid, conn));
+    });
+}
+
+#[test]
+fn round_robin_turns() {
+    setup(|fixture: &BucketFixture, conn: &PgConnection| {
+        let normal_user_uuid: UserUuid = UserUuid(fixture.user_fixture.normal_user.uuid);
+        let admin_user_uuid: UserUuid = UserUuid(fixture.user_fixture.admin_user.uuid);
+        let bucket_uuid: BucketUuid = BucketUuid(fixture.private_bucket.uuid);
+        Bucket::add_owner(admin_user_uuid, bucket_uuid, conn).expect("add owner");
+
+        // Turns are taken in order of user name, so "Admin" goes first.
+        let session = Bucket::set_turn_mode(bucket_uuid, TurnMode::RoundRobin, None, conn).expect("set turn mode");
+        assert_eq!(session.bucket.turn_user_uuid, Some(admin_user_uuid.0));
+        assert!(Bucket::ensure_turn(admin_user_uuid, bucket_uuid, SessionAction::Draw, conn).is_ok());
+        match Bucket::ensure_turn(normal_user_uuid, bucket_uuid, SessionAction::Draw, conn) {
+            Err(Error::OutOfTurn { .. }) => {}
+            other => panic!("Expected the draw to be out of turn, got {:?}", other),
+        }
+
+        let session = Bucket::draw_question(bucket_uuid, conn).expect("draw question");
+        let question_uuid = QuestionUuid(session.bucket.current_question_uuid.expect("current question"));
+        Bucket::finish_current_question(question_uuid, conn).expect("finish question");
+
+        let session = Bucket::get_session(bucket_uuid, conn).expect("get session");
+        assert_eq!(session.bucket.turn_user_uuid, Some(normal_user_uuid.0));
+        assert!(session.current_question.is_none());
+    });
+}
+
+#[test]
+fn shuffle_deck_draws_every_question_once() {
+    setup(|fixture: &BucketFixture, conn: &PgConnection| {
+        let bucket_uuid: BucketUuid = BucketUuid(fixture.private_bucket.uuid);
+        Bucket::set_draw_mode(bucket_uuid, DrawMode::ShuffleDeck, conn).expect("set draw mode");
+
+        let first = Question::get_random_question(bucket_uuid, conn).expect("draw first question");
+        let second = Question::get_random_question(bucket_uuid, conn).expect("draw second question");
+        assert_ne!(first.question.uuid, second.question.uuid);
+
+        // Once the deck runs out, it is reshuffled rather than failing.
+        Question::get_random_question(bucket_uuid, conn).expect("draw from reshuffled deck");
+
+        let history = Question::get_draw_history(bucket_uuid, 10, conn).expect("get draw history");
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[1].question.uuid, second.question.uuid);
+    });
+}
+
+#[test]
+fn reshuffled_deck_does_not_repeat_current_question() {
+    setup(|fixture: &BucketFixture, conn: &PgConnection| {
+        let bucket_uuid: BucketUuid = BucketUuid(fixture.private_bucket.uuid);
+        Bucket::set_draw_mode(bucket_uuid, DrawMode::ShuffleDeck, conn).expect("set draw mode");
+
+        // Every other draw starts a new deck, and its first pick must not be the question just drawn.
+        let mut previous = Bucket::draw_question(bucket_uuid, conn).expect("draw question");
+        for _ in 0..10 {
+            let next = Bucket::draw_question(bucket_uuid, conn).expect("draw question");
+            let previous_uuid = previous.current_question.as_ref().map(|question| question.question.uuid);
+            let next_uuid = next.current_question.as_ref().map(|question| question.question.uuid);
+            assert_ne!(previous_uuid, next_uuid);
+            previous = next;
+        }
+    });
+}
+
+#[test]
+fn bucket_stats() {
+    setup(|fixture: &BucketFixture, conn: &PgConnection| {
+        let normal_user_uuid: UserUuid = UserUuid(fixture.user_fixture.normal_user.uuid);
+        let admin_user_uuid: UserUuid = UserUuid(fixture.user_fixture.admin_user.uuid);
+        let bucket_uuid: BucketUuid = BucketUuid(fixture.private_bucket.uuid);
+        let question_uuid: QuestionUuid = QuestionUuid(fixture.question_1.uuid);
+
+        Question::get_random_question(bucket_uuid, conn).expect("draw question");
+        Question::put_question_on_floor(question_uuid, conn).expect("put question on floor");
+        for author_uuid in vec![Some(normal_user_uuid.0), None] {
+            let new_answer = NewAnswer {
+                author_uuid,
+                question_uuid: question_uuid.0,
+                answer_text: Some("An answer".to_string()),
+            };
+            Answer::create_answer(new_answer, conn).expect("create answer");
+        }
+        Question::favorite_question(question_uuid, admin_user_uuid, conn).expect("favorite question");
+
+        let stats = Bucket::get_stats(bucket_uuid, conn).expect("get stats");
+        assert_eq!(stats.total_questions, 2);
+        assert_eq!(stats.questions_on_floor, 1);
+        assert_eq!(stats.questions_in_bucket, 1);
+        assert_eq!(stats.participants.len(), 1);
+        assert_eq!(stats.participants[0].user.uuid, normal_user_uuid.0);
+        assert_eq!(stats.participants[0].answers, 1);
+        assert_eq!(stats.anonymous_answers, 1);
+        assert_eq!(stats.most_favorited.len(), 1);
+        assert_eq!(stats.most_favorited[0].question.uuid, question_uuid.0);
+        assert_eq!(stats.sessions.len(), 1);
+        assert_eq!(stats.sessions[0].draws, 1);
+        assert_eq!(stats.sessions[0].answers, 2);
+    });
+}
+
+#[test]
+fn merge_questions() {
+    setup(|fixture: &BucketFixture, conn: &PgConnection| {
+        let normal_user_uuid: UserUuid = UserUuid(fixture.user_fixture.normal_user.uuid);
+        let kept_uuid: QuestionUuid = QuestionUuid(fixture.question_1.uuid);
+        let merged_uuid: QuestionUuid = QuestionUuid(fixture.question_2.uuid);
+
+        let new_answer = NewAnswer {
+            author_uuid: Some(normal_user_uuid.0),
+            question_uuid: merged_uuid.0,
+            answer_text: Some("An answer".to_string()),
+        };
+        Answer::create_answer(new_answer, conn).expect("create answer");
+        Question::favorite_question(kept_uuid, normal_user_uuid, conn).expect("favorite kept question");
+        Question::favorite_question(merged_uuid, normal_user_uuid, conn).expect("favorite merged question");
+
+        let merged = Question::merge_questions(kept_uuid, vec![merged_uuid], conn).expect("merge questions");
+        assert_eq!(merged.answers.len(), 1);
+        assert!(Question::get_question(merged_uuid, conn).is_err());
+        let favorites = Question::get_favorite_questions(normal_user_uuid, conn).expect("get favorites");
+        assert_eq!(favorites.len(), 1);
+
+        match Question::merge_questions(kept_uuid, vec![kept_uuid], conn) {
+            Err(Error::BadRequest) => {}
+            other => panic!("Expected merging a question into itself to be rejected, got {:?}", other),
+        }
+    });
+}
+
+#[test]
+fn clone_bucket() {
+    setup(|fixture: &BucketFixture, conn: &PgConnection| {
+        let normal_user_uuid: UserUuid = UserUuid(fixture.user_fixture.normal_user.uuid);
+        let bucket_uuid: BucketUuid = BucketUuid(fixture.private_bucket.uuid);
+        Question::put_question_on_floor(QuestionUuid(fixture.question_1.uuid), conn).expect("put question on floor");
+
+        let clone = Bucket::clone_bucket(bucket_uuid, "Cloned Bucket".to_string(), normal_user_uuid, false, conn).expect("clone bucket");
+        let clone_uuid = BucketUuid(clone.uuid);
+        assert!(Bucket::is_user_owner(normal_user_uuid, clone_uuid, conn));
+        assert!(!clone.is_template);
+
+        let questions = Question::get_questions_for_bucket(clone_uuid, conn).expect("get cloned questions");
+        assert_eq!(questions.len(), 2);
+        assert_eq!(Question::get_number_of_questions_in_bucket(clone_uuid, conn).expect("count questions in bucket"), 2);
+    });
+}
+
+#[test]
+fn redeem_invite() {
+    setup(|fixture: &BucketFixture, conn: &PgConnection| {
+        let admin_uuid: UserUuid = UserUuid(fixture.user_fixture.admin_user.uuid);
+        let bucket_uuid: BucketUuid = BucketUuid(fixture.private_bucket.uuid);
+        let new_invite = NewBucketInvite {
+            bucket_uuid: bucket_uuid.0,
+            creator_uuid: fixture.user_fixture.normal_user.uuid,
+            token_hash: "token hash".to_string(),
+            max_uses: Some(1),
+            expires_at: None,
+        };
+        let invite = BucketInvite::create_invite(new_invite, conn).expect("create invite");
+        let invite_uuid = BucketInviteUuid(invite.uuid);
+        assert_eq!(BucketInvite::get_active_invites(bucket_uuid, conn).expect("get active invites").len(), 1);
+
+        // A token with the wrong secret doesn't use up the invite.
+        match BucketInvite::redeem_invite(invite_uuid, "other hash", admin_uuid, conn) {
+            Err(Error::NotAuthorized { .. }) => {}
+            other => panic!("Expected the wrong token to be rejected, got {:?}", other),
+        }
+        assert!(!Bucket::is_user_approved(admin_uuid, bucket_uuid, conn));
+
+        BucketInvite::redeem_invite(invite_uuid, "token hash", admin_uuid, conn).expect("redeem invite");
+        assert!(Bucket::is_user_approved(admin_uuid, bucket_uuid, conn));
+
+        // The only use has been spent, so the invite is no longer active.
+        assert!(BucketInvite::get_active_invites(bucket_uuid, conn).expect("get active invites").is_empty());
+        let new_user = NewUser {
+            user_name: "Latecomer".to_string(),
+            display_name: "Latecomer".to_string(),
+            password_hash: "not a real hash".to_string(),
+            failed_login_count: 0,
+            banned: false,
+            roles: vec![],
+        };
+        let latecomer_uuid = UserUuid(User::create_user(new_user, conn).expect("create user").uuid);
+        match BucketInvite::redeem_invite(invite_uuid, "token hash", latecomer_uuid, conn) {
+            Err(Error::NotAuthorized { .. }) => {}
+            other => panic!("Expected the used up invite to be rejected, got {:?}", other),
+        }
+        assert!(!Bucket::is_user_approved(latecomer_uuid, bucket_uuid, conn));
+    });
+}
+
 #[test]
 fn is_user_allowed_to_join_bucket() {
     setup(|fixture: &BucketFixture, conn: &PgConnection| {
@@ -145,6 +412,33 @@ fn approval_join_request() {
     });
 }
 
+/// Repeated requests to join, and becoming an owner afterwards, leave the user in the bucket once.
+#[test]
+fn joining_twice_keeps_one_membership() {
+    setup(|fixture: &BucketFixture, conn: &PgConnection| {
+        let owner_user_uuid: UserUuid = UserUuid(fixture.user_fixture.normal_user.uuid);
+        let bucket_uuid: BucketUuid = BucketUuid(fixture.private_bucket.uuid);
+        let join_user_uuid: UserUuid = UserUuid(fixture.user_fixture.admin_user.uuid);
+
+        let approved = Bucket::request_to_join_bucket(join_user_uuid, bucket_uuid, conn).expect("request to join");
+        assert!(!approved);
+        let join_request_user = NewBucketUser {
+            bucket_uuid: bucket_uuid.0,
+            user_uuid: join_user_uuid.0,
+            owner: false,
+            approved: false,
+        };
+        Bucket::add_user_to_bucket(join_request_user, conn).expect("add user to bucket again");
+
+        Bucket::add_owner(join_user_uuid, bucket_uuid, conn).expect("add owner");
+        Bucket::add_owner(join_user_uuid, bucket_uuid, conn).expect("add owner again");
+
+        let users: Vec<User> = Bucket::get_users_with_approval(bucket_uuid, owner_user_uuid, conn).expect("get_users_with_approval");
+        assert_eq!(users.len(), 1);
+        assert!(Bucket::is_user_owner(join_user_uuid, bucket_uuid, conn));
+    });
+}
+
 /// Even though the admin user should not be able to add a question
 /// because it doesn't belong to the bucket,
 /// the DB method is not responsible for enforcing that constraint.
@@ -212,4 +506,108 @@ fn answer_question() {
         let answer_data: &AnswerData = question_data.answers.first().expect("Should be 1 answer");
         assert_eq!(answer_data.answer.answer_text, answer_text);
     });
-}
\ No newline at end of file
+}
+#[test]
+fn removing_the_turn_user_passes_the_turn() {
+    setup(|fixture: &BucketFixture, conn: &PgConnection| {
+        let normal_user_uuid: UserUuid = UserUuid(fixture.user_fixture.normal_user.uuid);
+        let admin_user_uuid: UserUuid = UserUuid(fixture.user_fixture.admin_user.uuid);
+        let bucket_uuid: BucketUuid = BucketUuid(fixture.private_bucket.uuid);
+        Bucket::add_owner(admin_user_uuid, bucket_uuid, conn).expect("add owner");
+
+        let session = Bucket::set_turn_mode(bucket_uuid, TurnMode::RoundRobin, None, conn).expect("set turn mode");
+        assert_eq!(session.bucket.turn_user_uuid, Some(admin_user_uuid.0));
+
+        Bucket::remove_user_from_bucket(admin_user_uuid, bucket_uuid, conn).expect("remove user");
+        let session = Bucket::get_session(bucket_uuid, conn).expect("get session");
+        assert_eq!(session.bucket.turn_user_uuid, Some(normal_user_uuid.0));
+
+        // With nobody left to take a turn, the turn is cleared rather than handed back to the user who left.
+        Bucket::remove_user_from_bucket(normal_user_uuid, bucket_uuid, conn).expect("remove user");
+        let session = Bucket::get_session(bucket_uuid, conn).expect("get session");
+        assert_eq!(session.bucket.turn_user_uuid, None);
+    });
+}
+
+#[test]
+fn expired_turns_are_skipped() {
+    setup(|fixture: &BucketFixture, conn: &PgConnection| {
+        let normal_user_uuid: UserUuid = UserUuid(fixture.user_fixture.normal_user.uuid);
+        let admin_user_uuid: UserUuid = UserUuid(fixture.user_fixture.admin_user.uuid);
+        let bucket_uuid: BucketUuid = BucketUuid(fixture.private_bucket.uuid);
+        Bucket::add_owner(admin_user_uuid, bucket_uuid, conn).expect("add owner");
+        Bucket::set_turn_mode(bucket_uuid, TurnMode::RoundRobin, Some(1), conn).expect("set turn mode");
+
+        assert!(Bucket::skip_expired_turns(conn).expect("skip turns").is_empty());
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+        assert_eq!(Bucket::skip_expired_turns(conn).expect("skip turns"), vec![bucket_uuid]);
+
+        let session = Bucket::get_session(bucket_uuid, conn).expect("get session");
+        assert_eq!(session.bucket.turn_user_uuid, Some(normal_user_uuid.0));
+    });
+}
+
+#[test]
+fn session_draw_then_finish() {
+    setup(|fixture: &BucketFixture, conn: &PgConnection| {
+        let bucket_uuid: BucketUuid = BucketUuid(fixture.private_bucket.uuid);
+
+        let session = Bucket::draw_question(bucket_uuid, conn).expect("draw question");
+        let current = session.current_question.expect("current question");
+        assert_eq!(session.bucket.current_question_uuid, Some(current.question.uuid));
+        assert_eq!(session.questions_in_bucket, 2);
+
+        Bucket::finish_current_question(QuestionUuid(current.question.uuid), conn).expect("finish question");
+        let session = Bucket::get_session(bucket_uuid, conn).expect("get session");
+        assert!(session.current_question.is_none());
+        assert_eq!(session.bucket.current_question_uuid, None);
+        assert_eq!(session.questions_in_bucket, 2);
+
+        // Finishing a question that isn't current leaves the session alone.
+        let session = Bucket::draw_question(bucket_uuid, conn).expect("draw question");
+        let current_uuid = session.bucket.current_question_uuid.expect("current question");
+        let other_uuid = if current_uuid == fixture.question_1.uuid { fixture.question_2.uuid } else { fixture.question_1.uuid };
+        Bucket::finish_current_question(QuestionUuid(other_uuid), conn).expect("finish question");
+        let session = Bucket::get_session(bucket_uuid, conn).expect("get session");
+        assert_eq!(session.bucket.current_question_uuid, Some(current_uuid));
+    });
+}
+
+#[test]
+fn session_draw_then_discard() {
+    setup(|fixture: &BucketFixture, conn: &PgConnection| {
+        let bucket_uuid: BucketUuid = BucketUuid(fixture.private_bucket.uuid);
+
+        let session = Bucket::draw_question(bucket_uuid, conn).expect("draw question");
+        let current_uuid = session.bucket.current_question_uuid.expect("current question");
+
+        let session = Bucket::discard_current_question(bucket_uuid, conn).expect("discard question");
+        assert!(session.current_question.is_none());
+        assert_eq!(session.questions_in_bucket, 1);
+        Question::get_question(QuestionUuid(current_uuid), conn).expect_err("discarded question should be deleted");
+
+        // Discarding without a current question does nothing.
+        let session = Bucket::discard_current_question(bucket_uuid, conn).expect("discard question");
+        assert!(session.current_question.is_none());
+        assert_eq!(session.questions_in_bucket, 1);
+    });
+}
+
+/// Drawing while a question is current replaces it with a different question, without using up either.
+#[test]
+fn session_draw_while_question_is_current() {
+    setup(|fixture: &BucketFixture, conn: &PgConnection| {
+        let bucket_uuid: BucketUuid = BucketUuid(fixture.private_bucket.uuid);
+
+        let first = Bucket::draw_question(bucket_uuid, conn).expect("draw question");
+        let first_uuid = first.bucket.current_question_uuid.expect("current question");
+
+        let second = Bucket::draw_question(bucket_uuid, conn).expect("draw question");
+        let second_uuid = second.bucket.current_question_uuid.expect("current question");
+        assert_ne!(first_uuid, second_uuid);
+        assert_eq!(second.current_question.expect("current question").question.uuid, second_uuid);
+        assert_eq!(second.questions_in_bucket, 2);
+
+        Question::get_question(QuestionUuid(first_uuid), conn).expect("replaced question should still exist");
+    });
+}
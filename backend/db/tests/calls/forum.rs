@@ -1,4 +1,5 @@
 use db::thread::{Thread, MinimalThreadData};
+use db::PaginatedData;
 use db::post::{Post, NewPost, EditPostChangeset, PostData, PostVote, Vote, VoteCounts};
 use common::setup::*;
 use diesel::PgConnection;
@@ -10,6 +11,15 @@ use uuid::Uuid;
 use test::Bencher;
 use testing_fixtures::fixtures::forum::ForumFixture;
 use identifiers::user::UserUuid;
+use db::user::User;
+use db::reputation::{UPVOTE_REPUTATION, DOWNVOTE_REPUTATION};
+use db::poll::{Poll, NewPoll, PollData};
+use db::schema::polls;
+use diesel::{self, ExpressionMethods, QueryDsl, RunQueryDsl};
+use chrono::{Duration, NaiveDateTime};
+use error::Error;
+use identifiers::poll::PollUuid;
+use identifiers::poll_option::PollOptionUuid;
 
 
 
@@ -17,10 +27,15 @@ use identifiers::user::UserUuid;
 fn get_paginated() {
     setup(|fixture: &ForumFixture, conn: &PgConnection| {
         let forum_uuid = ForumUuid(fixture.forum.uuid);
-        let threads: Vec<MinimalThreadData> = Thread::get_paginated(forum_uuid, 1, 10, conn)
+        let page: PaginatedData<MinimalThreadData> = Thread::get_paginated(forum_uuid, 1, 10, conn)
             .expect("get threads in forum");
 
-        let thread_uuids: Vec<Uuid> = threads.into_iter().map(|x| x.thread.uuid).collect();
+        assert_eq!(page.page, 1);
+        assert_eq!(page.page_size, 10);
+        assert_eq!(page.total_items, page.items.len() as i64);
+        assert_eq!(page.total_pages, 1);
+
+        let thread_uuids: Vec<Uuid> = page.items.into_iter().map(|x| x.thread.uuid).collect();
 
         assert!(thread_uuids.contains(&fixture.populated_thread.uuid));
         assert!(thread_uuids.contains(&fixture.empty_thread.uuid));
@@ -28,7 +43,8 @@ fn get_paginated() {
 
         // Won't get anything in the second index
         let threads: Vec<MinimalThreadData> = Thread::get_paginated(forum_uuid, 2, 10, conn)
-            .expect("get threads in forum");
+            .expect("get threads in forum")
+            .items;
 
         assert_eq!(threads.len(), 0);
     })
@@ -51,7 +67,8 @@ fn archive() {
         Thread::archive_thread(thread_1_uuid, conn).expect("Archive thread");
 
         let threads: Vec<MinimalThreadData> = Thread::get_paginated(forum_uuid, 1, 10, conn)
-            .expect("get threads in forum");
+            .expect("get threads in forum")
+            .items;
 
         let thread_uuids: Vec<Uuid> = threads.into_iter().map(|x| x.thread.uuid).collect();
 
@@ -271,6 +288,190 @@ fn voting_many() {
 }
 
 
+/// Gets the reputation of the fixture's normal user, who authored all of the fixture's posts.
+fn author_reputation(fixture: &ForumFixture, conn: &PgConnection) -> i32 {
+    User::get_user(UserUuid(fixture.user_fixture.normal_user.uuid), conn)
+        .expect("Should get author")
+        .reputation
+}
+
+#[test]
+fn voting_up_adjusts_reputation() {
+    setup(|fixture: &ForumFixture, conn: &PgConnection| {
+        let starting_reputation = author_reputation(fixture, conn);
+        let vote = Vote {
+            post_uuid: PostUuid(fixture.post_1.uuid),
+            user_uuid: UserUuid(fixture.user_fixture.admin_user.uuid)
+        };
+
+        Post::vote(PostVote::Up(vote), conn).expect("Vote should be cast");
+        assert_eq!(author_reputation(fixture, conn), starting_reputation + UPVOTE_REPUTATION);
+
+        Post::vote(PostVote::Up(vote), conn).expect_err("Should not be able to vote twice");
+        assert_eq!(author_reputation(fixture, conn), starting_reputation + UPVOTE_REPUTATION);
+    });
+}
+
+#[test]
+fn voting_down_adjusts_reputation() {
+    setup(|fixture: &ForumFixture, conn: &PgConnection| {
+        let starting_reputation = author_reputation(fixture, conn);
+        let vote = Vote {
+            post_uuid: PostUuid(fixture.post_1.uuid),
+            user_uuid: UserUuid(fixture.user_fixture.admin_user.uuid)
+        };
+
+        Post::vote(PostVote::Down(vote), conn).expect("Vote should be cast");
+        assert_eq!(author_reputation(fixture, conn), starting_reputation + DOWNVOTE_REPUTATION);
+    });
+}
+
+/// Changing or revoking a vote should undo the reputation the original vote granted.
+#[test]
+fn voting_reversal_adjusts_reputation() {
+    setup(|fixture: &ForumFixture, conn: &PgConnection| {
+        let starting_reputation = author_reputation(fixture, conn);
+        let post_uuid = PostUuid(fixture.post_1.uuid);
+        let user_uuid = UserUuid(fixture.user_fixture.admin_user.uuid);
+        let vote = Vote {
+            post_uuid,
+            user_uuid
+        };
+
+        Post::vote(PostVote::Up(vote), conn).expect("Vote should be cast");
+        Post::vote(PostVote::Down(vote), conn).expect("Vote should be cast");
+        assert_eq!(author_reputation(fixture, conn), starting_reputation + DOWNVOTE_REPUTATION);
+
+        Post::revoke_vote(user_uuid, post_uuid, conn).expect("vote should be revoked");
+        assert_eq!(author_reputation(fixture, conn), starting_reputation);
+    });
+}
+
+/// Voting on your own post shouldn't change your reputation.
+#[test]
+fn voting_on_own_post_keeps_reputation() {
+    setup(|fixture: &ForumFixture, conn: &PgConnection| {
+        let starting_reputation = author_reputation(fixture, conn);
+        let vote = Vote {
+            post_uuid: PostUuid(fixture.post_1.uuid),
+            user_uuid: UserUuid(fixture.user_fixture.normal_user.uuid)
+        };
+
+        Post::vote(PostVote::Up(vote), conn).expect("Vote should be cast");
+        assert_eq!(author_reputation(fixture, conn), starting_reputation);
+    });
+}
+
+
+fn new_poll(fixture: &ForumFixture, closes_at: Option<NaiveDateTime>, hide_results_until_closed: bool) -> NewPoll {
+    NewPoll {
+        thread_uuid: fixture.empty_thread.uuid,
+        question: "Cats or dogs?".to_string(),
+        multiple_choice: false,
+        closes_at,
+        hide_results_until_closed,
+    }
+}
+
+fn create_poll(fixture: &ForumFixture, conn: &PgConnection) -> PollData {
+    let options = vec!["Cats".to_string(), "Dogs".to_string(), "Neither".to_string()];
+    Poll::create_poll(new_poll(fixture, None, false), options, conn).expect("create poll")
+}
+
+fn option_uuid(poll_data: &PollData, position: usize) -> PollOptionUuid {
+    PollOptionUuid(poll_data.options[position].0.uuid)
+}
+
+fn tallies(poll_data: &PollData) -> Vec<i64> {
+    poll_data.options.iter().map(|(_, count)| *count).collect()
+}
+
+#[test]
+fn poll_create() {
+    setup(|fixture: &ForumFixture, conn: &PgConnection| {
+        let poll_data = create_poll(fixture, conn);
+        let contents: Vec<String> = poll_data.options.iter().map(|(option, _)| option.content.clone()).collect();
+        assert_eq!(contents, vec!["Cats".to_string(), "Dogs".to_string(), "Neither".to_string()]);
+        assert_eq!(tallies(&poll_data), vec![0, 0, 0]);
+
+        let user_uuid = UserUuid(fixture.user_fixture.normal_user.uuid);
+        let thread_uuid = ThreadUuid(fixture.empty_thread.uuid);
+        let read = Poll::get_poll_data_for_thread(thread_uuid, Some(user_uuid), conn)
+            .expect("get poll")
+            .expect("thread has a poll");
+        assert_eq!(read, poll_data);
+    })
+}
+
+#[test]
+fn poll_create_rejects_invalid_settings() {
+    setup(|fixture: &ForumFixture, conn: &PgConnection| {
+        let options = || vec!["Cats".to_string(), "Dogs".to_string()];
+        let one_option = vec!["Cats".to_string()];
+        let past = Utc::now().naive_utc() - Duration::minutes(1);
+
+        match Poll::create_poll(new_poll(fixture, None, false), one_option, conn) {
+            Err(Error::BadRequest) => {}
+            other => panic!("Expected a poll with one option to be rejected, got {:?}", other),
+        }
+        match Poll::create_poll(new_poll(fixture, Some(past), false), options(), conn) {
+            Err(Error::BadRequest) => {}
+            other => panic!("Expected a poll that has already closed to be rejected, got {:?}", other),
+        }
+        // The results would never be shown.
+        match Poll::create_poll(new_poll(fixture, None, true), options(), conn) {
+            Err(Error::BadRequest) => {}
+            other => panic!("Expected a poll that hides its results forever to be rejected, got {:?}", other),
+        }
+    })
+}
+
+#[test]
+fn poll_vote_replaces_previous_vote() {
+    setup(|fixture: &ForumFixture, conn: &PgConnection| {
+        let poll_data = create_poll(fixture, conn);
+        let poll_uuid = PollUuid(poll_data.poll.uuid);
+        let user_uuid = UserUuid(fixture.user_fixture.normal_user.uuid);
+
+        let voted = Poll::vote(poll_uuid, user_uuid, vec![option_uuid(&poll_data, 0)], conn).expect("vote");
+        assert_eq!(tallies(&voted), vec![1, 0, 0]);
+        assert_eq!(voted.user_votes, vec![option_uuid(&poll_data, 0).0]);
+
+        let revoted = Poll::vote(poll_uuid, user_uuid, vec![option_uuid(&poll_data, 1)], conn).expect("vote again");
+        assert_eq!(tallies(&revoted), vec![0, 1, 0]);
+        assert_eq!(revoted.user_votes, vec![option_uuid(&poll_data, 1).0]);
+
+        // Single choice polls only take one option.
+        let both = vec![option_uuid(&poll_data, 0), option_uuid(&poll_data, 1)];
+        match Poll::vote(poll_uuid, user_uuid, both, conn) {
+            Err(Error::BadRequest) => {}
+            other => panic!("Expected voting for two options to be rejected, got {:?}", other),
+        }
+    })
+}
+
+#[test]
+fn poll_closed_rejects_votes() {
+    setup(|fixture: &ForumFixture, conn: &PgConnection| {
+        let closes_at = Utc::now().naive_utc() + Duration::days(1);
+        let options = vec!["Cats".to_string(), "Dogs".to_string()];
+        let poll_data = Poll::create_poll(new_poll(fixture, Some(closes_at), true), options, conn).expect("create poll");
+        let poll_uuid = PollUuid(poll_data.poll.uuid);
+        let user_uuid = UserUuid(fixture.user_fixture.normal_user.uuid);
+
+        // Let the poll close.
+        diesel::update(polls::table.find(poll_data.poll.uuid))
+            .set(polls::closes_at.eq(Some(Utc::now().naive_utc() - Duration::minutes(1))))
+            .execute(conn)
+            .expect("close poll");
+
+        match Poll::vote(poll_uuid, user_uuid, vec![option_uuid(&poll_data, 0)], conn) {
+            Err(Error::BadRequest) => {}
+            other => panic!("Expected a vote on a closed poll to be rejected, got {:?}", other),
+        }
+    })
+}
+
 #[bench]
 fn get_posts(b: &mut Bencher) {
     setup(|fixture: &ForumFixture, conn: &PgConnection| {
@@ -2,3 +2,5 @@ mod user;
 mod auth;
 mod forum;
 mod bucket;
+mod article;
+mod answer;
@@ -1,6 +1,8 @@
 use common::setup::*;
+use chrono::{Duration, Utc};
 use diesel::PgConnection;
 use db::user::{User, NewUser};
+use db::user_ban::{UserBan, NewUserBan};
 use test::Bencher;
 
 
@@ -183,3 +185,35 @@ fn crd_user_bench(b: &mut Bencher) {
         );
     });
 }
+
+#[test]
+fn expired_bans_are_lifted() {
+    setup(|fixture: &UserFixture, conn: &PgConnection| {
+        let user_uuid = UserUuid(fixture.normal_user.uuid);
+        let new_ban = NewUserBan {
+            user_uuid: user_uuid.0,
+            admin_uuid: Some(fixture.admin_user.uuid),
+            reason: "Spam".to_string(),
+            expires_at: Some(Utc::now().naive_utc() - Duration::minutes(1)),
+        };
+        UserBan::ban_user(new_ban, conn).expect("ban user");
+        assert!(User::is_user_banned(user_uuid, conn).expect("is user banned"));
+        assert!(UserBan::get_active_ban(user_uuid, conn).expect("get ban").is_none());
+
+        let lifted = UserBan::lift_expired_bans(conn).expect("lift expired bans");
+        assert_eq!(lifted, vec![user_uuid]);
+        assert!(!User::is_user_banned(user_uuid, conn).expect("is user banned"));
+    })
+}
+
+/// Pages past the end are empty, but still report the totals.
+#[test]
+fn get_paginated_past_the_end() {
+    setup(|_fixture: &UserFixture, conn: &PgConnection| {
+        let page = User::get_paginated(5, 1, conn).expect("get page of users");
+        assert_eq!(page.items.len(), 0);
+        assert_eq!(page.page, 5);
+        assert_eq!(page.total_items, 2);
+        assert_eq!(page.total_pages, 2);
+    })
+}
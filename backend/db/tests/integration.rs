@@ -4,6 +4,7 @@ extern crate test;
 extern crate wire;
 extern crate identifiers;
 extern crate db;
+extern crate error;
 extern crate auth as auth_lib;
 extern crate diesel;
 //extern crate migrations_internals;
@@ -1,5 +1,7 @@
+extern crate chrono;
 extern crate diesel;
 
+use chrono::NaiveDateTime;
 use diesel::result::Error as DieselError;
 
 #[cfg(feature = "rocket_support")]
@@ -50,10 +52,28 @@ pub enum Error {
     /// The JWT 'bearer schema' was not followed.
     MalformedToken,
     /// The user has been banned and therefore can't perform their desired action.
-    UserBanned,
+    UserBanned {
+        reason: String,
+        /// The ban is lifted at this time. It is permanent if None.
+        banned_until: Option<NaiveDateTime>,
+    },
+    /// The action belongs to another participant's turn in a turn-based bucket session.
+    OutOfTurn {
+        reason: &'static str,
+    },
 }
 
-pub type LoginResult = Result<String, LoginError>;
+/// Tells the banned user why they were banned, and for how long.
+fn ban_message(reason: &str, banned_until: Option<NaiveDateTime>) -> String {
+    match banned_until {
+        Some(banned_until) => format!(
+            "Your account has been banned until {} UTC. ({})",
+            banned_until.format("%Y-%m-%d %H:%M"),
+            reason
+        ),
+        None => format!("Your account has been banned. ({})", reason),
+    }
+}
 
 /// Logs the user in by validating their password and returning a jwt.
 
@@ -64,9 +84,24 @@ pub enum LoginError {
     AccountLocked,
     PasswordHashingError(&'static str),
     JwtError(JwtError),
+    /// The refresh token was malformed, expired, revoked, or had already been used.
+    InvalidRefreshToken,
+    /// The password reset token was malformed, expired, or had already been used.
+    InvalidResetToken,
+    AccountBanned {
+        reason: String,
+        banned_until: Option<NaiveDateTime>,
+    },
     OtherError(&'static str),
 }
 
+/// Allows login steps to be run in a transaction, which requires that database errors can be converted.
+impl From<DieselError> for LoginError {
+    fn from(_: DieselError) -> LoginError {
+        LoginError::OtherError("DB error")
+    }
+}
+
 /// An error that can occur in the course of handling JWTs.
 #[derive(Debug, Clone)]
 pub enum JwtError {
@@ -151,10 +186,11 @@ mod rocket_support {
                     .merge("Login token was not specified correctly.".respond_to(req)?)
                     .status(Status::Unauthorized)
                     .ok(),
-                UserBanned => build
-                    .merge("Your account has been banned.".respond_to(req)?)
+                UserBanned { reason, banned_until } => build
+                    .merge(ban_message(&reason, banned_until).respond_to(req)?)
                     .status(Status::Forbidden)
                     .ok(),
+                OutOfTurn { reason } => build.merge(reason.respond_to(req)?).status(Status::Conflict).ok(),
             }
         }
     }
@@ -168,6 +204,9 @@ mod rocket_support {
                 LoginError::AccountLocked => Err(Status::Unauthorized),
                 LoginError::UsernameDoesNotExist => Err(Status::NotFound),
                 LoginError::JwtError(_) => Err(Status::InternalServerError),
+                LoginError::InvalidRefreshToken => Err(Status::Unauthorized),
+                LoginError::InvalidResetToken => Err(Status::Unauthorized),
+                LoginError::AccountBanned { .. } => Err(Status::Forbidden),
                 LoginError::PasswordHashingError(_) => Err(Status::InternalServerError),
                 LoginError::OtherError(_) => Err(Status::InternalServerError),
             }
@@ -215,7 +254,8 @@ pub mod warp_support {
                 Error::NotAuthorized { reason } => {
                     format!("You are forbidden from accessing this resource. ({})", reason)
                 }
-                Error::UserBanned => "Your account has been banned".to_string(),
+                Error::UserBanned { reason, banned_until } => ban_message(reason, *banned_until),
+                Error::OutOfTurn { reason } => format!("It isn't your turn. ({})", reason),
                 Error::BadRequest => "Your request is malformed".to_string(),
                 Error::InternalServerError => "Internal server error encountered".to_string(),
                 Error::NotFound { type_name } => {
@@ -256,12 +296,13 @@ pub mod warp_support {
             Error::ExpiredToken => *resp.status_mut() = StatusCode::UNAUTHORIZED,
             Error::MalformedToken => *resp.status_mut() = StatusCode::UNAUTHORIZED, // Unauthorized is for requests that require authentication and the authentication is out of date or not present
             Error::NotAuthorized { .. } => *resp.status_mut() = StatusCode::FORBIDDEN, // Forbidden is for requests that will not served due to a lack of privileges
-            Error::UserBanned => *resp.status_mut() = StatusCode::FORBIDDEN,
+            Error::UserBanned { .. } => *resp.status_mut() = StatusCode::FORBIDDEN,
             Error::BadRequest => *resp.status_mut() = StatusCode::BAD_REQUEST,
             Error::NotFound { .. } => *resp.status_mut() = StatusCode::NOT_FOUND,
             Error::InternalServerError => *resp.status_mut() = StatusCode::INTERNAL_SERVER_ERROR,
             Error::ThreadImmutable => *resp.status_mut() = StatusCode::BAD_REQUEST,
             Error::MissingToken => *resp.status_mut() = StatusCode::UNAUTHORIZED,
+            Error::OutOfTurn { .. } => *resp.status_mut() = StatusCode::CONFLICT, // Conflict is for requests that don't fit the current state of the resource
         }
 
         //        warn!("rewrote error response: {:?}", resp);
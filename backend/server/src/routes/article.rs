@@ -24,7 +24,8 @@ fn get_article(article_uuid: ArticleUuid, conn: Conn) -> Result<Json<FullArticle
 /// Gets the published articles.
 #[get("/articles/<index>/<page_size>", rank = 0)]
 fn get_published_articles(index: i32, page_size: i32, conn: Conn) -> Result<Json<Vec<ArticlePreviewResponse>>, Error> {
-    Article::get_paginated(index, page_size, &conn)
+    Article::get_paginated(index, page_size, ArticleFilter::All, &conn)
+        .map(|page| page.items)
         .map(convert_vector)
         .map(Json)
 }
@@ -77,7 +78,7 @@ fn update_article(
     }
 
     let update_article = update_article_request.into_inner();
-    Article::update_article(update_article.into(), &conn)
+    Article::update_article(update_article.into(), user.user_uuid, &conn)
         .map(MinimalArticleResponse::from)
         .map(Json)
 }
@@ -92,7 +93,7 @@ fn publish_article(article_uuid: ArticleUuid, user: NormalUser, conn: Conn) -> R
         });
     }
 
-    Article::set_publish_status(article_uuid, true, &conn).map(|_| NoContent)
+    Article::set_publish_status(article_uuid, true, DEFAULT_ARTICLE_COMMENTS_FORUM, &conn).map(|_| NoContent)
 }
 
 /// Given an article id, set the corresponding article's date_published colum to NULL.
@@ -105,7 +106,7 @@ fn unpublish_article(article_uuid: ArticleUuid, user: NormalUser, conn: Conn) ->
         });
     }
 
-    Article::set_publish_status(article_uuid, false, &conn).map(|_| NoContent)
+    Article::set_publish_status(article_uuid, false, DEFAULT_ARTICLE_COMMENTS_FORUM, &conn).map(|_| NoContent)
 }
 
 impl Routable for Article {
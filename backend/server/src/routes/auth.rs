@@ -3,35 +3,70 @@ use rocket::Route;
 use super::Routable;
 
 use pool::Conn;
-use rocket::State;
+use rocket::{
+    request::{
+        self,
+        FromRequest,
+        Request,
+    },
+    Outcome,
+    State,
+};
 use rocket_contrib::Json;
 
 //use auth;
-use auth_lib::{
-    Secret,
-    ServerJwt,
-};
+use auth_lib::Secret;
 use db::auth as auth_db;
-use error::LoginResult;
-use wire::login::LoginRequest;
+use error::LoginError;
+use wire::login::{
+    LoginRequest,
+    RefreshTokenRequest,
+    TokensResponse,
+};
+
+/// The User-Agent header of the request, if it has one.
+struct UserAgent(Option<String>);
+
+impl<'a, 'r> FromRequest<'a, 'r> for UserAgent {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<UserAgent, ()> {
+        Outcome::Success(UserAgent(request.headers().get_one("User-Agent").map(String::from)))
+    }
+}
 
 /// Logs the user in.
-/// If successful, it generates a JWT which is used to verify other actions.
+/// If successful, it starts a session and generates a short lived JWT which is used to verify other actions,
+/// along with a refresh token used to get new JWTs.
 #[post("/login", data = "<login_request>")]
-fn login(login_request: Json<LoginRequest>, secret: State<Secret>, conn: Conn) -> LoginResult {
-    auth_db::login(login_request.into_inner(), &secret, &conn)
+fn login(
+    login_request: Json<LoginRequest>,
+    user_agent: UserAgent,
+    secret: State<Secret>,
+    conn: Conn,
+) -> Result<Json<TokensResponse>, LoginError> {
+    auth_db::login(login_request.into_inner(), user_agent.0, &secret, &conn).map(Json)
+}
+
+/// Exchanges a refresh token for a new JWT and a new refresh token.
+#[post("/refresh", data = "<refresh_request>")]
+fn refresh(
+    refresh_request: Json<RefreshTokenRequest>,
+    secret: State<Secret>,
+    conn: Conn,
+) -> Result<Json<TokensResponse>, LoginError> {
+    auth_db::refresh(&refresh_request.refresh_token, &secret, &conn).map(Json)
 }
 
-/// Given just a JWT from the header, verify the JWT,
-/// and produce another JWT with an expiry time farther out in the future.
-#[get("/reauth")]
-fn reauth(jwt: ServerJwt, secret: State<Secret>) -> LoginResult {
-    auth_db::reauth(jwt, &secret)
+/// Ends the session the refresh token belongs to.
+#[post("/logout", data = "<refresh_request>")]
+fn logout(refresh_request: Json<RefreshTokenRequest>, conn: Conn) -> Result<(), LoginError> {
+    auth_db::logout(&refresh_request.refresh_token, &conn)
 }
 
 /// Acts as a namespace for auth related methods
 pub struct Auth {}
 impl Routable for Auth {
-    const ROUTES: &'static Fn() -> Vec<Route> = &|| routes![login, reauth];
+    const ROUTES: &'static Fn() -> Vec<Route> = &|| routes![login, refresh, logout];
     const PATH: &'static str = "/auth";
 }
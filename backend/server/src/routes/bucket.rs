@@ -1,4 +1,9 @@
 use auth_lib::user_authorization::NormalUser;
+use chrono::{
+    Duration,
+    NaiveDateTime,
+    Utc,
+};
 use db::bucket::*;
 use error::BackendResult;
 use pool::Conn;
@@ -95,7 +100,12 @@ fn set_publicity(
         };
         return Err(e);
     }
-    Bucket::set_bucket_publicity(bucket_uuid, is_public_param.is_public, &conn)
+    let public_until: Option<NaiveDateTime> = if is_public_param.is_public {
+        Some(Utc::now().naive_utc() + Duration::days(1))
+    } else {
+        None
+    };
+    Bucket::set_bucket_publicity(bucket_uuid, public_until, &conn).map(|_| ())
 }
 
 /// Gets the bucket at the given Id.
@@ -189,21 +199,10 @@ fn create_bucket(
     user: NormalUser,
     conn: Conn,
 ) -> BackendResult<Json<BucketResponse>> {
-    // create the bucket
-    let bucket_response = Bucket::create_bucket(new_bucket.into_inner().into(), &conn)
+    // Create the bucket, with the user who made the request as the owner
+    Bucket::create_bucket_with_owner(new_bucket.into_inner().into(), user.user_uuid, &conn)
         .map(BucketResponse::from)
-        .map(Json)?;
-
-    // Add the user who made the request to the bucket as the owner
-    let new_bucket_user = NewBucketUser {
-        bucket_uuid: bucket_response.uuid.0,
-        user_uuid: user.user_uuid.0,
-        owner: true,
-        approved: true,
-    };
-    Bucket::add_user_to_bucket(new_bucket_user, &conn)?;
-
-    Ok(bucket_response)
+        .map(Json)
 }
 
 impl Routable for Bucket {
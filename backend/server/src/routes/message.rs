@@ -30,6 +30,7 @@ fn get_messages_for_chat(
     }
 
     Message::get_messages_for_chat(chat_uuid, index, 25, &conn)
+        .map(|page| page.items)
         .map_vec::<MessageResponse>()
         .map(Json)
 }
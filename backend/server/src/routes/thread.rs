@@ -37,8 +37,9 @@ fn create_thread(
 
     let new_thread: NewThread = new_thread_request.clone().into();
     let post_content: String = new_thread_request.post_content;
+    let poll = new_thread_request.poll;
 
-    Thread::create_thread_with_initial_post(new_thread, post_content, &conn)
+    Thread::create_thread_with_initial_post(new_thread, post_content, poll, &conn)
         .map(ThreadResponse::from)
         .map(Json)
 }
@@ -93,6 +94,7 @@ fn get_threads_by_forum_id(
 ) -> BackendResult<Json<Vec<MinimalThreadResponse>>> {
     let results_per_page: i32 = 25;
     Thread::get_paginated(forum_uuid, index, results_per_page, &conn)
+        .map(|page| page.items)
         .map_vec::<MinimalThreadResponse>()
         .map(Json)
 }
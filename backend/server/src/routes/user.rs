@@ -37,7 +37,7 @@ fn get_user(user_uuid: UserUuid, conn: Conn) -> Result<Json<UserResponse>, Error
 #[get("/users/<index>")]
 fn get_users(index: i32, _admin: AdminUser, conn: Conn) -> BackendResult<Json<Vec<FullUserResponse>>> {
     User::get_paginated(index, 25, &conn)
-        .map(|x| x.0)
+        .map(|page| page.items)
         .map(convert_vector)
         .map(Json)
 }
@@ -18,7 +18,10 @@ use rocket::{
 use testing_common::setup::setup_client;
 use testing_fixtures::fixtures::user::PASSWORD;
 use wire::{
-    login::LoginRequest,
+    login::{
+        LoginRequest,
+        TokensResponse,
+    },
     user::UpdateDisplayNameRequest,
 };
 
@@ -42,7 +45,8 @@ fn login_auth() {
         // TODO, Make the rocket init point to the test db
         eprintln!("{:?}", response);
         assert_eq!(response.status(), Status::Ok);
-        let jwt_string: String = response.body().unwrap().into_string().unwrap();
+        let tokens: TokensResponse = serde_json::from_str(&response.body().unwrap().into_string().unwrap()).unwrap();
+        let jwt_string: String = tokens.jwt;
 
         let request_body: UpdateDisplayNameRequest = UpdateDisplayNameRequest {
             user_name: fixture.normal_user.user_name.clone(),
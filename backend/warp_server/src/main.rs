@@ -12,7 +12,11 @@ extern crate identifiers;
 extern crate uuid;
 extern crate wire;
 
+extern crate chrono;
 extern crate clap;
+extern crate csv;
+extern crate futures;
+extern crate pulldown_cmark;
 extern crate serde;
 extern crate serde_json;
 extern crate simplelog;
@@ -33,6 +37,9 @@ mod routes;
 //mod error;
 mod logging;
 mod state;
+mod question_file;
+mod question_similarity;
+mod static_site;
 mod util;
 mod uuid_integration;
 
@@ -55,6 +62,12 @@ fn main() {
         let _user = configuration::create_admin(&state_config.database_url).expect("Could not create admin user");
         println!("Created Admin user with UserName: Admin, Password: Admin. Please change the password immediately");
     }
+
+    if let Some(export_config) = config.export_static {
+        let exported = static_site::export(&state_config.database_url, &export_config).expect("Could not export static site");
+        println!("Exported {} articles to {:?}", exported, export_config.out_dir);
+        return;
+    }
     let state = State::init(state_config);
 
     warp::serve(self::routes::routes(&state)).run(([127, 0, 0, 1], PORT))
@@ -62,6 +75,7 @@ fn main() {
 
 mod configuration {
     use db::{
+        article::DEFAULT_ARTICLE_COMMENTS_FORUM,
         user::NewUser,
         User,
     };
@@ -73,20 +87,31 @@ mod configuration {
     use clap::{
         App,
         Arg,
+        SubCommand,
     };
     use crate::{
         error::Error,
         state::StateConfig,
+        static_site::ExportConfig,
     };
+    use std::path::PathBuf;
 
     pub struct Config {
         pub create_admin: bool,
+        /// If present, the published articles are exported as a static site instead of starting the server.
+        pub export_static: Option<ExportConfig>,
     }
 
     /// Parses CLI arguments and provides a config for pre-launch setup and another config the server.
     pub fn parse_arguments() -> (Config, StateConfig) {
         const CREATE_ADMIN: &'static str = "create_admin";
         const SECRET_KEY: &'static str = "secret_key";
+        const ARTICLE_COMMENTS_FORUM: &'static str = "article_comments_forum";
+        const MAIL_FILE: &'static str = "mail_file";
+        const EXPORT_STATIC: &'static str = "export_static";
+        const OUT_DIR: &'static str = "out_dir";
+        const SITE_TITLE: &'static str = "site_title";
+        const BASE_URL: &'static str = "base_url";
 
         let matches = App::new("Weekend At Joes Backend")
             .version("0.1.0")
@@ -108,18 +133,84 @@ mod configuration {
                     )
                     .takes_value(true),
             )
+            .arg(
+                Arg::with_name(ARTICLE_COMMENTS_FORUM)
+                    .long("article_comments_forum")
+                    .value_name("TITLE")
+                    .help(
+                        "The title of the forum where discussion threads for published articles are created. The forum is created if it doesn't exist.",
+                    )
+                    .default_value(DEFAULT_ARTICLE_COMMENTS_FORUM)
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name(MAIL_FILE)
+                    .long("mail_file")
+                    .value_name("FILE")
+                    .help(
+                        "A file that mail to users, such as password reset tokens, is appended to. If no file is provided, then mail is written to the log.",
+                    )
+                    .takes_value(true),
+            )
+            .subcommand(
+                SubCommand::with_name(EXPORT_STATIC)
+                    .about("Writes the published articles out as a static site, then exits without starting the server.")
+                    .arg(
+                        Arg::with_name(OUT_DIR)
+                            .long("out_dir")
+                            .short("o")
+                            .value_name("DIR")
+                            .help("The directory the site is written to. It is created if it doesn't exist.")
+                            .required(true)
+                            .takes_value(true),
+                    )
+                    .arg(
+                        Arg::with_name(SITE_TITLE)
+                            .long("site_title")
+                            .value_name("TITLE")
+                            .help("The title used for the index page and the feed.")
+                            .default_value("Weekend At Joes")
+                            .takes_value(true),
+                    )
+                    .arg(
+                        Arg::with_name(BASE_URL)
+                            .long("base_url")
+                            .value_name("URL")
+                            .help("The url the site will be hosted at. The feed requires absolute links to the articles.")
+                            .default_value("http://www.weekendatjo.es")
+                            .takes_value(true),
+                    ),
+            )
             .get_matches();
 
         let create_admin: bool = matches.is_present(CREATE_ADMIN);
         let secret_key: Option<String> = matches.value_of(SECRET_KEY).map(String::from);
+        let article_comments_forum: String = matches
+            .value_of(ARTICLE_COMMENTS_FORUM)
+            .unwrap_or(DEFAULT_ARTICLE_COMMENTS_FORUM)
+            .to_string();
+        let mail_file: Option<PathBuf> = matches.value_of(MAIL_FILE).map(PathBuf::from);
 
         let database_url: String = pool::DATABASE_URL.to_string();
 
-        let config = Config { create_admin };
+        let export_static: Option<ExportConfig> = matches.subcommand_matches(EXPORT_STATIC).map(|export_matches| {
+            ExportConfig {
+                out_dir: PathBuf::from(export_matches.value_of(OUT_DIR).unwrap_or(".")),
+                site_title: export_matches.value_of(SITE_TITLE).unwrap_or_default().to_string(),
+                base_url: export_matches.value_of(BASE_URL).unwrap_or_default().to_string(),
+            }
+        });
+
+        let config = Config {
+            create_admin,
+            export_static,
+        };
 
         let state_config = StateConfig {
             specified_secret: secret_key,
             database_url,
+            article_comments_forum,
+            mail_file,
         };
         (config, state_config)
     }
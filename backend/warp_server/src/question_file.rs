@@ -0,0 +1,321 @@
+//! Reads and writes the files used to move questions between buckets.
+//!
+//! Files can be either JSON or CSV, as described by `QuestionFileFormat`.
+//! Reading a file never fails as a whole; rows that can't be used are reported alongside the questions that can.
+
+use db::question::QuestionData;
+use std::collections::HashSet;
+use wire::question::{
+    AnswerFileEntry,
+    QuestionFileEntry,
+    QuestionFileFormat,
+    SkippedQuestionRow,
+};
+
+/// The columns that questions and their answers are read from, and written to, in CSV files.
+const QUESTION_TEXT_COLUMN: &str = "question_text";
+const ANSWER_TEXT_COLUMN: &str = "answer_text";
+const AUTHOR_COLUMN: &str = "author";
+
+/// The questions read from a file that are ready to be added to a bucket, along with their answers.
+#[derive(Debug, Default, PartialEq)]
+pub struct QuestionImport {
+    pub questions: Vec<QuestionFileEntry>,
+    pub duplicates: Vec<SkippedQuestionRow>,
+    pub errors: Vec<SkippedQuestionRow>,
+}
+
+/// Reads the questions out of the file, skipping any that are invalid, already present in `existing_questions`,
+/// or that appear more than once in the file.
+///
+/// Questions are considered the same if they only differ by case or whitespace.
+pub fn import_questions(format: QuestionFileFormat, contents: &str, existing_questions: &[String]) -> QuestionImport {
+    let rows: Vec<(usize, Result<QuestionFileEntry, String>)> = match format {
+        QuestionFileFormat::Json => read_json(contents),
+        QuestionFileFormat::Csv => read_csv(contents),
+    };
+
+    let existing: HashSet<String> = existing_questions
+        .iter()
+        .map(|question_text| normalize(question_text))
+        .collect();
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut import = QuestionImport::default();
+
+    for (row_number, row) in rows {
+        match row.and_then(validate) {
+            Ok(entry) => {
+                let normalized = normalize(&entry.question_text);
+                if existing.contains(&normalized) {
+                    import.duplicates.push(SkippedQuestionRow {
+                        row: row_number,
+                        reason: format!("\"{}\" is already in the bucket", entry.question_text),
+                    });
+                } else if !seen.insert(normalized) {
+                    import.duplicates.push(SkippedQuestionRow {
+                        row: row_number,
+                        reason: format!("\"{}\" appears earlier in the file", entry.question_text),
+                    });
+                } else {
+                    import.questions.push(entry);
+                }
+            }
+            Err(reason) => import.errors.push(SkippedQuestionRow {
+                row: row_number,
+                reason,
+            }),
+        }
+    }
+    import
+}
+
+/// Writes the questions and their answers to a JSON file.
+pub fn export_json(questions: Vec<QuestionData>) -> String {
+    write_json(questions.into_iter().map(to_file_entry).collect())
+}
+
+/// Writes the questions and their answers to a CSV file.
+/// Each answer gets its own row, and questions without answers get a row with empty answer columns.
+/// The rows of a question are next to each other, which is how they are grouped back together when imported.
+pub fn export_csv(questions: Vec<QuestionData>) -> String {
+    write_csv(questions.into_iter().map(to_file_entry).collect())
+}
+
+fn to_file_entry(question_data: QuestionData) -> QuestionFileEntry {
+    QuestionFileEntry {
+        question_text: question_data.question.question_text,
+        answers: question_data
+            .answers
+            .into_iter()
+            .map(|answer_data| AnswerFileEntry {
+                answer_text: answer_data.answer.answer_text,
+                author: answer_data.user.map(|user| user.display_name),
+            })
+            .collect(),
+    }
+}
+
+fn write_json(entries: Vec<QuestionFileEntry>) -> String {
+    serde_json::to_string_pretty(&entries).expect("Question file entries should always serialize")
+}
+
+fn write_csv(entries: Vec<QuestionFileEntry>) -> String {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    writer
+        .write_record(&[QUESTION_TEXT_COLUMN, ANSWER_TEXT_COLUMN, AUTHOR_COLUMN])
+        .expect("Writing to memory can't fail");
+
+    for entry in entries {
+        if entry.answers.is_empty() {
+            writer
+                .write_record(&[entry.question_text.as_str(), "", ""])
+                .expect("Writing to memory can't fail");
+        }
+        for answer in &entry.answers {
+            writer
+                .write_record(&[
+                    entry.question_text.as_str(),
+                    answer.answer_text.as_ref().map(String::as_str).unwrap_or(""),
+                    answer.author.as_ref().map(String::as_str).unwrap_or(""),
+                ])
+                .expect("Writing to memory can't fail");
+        }
+    }
+
+    let bytes = writer.into_inner().expect("Writing to memory can't fail");
+    String::from_utf8(bytes).expect("Only strings were written")
+}
+
+/// Reads the entries of a JSON file, numbering them from 1.
+fn read_json(contents: &str) -> Vec<(usize, Result<QuestionFileEntry, String>)> {
+    // Each element is parsed on its own, so that one bad entry doesn't prevent the rest from being imported.
+    match serde_json::from_str::<Vec<serde_json::Value>>(contents) {
+        Ok(values) => values
+            .into_iter()
+            .enumerate()
+            .map(|(index, value)| {
+                let entry = serde_json::from_value::<QuestionFileEntry>(value)
+                    .map_err(|e| format!("Could not read the question: {}", e));
+                (index + 1, entry)
+            })
+            .collect(),
+        Err(e) => vec![(1, Err(format!("The file is not a JSON array of questions: {}", e)))],
+    }
+}
+
+/// Reads the questions of a CSV file, numbering the rows after the header from 1.
+///
+/// Consecutive rows with the same question are read as one question with several answers,
+/// and each question is reported at its first row.
+/// The answer columns are optional.
+fn read_csv(contents: &str) -> Vec<(usize, Result<QuestionFileEntry, String>)> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(contents.as_bytes());
+
+    let headers: Option<csv::StringRecord> = reader.headers().ok().cloned();
+    let column = |name: &str| -> Option<usize> {
+        headers
+            .as_ref()
+            .and_then(|headers| headers.iter().position(|header| header.trim() == name))
+    };
+    let question_column = match column(QUESTION_TEXT_COLUMN) {
+        Some(question_column) => question_column,
+        None => {
+            return vec![(
+                1,
+                Err(format!("The header row must contain a \"{}\" column", QUESTION_TEXT_COLUMN)),
+            )]
+        }
+    };
+    let answer_column: Option<usize> = column(ANSWER_TEXT_COLUMN);
+    let author_column: Option<usize> = column(AUTHOR_COLUMN);
+
+    let mut rows: Vec<(usize, Result<QuestionFileEntry, String>)> = vec![];
+    for (index, record) in reader.records().enumerate() {
+        let row_number = index + 1;
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                rows.push((row_number, Err(format!("Could not read the row: {}", e))));
+                continue;
+            }
+        };
+        let question_text = match record.get(question_column) {
+            Some(question_text) => question_text,
+            None => {
+                rows.push((row_number, Err(format!("The row has no \"{}\" column", QUESTION_TEXT_COLUMN))));
+                continue;
+            }
+        };
+        let cell = |column: Option<usize>| -> Option<String> {
+            column
+                .and_then(|column| record.get(column))
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(String::from)
+        };
+        // Rows without an answer only name the question.
+        let answer: Option<AnswerFileEntry> = cell(answer_column).map(|answer_text| AnswerFileEntry {
+            answer_text: Some(answer_text),
+            author: cell(author_column),
+        });
+
+        match rows.last_mut() {
+            Some((_, Ok(entry))) if entry.question_text == question_text => {
+                entry.answers.extend(answer);
+            }
+            _ => rows.push((
+                row_number,
+                Ok(QuestionFileEntry {
+                    question_text: question_text.to_string(),
+                    answers: answer.into_iter().collect(),
+                }),
+            )),
+        }
+    }
+    rows
+}
+
+fn validate(entry: QuestionFileEntry) -> Result<QuestionFileEntry, String> {
+    let question_text = entry.question_text.trim();
+    if question_text.is_empty() {
+        return Err("The question is empty".into());
+    }
+    Ok(QuestionFileEntry {
+        question_text: question_text.to_string(),
+        ..entry
+    })
+}
+
+fn normalize(question_text: &str) -> String {
+    question_text
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn question_texts(import: &QuestionImport) -> Vec<String> {
+        import.questions.iter().map(|entry| entry.question_text.clone()).collect()
+    }
+
+    fn exported_entries() -> Vec<QuestionFileEntry> {
+        vec![
+            QuestionFileEntry {
+                question_text: "What is your favorite color?".to_string(),
+                answers: vec![
+                    AnswerFileEntry {
+                        answer_text: Some("Blue".to_string()),
+                        author: Some("Joe".to_string()),
+                    },
+                    AnswerFileEntry {
+                        answer_text: Some("Green, mostly".to_string()),
+                        author: None,
+                    },
+                ],
+            },
+            QuestionFileEntry {
+                question_text: "Cats or dogs?".to_string(),
+                answers: vec![],
+            },
+        ]
+    }
+
+    #[test]
+    fn import_csv_skips_duplicates_and_empty_rows() {
+        let contents = "author,question_text\nJoe,What is your favorite color?\nJoe,\nJoe,  what is your FAVORITE   color?\nJoe,Cats or dogs?\n";
+        let existing = vec!["Cats or Dogs?".to_string()];
+
+        let import = import_questions(QuestionFileFormat::Csv, contents, &existing);
+
+        assert_eq!(question_texts(&import), vec!["What is your favorite color?".to_string()]);
+        assert_eq!(import.errors.iter().map(|e| e.row).collect::<Vec<_>>(), vec![2]);
+        assert_eq!(import.duplicates.iter().map(|e| e.row).collect::<Vec<_>>(), vec![3, 4]);
+        assert!(import.duplicates[0].reason.contains("earlier in the file"));
+        assert!(import.duplicates[1].reason.contains("already in the bucket"));
+    }
+
+    #[test]
+    fn import_csv_requires_question_column() {
+        let import = import_questions(QuestionFileFormat::Csv, "question\nHello?\n", &[]);
+        assert!(import.questions.is_empty());
+        assert_eq!(import.errors.len(), 1);
+    }
+
+    #[test]
+    fn import_json_reports_bad_entries() {
+        let contents = r#"[{"question_text": "Hello?", "answers": []}, {"text": "Missing"}, {"question_text": "Goodbye?"}]"#;
+
+        let import = import_questions(QuestionFileFormat::Json, contents, &[]);
+
+        assert_eq!(question_texts(&import), vec!["Hello?".to_string(), "Goodbye?".to_string()]);
+        assert_eq!(import.errors.iter().map(|e| e.row).collect::<Vec<_>>(), vec![2]);
+    }
+
+    #[test]
+    fn csv_export_can_be_imported() {
+        let contents = write_csv(exported_entries());
+
+        let import = import_questions(QuestionFileFormat::Csv, &contents, &[]);
+
+        assert_eq!(import.questions, exported_entries());
+        assert!(import.duplicates.is_empty());
+        assert!(import.errors.is_empty());
+    }
+
+    #[test]
+    fn json_export_can_be_imported() {
+        let contents = write_json(exported_entries());
+
+        let import = import_questions(QuestionFileFormat::Json, &contents, &[]);
+
+        assert_eq!(import.questions, exported_entries());
+        assert!(import.duplicates.is_empty());
+        assert!(import.errors.is_empty());
+    }
+}
@@ -0,0 +1,117 @@
+//! Finds questions that are worded differently but ask the same thing.
+//!
+//! Questions are compared by the trigrams of their normalized text, the same way Postgres' `pg_trgm` does.
+//! The similarity of two questions is the number of trigrams they share, divided by the number of distinct trigrams in either.
+
+use std::collections::BTreeSet;
+
+/// How similar two questions need to be before they are considered likely duplicates.
+pub const SIMILARITY_THRESHOLD: f64 = 0.6;
+
+type Trigrams = BTreeSet<[char; 3]>;
+
+/// Finds the questions in `existing_questions` that are likely duplicates of `question_text`.
+/// Returns their indices and similarities, most similar first.
+pub fn find_similar(question_text: &str, existing_questions: &[&str]) -> Vec<(usize, f64)> {
+    let trigrams = trigrams_of(question_text);
+    let mut similar: Vec<(usize, f64)> = existing_questions
+        .iter()
+        .map(|existing| jaccard(&trigrams, &trigrams_of(existing)))
+        .enumerate()
+        .filter(|(_, similarity)| *similarity >= SIMILARITY_THRESHOLD)
+        .collect();
+    similar.sort_by(|a, b| b.1.partial_cmp(&a.1).expect("Similarities are never NaN"));
+    similar
+}
+
+/// Groups the questions into clusters of likely duplicates, leaving out questions that aren't similar to any other.
+///
+/// Similarity is treated as transitive, so a question is in a cluster if it is similar to any other question in it.
+/// Each cluster holds the indices of its questions in ascending order.
+pub fn duplicate_clusters(questions: &[&str]) -> Vec<Vec<usize>> {
+    let trigrams: Vec<Trigrams> = questions.iter().map(|question| trigrams_of(question)).collect();
+
+    // Each question starts in its own cluster, and clusters are joined whenever two of their questions are similar.
+    let mut cluster_of: Vec<usize> = (0..questions.len()).collect();
+    for i in 0..questions.len() {
+        for j in (i + 1)..questions.len() {
+            if jaccard(&trigrams[i], &trigrams[j]) >= SIMILARITY_THRESHOLD {
+                let (from, to) = (cluster_of[j], cluster_of[i]);
+                if from != to {
+                    cluster_of.iter_mut().filter(|cluster| **cluster == from).for_each(|cluster| *cluster = to);
+                }
+            }
+        }
+    }
+
+    let mut clusters: Vec<Vec<usize>> = vec![];
+    for cluster in 0..questions.len() {
+        let members: Vec<usize> = (0..questions.len()).filter(|index| cluster_of[*index] == cluster).collect();
+        if members.len() > 1 {
+            clusters.push(members);
+        }
+    }
+    clusters
+}
+
+/// Lowercases the text and reduces it to words made of letters and digits.
+fn normalize(question_text: &str) -> Vec<String> {
+    question_text
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Like `pg_trgm`, each word is padded with two spaces before and one after, so short words still produce trigrams.
+fn trigrams_of(question_text: &str) -> Trigrams {
+    normalize(question_text)
+        .into_iter()
+        .flat_map(|word| {
+            let padded: Vec<char> = "  ".chars().chain(word.chars()).chain(" ".chars()).collect();
+            padded.windows(3).map(|w| [w[0], w[1], w[2]]).collect::<Vec<[char; 3]>>()
+        })
+        .collect()
+}
+
+fn jaccard(a: &Trigrams, b: &Trigrams) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rephrasings_are_similar() {
+        let existing = ["What's your favourite colour?", "Cats or dogs?", "What is your favorite color?"];
+
+        let similar = find_similar("what is your FAVORITE color", &existing);
+
+        assert_eq!(similar.iter().map(|(index, _)| *index).collect::<Vec<_>>(), vec![2, 0]);
+        assert_eq!(similar[0].1, 1.0);
+    }
+
+    #[test]
+    fn different_questions_are_not_similar() {
+        assert!(find_similar("Where did you grow up?", &["What did you have for breakfast?"]).is_empty());
+    }
+
+    #[test]
+    fn clusters_join_transitively() {
+        let questions = [
+            "What is your favorite movie?",
+            "Cats or dogs?",
+            "What is your favorite movie ever?",
+            "What is your all time favorite movie ever?",
+            "Cats or dogs??",
+        ];
+
+        assert_eq!(duplicate_clusters(&questions), vec![vec![0, 2, 3], vec![1, 4]]);
+    }
+}
@@ -3,26 +3,42 @@ use crate::{
         log_attach,
         HttpMethod,
     },
+    routes::{
+        bucket::ensure_approved,
+        bucket_session::{
+            ensure_turn,
+            publish_session,
+        },
+        question::ensure_approved_for_question,
+    },
     state::{
+        bucket_sessions::BucketSessions,
         State,
-        jwt::optional_normal_user_filter
+        jwt::normal_user_filter
     },
     util::{
         convert_and_json,
+        convert_vector_and_json,
         json_body_filter,
     },
+    uuid_integration::uuid_wrap_filter,
 };
 use db::{
     answer::{
         Answer,
         AnswerData,
         NewAnswer,
+        UserAnswerData,
     },
+    bucket::SessionAction,
+    Bucket,
     Question,
     User,
 };
 use error::Error;
 use identifiers::{
+    answer::AnswerUuid,
+    bucket::BucketUuid,
     question::QuestionUuid,
     user::UserUuid,
 };
@@ -30,18 +46,26 @@ use pool::PooledConn;
 use warp::{
     self,
     filters::BoxedFilter,
+    reject::Rejection,
     reply::Reply,
     Filter,
 };
 use wire::answer::{
     AnswerResponse,
+    EditAnswerRequest,
     NewAnswerRequest,
+    UserAnswerResponse,
 };
 
 pub fn answer_api(s: &State) -> BoxedFilter<(impl Reply,)> {
     info!("Attaching Answer API");
+    let api = answer_question(s)
+        .or(edit_answer(s))
+        .or(delete_answer(s))
+        .or(get_answers_by_user(s));
+
     warp::path("answer")
-        .and(answer_question(s))
+        .and(api)
         .with(warp::log("answer"))
         .boxed()
 }
@@ -51,13 +75,16 @@ fn answer_question(s: &State) -> BoxedFilter<(impl Reply,)> {
 
     warp::post2()
         .and(json_body_filter(16))
-        .and(optional_normal_user_filter(s))
+        .and(normal_user_filter(s))
         .and(s.db.clone())
-        .and_then(|request: NewAnswerRequest, user_uuid: Option<UserUuid>, conn: PooledConn| {
+        .and(s.bucket_sessions.clone())
+        .and_then(|request: NewAnswerRequest, user_uuid: UserUuid, conn: PooledConn, bucket_sessions: BucketSessions| {
             let new_answer: NewAnswerRequest = request;
             let question_uuid: QuestionUuid = new_answer.question_uuid.clone(); // spurious clone
+            let bucket_uuid = ensure_approved_for_question(question_uuid, user_uuid, &conn)?;
+            ensure_turn(user_uuid, bucket_uuid, SessionAction::Answer(question_uuid), &bucket_sessions, &conn)?;
 
-            let new_answer: NewAnswer = NewAnswer::attach_user_id(new_answer, user_uuid);
+            let new_answer: NewAnswer = NewAnswer::attach_user_id(new_answer, Some(user_uuid));
 
             let answer_author: Option<User> = new_answer.author_uuid
                 .map(UserUuid)
@@ -67,13 +94,247 @@ fn answer_question(s: &State) -> BoxedFilter<(impl Reply,)> {
 
             Question::put_question_on_floor(question_uuid, &conn).map_err(Error::simple_reject)?;
 
-            Answer::create_answer(new_answer, &conn)
-                .map(|answer| AnswerData {
+            let answer: Answer = Answer::create_answer(new_answer, &conn).map_err(Error::simple_reject)?;
+
+            // The answered question is done, so the session moves on from it, ending the turn if taking turns.
+            Bucket::finish_current_question(question_uuid, &conn).map_err(Error::simple_reject)?;
+            publish_session(bucket_uuid, &bucket_sessions, &conn);
+
+            Ok::<_, Rejection>(convert_and_json::<AnswerData, AnswerResponse>(AnswerData {
+                answer,
+                user: answer_author,
+            }))
+        })
+        .boxed()
+}
+
+/// Lets authors change the text of their answers, as long as they still participate in the bucket.
+fn edit_answer(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Put, "answer/<uuid>");
+
+    warp::put2()
+        .and(uuid_wrap_filter())
+        .and(json_body_filter(16))
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and(s.bucket_sessions.clone())
+        .and_then(
+            |answer_uuid: AnswerUuid,
+             request: EditAnswerRequest,
+             user_uuid: UserUuid,
+             conn: PooledConn,
+             bucket_sessions: BucketSessions| {
+                let answer: Answer = Answer::get_answer(answer_uuid, &conn).map_err(Error::simple_reject)?;
+                if answer.author_uuid != Some(user_uuid.0) {
+                    return Error::NotAuthorized {
+                        reason: "Only the author of an answer can edit it",
+                    }
+                    .reject();
+                }
+                let bucket_uuid = get_bucket_for_answer(&answer, &conn)?;
+                ensure_approved(user_uuid, bucket_uuid, &conn)?;
+
+                let answer: Answer = Answer::edit_answer(answer_uuid, request.answer_text, &conn).map_err(Error::simple_reject)?;
+                let author: User = User::get_user(user_uuid, &conn).map_err(Error::simple_reject)?;
+                publish_session(bucket_uuid, &bucket_sessions, &conn);
+
+                Ok::<_, Rejection>(convert_and_json::<AnswerData, AnswerResponse>(AnswerData {
                     answer,
-                    user: answer_author,
-                })
-                .map(convert_and_json::<AnswerData, AnswerResponse>)
+                    user: Some(author),
+                }))
+            },
+        )
+        .boxed()
+}
+
+/// Lets authors delete their answers, and owners of the bucket delete any answer in it.
+/// Authors who no longer participate in the bucket can't delete their answers.
+fn delete_answer(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Delete, "answer/<uuid>");
+
+    warp::delete2()
+        .and(uuid_wrap_filter())
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and(s.bucket_sessions.clone())
+        .and_then(
+            |answer_uuid: AnswerUuid, user_uuid: UserUuid, conn: PooledConn, bucket_sessions: BucketSessions| {
+                let answer: Answer = Answer::get_answer(answer_uuid, &conn).map_err(Error::simple_reject)?;
+                let bucket_uuid = get_bucket_for_answer(&answer, &conn)?;
+                ensure_approved(user_uuid, bucket_uuid, &conn)?;
+                let is_author = answer.author_uuid == Some(user_uuid.0);
+                if !is_author && !Bucket::is_user_owner(user_uuid, bucket_uuid, &conn) {
+                    return Error::NotAuthorized {
+                        reason: "Only the author of an answer, or an owner of its bucket, can delete it",
+                    }
+                    .reject();
+                }
+
+                Answer::delete_answer(answer_uuid, &conn).map_err(Error::simple_reject)?;
+                publish_session(bucket_uuid, &bucket_sessions, &conn);
+                Ok::<_, Rejection>(warp::reply::json(&answer_uuid))
+            },
+        )
+        .boxed()
+}
+
+/// Gets the answers a user has given, newest first.
+/// Answers in buckets the requesting user doesn't participate in are left out, unless they are the requesting user's own.
+fn get_answers_by_user(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Get, "answer/user/<uuid>");
+
+    warp::get2()
+        .and(warp::path("user"))
+        .and(uuid_wrap_filter())
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(|author_uuid: UserUuid, user_uuid: UserUuid, conn: PooledConn| {
+            Answer::get_answers_by_user(author_uuid, user_uuid, &conn)
+                .map(convert_vector_and_json::<UserAnswerData, UserAnswerResponse>)
                 .map_err(Error::simple_reject)
         })
         .boxed()
 }
+
+fn get_bucket_for_answer(answer: &Answer, conn: &PooledConn) -> Result<BucketUuid, Rejection> {
+    Question::get_question(QuestionUuid(answer.question_uuid), conn)
+        .map(|question| BucketUuid(question.bucket_uuid))
+        .map_err(Error::simple_reject)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        state::jwt::AUTHORIZATION_HEADER_KEY,
+        util::test::deserialize,
+    };
+    use db::bucket::NewBucketUser;
+    use error::warp_support::customize_error;
+    use pool::Pool;
+    use testing_common::setup::setup_warp;
+    use testing_fixtures::fixtures::bucket::BucketFixture;
+    use wire::user::BEARER;
+
+    /// Answers the fixture's first question, in the private bucket, as the normal user.
+    fn answer_question_1(fixture: &BucketFixture, conn: &PooledConn) -> Answer {
+        let new_answer = NewAnswer {
+            author_uuid: Some(fixture.user_fixture.normal_user.uuid),
+            question_uuid: fixture.question_1.uuid,
+            answer_text: Some("An answer".to_string()),
+        };
+        Answer::create_answer(new_answer, conn).expect("create answer")
+    }
+
+    #[test]
+    fn edit_only_by_author() {
+        setup_warp(|fixture: &BucketFixture, pool: Pool| {
+            let answer: Answer = {
+                let conn: PooledConn = pool.get().expect("get connection");
+                let new_bucket_user = NewBucketUser {
+                    bucket_uuid: fixture.private_bucket.uuid,
+                    user_uuid: fixture.user_fixture.admin_user.uuid,
+                    owner: false,
+                    approved: true,
+                };
+                Bucket::add_user_to_bucket(new_bucket_user, &conn).expect("add user to bucket");
+                answer_question_1(fixture, &conn)
+            };
+            let s = State::testing_init(pool, fixture.user_fixture.secret.clone());
+            let request = EditAnswerRequest {
+                answer_text: Some("A better answer".to_string()),
+            };
+
+            let admin_jwt: String =
+                crate::routes::auth::tests::get_jwt_string(&s, fixture.user_fixture.admin_user.user_name.clone());
+            let response = warp::test::request()
+                .method("PUT")
+                .header(AUTHORIZATION_HEADER_KEY, format!("{} {}", BEARER, admin_jwt))
+                .json(&request)
+                .path(&format!("/answer/{}", answer.uuid))
+                .reply(&answer_api(&s).recover(customize_error));
+            assert_eq!(response.status(), 403);
+
+            let normal_jwt: String =
+                crate::routes::auth::tests::get_jwt_string(&s, fixture.user_fixture.normal_user.user_name.clone());
+            let response = warp::test::request()
+                .method("PUT")
+                .header(AUTHORIZATION_HEADER_KEY, format!("{} {}", BEARER, normal_jwt))
+                .json(&request)
+                .path(&format!("/answer/{}", answer.uuid))
+                .reply(&answer_api(&s).recover(customize_error));
+            assert_eq!(response.status(), 200);
+            let edited: AnswerResponse = deserialize(response);
+            assert_eq!(edited.answer_text, Some("A better answer".to_string()));
+        })
+    }
+
+    /// Authors who are no longer approved to participate in the bucket can't change their answers.
+    #[test]
+    fn edit_and_delete_require_approval() {
+        setup_warp(|fixture: &BucketFixture, pool: Pool| {
+            let answer: Answer = {
+                let conn: PooledConn = pool.get().expect("get connection");
+                let answer = answer_question_1(fixture, &conn);
+                let user_uuid = UserUuid(fixture.user_fixture.normal_user.uuid);
+                let bucket_uuid = BucketUuid(fixture.private_bucket.uuid);
+                Bucket::set_user_approval(user_uuid, bucket_uuid, false, &conn).expect("revoke approval");
+                answer
+            };
+            let s = State::testing_init(pool, fixture.user_fixture.secret.clone());
+            let jwt: String =
+                crate::routes::auth::tests::get_jwt_string(&s, fixture.user_fixture.normal_user.user_name.clone());
+
+            let request = EditAnswerRequest {
+                answer_text: Some("A better answer".to_string()),
+            };
+            let response = warp::test::request()
+                .method("PUT")
+                .header(AUTHORIZATION_HEADER_KEY, format!("{} {}", BEARER, jwt))
+                .json(&request)
+                .path(&format!("/answer/{}", answer.uuid))
+                .reply(&answer_api(&s).recover(customize_error));
+            assert_eq!(response.status(), 403);
+
+            let response = warp::test::request()
+                .method("DELETE")
+                .header(AUTHORIZATION_HEADER_KEY, format!("{} {}", BEARER, jwt))
+                .path(&format!("/answer/{}", answer.uuid))
+                .reply(&answer_api(&s).recover(customize_error));
+            assert_eq!(response.status(), 403);
+        })
+    }
+
+    /// Users who have only asked to join a bucket can't answer its questions until they are approved.
+    #[test]
+    fn answering_requires_approval() {
+        setup_warp(|fixture: &BucketFixture, pool: Pool| {
+            {
+                let conn: PooledConn = pool.get().expect("get connection");
+                let new_bucket_user = NewBucketUser {
+                    bucket_uuid: fixture.private_bucket.uuid,
+                    user_uuid: fixture.user_fixture.admin_user.uuid,
+                    owner: false,
+                    approved: false,
+                };
+                Bucket::add_user_to_bucket(new_bucket_user, &conn).expect("add user to bucket");
+            }
+            let s = State::testing_init(pool, fixture.user_fixture.secret.clone());
+            let jwt: String =
+                crate::routes::auth::tests::get_jwt_string(&s, fixture.user_fixture.admin_user.user_name.clone());
+
+            let request = NewAnswerRequest {
+                question_uuid: QuestionUuid(fixture.question_1.uuid),
+                answer_text: Some("An answer".to_string()),
+            };
+            let response = warp::test::request()
+                .method("POST")
+                .header(AUTHORIZATION_HEADER_KEY, format!("{} {}", BEARER, jwt))
+                .json(&request)
+                .path("/answer/")
+                .reply(&answer_api(&s).recover(customize_error));
+            assert_eq!(response.status(), 403);
+        })
+    }
+}
+
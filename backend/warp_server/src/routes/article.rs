@@ -1,25 +1,50 @@
 use warp::{
     filters::BoxedFilter,
+    reject::Rejection,
     reply::Reply,
     Filter,
 };
 //use db::Conn;
-use db::article::{
-    Article,
-    ArticleData,
-    NewArticle,
+use auth::ServerJwt;
+use db::{
+    article::{
+        Article,
+        ArticleData,
+        ArticleFilter,
+        NewArticle,
+    },
+    article_revision::{
+        ArticleDiffData,
+        ArticleRevision,
+        ArticleRevisionData,
+    },
+    category::{
+        Category,
+        NewCategory,
+    },
 };
 use identifiers::{
     article::ArticleUuid,
+    category::CategoryUuid,
     user::UserUuid,
 };
 use uuid::Uuid;
-use wire::article::{
-    ArticlePreviewResponse,
-    FullArticleResponse,
-    MinimalArticleResponse,
-    NewArticleRequest,
-    UpdateArticleRequest,
+use wire::{
+    article::{
+        ArticleDiffResponse,
+        ArticlePreviewResponse,
+        ArticleRevisionResponse,
+        CategoryResponse,
+        FullArticleResponse,
+        MinimalArticleResponse,
+        NewArticleRequest,
+        NewCategoryRequest,
+        SchedulePublishRequest,
+        SetArticleCategoryRequest,
+        SetArticleTagsRequest,
+        UpdateArticleRequest,
+    },
+    user::UserRole,
 };
 
 use crate::{
@@ -28,11 +53,17 @@ use crate::{
         HttpMethod,
     },
     state::{
-        jwt::normal_user_filter,
+        jwt::{
+            jwt_filter,
+            normal_user_filter,
+            optional_normal_user_filter,
+            publisher_user_filter,
+        },
         State,
     },
     util::{
         convert_and_json,
+        convert_page_and_json,
         convert_vector_and_json,
         json_body_filter,
     },
@@ -49,12 +80,25 @@ pub fn article_api(s: &State) -> BoxedFilter<(impl warp::Reply,)> {
     warp::path("article")
         .and(
             get_article(s)
+                .or(get_article_by_slug(s))
                 .or(create_article(s))
                 .or(update_article(s))
                 .or(get_published_articles(s))
                 .or(get_owned_unpublished_articles(s))
                 .or(publish(s))
-                .or(unpublish(s)),
+                .or(schedule_publish(s))
+                .or(unpublish(s))
+                .or(get_revision_diff(s))
+                .or(get_revision(s))
+                .or(get_revisions(s))
+                .or(rollback(s))
+                .or(get_categories(s))
+                .or(create_category(s))
+                .or(set_tags(s))
+                .or(set_category(s))
+                .or(get_tagged_articles(s))
+                .or(get_articles_in_category(s))
+                .or(get_related_articles(s)),
         )
         .with(warp::log("article"))
         .boxed()
@@ -65,12 +109,56 @@ fn get_article(s: &State) -> BoxedFilter<(impl Reply,)> {
 
     warp::get2()
         .and(uuid_wrap_filter())
+        .and(optional_normal_user_filter(s))
+        .and(s.article_comments_forum.clone())
         .and(s.db.clone())
-        .and_then(|article_uuid: ArticleUuid, conn: PooledConn| {
-            Article::get_article_data(article_uuid, &conn)
-                .map(convert_and_json::<ArticleData, FullArticleResponse>)
-                .map_err(Error::simple_reject)
+        .and_then(
+            |article_uuid: ArticleUuid, user_uuid: Option<UserUuid>, comments_forum: String, conn: PooledConn| {
+                Article::get_article_data(article_uuid, &conn)
+                    .and_then(|data| hide_unpublished(data, user_uuid))
+                    .and_then(|data| open_comment_thread(data, &comments_forum, &conn))
+                    .map(convert_and_json::<ArticleData, FullArticleResponse>)
+                    .map_err(Error::simple_reject)
+            },
+        )
+        .boxed()
+}
+
+/// Unpublished and scheduled articles are reported as missing to everyone but their author.
+fn hide_unpublished(data: ArticleData, user_uuid: Option<UserUuid>) -> Result<ArticleData, Error> {
+    if data.article.is_visible_to(user_uuid) {
+        Ok(data)
+    } else {
+        Err(Error::NotFound {
+            type_name: "Article".to_string(),
         })
+    }
+}
+
+/// Scheduled articles get their comment thread when they are first read after being published.
+fn open_comment_thread(data: ArticleData, comments_forum: &str, conn: &PooledConn) -> Result<ArticleData, Error> {
+    let article = Article::open_comment_thread(data.article, comments_forum, conn)?;
+    Ok(ArticleData { article, ..data })
+}
+
+fn get_article_by_slug(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Get, "article/by_slug/<slug>");
+
+    warp::get2()
+        .and(warp::path("by_slug"))
+        .and(warp::path::param::<String>())
+        .and(optional_normal_user_filter(s))
+        .and(s.article_comments_forum.clone())
+        .and(s.db.clone())
+        .and_then(
+            |slug: String, user_uuid: Option<UserUuid>, comments_forum: String, conn: PooledConn| {
+                Article::get_article_data_by_slug(&slug, &conn)
+                    .and_then(|data| hide_unpublished(data, user_uuid))
+                    .and_then(|data| open_comment_thread(data, &comments_forum, &conn))
+                    .map(convert_and_json::<ArticleData, FullArticleResponse>)
+                    .map_err(Error::simple_reject)
+            },
+        )
         .boxed()
 }
 
@@ -81,8 +169,8 @@ fn get_published_articles(s: &State) -> BoxedFilter<(impl Reply,)> {
         .and(warp::path::param::<i32>())
         .and(s.db.clone())
         .and_then(|index: i32, page_size: i32, conn: PooledConn| {
-            Article::get_paginated(index, page_size, &conn)
-                .map(convert_vector_and_json::<ArticleData, ArticlePreviewResponse>)
+            Article::get_paginated(index, page_size, ArticleFilter::All, &conn)
+                .map(convert_page_and_json::<ArticleData, ArticlePreviewResponse>)
                 .map_err(Error::simple_reject)
         })
         .boxed()
@@ -137,7 +225,7 @@ fn update_article(s: &State) -> BoxedFilter<(impl Reply,)> {
                 .reject();
             }
 
-            Article::update_article(request.into(), &conn)
+            Article::update_article(request.into(), user_uuid, &conn)
                 .map(convert_and_json::<Article, MinimalArticleResponse>)
                 .map_err(Error::simple_reject)
         })
@@ -151,20 +239,57 @@ fn publish(s: &State) -> BoxedFilter<(impl Reply,)> {
         .and(warp::path("publish"))
         .and(uuid_wrap_filter())
         .and(normal_user_filter(s))
+        .and(s.article_comments_forum.clone())
         .and(s.db.clone())
-        .and_then(|article_uuid: ArticleUuid, user_uuid: UserUuid, conn: PooledConn| {
-            let article_to_update: Article = Article::get_article(article_uuid, &conn).map_err(Error::simple_reject)?;
-            if article_to_update.author_uuid != user_uuid.0 {
-                return Error::NotAuthorized {
-                    reason: "User not author",
+        .and_then(
+            |article_uuid: ArticleUuid, user_uuid: UserUuid, comments_forum: String, conn: PooledConn| {
+                let article_to_update: Article =
+                    Article::get_article(article_uuid, &conn).map_err(Error::simple_reject)?;
+                if article_to_update.author_uuid != user_uuid.0 {
+                    return Error::NotAuthorized {
+                        reason: "User not author",
+                    }
+                    .reject();
                 }
-                .reject();
-            }
 
-            Article::set_publish_status(article_uuid, true, &conn)
-                .map(|_| warp::http::StatusCode::NO_CONTENT)
-                .map_err(Error::simple_reject)
-        })
+                Article::set_publish_status(article_uuid, true, &comments_forum, &conn)
+                    .map(|_| warp::http::StatusCode::NO_CONTENT)
+                    .map_err(Error::simple_reject)
+            },
+        )
+        .boxed()
+}
+
+fn schedule_publish(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Put, "article/schedule/<uuid>");
+
+    warp::put2()
+        .and(warp::path("schedule"))
+        .and(uuid_wrap_filter())
+        .and(json_body_filter(4))
+        .and(normal_user_filter(s))
+        .and(s.article_comments_forum.clone())
+        .and(s.db.clone())
+        .and_then(
+            |article_uuid: ArticleUuid,
+             request: SchedulePublishRequest,
+             user_uuid: UserUuid,
+             comments_forum: String,
+             conn: PooledConn| {
+                let article_to_update: Article =
+                    Article::get_article(article_uuid, &conn).map_err(Error::simple_reject)?;
+                if article_to_update.author_uuid != user_uuid.0 {
+                    return Error::NotAuthorized {
+                        reason: "User not author",
+                    }
+                    .reject();
+                }
+
+                Article::schedule_publish(article_uuid, request.publish_date, &comments_forum, &conn)
+                    .map(convert_and_json::<Article, MinimalArticleResponse>)
+                    .map_err(Error::simple_reject)
+            },
+        )
         .boxed()
 }
 
@@ -175,8 +300,9 @@ fn unpublish(s: &State) -> BoxedFilter<(impl Reply,)> {
         .and(warp::path("unpublish"))
         .and(uuid_filter())
         .and(normal_user_filter(s))
+        .and(s.article_comments_forum.clone())
         .and(s.db.clone())
-        .and_then(|uuid: Uuid, user_uuid: UserUuid, conn: PooledConn| {
+        .and_then(|uuid: Uuid, user_uuid: UserUuid, comments_forum: String, conn: PooledConn| {
             let article_uuid = ArticleUuid(uuid);
             let article_to_update: Article = Article::get_article(article_uuid, &conn).map_err(Error::simple_reject)?;
             if article_to_update.author_uuid != user_uuid.0 {
@@ -186,9 +312,339 @@ fn unpublish(s: &State) -> BoxedFilter<(impl Reply,)> {
                 .reject();
             }
 
-            Article::set_publish_status(ArticleUuid(uuid), false, &conn)
+            Article::set_publish_status(ArticleUuid(uuid), false, &comments_forum, &conn)
                 .map(|_| warp::http::StatusCode::NO_CONTENT)
                 .map_err(Error::simple_reject)
         })
         .boxed()
 }
+
+/// Rejects the request if the article's history shouldn't be visible to the user.
+fn ensure_visible(article_uuid: ArticleUuid, user_uuid: Option<UserUuid>, conn: &PooledConn) -> Result<(), Rejection> {
+    let article: Article = Article::get_article(article_uuid, conn).map_err(Error::simple_reject)?;
+    if article.is_visible_to(user_uuid) {
+        Ok(())
+    } else {
+        Error::NotFound {
+            type_name: "Article".to_string(),
+        }
+        .reject()
+    }
+}
+
+fn get_revisions(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Get, "article/revisions/<uuid>");
+
+    warp::get2()
+        .and(warp::path("revisions"))
+        .and(uuid_wrap_filter())
+        .and(warp::path::end())
+        .and(optional_normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(|article_uuid: ArticleUuid, user_uuid: Option<UserUuid>, conn: PooledConn| {
+            ensure_visible(article_uuid, user_uuid, &conn)?;
+            ArticleRevision::get_revisions_for_article(article_uuid, &conn)
+                .map(convert_vector_and_json::<ArticleRevisionData, ArticleRevisionResponse>)
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
+
+fn get_revision(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Get, "article/revisions/<uuid>/<revision=i32>");
+
+    warp::get2()
+        .and(warp::path("revisions"))
+        .and(uuid_wrap_filter())
+        .and(warp::path::param::<i32>())
+        .and(optional_normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(
+            |article_uuid: ArticleUuid, revision_number: i32, user_uuid: Option<UserUuid>, conn: PooledConn| {
+                ensure_visible(article_uuid, user_uuid, &conn)?;
+                ArticleRevision::get_revision(article_uuid, revision_number, &conn)
+                    .map(convert_and_json::<ArticleRevisionData, ArticleRevisionResponse>)
+                    .map_err(Error::simple_reject)
+            },
+        )
+        .boxed()
+}
+
+fn get_revision_diff(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Get, "article/revisions/<uuid>/diff/<from=i32>/<to=i32>");
+
+    warp::get2()
+        .and(warp::path("revisions"))
+        .and(uuid_wrap_filter())
+        .and(warp::path("diff"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::param::<i32>())
+        .and(optional_normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(
+            |article_uuid: ArticleUuid, from: i32, to: i32, user_uuid: Option<UserUuid>, conn: PooledConn| {
+                ensure_visible(article_uuid, user_uuid, &conn)?;
+                ArticleRevision::diff_revisions(article_uuid, from, to, &conn)
+                    .map(convert_and_json::<ArticleDiffData, ArticleDiffResponse>)
+                    .map_err(Error::simple_reject)
+            },
+        )
+        .boxed()
+}
+
+/// Restores an article to an earlier revision.
+/// Only the author of the article or an admin may do this.
+fn rollback(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Put, "article/rollback/<uuid>/<revision=i32>");
+
+    warp::put2()
+        .and(warp::path("rollback"))
+        .and(uuid_wrap_filter())
+        .and(warp::path::param::<i32>())
+        .and(jwt_filter(s))
+        .and(s.db.clone())
+        .and_then(
+            |article_uuid: ArticleUuid, revision_number: i32, server_jwt: ServerJwt, conn: PooledConn| {
+                let user_uuid: UserUuid = server_jwt.0.sub;
+                let article: Article = Article::get_article(article_uuid, &conn).map_err(Error::simple_reject)?;
+                let is_admin = server_jwt.0.user_roles.contains(&UserRole::Admin);
+                if article.author_uuid != user_uuid.0 && !is_admin {
+                    return Error::NotAuthorized {
+                        reason: "User not author or admin",
+                    }
+                    .reject();
+                }
+
+                ArticleRevision::rollback(article_uuid, revision_number, user_uuid, &conn)
+                    .map(convert_and_json::<Article, MinimalArticleResponse>)
+                    .map_err(Error::simple_reject)
+            },
+        )
+        .boxed()
+}
+
+/// The number of articles returned when asking for related articles.
+const RELATED_ARTICLES_COUNT: usize = 5;
+
+fn get_categories(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Get, "article/categories");
+
+    warp::get2()
+        .and(warp::path("categories"))
+        .and(s.db.clone())
+        .and_then(|conn: PooledConn| {
+            Category::get_categories(&conn)
+                .map(convert_vector_and_json::<Category, CategoryResponse>)
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
+
+fn create_category(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Post, "article/categories");
+
+    warp::post2()
+        .and(warp::path("categories"))
+        .and(json_body_filter(4))
+        .and(publisher_user_filter(s))
+        .and(s.db.clone())
+        .and_then(|request: NewCategoryRequest, _user: UserUuid, conn: PooledConn| {
+            let new_category: NewCategory = request.into();
+            Category::create_category(new_category, &conn)
+                .map(convert_and_json::<Category, CategoryResponse>)
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
+
+fn set_tags(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Put, "article/tags/<uuid>");
+
+    warp::put2()
+        .and(warp::path("tags"))
+        .and(uuid_wrap_filter())
+        .and(json_body_filter(4))
+        .and(publisher_user_filter(s))
+        .and(s.db.clone())
+        .and_then(
+            |article_uuid: ArticleUuid, request: SetArticleTagsRequest, user_uuid: UserUuid, conn: PooledConn| {
+                let article_to_update: Article =
+                    Article::get_article(article_uuid, &conn).map_err(Error::simple_reject)?;
+                if article_to_update.author_uuid != user_uuid.0 {
+                    return Error::NotAuthorized {
+                        reason: "User not author",
+                    }
+                    .reject();
+                }
+
+                Article::set_tags(article_uuid, request.tags, &conn)
+                    .map(convert_and_json::<ArticleData, FullArticleResponse>)
+                    .map_err(Error::simple_reject)
+            },
+        )
+        .boxed()
+}
+
+fn set_category(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Put, "article/category/<uuid>");
+
+    warp::put2()
+        .and(warp::path("category"))
+        .and(uuid_wrap_filter())
+        .and(json_body_filter(4))
+        .and(publisher_user_filter(s))
+        .and(s.db.clone())
+        .and_then(
+            |article_uuid: ArticleUuid, request: SetArticleCategoryRequest, user_uuid: UserUuid, conn: PooledConn| {
+                let article_to_update: Article =
+                    Article::get_article(article_uuid, &conn).map_err(Error::simple_reject)?;
+                if article_to_update.author_uuid != user_uuid.0 {
+                    return Error::NotAuthorized {
+                        reason: "User not author",
+                    }
+                    .reject();
+                }
+
+                Article::set_category(article_uuid, request.category_uuid, &conn)
+                    .map(convert_and_json::<Article, MinimalArticleResponse>)
+                    .map_err(Error::simple_reject)
+            },
+        )
+        .boxed()
+}
+
+fn get_tagged_articles(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Get, "article/tagged/<tag>/<index=i32>/<page_size=i32>");
+
+    warp::get2()
+        .and(warp::path("tagged"))
+        .and(warp::path::param::<String>())
+        .and(warp::path::param::<i32>())
+        .and(warp::path::param::<i32>())
+        .and(s.db.clone())
+        .and_then(|tag: String, index: i32, page_size: i32, conn: PooledConn| {
+            Article::get_paginated(index, page_size, ArticleFilter::Tag(tag), &conn)
+                .map(convert_page_and_json::<ArticleData, ArticlePreviewResponse>)
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
+
+fn get_articles_in_category(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Get, "article/in_category/<uuid>/<index=i32>/<page_size=i32>");
+
+    warp::get2()
+        .and(warp::path("in_category"))
+        .and(uuid_wrap_filter())
+        .and(warp::path::param::<i32>())
+        .and(warp::path::param::<i32>())
+        .and(s.db.clone())
+        .and_then(|category_uuid: CategoryUuid, index: i32, page_size: i32, conn: PooledConn| {
+            Article::get_paginated(index, page_size, ArticleFilter::Category(category_uuid), &conn)
+                .map(convert_page_and_json::<ArticleData, ArticlePreviewResponse>)
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
+
+fn get_related_articles(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Get, "article/related/<uuid>");
+
+    warp::get2()
+        .and(warp::path("related"))
+        .and(uuid_wrap_filter())
+        .and(optional_normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(|article_uuid: ArticleUuid, user_uuid: Option<UserUuid>, conn: PooledConn| {
+            Article::get_related_articles(article_uuid, user_uuid, RELATED_ARTICLES_COUNT, &conn)
+                .map(convert_vector_and_json::<ArticleData, ArticlePreviewResponse>)
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{
+        Duration,
+        Utc,
+    };
+    use db::article::DEFAULT_ARTICLE_COMMENTS_FORUM;
+    use error::warp_support::customize_error;
+    use crate::{
+        routes::auth::tests::get_jwt_string,
+        state::jwt::AUTHORIZATION_HEADER_KEY,
+        util::test::deserialize,
+    };
+    use pool::Pool;
+    use testing_common::setup::setup_warp;
+    use testing_fixtures::fixtures::user::UserFixture;
+    use wire::user::BEARER;
+
+    /// Creates an article by the normal user that is scheduled to be published tomorrow.
+    fn create_scheduled_article(fixture: &UserFixture, pool: &Pool) -> Article {
+        let conn: PooledConn = pool.get().expect("get connection");
+        let new_article = NewArticle {
+            title: "Scheduled".to_string(),
+            slug: "scheduled".to_string(),
+            body: "Article body".to_string(),
+            author_uuid: fixture.normal_user.uuid,
+        };
+        let article = Article::create_article(new_article, &conn).expect("create article");
+        let tomorrow = Utc::now().naive_utc() + Duration::days(1);
+        Article::schedule_publish(
+            ArticleUuid(article.uuid),
+            tomorrow,
+            DEFAULT_ARTICLE_COMMENTS_FORUM,
+            &conn,
+        )
+        .expect("schedule article")
+    }
+
+    #[test]
+    fn scheduled_articles_are_only_visible_to_their_author() {
+        setup_warp(|fixture: &UserFixture, pool: Pool| {
+            let s = State::testing_init(pool.clone(), fixture.secret.clone());
+            let article = create_scheduled_article(fixture, &pool);
+            let author_jwt = get_jwt_string(&s, fixture.normal_user.user_name.clone());
+            let other_jwt = get_jwt_string(&s, fixture.admin_user.user_name.clone());
+            let paths = vec![
+                format!("/article/{}", article.uuid),
+                format!("/article/by_slug/{}", article.slug),
+            ];
+
+            for path in paths {
+                let response = warp::test::request()
+                    .method("GET")
+                    .path(&path)
+                    .reply(&article_api(&s).recover(customize_error));
+                assert_eq!(response.status(), 404, "anonymous read of {}", path);
+
+                let response = warp::test::request()
+                    .method("GET")
+                    .header(AUTHORIZATION_HEADER_KEY, format!("{} {}", BEARER, other_jwt))
+                    .path(&path)
+                    .reply(&article_api(&s).recover(customize_error));
+                assert_eq!(response.status(), 404, "other user's read of {}", path);
+
+                let response = warp::test::request()
+                    .method("GET")
+                    .header(AUTHORIZATION_HEADER_KEY, format!("{} {}", BEARER, author_jwt))
+                    .path(&path)
+                    .reply(&article_api(&s).recover(customize_error));
+                assert_eq!(response.status(), 200, "author's read of {}", path);
+                let response: FullArticleResponse = deserialize(response);
+                assert_eq!(response.id, ArticleUuid(article.uuid));
+            }
+
+            let response = warp::test::request()
+                .method("GET")
+                .path("/article/1/25")
+                .reply(&article_api(&s).recover(customize_error));
+            assert_eq!(response.status(), 200);
+            let page: wire::Page<ArticlePreviewResponse> = deserialize(response);
+            assert!(page.items.is_empty());
+        })
+    }
+}
@@ -6,60 +6,79 @@ use warp::{
     Filter,
 };
 
-use auth::{
-    Secret,
-    ServerJwt,
-};
+use auth::Secret;
 use crate::{
-    error::Error,
+    error::{
+        Error,
+        LoginError,
+    },
     logging::{
         log_attach,
         HttpMethod,
     },
     state::{
-        jwt::jwt_filter,
+        jwt::normal_user_filter,
+        mailer::{
+            Mail,
+            Mailer,
+        },
         State,
     },
+    util::{
+        convert_vector_and_json,
+        json_body_filter,
+    },
+    uuid_integration::uuid_wrap_filter,
+};
+use db::{
+    auth as auth_db,
+    user_session::UserSession,
+};
+use identifiers::{
+    user::UserUuid,
+    user_session::UserSessionUuid,
 };
-use db::auth as auth_db;
 use pool::PooledConn;
-use wire::login::LoginRequest;
+use std::sync::Arc;
+use wire::login::{
+    ChangePasswordRequest,
+    ForgotPasswordRequest,
+    LoginRequest,
+    RefreshTokenRequest,
+    ResetPasswordRequest,
+    UserSessionResponse,
+};
 
 pub fn auth_api(s: &State) -> BoxedFilter<(impl warp::Reply,)> {
     info!("Attaching Auth API");
+    let api = login(s)
+        .or(refresh(s))
+        .or(logout(s))
+        .or(get_sessions(s))
+        .or(end_all_sessions(s))
+        .or(end_session(s))
+        .or(change_password(s))
+        .or(forgot_password(s))
+        .or(reset_password(s));
+
     warp::path("auth")
-        .and(reauth(s).or(login(s)))
+        .and(api)
         .with(warp::log("auth"))
         .boxed()
 }
 
-fn reauth(s: &State) -> BoxedFilter<(impl Reply,)> {
-    log_attach(HttpMethod::Get, "auth/reauth");
-    warp::get2()
-        .and(warp::path("reauth"))
-        .and(s.secret.clone())
-        .and(jwt_filter(s))
-        .and_then(|secret: Secret, jwt: ServerJwt| {
-            auth_db::reauth(jwt, &secret).map_err(|_| {
-                Error::NotAuthorized {
-                    reason: "Could not reauth",
-                }
-                .simple_reject()
-            })
-        })
-        .boxed()
-}
-
 fn login(s: &State) -> BoxedFilter<(impl Reply,)> {
     log_attach(HttpMethod::Post, "auth/login");
 
-    fn handle_login(secret: Secret, conn: PooledConn, login_request: LoginRequest) -> Result<impl Reply, Rejection> {
-        auth_db::login(login_request, &secret, &conn).map_err(|_| {
-            Error::NotAuthorized {
-                reason: "Invalid password or username",
-            }
-            .simple_reject()
-        }) // TODO I can do better than this error message.
+    fn handle_login(
+        secret: Secret,
+        conn: PooledConn,
+        login_request: LoginRequest,
+        user_agent: Option<String>,
+    ) -> Result<impl Reply, Rejection> {
+        auth_db::login(login_request, user_agent, &secret, &conn)
+            .map(|tokens| warp::reply::json(&tokens))
+            .map_err(|e| login_rejection(e, "Invalid password or username")) // TODO I can do better than this error message.
     }
 
     warp::post2()
@@ -67,22 +86,214 @@ fn login(s: &State) -> BoxedFilter<(impl Reply,)> {
         .and(s.secret.clone())
         .and(s.db.clone())
         .and(warp::body::json())
+        .and(user_agent_filter())
         .and_then(handle_login)
         .boxed()
 }
 
+/// Exchanges a refresh token for a new JWT and refresh token.
+fn refresh(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Post, "auth/refresh");
+    warp::post2()
+        .and(warp::path("refresh"))
+        .and(json_body_filter(1))
+        .and(s.secret.clone())
+        .and(s.db.clone())
+        .and_then(|request: RefreshTokenRequest, secret: Secret, conn: PooledConn| {
+            auth_db::refresh(&request.refresh_token, &secret, &conn)
+                .map(|tokens| warp::reply::json(&tokens))
+                .map_err(|e| login_rejection(e, "The refresh token is not valid"))
+        })
+        .boxed()
+}
+
+/// Ends the session the refresh token belongs to.
+fn logout(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Post, "auth/logout");
+    warp::post2()
+        .and(warp::path("logout"))
+        .and(json_body_filter(1))
+        .and(s.db.clone())
+        .and_then(|request: RefreshTokenRequest, conn: PooledConn| {
+            auth_db::logout(&request.refresh_token, &conn)
+                .map(|_| warp::http::StatusCode::NO_CONTENT)
+                .map_err(|_| {
+                    Error::NotAuthorized {
+                        reason: "The refresh token is not valid",
+                    }
+                    .simple_reject()
+                })
+        })
+        .boxed()
+}
+
+/// Gets the places the user is logged in.
+fn get_sessions(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Get, "auth/sessions");
+    warp::get2()
+        .and(warp::path("sessions"))
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(|user_uuid: UserUuid, conn: PooledConn| {
+            UserSession::get_active_sessions(user_uuid, &conn)
+                .map(convert_vector_and_json::<UserSession, UserSessionResponse>)
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
+
+/// Logs the user out of one of their sessions.
+fn end_session(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Delete, "auth/sessions/<uuid>");
+    warp::delete2()
+        .and(warp::path("sessions"))
+        .and(uuid_wrap_filter())
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(|session_uuid: UserSessionUuid, user_uuid: UserUuid, conn: PooledConn| {
+            let session: UserSession = UserSession::get_session(session_uuid, &conn).map_err(Error::simple_reject)?;
+            if session.user_uuid != user_uuid.0 {
+                return Error::NotAuthorized {
+                    reason: "You can only end your own sessions",
+                }
+                .reject();
+            }
+            UserSession::end_session(session_uuid, &conn)
+                .map(|_| warp::http::StatusCode::NO_CONTENT)
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
+
+/// Logs the user out everywhere, including the session making the request.
+fn end_all_sessions(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Delete, "auth/sessions");
+    warp::delete2()
+        .and(warp::path("sessions"))
+        .and(warp::path::end())
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(|user_uuid: UserUuid, conn: PooledConn| {
+            UserSession::end_all_sessions(user_uuid, &conn)
+                .map(|_| warp::http::StatusCode::NO_CONTENT)
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
+
+/// Sets a new password for the user, which requires their current password.
+fn change_password(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Put, "auth/password");
+    warp::put2()
+        .and(warp::path("password"))
+        .and(json_body_filter(1))
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(|request: ChangePasswordRequest, user_uuid: UserUuid, conn: PooledConn| {
+            if request.new_password.is_empty() {
+                return Error::BadRequest.reject();
+            }
+            auth_db::change_password(user_uuid, &request.current_password, &request.new_password, &conn)
+                .map(|_| warp::http::StatusCode::NO_CONTENT)
+                .map_err(|e| login_rejection(e, "The current password is incorrect"))
+        })
+        .boxed()
+}
+
+/// Mails a password reset token to the user.
+///
+/// The response is the same whether or not the user exists or has an email,
+/// so this can't be used to find out which user names are taken.
+fn forgot_password(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Post, "auth/forgot_password");
+    warp::post2()
+        .and(warp::path("forgot_password"))
+        .and(json_body_filter(1))
+        .and(s.db.clone())
+        .and(s.mailer.clone())
+        .and_then(|request: ForgotPasswordRequest, conn: PooledConn, mailer: Arc<dyn Mailer>| {
+            let reset = auth_db::request_password_reset(&request.user_name, &conn)
+                .map_err(|_| Error::InternalServerError.simple_reject())?;
+            if let Some((email, reset_token)) = reset {
+                let mail = Mail {
+                    to: email,
+                    subject: "Password reset".to_string(),
+                    body: format!(
+                        "Someone asked to reset the password for {}. If it wasn't you, you can ignore this email.\n\nReset token: {}\n\nThe token expires in {} minutes.",
+                        request.user_name,
+                        reset_token,
+                        auth_db::PASSWORD_RESET_MINUTES
+                    ),
+                };
+                if let Err(e) = mailer.send(mail) {
+                    warn!("Could not send the password reset mail for user {}: {:?}", request.user_name, e);
+                }
+            }
+            Ok::<_, Rejection>(warp::http::StatusCode::NO_CONTENT)
+        })
+        .boxed()
+}
+
+/// Uses a mailed reset token to set a new password, ending all of the user's sessions.
+fn reset_password(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Post, "auth/reset_password");
+    warp::post2()
+        .and(warp::path("reset_password"))
+        .and(json_body_filter(1))
+        .and(s.db.clone())
+        .and_then(|request: ResetPasswordRequest, conn: PooledConn| {
+            if request.new_password.is_empty() {
+                return Error::BadRequest.reject();
+            }
+            auth_db::reset_password(&request.reset_token, &request.new_password, &conn)
+                .map(|_| warp::http::StatusCode::NO_CONTENT)
+                .map_err(|e| login_rejection(e, "The reset token is not valid"))
+        })
+        .boxed()
+}
+
+/// Banned users are told why they were banned, and other failures are rejected with the given reason.
+fn login_rejection(error: LoginError, reason: &'static str) -> Rejection {
+    match error {
+        LoginError::AccountBanned { reason, banned_until } => Error::UserBanned { reason, banned_until }.simple_reject(),
+        _ => Error::NotAuthorized { reason }.simple_reject(),
+    }
+}
+
+/// Gets the User-Agent header, so users can tell their sessions apart.
+fn user_agent_filter() -> BoxedFilter<(Option<String>,)> {
+    warp::header::header::<String>("user-agent")
+        .map(Some)
+        .or(warp::any().map(|| None))
+        .unify::<(Option<String>,)>()
+        .boxed()
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use error::warp_support::customize_error;
     use crate::{
-        state::jwt::AUTHORIZATION_HEADER_KEY,
-        util::test::deserialize_string,
+        routes::user::user_api,
+        state::{
+            jwt::AUTHORIZATION_HEADER_KEY,
+            mailer::FileMailer,
+        },
+        util::test::deserialize,
     };
     use pool::Pool;
     use serde_json::to_string as serde_ser;
+    use std::fs;
+    use uuid::Uuid;
     use testing_common::setup::setup_warp;
     use testing_fixtures::fixtures::user::UserFixture;
-    use wire::user::BEARER;
+    use wire::{
+        login::TokensResponse,
+        user::{
+            UpdateEmailRequest,
+            BEARER,
+        },
+    };
 
     /// Utility for getting the jwt string.
     /// This should make
@@ -96,8 +307,8 @@ pub mod tests {
             .json(&request)
             .path("/auth/login")
             .reply(&auth_api(&s));
-        let jwt_string: String = deserialize_string(response);
-        jwt_string
+        let tokens: TokensResponse = deserialize(response);
+        tokens.jwt
     }
 
     pub fn get_jwt_string(s: &State, user_name: String) -> String {
@@ -110,8 +321,8 @@ pub mod tests {
             .json(&request)
             .path("/auth/login")
             .reply(&auth_api(&s));
-        let jwt_string: String = deserialize_string(response);
-        jwt_string
+        let tokens: TokensResponse = deserialize(response);
+        tokens.jwt
     }
 
     #[test]
@@ -125,22 +336,181 @@ pub mod tests {
             let response = warp::test::request()
                 .method("POST")
                 .json(&request)
+                .header("user-agent", "Test Browser")
                 .path("/auth/login")
                 .reply(&auth_api(&s));
 
             assert_eq!(response.status(), 200);
-            let jwt_string: String = deserialize_string(response);
+            let tokens: TokensResponse = deserialize(response);
 
             let response = warp::test::request()
                 .method("GET")
-                .header(AUTHORIZATION_HEADER_KEY, format!("{} {}", BEARER, jwt_string).as_str())
-                .path("/auth/reauth")
+                .header(AUTHORIZATION_HEADER_KEY, format!("{} {}", BEARER, tokens.jwt).as_str())
+                .path("/auth/sessions")
                 .reply(&auth_api(&s));
+            assert_eq!(response.status(), 200);
+            let sessions: Vec<UserSessionResponse> = deserialize(response);
+            assert_eq!(sessions.len(), 1);
+            assert_eq!(sessions[0].user_agent, Some("Test Browser".to_string()));
 
+            let refresh_request = RefreshTokenRequest {
+                refresh_token: tokens.refresh_token.clone(),
+            };
+            let response = warp::test::request()
+                .method("POST")
+                .json(&refresh_request)
+                .path("/auth/refresh")
+                .reply(&auth_api(&s));
             assert_eq!(response.status(), 200);
-            let new_jwt_string: String = deserialize_string(response);
+            let new_tokens: TokensResponse = deserialize(response);
+            assert_ne!(new_tokens.refresh_token, tokens.refresh_token);
+
+            // The old refresh token has been used, so using it again ends the session.
+            let response = warp::test::request()
+                .method("POST")
+                .json(&refresh_request)
+                .path("/auth/refresh")
+                .reply(&auth_api(&s).recover(customize_error));
+            assert_eq!(response.status(), 403);
+
+            let response = warp::test::request()
+                .method("POST")
+                .json(&RefreshTokenRequest {
+                    refresh_token: new_tokens.refresh_token,
+                })
+                .path("/auth/refresh")
+                .reply(&auth_api(&s).recover(customize_error));
+            assert_eq!(response.status(), 403);
+        })
+    }
+
+    #[test]
+    fn change_password() {
+        setup_warp(|fixture: &UserFixture, pool: Pool| {
+            let s = State::testing_init(pool, fixture.secret.clone());
+            let user_name: String = fixture.normal_user.user_name.clone();
+            let jwt: String = get_jwt_string(&s, user_name.clone());
+
+            let request = ChangePasswordRequest {
+                current_password: "not the password".to_string(),
+                new_password: "new password".to_string(),
+            };
+            let response = warp::test::request()
+                .method("PUT")
+                .json(&request)
+                .header(AUTHORIZATION_HEADER_KEY, format!("{} {}", BEARER, jwt).as_str())
+                .path("/auth/password")
+                .reply(&auth_api(&s).recover(customize_error));
+            assert_eq!(response.status(), 403);
+
+            let request = ChangePasswordRequest {
+                current_password: String::from(testing_fixtures::fixtures::user::PASSWORD),
+                new_password: "new password".to_string(),
+            };
+            let response = warp::test::request()
+                .method("PUT")
+                .json(&request)
+                .header(AUTHORIZATION_HEADER_KEY, format!("{} {}", BEARER, jwt).as_str())
+                .path("/auth/password")
+                .reply(&auth_api(&s));
+            assert_eq!(response.status(), 204);
 
-            assert_ne!(new_jwt_string, jwt_string);
+            let response = warp::test::request()
+                .method("POST")
+                .json(&LoginRequest {
+                    user_name,
+                    password: "new password".to_string(),
+                })
+                .path("/auth/login")
+                .reply(&auth_api(&s));
+            assert_eq!(response.status(), 200);
+        })
+    }
+
+    #[test]
+    fn reset_password_with_mailed_token() {
+        setup_warp(|fixture: &UserFixture, pool: Pool| {
+            let mail_file = std::env::temp_dir().join(format!("password_reset_{}.txt", Uuid::new_v4()));
+            let s = State::testing_init(pool, fixture.secret.clone())
+                .with_mailer(Arc::new(FileMailer::new(mail_file.clone())));
+            let user_name: String = fixture.normal_user.user_name.clone();
+
+            let response = warp::test::request()
+                .method("POST")
+                .json(&LoginRequest {
+                    user_name: user_name.clone(),
+                    password: String::from(testing_fixtures::fixtures::user::PASSWORD),
+                })
+                .path("/auth/login")
+                .reply(&auth_api(&s));
+            let tokens: TokensResponse = deserialize(response);
+
+            let response = warp::test::request()
+                .method("PUT")
+                .json(&UpdateEmailRequest {
+                    email: Some("user@example.com".to_string()),
+                })
+                .header(AUTHORIZATION_HEADER_KEY, format!("{} {}", BEARER, tokens.jwt).as_str())
+                .path("/user/email")
+                .reply(&user_api(&s));
+            assert_eq!(response.status(), 200);
+
+            let response = warp::test::request()
+                .method("POST")
+                .json(&ForgotPasswordRequest {
+                    user_name: user_name.clone(),
+                })
+                .path("/auth/forgot_password")
+                .reply(&auth_api(&s));
+            assert_eq!(response.status(), 204);
+
+            let mail: String = fs::read_to_string(&mail_file).expect("Mail should have been written");
+            let _ = fs::remove_file(&mail_file);
+            assert!(mail.contains("To: user@example.com"));
+            let reset_token: String = mail
+                .lines()
+                .find(|line| line.starts_with("Reset token: "))
+                .map(|line| line.trim_start_matches("Reset token: ").to_string())
+                .expect("Mail should contain the reset token");
+
+            let request = ResetPasswordRequest {
+                reset_token,
+                new_password: "new password".to_string(),
+            };
+            let response = warp::test::request()
+                .method("POST")
+                .json(&request)
+                .path("/auth/reset_password")
+                .reply(&auth_api(&s));
+            assert_eq!(response.status(), 204);
+
+            // Reset tokens can only be used once.
+            let response = warp::test::request()
+                .method("POST")
+                .json(&request)
+                .path("/auth/reset_password")
+                .reply(&auth_api(&s).recover(customize_error));
+            assert_eq!(response.status(), 403);
+
+            // The session from before the reset has been ended.
+            let response = warp::test::request()
+                .method("POST")
+                .json(&RefreshTokenRequest {
+                    refresh_token: tokens.refresh_token,
+                })
+                .path("/auth/refresh")
+                .reply(&auth_api(&s).recover(customize_error));
+            assert_eq!(response.status(), 403);
+
+            let response = warp::test::request()
+                .method("POST")
+                .json(&LoginRequest {
+                    user_name,
+                    password: "new password".to_string(),
+                })
+                .path("/auth/login")
+                .reply(&auth_api(&s));
+            assert_eq!(response.status(), 200);
         })
     }
 }
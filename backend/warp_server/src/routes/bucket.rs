@@ -1,6 +1,12 @@
+use chrono::{
+    Duration,
+    NaiveDateTime,
+    Utc,
+};
 use error::Error;
 use warp::{
     filters::BoxedFilter,
+    reject::Rejection,
     reply::Reply,
     Filter,
 };
@@ -11,7 +17,9 @@ use crate::{
         log_attach,
         HttpMethod,
     },
+    routes::bucket_session::publish_session,
     state::{
+        bucket_sessions::BucketSessions,
         jwt::normal_user_filter,
         State,
     },
@@ -19,26 +27,59 @@ use crate::{
         convert_and_json,
         convert_vector_and_json,
         json_body_filter,
+        query_uuid,
     },
     uuid_integration::uuid_wrap_filter,
 };
-use db::bucket::Bucket;
+use db::{
+    bucket::{
+        Bucket,
+        BucketStatsData,
+        UsersInBucketData,
+    },
+    User,
+};
 use identifiers::{
     bucket::BucketUuid,
     user::UserUuid,
 };
 use pool::PooledConn;
-use wire::bucket::{
-    BucketResponse,
-    NewBucketRequest,
+use uuid::Uuid;
+use wire::{
+    bucket::{
+        BucketResponse,
+        BucketPublicityRequest,
+        BucketStatsResponse,
+        BucketTemplateRequest,
+        CloneBucketRequest,
+        BucketUsersResponse,
+        NewBucketRequest,
+        MAX_PUBLIC_MINUTES,
+    },
+    user::UserResponse,
 };
 
 pub fn bucket_api(s: &State) -> BoxedFilter<(impl warp::Reply,)> {
     info!("Attaching Bucket API");
     let api = get_bucket_by_uuid(s)
         .or(create_bucket(s))
-        .or(get_bucket_by_name(s))
-        .or(get_buckets_belonging_to_user(s));
+        .or(get_buckets_belonging_to_user(s))
+        .or(request_to_join_bucket(s))
+        .or(get_join_requests_for_owned_buckets(s))
+        .or(approve_user(s))
+        .or(remove_user(s))
+        .or(get_users_in_bucket(s))
+        .or(get_bucket_stats(s))
+        .or(get_owner_status(s))
+        .or(get_public_buckets(s))
+        .or(set_publicity(s))
+        .or(set_template(s))
+        .or(get_templates(s))
+        .or(clone_bucket(s))
+        .or(transfer_ownership(s))
+        .or(add_owner(s))
+        .or(delete_bucket(s))
+        .or(get_bucket_by_name(s));
 
     warp::path("bucket").and(api).with(warp::log("bucket")).boxed()
 }
@@ -65,10 +106,9 @@ pub fn create_bucket(s: &State) -> BoxedFilter<(impl Reply,)> {
         .and(normal_user_filter(s))
         .and(s.db.clone())
         .and_then(|request: NewBucketRequest, user_uuid: UserUuid, conn: PooledConn| {
-            Bucket::create_bucket(request.into(), &conn)
+            Bucket::create_bucket_with_owner(request.into(), user_uuid, &conn)
                 .map(convert_and_json::<Bucket, BucketResponse>)
                 .map_err(Error::simple_reject)
-            // TODO set the owner of the bucket
         })
         .boxed()
 }
@@ -100,3 +140,316 @@ pub fn get_buckets_belonging_to_user(s: &State) -> BoxedFilter<(impl Reply,)> {
         })
         .boxed()
 }
+
+/// Asks the owners of the bucket to let the user participate in it.
+/// While the bucket is public, the user is let in without needing approval.
+/// Replies with whether the user is now approved.
+pub fn request_to_join_bucket(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Post, "bucket/join_request/<uuid>");
+    warp::post2()
+        .and(warp::path("join_request"))
+        .and(uuid_wrap_filter())
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(|bucket_uuid: BucketUuid, user_uuid: UserUuid, conn: PooledConn| {
+            Bucket::request_to_join_bucket(user_uuid, bucket_uuid, &conn)
+                .map(convert_and_json::<bool, bool>)
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
+
+/// Gets the users waiting to be approved, for every bucket the user owns.
+pub fn get_join_requests_for_owned_buckets(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Get, "bucket/join_requests");
+    warp::get2()
+        .and(warp::path("join_requests"))
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(|user_uuid: UserUuid, conn: PooledConn| {
+            Bucket::get_users_requiring_approval_for_owned_buckets(user_uuid, &conn)
+                .map(convert_vector_and_json::<UsersInBucketData, BucketUsersResponse>)
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
+
+/// Lets a user who has requested to join the bucket participate in it.
+pub fn approve_user(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Put, "bucket/approve/<uuid>?user_uuid=<uuid>");
+    warp::put2()
+        .and(warp::path("approve"))
+        .and(uuid_wrap_filter())
+        .and(query_uuid("user_uuid"))
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(|bucket_uuid: BucketUuid, target_uuid: Uuid, user_uuid: UserUuid, conn: PooledConn| {
+            ensure_owner(user_uuid, bucket_uuid, &conn)?;
+            Bucket::set_user_approval(UserUuid(target_uuid), bucket_uuid, true, &conn)
+                .map(|_| warp::http::StatusCode::NO_CONTENT)
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
+
+/// Rejects a request to join the bucket, or removes a participant from it.
+/// Owners can remove anyone, and everyone else can only remove themselves.
+pub fn remove_user(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Delete, "bucket/users/<uuid>?user_uuid=<uuid>");
+    warp::delete2()
+        .and(warp::path("users"))
+        .and(uuid_wrap_filter())
+        .and(query_uuid("user_uuid"))
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and(s.bucket_sessions.clone())
+        .and_then(
+            |bucket_uuid: BucketUuid,
+             target_uuid: Uuid,
+             user_uuid: UserUuid,
+             conn: PooledConn,
+             bucket_sessions: BucketSessions| {
+                if target_uuid != user_uuid.0 {
+                    ensure_owner(user_uuid, bucket_uuid, &conn)?;
+                }
+                Bucket::remove_user_from_bucket(UserUuid(target_uuid), bucket_uuid, &conn).map_err(Error::simple_reject)?;
+                bucket_sessions.unsubscribe(bucket_uuid, UserUuid(target_uuid));
+                // If it was the removed user's turn, the turn has moved on.
+                publish_session(bucket_uuid, &bucket_sessions, &conn);
+                Ok::<_, Rejection>(warp::http::StatusCode::NO_CONTENT)
+            },
+        )
+        .boxed()
+}
+
+/// Gets the other approved participants of the bucket.
+pub fn get_users_in_bucket(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Get, "bucket/users/<uuid>");
+    warp::get2()
+        .and(warp::path("users"))
+        .and(uuid_wrap_filter())
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(|bucket_uuid: BucketUuid, user_uuid: UserUuid, conn: PooledConn| {
+            ensure_approved(user_uuid, bucket_uuid, &conn)?;
+            Bucket::get_users_with_approval(bucket_uuid, user_uuid, &conn)
+                .map(convert_vector_and_json::<User, UserResponse>)
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
+
+/// Gets statistics about the bucket's questions, answers, and past sessions.
+pub fn get_bucket_stats(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Get, "bucket/stats/<uuid>");
+    warp::get2()
+        .and(warp::path("stats"))
+        .and(uuid_wrap_filter())
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(|bucket_uuid: BucketUuid, user_uuid: UserUuid, conn: PooledConn| {
+            ensure_approved(user_uuid, bucket_uuid, &conn)?;
+            Bucket::get_stats(bucket_uuid, &conn)
+                .map(convert_and_json::<BucketStatsData, BucketStatsResponse>)
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
+
+pub fn get_owner_status(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Get, "bucket/owner_status/<uuid>");
+    warp::get2()
+        .and(warp::path("owner_status"))
+        .and(uuid_wrap_filter())
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .map(|bucket_uuid: BucketUuid, user_uuid: UserUuid, conn: PooledConn| {
+            let is_owner: bool = Bucket::is_user_owner(user_uuid, bucket_uuid, &conn);
+            warp::reply::json(&is_owner)
+        })
+        .boxed()
+}
+
+/// Gets the buckets that are currently open to the public, that the user hasn't joined yet.
+pub fn get_public_buckets(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Get, "bucket/public");
+    warp::get2()
+        .and(warp::path("public"))
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(|user_uuid: UserUuid, conn: PooledConn| {
+            Bucket::get_public_buckets(user_uuid, &conn)
+                .map(convert_vector_and_json::<Bucket, BucketResponse>)
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
+
+/// Opens the bucket to the public for a number of minutes, or closes it.
+/// Once the time is up, the bucket closes on its own.
+pub fn set_publicity(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Put, "bucket/publicity/<uuid>");
+    warp::put2()
+        .and(warp::path("publicity"))
+        .and(uuid_wrap_filter())
+        .and(json_body_filter(1))
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(
+            |bucket_uuid: BucketUuid, request: BucketPublicityRequest, user_uuid: UserUuid, conn: PooledConn| {
+                ensure_owner(user_uuid, bucket_uuid, &conn)?;
+                let public_until: Option<NaiveDateTime> = match request.public_for_minutes {
+                    Some(minutes) if minutes <= 0 || minutes > MAX_PUBLIC_MINUTES => return Error::BadRequest.reject(),
+                    Some(minutes) => Some(Utc::now().naive_utc() + Duration::minutes(minutes)),
+                    None => None,
+                };
+                Bucket::set_bucket_publicity(bucket_uuid, public_until, &conn)
+                    .map(convert_and_json::<Bucket, BucketResponse>)
+                    .map_err(Error::simple_reject)
+            },
+        )
+        .boxed()
+}
+
+/// Publishes the bucket as a template that anyone can clone, or withdraws it.
+pub fn set_template(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Put, "bucket/template/<uuid>");
+    warp::put2()
+        .and(warp::path("template"))
+        .and(uuid_wrap_filter())
+        .and(json_body_filter(1))
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(
+            |bucket_uuid: BucketUuid, request: BucketTemplateRequest, user_uuid: UserUuid, conn: PooledConn| {
+                ensure_owner(user_uuid, bucket_uuid, &conn)?;
+                Bucket::set_template(bucket_uuid, request.is_template, &conn)
+                    .map(convert_and_json::<Bucket, BucketResponse>)
+                    .map_err(Error::simple_reject)
+            },
+        )
+        .boxed()
+}
+
+pub fn get_templates(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Get, "bucket/templates");
+    warp::get2()
+        .and(warp::path("templates"))
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(|_user_uuid: UserUuid, conn: PooledConn| {
+            Bucket::get_templates(&conn)
+                .map(convert_vector_and_json::<Bucket, BucketResponse>)
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
+
+/// Creates a new bucket with the same questions as an existing one, with the requesting user as its owner.
+/// Owners can clone their buckets, and anyone can clone a template, but only owners can copy the answers too.
+pub fn clone_bucket(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Post, "bucket/clone/<uuid>");
+    warp::post2()
+        .and(warp::path("clone"))
+        .and(uuid_wrap_filter())
+        .and(json_body_filter(4))
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(
+            |bucket_uuid: BucketUuid, request: CloneBucketRequest, user_uuid: UserUuid, conn: PooledConn| {
+                let source: Bucket = Bucket::get_bucket(bucket_uuid, &conn).map_err(Error::simple_reject)?;
+                let is_owner: bool = Bucket::is_user_owner(user_uuid, bucket_uuid, &conn);
+                if !is_owner && (!source.is_template || request.include_answers) {
+                    return Error::NotAuthorized {
+                        reason: "Only the owners of the bucket can clone it, unless it is a template without its answers",
+                    }
+                    .reject();
+                }
+
+                Bucket::clone_bucket(bucket_uuid, request.bucket_name, user_uuid, request.include_answers, &conn)
+                    .map(convert_and_json::<Bucket, BucketResponse>)
+                    .map_err(Error::simple_reject)
+            },
+        )
+        .boxed()
+}
+
+/// Hands the requesting owner's ownership of the bucket over to another user.
+pub fn transfer_ownership(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Put, "bucket/transfer/<uuid>?user_uuid=<uuid>");
+    warp::put2()
+        .and(warp::path("transfer"))
+        .and(uuid_wrap_filter())
+        .and(query_uuid("user_uuid"))
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(|bucket_uuid: BucketUuid, new_owner_uuid: Uuid, user_uuid: UserUuid, conn: PooledConn| {
+            ensure_owner(user_uuid, bucket_uuid, &conn)?;
+            if new_owner_uuid == user_uuid.0 {
+                return Error::BadRequest.reject();
+            }
+            Bucket::transfer_ownership(user_uuid, UserUuid(new_owner_uuid), bucket_uuid, &conn)
+                .map(|_| warp::http::StatusCode::NO_CONTENT)
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
+
+/// Makes another user a co-owner of the bucket.
+pub fn add_owner(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Put, "bucket/owners/<uuid>?user_uuid=<uuid>");
+    warp::put2()
+        .and(warp::path("owners"))
+        .and(uuid_wrap_filter())
+        .and(query_uuid("user_uuid"))
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(|bucket_uuid: BucketUuid, new_owner_uuid: Uuid, user_uuid: UserUuid, conn: PooledConn| {
+            ensure_owner(user_uuid, bucket_uuid, &conn)?;
+            Bucket::add_owner(UserUuid(new_owner_uuid), bucket_uuid, &conn)
+                .map(|_| warp::http::StatusCode::NO_CONTENT)
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
+
+/// Deletes the bucket, along with its questions and answers.
+pub fn delete_bucket(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Delete, "bucket/<uuid>");
+    warp::delete2()
+        .and(uuid_wrap_filter())
+        .and(warp::path::end())
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(|bucket_uuid: BucketUuid, user_uuid: UserUuid, conn: PooledConn| {
+            ensure_owner(user_uuid, bucket_uuid, &conn)?;
+            Bucket::delete_bucket(bucket_uuid, &conn)
+                .map(|_| warp::http::StatusCode::NO_CONTENT)
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
+
+/// Rejects the request unless the user owns the bucket.
+pub(crate) fn ensure_owner(user_uuid: UserUuid, bucket_uuid: BucketUuid, conn: &PooledConn) -> Result<(), Rejection> {
+    if Bucket::is_user_owner(user_uuid, bucket_uuid, conn) {
+        Ok(())
+    } else {
+        Error::NotAuthorized {
+            reason: "Only the owners of the bucket can do that",
+        }
+        .reject()
+    }
+}
+
+/// Rejects the request unless the user has been approved to participate in the bucket.
+pub(crate) fn ensure_approved(user_uuid: UserUuid, bucket_uuid: BucketUuid, conn: &PooledConn) -> Result<(), Rejection> {
+    if Bucket::is_user_approved(user_uuid, bucket_uuid, conn) {
+        Ok(())
+    } else {
+        Error::NotAuthorized {
+            reason: "You have not been approved to participate in this bucket",
+        }
+        .reject()
+    }
+}
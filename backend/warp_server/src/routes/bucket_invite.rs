@@ -0,0 +1,184 @@
+use chrono::{
+    Duration,
+    NaiveDateTime,
+    Utc,
+};
+use error::Error;
+use warp::{
+    self,
+    filters::BoxedFilter,
+    reject::Rejection,
+    reply::Reply,
+    Filter,
+};
+
+use crate::{
+    logging::{
+        log_attach,
+        HttpMethod,
+    },
+    routes::bucket::ensure_owner,
+    state::{
+        jwt::normal_user_filter,
+        State,
+    },
+    util::{
+        convert_and_json,
+        json_body_filter,
+    },
+    uuid_integration::uuid_wrap_filter,
+};
+use db::{
+    bucket::Bucket,
+    bucket_invite::{
+        BucketInvite,
+        NewBucketInvite,
+    },
+};
+use identifiers::{
+    bucket::BucketUuid,
+    bucket_invite::BucketInviteUuid,
+    user::UserUuid,
+};
+use pool::PooledConn;
+use uuid::Uuid;
+use wire::bucket::{
+    BucketResponse,
+    InviteLinkResponse,
+    NewInviteRequest,
+    MAX_INVITE_MINUTES,
+};
+
+pub fn bucket_invite_api(s: &State) -> BoxedFilter<(impl warp::Reply,)> {
+    info!("Attaching Bucket Invite API");
+    let api = redeem_invite(s)
+        .or(create_invite(s))
+        .or(get_invites(s))
+        .or(revoke_invite(s));
+
+    warp::path("bucket_invite")
+        .and(api)
+        .with(warp::log("bucket_invite"))
+        .boxed()
+}
+
+/// Creates an invite link for the bucket.
+pub fn create_invite(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Post, "bucket_invite/<bucket_uuid>");
+    warp::post2()
+        .and(uuid_wrap_filter())
+        .and(json_body_filter(1))
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(
+            |bucket_uuid: BucketUuid, request: NewInviteRequest, user_uuid: UserUuid, conn: PooledConn| {
+                ensure_owner(user_uuid, bucket_uuid, &conn)?;
+                if let Some(max_uses) = request.max_uses {
+                    if max_uses <= 0 {
+                        return Error::BadRequest.reject();
+                    }
+                }
+                let expires_at: Option<NaiveDateTime> = match request.expires_in_minutes {
+                    Some(minutes) if minutes <= 0 || minutes > MAX_INVITE_MINUTES => return Error::BadRequest.reject(),
+                    Some(minutes) => Some(Utc::now().naive_utc() + Duration::minutes(minutes)),
+                    None => None,
+                };
+
+                let invite_secret: String = auth::generate_refresh_secret();
+                let new_invite = NewBucketInvite {
+                    bucket_uuid: bucket_uuid.0,
+                    creator_uuid: user_uuid.0,
+                    token_hash: auth::hash_refresh_secret(&invite_secret),
+                    max_uses: request.max_uses,
+                    expires_at,
+                };
+                BucketInvite::create_invite(new_invite, &conn)
+                    .map(|invite| {
+                        let token = format!("{}.{}", invite.uuid, invite_secret);
+                        warp::reply::json(&invite_link(invite, Some(token)))
+                    })
+                    .map_err(Error::simple_reject)
+            },
+        )
+        .boxed()
+}
+
+/// Gets the bucket's invites that can still be used.
+/// Their tokens aren't included, because only the link handed out when an invite is created contains it.
+pub fn get_invites(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Get, "bucket_invite/<bucket_uuid>");
+    warp::get2()
+        .and(uuid_wrap_filter())
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(|bucket_uuid: BucketUuid, user_uuid: UserUuid, conn: PooledConn| {
+            ensure_owner(user_uuid, bucket_uuid, &conn)?;
+            BucketInvite::get_active_invites(bucket_uuid, &conn)
+                .map(|invites| {
+                    let links: Vec<InviteLinkResponse> = invites
+                        .into_iter()
+                        .map(|invite| invite_link(invite, None))
+                        .collect();
+                    warp::reply::json(&links)
+                })
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
+
+/// Revokes the invite, so its link stops working.
+pub fn revoke_invite(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Delete, "bucket_invite/<invite_uuid>");
+    warp::delete2()
+        .and(uuid_wrap_filter())
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(|invite_uuid: BucketInviteUuid, user_uuid: UserUuid, conn: PooledConn| {
+            let invite: BucketInvite = BucketInvite::get_invite(invite_uuid, &conn).map_err(Error::simple_reject)?;
+            ensure_owner(user_uuid, BucketUuid(invite.bucket_uuid), &conn)?;
+            BucketInvite::revoke_invite(invite_uuid, &conn)
+                .map(|_| warp::http::StatusCode::NO_CONTENT)
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
+
+/// Joins the bucket the invite token was made for.
+pub fn redeem_invite(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Post, "bucket_invite/redeem/<token>");
+    warp::post2()
+        .and(warp::path("redeem"))
+        .and(warp::path::param::<String>())
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(|token: String, user_uuid: UserUuid, conn: PooledConn| {
+            let (invite_uuid, invite_secret) = parse_invite_token(&token)?;
+            BucketInvite::redeem_invite(invite_uuid, &auth::hash_refresh_secret(invite_secret), user_uuid, &conn)
+                .map(convert_and_json::<Bucket, BucketResponse>)
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
+
+fn invite_link(invite: BucketInvite, token: Option<String>) -> InviteLinkResponse {
+    InviteLinkResponse {
+        uuid: BucketInviteUuid(invite.uuid),
+        bucket_uuid: BucketUuid(invite.bucket_uuid),
+        token,
+        max_uses: invite.max_uses,
+        uses: invite.uses,
+        expires_at: invite.expires_at,
+        created_at: invite.created_at,
+    }
+}
+
+/// Invite tokens are the invite's uuid followed by a random secret, so they can't be guessed from the uuid alone.
+fn parse_invite_token(token: &str) -> Result<(BucketInviteUuid, &str), Rejection> {
+    let mut parts = token.splitn(2, '.');
+    let (uuid, invite_secret) = match (parts.next(), parts.next()) {
+        (Some(uuid), Some(invite_secret)) => (uuid, invite_secret),
+        _ => return Error::BadRequest.reject(),
+    };
+    let invite_uuid: Uuid = Uuid::parse_str(uuid).map_err(|_| Error::BadRequest.simple_reject())?;
+    Ok((BucketInviteUuid(invite_uuid), invite_secret))
+}
@@ -0,0 +1,322 @@
+use error::Error;
+use futures::Stream;
+use warp::{
+    filters::BoxedFilter,
+    reject::Rejection,
+    reply::Reply,
+    Filter,
+};
+use crate::{
+    logging::{
+        log_attach,
+        HttpMethod,
+    },
+    routes::bucket::{
+        ensure_approved,
+        ensure_owner,
+    },
+    state::{
+        bucket_sessions::BucketSessions,
+        jwt::{
+            normal_user_filter,
+            query_token_user_filter,
+        },
+        State,
+    },
+    util::{
+        convert_and_json,
+        convert_vector_and_json,
+        json_body_filter,
+        query_uuid,
+    },
+    uuid_integration::uuid_wrap_filter,
+};
+use db::{
+    bucket::{
+        Bucket,
+        BucketSessionData,
+        SessionAction,
+    },
+    question::QuestionDrawData,
+    Question,
+};
+use identifiers::{
+    bucket::BucketUuid,
+    user::UserUuid,
+};
+use pool::PooledConn;
+use uuid::Uuid;
+use wire::{
+    bucket::{
+        BucketSessionResponse,
+        DrawModeRequest,
+        TurnMode,
+        TurnSettingsRequest,
+        MAX_TURN_SECONDS,
+    },
+    question::QuestionDrawResponse,
+};
+
+/// The number of draws returned in a bucket's draw history.
+const DRAW_HISTORY_LENGTH: i64 = 50;
+
+pub fn bucket_session_api(s: &State) -> BoxedFilter<(impl warp::Reply,)> {
+    info!("Attaching Bucket Session API");
+    let api = get_session_events(s)
+        .or(draw_question(s))
+        .or(discard_question(s))
+        .or(choose_answerer(s))
+        .or(set_turn_settings(s))
+        .or(set_draw_mode(s))
+        .or(get_draw_history(s))
+        .or(get_session(s));
+
+    warp::path("bucket_session").and(api).with(warp::log("bucket_session")).boxed()
+}
+
+/// Gets the state of the session.
+/// If the current turn has run out of time, it is skipped first.
+fn get_session(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Get, "bucket_session/<uuid>");
+
+    warp::get2()
+        .and(uuid_wrap_filter())
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and(s.bucket_sessions.clone())
+        .and_then(
+            |bucket_uuid: BucketUuid, user_uuid: UserUuid, conn: PooledConn, bucket_sessions: BucketSessions| {
+                ensure_approved(user_uuid, bucket_uuid, &conn)?;
+                skip_expired_turn(bucket_uuid, &bucket_sessions, &conn)?;
+                Bucket::get_session(bucket_uuid, &conn)
+                    .map(convert_and_json::<BucketSessionData, BucketSessionResponse>)
+                    .map_err(Error::simple_reject)
+            },
+        )
+        .boxed()
+}
+
+/// Streams the state of the session as server sent events.
+/// The current state is sent as soon as the stream opens, and again every time it changes.
+///
+/// Because EventSource can't set headers, the JWT is provided as a `token` query parameter.
+fn get_session_events(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Get, "bucket_session/events/<uuid>?token=<jwt>");
+
+    warp::get2()
+        .and(warp::path("events"))
+        .and(uuid_wrap_filter())
+        .and(query_token_user_filter(s))
+        .and(s.db.clone())
+        .and(s.bucket_sessions.clone())
+        .and(warp::sse())
+        .and_then(
+            |bucket_uuid: BucketUuid,
+             user_uuid: UserUuid,
+             conn: PooledConn,
+             bucket_sessions: BucketSessions,
+             sse: warp::sse::Sse| {
+                ensure_approved(user_uuid, bucket_uuid, &conn)?;
+                let current: BucketSessionResponse = Bucket::get_session(bucket_uuid, &conn)
+                    .map(BucketSessionResponse::from)
+                    .map_err(Error::simple_reject)?;
+                let events = bucket_sessions
+                    .subscribe(bucket_uuid, user_uuid, current)
+                    .map(warp::sse::json)
+                    .map_err(|()| -> warp::Error { unreachable!("unbounded receivers never error") });
+                Ok::<_, Rejection>(sse.reply(warp::sse::keep(events, None)))
+            },
+        )
+        .boxed()
+}
+
+fn draw_question(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Post, "bucket_session/draw/<uuid>");
+
+    warp::post2()
+        .and(warp::path("draw"))
+        .and(uuid_wrap_filter())
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and(s.bucket_sessions.clone())
+        .and_then(
+            |bucket_uuid: BucketUuid, user_uuid: UserUuid, conn: PooledConn, bucket_sessions: BucketSessions| {
+                ensure_approved(user_uuid, bucket_uuid, &conn)?;
+                ensure_turn(user_uuid, bucket_uuid, SessionAction::Draw, &bucket_sessions, &conn)?;
+                Bucket::draw_question(bucket_uuid, &conn)
+                    .map(|session| publish_session_data(session, &bucket_sessions))
+                    .map(|session| warp::reply::json(&session))
+                    .map_err(Error::simple_reject)
+            },
+        )
+        .boxed()
+}
+
+fn discard_question(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Post, "bucket_session/discard/<uuid>");
+
+    warp::post2()
+        .and(warp::path("discard"))
+        .and(uuid_wrap_filter())
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and(s.bucket_sessions.clone())
+        .and_then(
+            |bucket_uuid: BucketUuid, user_uuid: UserUuid, conn: PooledConn, bucket_sessions: BucketSessions| {
+                ensure_approved(user_uuid, bucket_uuid, &conn)?;
+                ensure_turn(user_uuid, bucket_uuid, SessionAction::Discard, &bucket_sessions, &conn)?;
+                Bucket::discard_current_question(bucket_uuid, &conn)
+                    .map(|session| publish_session_data(session, &bucket_sessions))
+                    .map(|session| warp::reply::json(&session))
+                    .map_err(Error::simple_reject)
+            },
+        )
+        .boxed()
+}
+
+/// In hot seat mode, the drawer chooses who answers the current question.
+fn choose_answerer(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Put, "bucket_session/hot_seat/<uuid>?user_uuid=<uuid>");
+
+    warp::put2()
+        .and(warp::path("hot_seat"))
+        .and(uuid_wrap_filter())
+        .and(query_uuid("user_uuid"))
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and(s.bucket_sessions.clone())
+        .and_then(
+            |bucket_uuid: BucketUuid,
+             answerer_uuid: Uuid,
+             user_uuid: UserUuid,
+             conn: PooledConn,
+             bucket_sessions: BucketSessions| {
+                ensure_approved(user_uuid, bucket_uuid, &conn)?;
+                ensure_turn(user_uuid, bucket_uuid, SessionAction::ChooseAnswerer, &bucket_sessions, &conn)?;
+                Bucket::choose_answerer(bucket_uuid, UserUuid(answerer_uuid), &conn)
+                    .map(|session| publish_session_data(session, &bucket_sessions))
+                    .map(|session| warp::reply::json(&session))
+                    .map_err(Error::simple_reject)
+            },
+        )
+        .boxed()
+}
+
+/// Lets owners change how participants take turns.
+fn set_turn_settings(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Put, "bucket_session/turns/<uuid>");
+
+    warp::put2()
+        .and(warp::path("turns"))
+        .and(uuid_wrap_filter())
+        .and(json_body_filter(1))
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and(s.bucket_sessions.clone())
+        .and_then(
+            |bucket_uuid: BucketUuid,
+             request: TurnSettingsRequest,
+             user_uuid: UserUuid,
+             conn: PooledConn,
+             bucket_sessions: BucketSessions| {
+                ensure_owner(user_uuid, bucket_uuid, &conn)?;
+                let turn_seconds: Option<i32> = match request.turn_seconds {
+                    _ if request.turn_mode == TurnMode::FreeForAll => None,
+                    Some(seconds) if seconds <= 0 || seconds > MAX_TURN_SECONDS => return Error::BadRequest.reject(),
+                    turn_seconds => turn_seconds,
+                };
+                Bucket::set_turn_mode(bucket_uuid, request.turn_mode, turn_seconds, &conn)
+                    .map(|session| publish_session_data(session, &bucket_sessions))
+                    .map(|session| warp::reply::json(&session))
+                    .map_err(Error::simple_reject)
+            },
+        )
+        .boxed()
+}
+
+/// Lets owners change how questions are drawn.
+fn set_draw_mode(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Put, "bucket_session/draw_mode/<uuid>");
+
+    warp::put2()
+        .and(warp::path("draw_mode"))
+        .and(uuid_wrap_filter())
+        .and(json_body_filter(1))
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and(s.bucket_sessions.clone())
+        .and_then(
+            |bucket_uuid: BucketUuid,
+             request: DrawModeRequest,
+             user_uuid: UserUuid,
+             conn: PooledConn,
+             bucket_sessions: BucketSessions| {
+                ensure_owner(user_uuid, bucket_uuid, &conn)?;
+                Bucket::set_draw_mode(bucket_uuid, request.draw_mode, &conn)
+                    .map(|session| publish_session_data(session, &bucket_sessions))
+                    .map(|session| warp::reply::json(&session))
+                    .map_err(Error::simple_reject)
+            },
+        )
+        .boxed()
+}
+
+/// Gets the questions most recently drawn in the bucket, newest first.
+fn get_draw_history(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Get, "bucket_session/draws/<uuid>");
+
+    warp::get2()
+        .and(warp::path("draws"))
+        .and(uuid_wrap_filter())
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(|bucket_uuid: BucketUuid, user_uuid: UserUuid, conn: PooledConn| {
+            ensure_approved(user_uuid, bucket_uuid, &conn)?;
+            Question::get_draw_history(bucket_uuid, DRAW_HISTORY_LENGTH, &conn)
+                .map(convert_vector_and_json::<QuestionDrawData, QuestionDrawResponse>)
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
+
+/// Rejects the request unless the user can take the action in the bucket's session right now.
+/// If the current turn has run out of time, it is skipped before checking.
+pub(crate) fn ensure_turn(
+    user_uuid: UserUuid,
+    bucket_uuid: BucketUuid,
+    action: SessionAction,
+    bucket_sessions: &BucketSessions,
+    conn: &PooledConn,
+) -> Result<(), Rejection> {
+    skip_expired_turn(bucket_uuid, bucket_sessions, conn)?;
+    Bucket::ensure_turn(user_uuid, bucket_uuid, action, conn).map_err(Error::simple_reject)
+}
+
+/// Skips the current turn if it has run out of time, letting everyone know.
+fn skip_expired_turn(bucket_uuid: BucketUuid, bucket_sessions: &BucketSessions, conn: &PooledConn) -> Result<(), Rejection> {
+    if Bucket::skip_expired_turn(bucket_uuid, conn).map_err(Error::simple_reject)? {
+        publish_session(bucket_uuid, bucket_sessions, conn);
+    }
+    Ok(())
+}
+
+/// Sends the session to everyone listening to its bucket, returning it so it can be used as a reply.
+fn publish_session_data(session: BucketSessionData, bucket_sessions: &BucketSessions) -> BucketSessionResponse {
+    let bucket_uuid = BucketUuid(session.bucket.uuid);
+    let response = BucketSessionResponse::from(session);
+    bucket_sessions.publish(bucket_uuid, response.clone());
+    response
+}
+
+/// Reloads the session of the bucket and sends it to everyone listening.
+/// Used by routes that change the session as a side effect, like answering or adding questions.
+///
+/// The change that triggered this has already been made, so a failure here shouldn't fail the request.
+pub(crate) fn publish_session(bucket_uuid: BucketUuid, bucket_sessions: &BucketSessions, conn: &PooledConn) {
+    match Bucket::get_session(bucket_uuid, conn) {
+        Ok(session) => {
+            publish_session_data(session, bucket_sessions);
+        }
+        Err(e) => warn!("Could not publish the session for bucket {}: {:?}", bucket_uuid.0, e),
+    }
+}
@@ -17,8 +17,9 @@ use crate::{
     },
     util::{
         convert_and_json,
-        convert_vector_and_json,
+        convert_page_and_json,
         json_body_filter,
+        page_size_filter,
         query_uuid,
     },
 };
@@ -49,21 +50,22 @@ pub fn message_api(s: &State) -> BoxedFilter<(impl Reply,)> {
 }
 
 fn get_messages_for_chat(s: &State) -> BoxedFilter<(impl Reply,)> {
-    log_attach(HttpMethod::Get, "message/<index=i32>?chat_uuid=<uuid>");
+    log_attach(HttpMethod::Get, "message/<index=i32>?chat_uuid=<uuid>&page_size=<i32>");
 
     warp::get2()
         .and(warp::path::param())
         .and(query_uuid("chat_uuid")) // TODO Is this the query??
+        .and(page_size_filter(25))
         .and(normal_user_filter(s))
         .and(s.db.clone())
-        .and_then(|index: i32, chat_uuid: Uuid, user_uuid: UserUuid, conn: PooledConn| {
+        .and_then(|index: i32, chat_uuid: Uuid, page_size: i32, user_uuid: UserUuid, conn: PooledConn| {
             let chat_uuid = ChatUuid(chat_uuid);
             if !Chat::is_user_in_chat(&chat_uuid, user_uuid, &conn).map_err(Error::simple_reject)? {
                 return Error::BadRequest.reject();
             }
 
-            Message::get_messages_for_chat(chat_uuid, index, 25, &conn)
-                .map(convert_vector_and_json::<MessageData, MessageResponse>)
+            Message::get_messages_for_chat(chat_uuid, index, page_size, &conn)
+                .map(convert_page_and_json::<MessageData, MessageResponse>)
                 .map_err(Error::simple_reject)
         })
         .boxed()
@@ -4,11 +4,14 @@ mod answer;
 mod article;
 mod auth;
 mod bucket;
+mod bucket_invite;
+mod bucket_session;
 mod chat;
 mod forum;
 mod message;
 mod post;
 mod question;
+mod reputation;
 mod static_file;
 mod thread;
 mod user;
@@ -18,11 +21,14 @@ use self::{
     article::article_api,
     auth::auth_api,
     bucket::bucket_api,
+    bucket_invite::bucket_invite_api,
+    bucket_session::bucket_session_api,
     chat::chat_api,
     forum::forum_api,
     message::message_api,
     post::post_api,
     question::question_api,
+    reputation::reputation_api,
     static_file::static_files_handler,
     thread::thread_api,
     user::user_api,
@@ -59,11 +65,14 @@ fn api(s: &State) -> BoxedFilter<(impl warp::Reply,)> {
         .or(article_api(s))
         .or(answer_api(s))
         .or(bucket_api(s))
+        .or(bucket_invite_api(s))
+        .or(bucket_session_api(s))
         .or(chat_api(s))
         .or(forum_api(s))
         .or(message_api(s))
         .or(post_api(s))
         .or(question_api(s))
+        .or(reputation_api(s))
         .or(thread_api(s));
 
     warn!("Attaching Main API");
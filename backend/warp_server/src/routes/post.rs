@@ -17,11 +17,16 @@ use crate::{
     },
     uuid_integration::uuid_wrap_filter,
 };
-use db::post::{
-    ChildlessPostData,
-    EditPostChangeset,
-    NewPost,
-    Post,
+use db::{
+    post::{
+        ChildlessPostData,
+        EditPostChangeset,
+        NewPost,
+        Post,
+        PostVote,
+        Vote,
+    },
+    reputation::ReputationThreshold,
 };
 use error::Error;
 use identifiers::{
@@ -31,14 +36,18 @@ use identifiers::{
 };
 use pool::PooledConn;
 use warp::{
+    self,
     filters::BoxedFilter,
     reply::Reply,
     Filter,
 };
-use wire::post::{
-    EditPostRequest,
-    NewPostRequest,
-    PostResponse,
+use wire::{
+    post::{
+        EditPostRequest,
+        NewPostRequest,
+        PostResponse,
+    },
+    reputation::ReputationAction,
 };
 
 pub fn post_api(s: &State) -> BoxedFilter<(impl Reply,)> {
@@ -46,7 +55,10 @@ pub fn post_api(s: &State) -> BoxedFilter<(impl Reply,)> {
     let api = create_post(s)
         .or(edit_post(s))
         .or(censor_post(s))
-        .or(get_posts_by_user(s));
+        .or(get_posts_by_user(s))
+        .or(upvote(s))
+        .or(downvote(s))
+        .or(revoke_vote(s));
 
     warp::path("post").and(api).with(warp::log("post")).boxed()
 }
@@ -65,6 +77,14 @@ pub fn create_post(s: &State) -> BoxedFilter<(impl Reply,)> {
             if new_post.author_uuid != user_uuid.0 {
                 return Error::BadRequest.reject();
             }
+            let meets_threshold = ReputationThreshold::user_meets_threshold(user_uuid, ReputationAction::CreatePost, &conn)
+                .map_err(Error::simple_reject)?;
+            if !meets_threshold {
+                return Error::NotAuthorized {
+                    reason: "Insufficient reputation",
+                }
+                .reject();
+            }
             Post::create_and_get_user(new_post, &conn)
                 .map(convert_and_json::<ChildlessPostData, PostResponse>)
                 .map_err(Error::simple_reject)
@@ -127,3 +147,53 @@ pub fn get_posts_by_user(s: &State) -> BoxedFilter<(impl Reply,)> {
         })
         .boxed()
 }
+
+pub fn upvote(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Put, "post/upvote/<uuid>");
+
+    warp::put2()
+        .and(warp::path("upvote"))
+        .and(uuid_wrap_filter())
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(|post_uuid: PostUuid, user_uuid: UserUuid, conn: PooledConn| {
+            let vote = Vote { post_uuid, user_uuid };
+            Post::vote(PostVote::Up(vote), &conn)
+                .map(|_| warp::http::StatusCode::NO_CONTENT)
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
+
+pub fn downvote(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Put, "post/downvote/<uuid>");
+
+    warp::put2()
+        .and(warp::path("downvote"))
+        .and(uuid_wrap_filter())
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(|post_uuid: PostUuid, user_uuid: UserUuid, conn: PooledConn| {
+            let vote = Vote { post_uuid, user_uuid };
+            Post::vote(PostVote::Down(vote), &conn)
+                .map(|_| warp::http::StatusCode::NO_CONTENT)
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
+
+pub fn revoke_vote(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Put, "post/revoke_vote/<uuid>");
+
+    warp::put2()
+        .and(warp::path("revoke_vote"))
+        .and(uuid_wrap_filter())
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(|post_uuid: PostUuid, user_uuid: UserUuid, conn: PooledConn| {
+            Post::revoke_vote(user_uuid, post_uuid, &conn)
+                .map(|_| warp::http::StatusCode::NO_CONTENT)
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
@@ -1,6 +1,7 @@
 use error::Error;
 use warp::{
     filters::BoxedFilter,
+    reject::Rejection,
     reply::Reply,
     Filter,
 };
@@ -11,7 +12,20 @@ use crate::{
         log_attach,
         HttpMethod,
     },
+    question_file,
+    question_similarity,
+    routes::{
+        bucket::{
+            ensure_approved,
+            ensure_owner,
+        },
+        bucket_session::{
+            ensure_turn,
+            publish_session,
+        },
+    },
     state::{
+        bucket_sessions::BucketSessions,
         jwt::normal_user_filter,
         State,
     },
@@ -27,6 +41,7 @@ use crate::{
     },
 };
 use db::{
+    bucket::SessionAction,
     question::{
         NewQuestion,
         QuestionData,
@@ -39,12 +54,19 @@ use identifiers::{
     user::UserUuid,
 };
 use pool::PooledConn;
+use std::collections::HashMap;
 use uuid::Uuid;
 use wire::question::{
+    DuplicateClusterResponse,
+    ImportQuestionsRequest,
+    ImportQuestionsResponse,
+    MergeQuestionsRequest,
     NewQuestionRequest,
+    NewQuestionResponse,
+    QuestionFileFormat,
     QuestionResponse,
+    SimilarQuestionResponse,
 };
-use crate::state::jwt::optional_normal_user_filter;
 
 pub fn question_api(s: &State) -> BoxedFilter<(impl Reply,)> {
     info!("Attaching Question API");
@@ -59,6 +81,10 @@ pub fn question_api(s: &State) -> BoxedFilter<(impl Reply,)> {
         .or(favorite_question(s))
         .or(unfavorite_question(s))
         .or(get_favorite_questions(s))
+        .or(import_questions(s))
+        .or(export_questions(s))
+        .or(get_duplicate_questions(s))
+        .or(merge_questions(s))
     ;
 
     warp::path("question").and(api).with(warp::log("question")).boxed()
@@ -69,9 +95,11 @@ pub fn get_questions_for_bucket(s: &State) -> BoxedFilter<(impl Reply,)> {
 
     warp::get2()
         .and(query_uuid("bucket_uuid"))
+        .and(normal_user_filter(s))
         .and(s.db.clone())
-        .and_then(|bucket_uuid: Uuid, conn: PooledConn| {
+        .and_then(|bucket_uuid: Uuid, user_uuid: UserUuid, conn: PooledConn| {
             let bucket_uuid = BucketUuid(bucket_uuid);
+            ensure_approved(user_uuid, bucket_uuid, &conn)?;
             Question::get_questions_for_bucket(bucket_uuid, &conn)
                 .map(convert_vector_and_json::<QuestionData, QuestionResponse>)
                 .map_err(Error::simple_reject)
@@ -86,13 +114,19 @@ fn get_random_question(s: &State) -> BoxedFilter<(impl Reply,)> {
     warp::get2()
         .and(warp::path("random_question"))
         .and(query_uuid("bucket_uuid"))
+        .and(normal_user_filter(s))
         .and(s.db.clone())
-        .and_then(|bucket_uuid: Uuid, conn: PooledConn| {
-            let bucket_uuid = BucketUuid(bucket_uuid);
-            Question::get_random_question(bucket_uuid, &conn)
-                .map(convert_and_json::<QuestionData, QuestionResponse>)
-                .map_err(Error::simple_reject)
-        })
+        .and(s.bucket_sessions.clone())
+        .and_then(
+            |bucket_uuid: Uuid, user_uuid: UserUuid, conn: PooledConn, bucket_sessions: BucketSessions| {
+                let bucket_uuid = BucketUuid(bucket_uuid);
+                ensure_approved(user_uuid, bucket_uuid, &conn)?;
+                ensure_turn(user_uuid, bucket_uuid, SessionAction::Draw, &bucket_sessions, &conn)?;
+                Question::get_random_question(bucket_uuid, &conn)
+                    .map(convert_and_json::<QuestionData, QuestionResponse>)
+                    .map_err(Error::simple_reject)
+            },
+        )
         .boxed()
 }
 fn get_question(s: &State) -> BoxedFilter<(impl Reply,)> {
@@ -100,8 +134,10 @@ fn get_question(s: &State) -> BoxedFilter<(impl Reply,)> {
 
     warp::get2()
         .and(uuid_wrap_filter())
+        .and(normal_user_filter(s))
         .and(s.db.clone())
-        .and_then(|question_uuid: QuestionUuid, conn: PooledConn| {
+        .and_then(|question_uuid: QuestionUuid, user_uuid: UserUuid, conn: PooledConn| {
+            ensure_approved_for_question(question_uuid, user_uuid, &conn)?;
             Question::get_full_question(question_uuid, &conn)
                 .map(convert_and_json::<QuestionData, QuestionResponse>)
                 .map_err(Error::simple_reject)
@@ -109,27 +145,41 @@ fn get_question(s: &State) -> BoxedFilter<(impl Reply,)> {
         .boxed()
 }
 
-// TODO there should be a variant that doesn't require auth.
 fn create_question(s: &State) -> BoxedFilter<(impl Reply,)> {
     log_attach(HttpMethod::Post, "question/");
 
     warp::post2()
         .and(json_body_filter(12))
-        .and(optional_normal_user_filter(s))
+        .and(normal_user_filter(s))
         .and(s.db.clone())
-        .and_then(|request: NewQuestionRequest, user_uuid: Option<UserUuid>, conn: PooledConn| {
-//            let bucket_uuid: BucketUuid = request.bucket_uuid;
-//            let is_approved = Bucket::is_user_approved(user_uuid, bucket_uuid, &conn);
-//            if !is_approved {
-//                return Error::BadRequest.reject();
-//            }
+        .and(s.bucket_sessions.clone())
+        .and_then(
+            |request: NewQuestionRequest, user_uuid: UserUuid, conn: PooledConn, bucket_sessions: BucketSessions| {
+                let bucket_uuid = request.bucket_uuid;
+                ensure_approved(user_uuid, bucket_uuid, &conn)?;
 
-            let new_question: NewQuestion = NewQuestion::attach_user_id(request, user_uuid);
+                if !request.ignore_similar {
+                    let similar_questions = find_similar_questions(bucket_uuid, &request.question_text, &conn)?;
+                    if !similar_questions.is_empty() {
+                        let response = NewQuestionResponse {
+                            question: None,
+                            similar_questions,
+                        };
+                        return Ok(warp::reply::json(&response));
+                    }
+                }
 
-            Question::create_data(new_question, &conn)
-                .map(convert_and_json::<QuestionData, QuestionResponse>)
-                .map_err(Error::simple_reject)
-        })
+                let new_question: NewQuestion = NewQuestion::attach_user_id(request, Some(user_uuid));
+
+                let question: QuestionData = Question::create_data(new_question, &conn).map_err(Error::simple_reject)?;
+                publish_session(bucket_uuid, &bucket_sessions, &conn);
+                let response = NewQuestionResponse {
+                    question: Some(QuestionResponse::from(question)),
+                    similar_questions: vec![],
+                };
+                Ok::<_, Rejection>(warp::reply::json(&response))
+            },
+        )
         .boxed()
 }
 
@@ -140,12 +190,17 @@ fn delete_question(s: &State) -> BoxedFilter<(impl Reply,)> {
         .and(uuid_filter())
         .and(normal_user_filter(s))
         .and(s.db.clone())
-        .and_then(|question_uuid: Uuid, _user_uuid: UserUuid, conn: PooledConn| {
-            let question_uuid = QuestionUuid(question_uuid);
-            Question::delete_question(question_uuid.clone(), &conn)
-                .map_err(Error::simple_reject)
-                .map(|_| warp::reply::json(&question_uuid))
-        })
+        .and(s.bucket_sessions.clone())
+        .and_then(
+            |question_uuid: Uuid, user_uuid: UserUuid, conn: PooledConn, bucket_sessions: BucketSessions| {
+                let question_uuid = QuestionUuid(question_uuid);
+                let bucket_uuid = ensure_approved_for_question(question_uuid, user_uuid, &conn)?;
+                ensure_turn(user_uuid, bucket_uuid, SessionAction::Discard, &bucket_sessions, &conn)?;
+                Question::delete_question(question_uuid.clone(), &conn).map_err(Error::simple_reject)?;
+                publish_session(bucket_uuid, &bucket_sessions, &conn);
+                Ok::<_, Rejection>(warp::reply::json(&question_uuid))
+            },
+        )
         .boxed()
 }
 
@@ -157,12 +212,17 @@ fn put_question_back_in_bucket(s: &State) -> BoxedFilter<(impl Reply,)> {
         .and(warp::path("into_bucket"))
         .and(normal_user_filter(s))
         .and(s.db.clone())
-        .and_then(|question_uuid: Uuid, _user_uuid: UserUuid, conn: PooledConn| {
-            let question_uuid = QuestionUuid(question_uuid);
-            Question::put_question_in_bucket(question_uuid, &conn)
-                .map_err(Error::simple_reject)
-                .map(|_| warp::reply::json(&question_uuid))
-        })
+        .and(s.bucket_sessions.clone())
+        .and_then(
+            |question_uuid: Uuid, user_uuid: UserUuid, conn: PooledConn, bucket_sessions: BucketSessions| {
+                let question_uuid = QuestionUuid(question_uuid);
+                let bucket_uuid = ensure_approved_for_question(question_uuid, user_uuid, &conn)?;
+                ensure_turn(user_uuid, bucket_uuid, SessionAction::Discard, &bucket_sessions, &conn)?;
+                Question::put_question_in_bucket(question_uuid, &conn).map_err(Error::simple_reject)?;
+                publish_session(bucket_uuid, &bucket_sessions, &conn);
+                Ok::<_, Rejection>(warp::reply::json(&question_uuid))
+            },
+        )
         .boxed()
 }
 
@@ -172,9 +232,11 @@ fn questions_in_bucket(s: &State) -> BoxedFilter<(impl Reply,)> {
     warp::get2()
         .and(warp::path("quantity_in_bucket"))
         .and(query_uuid("bucket_uuid"))
+        .and(normal_user_filter(s))
         .and(s.db.clone())
-        .and_then(|bucket_uuid: Uuid, conn: PooledConn| {
+        .and_then(|bucket_uuid: Uuid, user_uuid: UserUuid, conn: PooledConn| {
             let bucket_uuid = BucketUuid(bucket_uuid);
+            ensure_approved(user_uuid, bucket_uuid, &conn)?;
             Question::get_number_of_questions_in_bucket(bucket_uuid, &conn)
                 .map(convert_and_json::<i64, i64>)
                 .map_err(Error::simple_reject)
@@ -182,6 +244,92 @@ fn questions_in_bucket(s: &State) -> BoxedFilter<(impl Reply,)> {
         .boxed()
 }
 
+/// Adds the questions in an uploaded file to the bucket, along with their answers.
+/// Rows that are invalid, or whose question is already in the bucket, are skipped and reported in the response.
+fn import_questions(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Post, "question/import/<bucket_uuid>");
+
+    warp::post2()
+        .and(warp::path("import"))
+        .and(uuid_wrap_filter())
+        .and(json_body_filter(512))
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and(s.bucket_sessions.clone())
+        .and_then(
+            |bucket_uuid: BucketUuid,
+             request: ImportQuestionsRequest,
+             user_uuid: UserUuid,
+             conn: PooledConn,
+             bucket_sessions: BucketSessions| {
+                ensure_owner(user_uuid, bucket_uuid, &conn)?;
+
+                let existing_questions: Vec<String> = Question::get_questions_for_bucket(bucket_uuid, &conn)
+                    .map_err(Error::simple_reject)?
+                    .into_iter()
+                    .map(|question_data| question_data.question.question_text)
+                    .collect();
+                let import = question_file::import_questions(request.format, &request.contents, &existing_questions);
+
+                let imported: Vec<QuestionData> =
+                    Question::create_questions(bucket_uuid, Some(user_uuid), import.questions, &conn)
+                        .map_err(Error::simple_reject)?;
+                publish_session(bucket_uuid, &bucket_sessions, &conn);
+
+                let response = ImportQuestionsResponse {
+                    imported: imported.into_iter().map(QuestionResponse::from).collect(),
+                    duplicates: import.duplicates,
+                    errors: import.errors,
+                };
+                Ok::<_, Rejection>(warp::reply::json(&response))
+            },
+        )
+        .boxed()
+}
+
+/// Downloads the questions in the bucket and their answers.
+/// The format is chosen by the optional `format` query parameter, which is either `json` (the default) or `csv`.
+fn export_questions(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Get, "question/export/<bucket_uuid>?format=<json|csv>");
+
+    let format = warp::query::query::<HashMap<String, String>>()
+        .map(|hm: HashMap<String, String>| hm.get("format").cloned())
+        .or(warp::any().map(|| None))
+        .unify()
+        .and_then(|format: Option<String>| match format.as_ref().map(String::as_str) {
+            Some("json") | None => Ok(QuestionFileFormat::Json),
+            Some("csv") => Ok(QuestionFileFormat::Csv),
+            Some(_) => Error::BadRequest.reject(),
+        });
+
+    warp::get2()
+        .and(warp::path("export"))
+        .and(uuid_wrap_filter())
+        .and(format)
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(
+            |bucket_uuid: BucketUuid, format: QuestionFileFormat, user_uuid: UserUuid, conn: PooledConn| {
+                ensure_owner(user_uuid, bucket_uuid, &conn)?;
+                let questions: Vec<QuestionData> =
+                    Question::get_questions_for_bucket(bucket_uuid, &conn).map_err(Error::simple_reject)?;
+
+                let (contents, content_type, extension) = match format {
+                    QuestionFileFormat::Json => (question_file::export_json(questions), "application/json", "json"),
+                    QuestionFileFormat::Csv => (question_file::export_csv(questions), "text/csv", "csv"),
+                };
+                warp::http::Response::builder()
+                    .header("Content-Type", content_type)
+                    .header(
+                        "Content-Disposition",
+                        format!("attachment; filename=\"questions.{}\"", extension).as_str(),
+                    )
+                    .body(contents)
+                    .map_err(|_| Error::InternalServerError.simple_reject())
+            },
+        )
+        .boxed()
+}
 
 fn favorite_question(s: &State) -> BoxedFilter<(impl Reply,)> {
 
@@ -193,6 +341,7 @@ fn favorite_question(s: &State) -> BoxedFilter<(impl Reply,)> {
         .and(normal_user_filter(s))
         .and(s.db.clone())
         .and_then(|question_uuid: QuestionUuid, user_uuid: UserUuid, conn: PooledConn| {
+            ensure_approved_for_question(question_uuid, user_uuid, &conn)?;
             Question::favorite_question(question_uuid, user_uuid, &conn)
                 .map(convert_and_json::<(), ()>)
                 .map_err(Error::simple_reject)
@@ -208,6 +357,7 @@ fn unfavorite_question(s: &State) -> BoxedFilter<(impl Reply,)> {
         .and(normal_user_filter(s))
         .and(s.db.clone())
         .and_then(|question_uuid: QuestionUuid, user_uuid: UserUuid, conn: PooledConn| {
+            ensure_approved_for_question(question_uuid, user_uuid, &conn)?;
             Question::unfavorite_question(question_uuid, user_uuid, &conn)
                 .map(convert_and_json::<(), ()>)
                 .map_err(Error::simple_reject)
@@ -227,4 +377,215 @@ fn get_favorite_questions(s: &State) -> BoxedFilter<(impl Reply,)> {
                 .map_err(Error::simple_reject)
         })
         .boxed()
-}
\ No newline at end of file
+}
+
+/// Lists groups of questions in the bucket that are likely duplicates of each other, so owners can merge them.
+fn get_duplicate_questions(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Get, "question/duplicates/<bucket_uuid>");
+
+    warp::get2()
+        .and(warp::path("duplicates"))
+        .and(uuid_wrap_filter())
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(|bucket_uuid: BucketUuid, user_uuid: UserUuid, conn: PooledConn| {
+            ensure_owner(user_uuid, bucket_uuid, &conn)?;
+
+            let questions: Vec<QuestionData> =
+                Question::get_questions_for_bucket(bucket_uuid, &conn).map_err(Error::simple_reject)?;
+            let clusters: Vec<Vec<usize>> = {
+                let question_texts: Vec<&str> = questions
+                    .iter()
+                    .map(|question_data| question_data.question.question_text.as_str())
+                    .collect();
+                question_similarity::duplicate_clusters(&question_texts)
+            };
+
+            let mut questions: Vec<Option<QuestionData>> = questions.into_iter().map(Some).collect();
+            let response: Vec<DuplicateClusterResponse> = clusters
+                .into_iter()
+                .map(|cluster| DuplicateClusterResponse {
+                    questions: cluster
+                        .into_iter()
+                        .filter_map(|index| questions[index].take())
+                        .map(QuestionResponse::from)
+                        .collect(),
+                })
+                .collect();
+            Ok::<_, Rejection>(warp::reply::json(&response))
+        })
+        .boxed()
+}
+
+/// Lets owners merge duplicate questions into one, keeping all of their answers.
+fn merge_questions(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Post, "question/merge");
+
+    warp::post2()
+        .and(warp::path("merge"))
+        .and(json_body_filter(4))
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and(s.bucket_sessions.clone())
+        .and_then(
+            |request: MergeQuestionsRequest, user_uuid: UserUuid, conn: PooledConn, bucket_sessions: BucketSessions| {
+                let kept_question: Question = Question::get_question(request.keep, &conn).map_err(Error::simple_reject)?;
+                let bucket_uuid = BucketUuid(kept_question.bucket_uuid);
+                ensure_owner(user_uuid, bucket_uuid, &conn)?;
+
+                let question: QuestionData =
+                    Question::merge_questions(request.keep, request.merge, &conn).map_err(Error::simple_reject)?;
+                publish_session(bucket_uuid, &bucket_sessions, &conn);
+                Ok::<_, Rejection>(convert_and_json::<QuestionData, QuestionResponse>(question))
+            },
+        )
+        .boxed()
+}
+
+/// Finds the questions already in the bucket that are likely duplicates of the new question.
+fn find_similar_questions(
+    bucket_uuid: BucketUuid,
+    question_text: &str,
+    conn: &PooledConn,
+) -> Result<Vec<SimilarQuestionResponse>, Rejection> {
+    let questions: Vec<QuestionData> = Question::get_questions_for_bucket(bucket_uuid, conn).map_err(Error::simple_reject)?;
+    let question_texts: Vec<&str> = questions
+        .iter()
+        .map(|question_data| question_data.question.question_text.as_str())
+        .collect();
+
+    let similar_questions = question_similarity::find_similar(question_text, &question_texts)
+        .into_iter()
+        .map(|(index, similarity)| SimilarQuestionResponse {
+            question_uuid: QuestionUuid(questions[index].question.uuid),
+            question_text: questions[index].question.question_text.clone(),
+            similarity,
+        })
+        .collect();
+    Ok(similar_questions)
+}
+
+/// Rejects the request unless the user has been approved to participate in the bucket the question belongs to.
+/// Returns the uuid of that bucket.
+pub(crate) fn ensure_approved_for_question(
+    question_uuid: QuestionUuid,
+    user_uuid: UserUuid,
+    conn: &PooledConn,
+) -> Result<BucketUuid, Rejection> {
+    let question: Question = Question::get_question(question_uuid, conn).map_err(Error::simple_reject)?;
+    let bucket_uuid = BucketUuid(question.bucket_uuid);
+    ensure_approved(user_uuid, bucket_uuid, conn)?;
+    Ok(bucket_uuid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::jwt::AUTHORIZATION_HEADER_KEY;
+    use db::bucket::{
+        Bucket,
+        NewBucketUser,
+    };
+    use error::warp_support::customize_error;
+    use pool::Pool;
+    use testing_common::setup::setup_warp;
+    use testing_fixtures::fixtures::bucket::BucketFixture;
+    use wire::{
+        bucket::TurnMode,
+        user::BEARER,
+    };
+
+    #[test]
+    fn random_question_respects_turns() {
+        setup_warp(|fixture: &BucketFixture, pool: Pool| {
+            let bucket_uuid = BucketUuid(fixture.private_bucket.uuid);
+            let admin_uuid = UserUuid(fixture.user_fixture.admin_user.uuid);
+            {
+                let conn: PooledConn = pool.get().expect("get connection");
+                Bucket::add_owner(admin_uuid, bucket_uuid, &conn).expect("add owner");
+                // Turns are taken in order of user name, so "Admin" goes first.
+                Bucket::set_turn_mode(bucket_uuid, TurnMode::RoundRobin, None, &conn).expect("set turn mode");
+            }
+            let s = State::testing_init(pool, fixture.user_fixture.secret.clone());
+            let path = format!("/question/random_question?bucket_uuid={}", bucket_uuid);
+
+            let normal_jwt: String =
+                crate::routes::auth::tests::get_jwt_string(&s, fixture.user_fixture.normal_user.user_name.clone());
+            let response = warp::test::request()
+                .method("GET")
+                .header(AUTHORIZATION_HEADER_KEY, format!("{} {}", BEARER, normal_jwt).as_str())
+                .path(&path)
+                .reply(&question_api(&s).recover(customize_error));
+            assert_eq!(response.status(), 409);
+
+            let admin_jwt: String =
+                crate::routes::auth::tests::get_jwt_string(&s, fixture.user_fixture.admin_user.user_name.clone());
+            let response = warp::test::request()
+                .method("GET")
+                .header(AUTHORIZATION_HEADER_KEY, format!("{} {}", BEARER, admin_jwt).as_str())
+                .path(&path)
+                .reply(&question_api(&s));
+            assert_eq!(response.status(), 200);
+        })
+    }
+
+    /// Users who have only asked to join a bucket can't see or add its questions until they are approved.
+    #[test]
+    fn questions_require_approval() {
+        setup_warp(|fixture: &BucketFixture, pool: Pool| {
+            let bucket_uuid = BucketUuid(fixture.private_bucket.uuid);
+            let admin_uuid = UserUuid(fixture.user_fixture.admin_user.uuid);
+            {
+                let conn: PooledConn = pool.get().expect("get connection");
+                let new_bucket_user = NewBucketUser {
+                    bucket_uuid: bucket_uuid.0,
+                    user_uuid: admin_uuid.0,
+                    owner: false,
+                    approved: false,
+                };
+                Bucket::add_user_to_bucket(new_bucket_user, &conn).expect("add user to bucket");
+            }
+            let s = State::testing_init(pool.clone(), fixture.user_fixture.secret.clone());
+            let jwt: String =
+                crate::routes::auth::tests::get_jwt_string(&s, fixture.user_fixture.admin_user.user_name.clone());
+
+            let response = warp::test::request()
+                .method("GET")
+                .header(AUTHORIZATION_HEADER_KEY, format!("{} {}", BEARER, jwt).as_str())
+                .path(&format!("/question?bucket_uuid={}", bucket_uuid))
+                .reply(&question_api(&s).recover(customize_error));
+            assert_eq!(response.status(), 403);
+
+            let response = warp::test::request()
+                .method("GET")
+                .header(AUTHORIZATION_HEADER_KEY, format!("{} {}", BEARER, jwt).as_str())
+                .path(&format!("/question/{}", fixture.question_1.uuid))
+                .reply(&question_api(&s).recover(customize_error));
+            assert_eq!(response.status(), 403);
+
+            let request = NewQuestionRequest {
+                bucket_uuid,
+                question_text: "Who goes first?".to_string(),
+                ignore_similar: true,
+            };
+            let response = warp::test::request()
+                .method("POST")
+                .header(AUTHORIZATION_HEADER_KEY, format!("{} {}", BEARER, jwt).as_str())
+                .json(&request)
+                .path("/question/")
+                .reply(&question_api(&s).recover(customize_error));
+            assert_eq!(response.status(), 403);
+
+            {
+                let conn: PooledConn = pool.get().expect("get connection");
+                Bucket::set_user_approval(admin_uuid, bucket_uuid, true, &conn).expect("approve user");
+            }
+            let response = warp::test::request()
+                .method("GET")
+                .header(AUTHORIZATION_HEADER_KEY, format!("{} {}", BEARER, jwt).as_str())
+                .path(&format!("/question?bucket_uuid={}", bucket_uuid))
+                .reply(&question_api(&s).recover(customize_error));
+            assert_eq!(response.status(), 200);
+        })
+    }
+}
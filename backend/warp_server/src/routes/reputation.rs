@@ -0,0 +1,71 @@
+use crate::{
+    logging::{
+        log_attach,
+        HttpMethod,
+    },
+    state::{
+        jwt::moderator_user_filter,
+        State,
+    },
+    util::{
+        convert_and_json,
+        convert_vector_and_json,
+        json_body_filter,
+    },
+};
+use db::reputation::{
+    NewReputationThreshold,
+    ReputationThreshold,
+};
+use error::Error;
+use identifiers::user::UserUuid;
+use pool::PooledConn;
+use warp::{
+    filters::BoxedFilter,
+    reply::Reply,
+    Filter,
+};
+use wire::reputation::{
+    ReputationThresholdRequest,
+    ReputationThresholdResponse,
+};
+
+pub fn reputation_api(s: &State) -> BoxedFilter<(impl Reply,)> {
+    info!("Attaching Reputation API");
+    let api = get_thresholds(s).or(set_threshold(s));
+
+    warp::path("reputation").and(api).with(warp::log("reputation")).boxed()
+}
+
+/// Gets the reputation required to perform each gated action.
+fn get_thresholds(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Get, "reputation/thresholds");
+
+    warp::get2()
+        .and(warp::path("thresholds"))
+        .and(s.db.clone())
+        .and_then(|conn: PooledConn| {
+            ReputationThreshold::get_thresholds(&conn)
+                .map(convert_vector_and_json::<ReputationThreshold, ReputationThresholdResponse>)
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
+
+/// Sets the reputation required to perform an action.
+fn set_threshold(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Put, "reputation/thresholds");
+
+    warp::put2()
+        .and(warp::path("thresholds"))
+        .and(json_body_filter(4))
+        .and(moderator_user_filter(s))
+        .and(s.db.clone())
+        .and_then(|request: ReputationThresholdRequest, _user: UserUuid, conn: PooledConn| {
+            let new_threshold: NewReputationThreshold = request.into();
+            ReputationThreshold::set_threshold(new_threshold, &conn)
+                .map(convert_and_json::<ReputationThreshold, ReputationThresholdResponse>)
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
@@ -1,4 +1,8 @@
 use crate::{
+    logging::{
+        log_attach,
+        HttpMethod,
+    },
     state::{
         jwt::{
             moderator_user_filter,
@@ -9,16 +13,24 @@ use crate::{
     },
     util::{
         convert_and_json,
-        convert_vector_and_json,
+        convert_page_and_json,
         json_body_filter,
+        page_size_filter,
     },
     uuid_integration::uuid_wrap_filter,
 };
-use db::thread::{
-    MinimalThreadData,
-    NewThread,
-    Thread,
-    ThreadData,
+use db::{
+    poll::{
+        Poll,
+        PollData,
+    },
+    reputation::ReputationThreshold,
+    thread::{
+        MinimalThreadData,
+        NewThread,
+        Thread,
+        ThreadData,
+    },
 };
 use error::Error;
 use identifiers::{
@@ -33,10 +45,18 @@ use warp::{
     reply::Reply,
     Filter,
 };
-use wire::thread::{
-    MinimalThreadResponse,
-    NewThreadRequest,
-    ThreadResponse,
+use wire::{
+    poll::{
+        NewPollRequest,
+        PollResponse,
+        PollVoteRequest,
+    },
+    reputation::ReputationAction,
+    thread::{
+        MinimalThreadResponse,
+        NewThreadRequest,
+        ThreadResponse,
+    },
 };
 
 pub fn thread_api(s: &State) -> BoxedFilter<(impl Reply,)> {
@@ -46,7 +66,8 @@ pub fn thread_api(s: &State) -> BoxedFilter<(impl Reply,)> {
         .or(unlock_thread(s))
         .or(archive_thread(s))
         .or(get_threads_by_forum_id(s))
-        .or(get_thread_contents(s));
+        .or(get_thread_contents(s))
+        .or(vote_in_poll(s));
 
     warp::path("thread").and(api).with(warp::log("thread")).boxed()
 }
@@ -60,11 +81,20 @@ pub fn create_thread(s: &State) -> BoxedFilter<(impl Reply,)> {
             if request.author_uuid != user_uuid {
                 return Error::BadRequest.reject();
             }
+            let meets_threshold = ReputationThreshold::user_meets_threshold(user_uuid, ReputationAction::CreateThread, &conn)
+                .map_err(Error::simple_reject)?;
+            if !meets_threshold {
+                return Error::NotAuthorized {
+                    reason: "Insufficient reputation",
+                }
+                .reject();
+            }
 
             let new_thread: NewThread = request.clone().into();
             let post_content: String = request.post_content;
+            let poll: Option<NewPollRequest> = request.poll;
 
-            Thread::create_thread_with_initial_post(new_thread, post_content, &conn)
+            Thread::create_thread_with_initial_post(new_thread, post_content, poll, &conn)
                 .map(convert_and_json::<ThreadData, ThreadResponse>)
                 .map_err(Error::simple_reject)
         })
@@ -118,11 +148,11 @@ pub fn get_threads_by_forum_id(s: &State) -> BoxedFilter<(impl Reply,)> {
         .and(warp::path("get")) // TODO: this api naming scheme is braindead
         .and(uuid_wrap_filter::<ForumUuid>())
         .and(warp::path::param::<i32>())
+        .and(page_size_filter(25))
         .and(s.db.clone())
-        .and_then(|forum_uuid: ForumUuid, index: i32, conn: PooledConn| {
-            let results_per_page: i32 = 25;
-            Thread::get_paginated(forum_uuid, index, results_per_page, &conn)
-                .map(convert_vector_and_json::<MinimalThreadData, MinimalThreadResponse>)
+        .and_then(|forum_uuid: ForumUuid, index: i32, page_size: i32, conn: PooledConn| {
+            Thread::get_paginated(forum_uuid, index, page_size, &conn)
+                .map(convert_page_and_json::<MinimalThreadData, MinimalThreadResponse>)
                 .map_err(Error::simple_reject)
         })
         .boxed()
@@ -142,3 +172,21 @@ pub fn get_thread_contents(s: &State) -> BoxedFilter<(impl Reply,)> {
         )
         .boxed()
 }
+
+/// Casts the user's vote in a thread's poll, replacing any vote they have already cast.
+pub fn vote_in_poll(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Put, "thread/poll/vote");
+
+    warp::put2()
+        .and(warp::path("poll"))
+        .and(warp::path("vote"))
+        .and(json_body_filter(4))
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(|request: PollVoteRequest, user_uuid: UserUuid, conn: PooledConn| {
+            Poll::vote(request.poll_uuid, user_uuid, request.option_uuids, &conn)
+                .map(convert_and_json::<PollData, PollResponse>)
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
@@ -1,18 +1,34 @@
-use db::user::{
-    NewUser,
-    User,
+use chrono::{
+    Duration,
+    NaiveDateTime,
+    Utc,
+};
+use db::{
+    user::{
+        NewUser,
+        User,
+    },
+    user_ban::{
+        NewUserBan,
+        UserBan,
+    },
+    user_session::UserSession,
 };
 use identifiers::user::UserUuid;
 use warp::{
     filters::BoxedFilter,
+    reject::Rejection,
     reply::Reply,
     Filter,
 };
 use wire::user::{
+    BanUserRequest,
     FullUserResponse,
     NewUserRequest,
     UpdateDisplayNameRequest,
+    UpdateEmailRequest,
     UserResponse,
+    UserBanResponse,
     UserRoleRequest,
 };
 
@@ -22,7 +38,10 @@ use crate::{
         HttpMethod,
     },
     state::{
-        banned_list::BannedList,
+        banned_list::{
+            Ban,
+            BannedList,
+        },
         jwt::{
             admin_user_filter,
             normal_user_filter,
@@ -31,13 +50,20 @@ use crate::{
     },
     util::{
         convert_and_json,
+        convert_page_and_json,
         convert_vector_and_json,
+        json_body_filter,
+        page_size_filter,
     },
     uuid_integration::uuid_wrap_filter,
 };
 use error::Error;
 use pool::PooledConn;
 
+/// The longest a temporary ban can last, which is about a century.
+/// Longer bans should be made permanent instead.
+const MAX_BAN_MINUTES: i64 = 60 * 24 * 365 * 100;
+
 pub fn user_api(s: &State) -> BoxedFilter<(impl warp::Reply,)> {
     info!("Attaching User API");
     warp::path("user")
@@ -46,9 +72,11 @@ pub fn user_api(s: &State) -> BoxedFilter<(impl warp::Reply,)> {
                 .or(get_users(s))
                 .or(create_user(s))
                 .or(update_user_display_name(s))
+                .or(update_email(s))
                 .or(add_role(s))
                 .or(ban_user(s))
-                .or(unban_user(s)),
+                .or(unban_user(s))
+                .or(get_bans(s)),
         )
         .with(warp::log("user"))
         .boxed()
@@ -69,16 +97,16 @@ fn get_user(s: &State) -> BoxedFilter<(impl Reply,)> {
 }
 
 fn get_users(s: &State) -> BoxedFilter<(impl Reply,)> {
-    log_attach(HttpMethod::Get, "user/<i32 where i32 >= 1>");
+    log_attach(HttpMethod::Get, "user/<i32 where i32 >= 1>?page_size=<i32>");
 
     warp::get2()
         .and(warp::path::param::<i32>())
+        .and(page_size_filter(25))
         .and(admin_user_filter(s))
         .and(s.db.clone())
-        .and_then(|index: i32, _admin: UserUuid, conn: PooledConn| {
-            User::get_paginated(index, 25, &conn)
-                .map(|x: (Vec<User>, i64)| x.0)
-                .map(convert_vector_and_json::<User, FullUserResponse>)
+        .and_then(|index: i32, page_size: i32, _admin: UserUuid, conn: PooledConn| {
+            User::get_paginated(index, page_size, &conn)
+                .map(convert_page_and_json::<User, FullUserResponse>)
                 .map_err(Error::simple_reject)
         })
         .boxed()
@@ -125,6 +153,28 @@ fn update_user_display_name(s: &State) -> BoxedFilter<(impl Reply,)> {
         .boxed()
 }
 
+/// Sets the email that password reset tokens are sent to.
+fn update_email(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Put, "user/email");
+    warp::put2()
+        .and(warp::path("email"))
+        .and(json_body_filter(1))
+        .and(normal_user_filter(s))
+        .and(s.db.clone())
+        .and_then(|request: UpdateEmailRequest, user_uuid: UserUuid, conn: PooledConn| {
+            let email: Option<String> = request.email.map(|email| email.trim().to_string());
+            if let Some(ref email) = email {
+                if !email.contains('@') {
+                    return Error::BadRequest.reject();
+                }
+            }
+            User::update_email(user_uuid, email, &conn)
+                .map(convert_and_json::<User, UserResponse>)
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
+
 fn add_role(s: &State) -> BoxedFilter<(impl Reply,)> {
     log_attach(HttpMethod::Put, "user/assign_role");
 
@@ -142,21 +192,41 @@ fn add_role(s: &State) -> BoxedFilter<(impl Reply,)> {
         .boxed()
 }
 
+/// Bans the user, either permanently or for a number of minutes, and logs them out everywhere.
+/// Their outstanding JWTs are rejected as soon as the ban is added to the banned list.
 fn ban_user(s: &State) -> BoxedFilter<(impl Reply,)> {
     log_attach(HttpMethod::Put, "user/ban/<uuid>");
     warp::put2()
         .and(path!("ban"))
         .and(uuid_wrap_filter::<UserUuid>())
+        .and(json_body_filter(4))
         .and(admin_user_filter(s))
         .and(s.banned_list.clone())
         .and(s.db.clone())
         .and_then(
-            |user_uuid: UserUuid, user: UserUuid, banned_list: BannedList, conn: PooledConn| {
-                let resp = User::set_ban_status(user_uuid, true, &conn)
-                    .map(convert_and_json::<User, UserResponse>)
-                    .map_err(Error::simple_reject);
-                banned_list.ban(user);
-                resp
+            |target_uuid: UserUuid, request: BanUserRequest, admin_uuid: UserUuid, banned_list: BannedList, conn: PooledConn| {
+                if target_uuid == admin_uuid || request.reason.trim().is_empty() {
+                    return Error::BadRequest.reject();
+                }
+                let expires_at: Option<NaiveDateTime> = match request.banned_for_minutes {
+                    Some(minutes) if minutes <= 0 || minutes > MAX_BAN_MINUTES => return Error::BadRequest.reject(),
+                    Some(minutes) => match Utc::now().naive_utc().checked_add_signed(Duration::minutes(minutes)) {
+                        Some(expires_at) => Some(expires_at),
+                        None => return Error::BadRequest.reject(),
+                    },
+                    None => None,
+                };
+
+                let new_ban = NewUserBan {
+                    user_uuid: target_uuid.0,
+                    admin_uuid: Some(admin_uuid.0),
+                    reason: request.reason,
+                    expires_at,
+                };
+                let ban: UserBan = UserBan::ban_user(new_ban, &conn).map_err(Error::simple_reject)?;
+                banned_list.ban(target_uuid, Ban::from(ban.clone()));
+                UserSession::end_all_sessions(target_uuid, &conn).map_err(Error::simple_reject)?;
+                Ok::<_, Rejection>(convert_and_json::<UserBan, UserBanResponse>(ban))
             },
         )
         .boxed()
@@ -172,20 +242,36 @@ fn unban_user(s: &State) -> BoxedFilter<(impl Reply,)> {
         .and(s.banned_list.clone())
         .and(s.db.clone())
         .and_then(
-            |user_uuid: UserUuid, user: UserUuid, banned_list: BannedList, conn: PooledConn| {
-                let resp = User::set_ban_status(user_uuid, false, &conn)
+            |target_uuid: UserUuid, _admin_uuid: UserUuid, banned_list: BannedList, conn: PooledConn| {
+                UserBan::unban_user(target_uuid, &conn).map_err(Error::simple_reject)?;
+                banned_list.unban(&target_uuid);
+                User::get_user(target_uuid, &conn)
                     .map(convert_and_json::<User, UserResponse>)
-                    .map_err(Error::simple_reject);
-                banned_list.unban(&user);
-                resp
+                    .map_err(Error::simple_reject)
             },
         )
         .boxed()
 }
 
+/// Gets the bans that haven't expired.
+fn get_bans(s: &State) -> BoxedFilter<(impl Reply,)> {
+    log_attach(HttpMethod::Get, "user/bans");
+    warp::get2()
+        .and(warp::path("bans"))
+        .and(admin_user_filter(s))
+        .and(s.db.clone())
+        .and_then(|_admin_uuid: UserUuid, conn: PooledConn| {
+            UserBan::get_active_bans(&conn)
+                .map(convert_vector_and_json::<UserBan, UserBanResponse>)
+                .map_err(Error::simple_reject)
+        })
+        .boxed()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use error::warp_support::customize_error;
     use crate::{
         state::jwt::AUTHORIZATION_HEADER_KEY,
         util::test::deserialize,
@@ -193,9 +279,12 @@ mod tests {
     use pool::Pool;
     use testing_common::setup::setup_warp;
     use testing_fixtures::fixtures::user::UserFixture;
-    use wire::user::{
-        UserRole,
-        BEARER,
+    use wire::{
+        user::{
+            UserRole,
+            BEARER,
+        },
+        Page,
     };
 
     #[test]
@@ -226,9 +315,33 @@ mod tests {
                 .reply(&user_api(&s));
 
             assert_eq!(response.status(), 200);
-            let users: Vec<FullUserResponse> = deserialize(response);
+            let users: Page<FullUserResponse> = deserialize(response);
+
+            assert_eq!(users.items.len(), 2);
+            assert_eq!(users.page, 1);
+            assert_eq!(users.total_items, 2);
+            assert_eq!(users.total_pages, 1);
+        })
+    }
+
+    #[test]
+    fn get_many_with_page_size() {
+        setup_warp(|fixture: &UserFixture, pool: Pool| {
+            let s = State::testing_init(pool, fixture.secret.clone());
+            let jwt: String = crate::routes::auth::tests::get_admin_jwt_string(&s, fixture);
+            let response = warp::test::request()
+                .method("GET")
+                .header(AUTHORIZATION_HEADER_KEY, format!("{} {}", BEARER, jwt))
+                .path("/user/1?page_size=1")
+                .reply(&user_api(&s));
+
+            assert_eq!(response.status(), 200);
+            let users: Page<FullUserResponse> = deserialize(response);
 
-            assert_eq!(users.len(), 2)
+            assert_eq!(users.items.len(), 1);
+            assert_eq!(users.page_size, 1);
+            assert_eq!(users.total_items, 2);
+            assert_eq!(users.total_pages, 2);
         })
     }
 
@@ -316,15 +429,57 @@ mod tests {
                 user_role: UserRole::Admin.into(),
             };
             let jwt: String = crate::routes::auth::tests::get_jwt_string(&s, admin_name);
+            let banned_jwt: String = crate::routes::auth::tests::get_jwt_string(&s, fixture.normal_user.user_name.clone());
 
+            let ban_request = BanUserRequest {
+                reason: "Spam".to_string(),
+                banned_for_minutes: Some(60),
+            };
             let response = warp::test::request()
                 .method("PUT")
                 .header(AUTHORIZATION_HEADER_KEY, format!("{} {}", BEARER, jwt))
+                .json(&ban_request)
                 .path(&format!("/user/ban/{}", fixture.normal_user.uuid.clone()))
                 .reply(&user_api(&s));
 
             assert_eq!(response.status(), 200);
-            let _user: UserResponse = deserialize(response);
+            let ban: UserBanResponse = deserialize(response);
+            assert_eq!(ban.user_uuid, UserUuid(fixture.normal_user.uuid));
+            assert_eq!(ban.admin_uuid, Some(UserUuid(fixture.admin_user.uuid)));
+
+            // The banned user's outstanding JWT stops working.
+            let update_request = UpdateDisplayNameRequest {
+                user_name: fixture.normal_user.user_name.clone(),
+                new_display_name: "New Name".to_string(),
+            };
+            let response = warp::test::request()
+                .method("PUT")
+                .header(AUTHORIZATION_HEADER_KEY, format!("{} {}", BEARER, banned_jwt))
+                .json(&update_request)
+                .path("/user/display_name")
+                .reply(&user_api(&s).recover(customize_error));
+            assert_eq!(response.status(), 403);
+        })
+    }
+
+    #[test]
+    fn ban_too_long() {
+        setup_warp(|fixture: &UserFixture, pool: Pool| {
+            let s = State::testing_init(pool, fixture.secret.clone());
+            let jwt: String = crate::routes::auth::tests::get_jwt_string(&s, fixture.admin_user.user_name.clone());
+
+            let ban_request = BanUserRequest {
+                reason: "Spam".to_string(),
+                banned_for_minutes: Some(i64::max_value()),
+            };
+            let response = warp::test::request()
+                .method("PUT")
+                .header(AUTHORIZATION_HEADER_KEY, format!("{} {}", BEARER, jwt))
+                .json(&ban_request)
+                .path(&format!("/user/ban/{}", fixture.normal_user.uuid.clone()))
+                .reply(&user_api(&s).recover(customize_error));
+
+            assert_eq!(response.status(), 400);
         })
     }
 
@@ -1,27 +1,75 @@
+use chrono::{
+    NaiveDateTime,
+    Utc,
+};
+use db::user_ban::UserBan;
 use identifiers::user::UserUuid;
+use pool::{
+    Pool,
+    PooledConn,
+};
 use std::{
-    collections::BTreeSet,
+    collections::BTreeMap,
     sync::{
         Arc,
         RwLock,
     },
+    thread,
+    time::Duration,
 };
 use warp::{
     filters::BoxedFilter,
     Filter,
 };
 
-/// The BannedList contains a list of users that should not have access to the system.
-/// This is an ephemeral protection mechanism, that will prevent users from using outstanding JWTs if their UUID is in this structure.
-/// If the server is restarted, and the JWT key is not rotated, then this will offer no protection
-/// as there is no persistence aspect to this.
+/// Why a user was banned, and until when.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ban {
+    pub reason: String,
+    /// The ban is permanent if None.
+    pub banned_until: Option<NaiveDateTime>,
+}
+
+impl Ban {
+    /// Checks if the ban has run out.
+    fn is_expired(&self) -> bool {
+        match self.banned_until {
+            Some(banned_until) => banned_until <= Utc::now().naive_utc(),
+            None => false,
+        }
+    }
+}
+
+impl From<UserBan> for Ban {
+    fn from(ban: UserBan) -> Ban {
+        Ban {
+            reason: ban.reason,
+            banned_until: ban.expires_at,
+        }
+    }
+}
+
+/// The BannedList contains the users that should not have access to the system.
+/// It prevents users from using outstanding JWTs if their UUID is in this structure.
+///
+/// Bans are stored in the database, and this is a cache of them, so checking a request for a ban doesn't need a database call.
+/// It is loaded from the database when the server starts, and bans should be added to both at the same time.
 #[derive(Debug, Default, Clone)]
-pub struct BannedList(Arc<RwLock<BTreeSet<UserUuid>>>);
+pub struct BannedList(Arc<RwLock<BTreeMap<UserUuid, Ban>>>);
 
 impl BannedList {
+    /// Creates the list from the bans stored in the database.
+    pub fn from_bans(bans: Vec<UserBan>) -> BannedList {
+        let bans: BTreeMap<UserUuid, Ban> = bans
+            .into_iter()
+            .map(|ban| (UserUuid(ban.user_uuid), Ban::from(ban)))
+            .collect();
+        BannedList(Arc::new(RwLock::new(bans)))
+    }
+
     /// Bans the user from this server.
-    pub fn ban(&self, user: UserUuid) {
-        self.0.write().unwrap().insert(user);
+    pub fn ban(&self, user: UserUuid, ban: Ban) {
+        self.0.write().unwrap().insert(user, ban);
     }
 
     /// Unbans the user from this server.
@@ -29,12 +77,111 @@ impl BannedList {
         self.0.write().unwrap().remove(user);
     }
 
-    /// Checks if a given user is banned.
-    pub fn is_banned(&self, user: &UserUuid) -> bool {
-        self.0.read().unwrap().contains(user)
+    /// Gets the user's ban, if they are banned.
+    /// Expired bans are lifted instead of being returned.
+    pub fn get_ban(&self, user: &UserUuid) -> Option<Ban> {
+        let ban: Ban = self.0.read().unwrap().get(user).cloned()?;
+        if ban.is_expired() {
+            self.lift_if_expired(user)
+        } else {
+            Some(ban)
+        }
+    }
+
+    /// Removes the user's ban if it has expired, returning it otherwise.
+    ///
+    /// The ban is checked under the write lock, so a ban that was reissued
+    /// after the expired one was seen is kept.
+    fn lift_if_expired(&self, user: &UserUuid) -> Option<Ban> {
+        let mut bans = self.0.write().unwrap();
+        let ban: Ban = bans.get(user).cloned()?;
+        if ban.is_expired() {
+            bans.remove(user);
+            None
+        } else {
+            Some(ban)
+        }
     }
 }
 
+/// How often expired bans are removed from the database.
+const BAN_EXPIRY_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Lifts bans in the database as they expire, and removes them from the list.
+/// Without this, users whose bans expired would still be marked as banned until the server restarted,
+/// even though the list had already let them back in.
+pub fn spawn_ban_expiry(pool: Pool, banned_list: BannedList) {
+    thread::spawn(move || loop {
+        thread::sleep(BAN_EXPIRY_INTERVAL);
+        let conn: PooledConn = match pool.get() {
+            Ok(conn) => conn,
+            Err(e) => {
+                warn!("Could not get a connection to lift expired bans: {:?}", e);
+                continue;
+            }
+        };
+        match UserBan::lift_expired_bans(&conn) {
+            Ok(lifted) => {
+                // The user may have been banned again since the database was updated.
+                for user in lifted {
+                    banned_list.lift_if_expired(&user);
+                }
+            }
+            Err(e) => warn!("Could not lift expired bans: {:?}", e),
+        }
+    });
+}
+
 pub fn banned_list_filter(banned_list: BannedList) -> BoxedFilter<(BannedList,)> {
     warp::any().map(move || banned_list.clone()).boxed()
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::Duration;
+    use uuid::Uuid;
+
+    #[test]
+    fn expired_bans_are_lifted() {
+        let banned_list = BannedList::default();
+        let user = UserUuid(Uuid::new_v4());
+        let ban = Ban {
+            reason: "Spam".to_string(),
+            banned_until: Some(Utc::now().naive_utc() - Duration::minutes(1)),
+        };
+        banned_list.ban(user, ban);
+
+        assert_eq!(banned_list.get_ban(&user), None);
+    }
+
+    #[test]
+    fn bans_last_until_they_expire() {
+        let banned_list = BannedList::default();
+        let user = UserUuid(Uuid::new_v4());
+        let ban = Ban {
+            reason: "Spam".to_string(),
+            banned_until: Some(Utc::now().naive_utc() + Duration::minutes(1)),
+        };
+        banned_list.ban(user, ban.clone());
+
+        assert_eq!(banned_list.get_ban(&user), Some(ban));
+        banned_list.unban(&user);
+        assert_eq!(banned_list.get_ban(&user), None);
+    }
+
+    #[test]
+    fn reissued_bans_are_not_lifted() {
+        let banned_list = BannedList::default();
+        let user = UserUuid(Uuid::new_v4());
+        let ban = Ban {
+            reason: "Spam".to_string(),
+            banned_until: None,
+        };
+        banned_list.ban(user, ban.clone());
+
+        // The expiry task lifting an older ban from the database must not remove the new one.
+        assert_eq!(banned_list.lift_if_expired(&user), Some(ban.clone()));
+        assert_eq!(banned_list.get_ban(&user), Some(ban));
+    }
+}
@@ -0,0 +1,124 @@
+use db::bucket::Bucket;
+use futures::sync::mpsc::{
+    self,
+    UnboundedReceiver,
+    UnboundedSender,
+};
+use identifiers::{
+    bucket::BucketUuid,
+    user::UserUuid,
+};
+use pool::{
+    Pool,
+    PooledConn,
+};
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc,
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+use warp::{
+    filters::BoxedFilter,
+    Filter,
+};
+use wire::bucket::BucketSessionResponse;
+
+/// A participant's connection to a bucket's session.
+type Listener = (UserUuid, UnboundedSender<BucketSessionResponse>);
+
+/// The participants currently listening for changes to the session of each bucket.
+/// Whenever a session changes, its new state is sent to every listener.
+#[derive(Default, Clone)]
+pub struct BucketSessions(Arc<Mutex<HashMap<BucketUuid, Vec<Listener>>>>);
+
+impl BucketSessions {
+    /// Starts listening for changes to the bucket's session.
+    /// The current state is the first thing received, so that late joiners are caught up immediately.
+    pub fn subscribe(
+        &self,
+        bucket_uuid: BucketUuid,
+        user_uuid: UserUuid,
+        current: BucketSessionResponse,
+    ) -> UnboundedReceiver<BucketSessionResponse> {
+        let (sender, receiver) = mpsc::unbounded();
+        let _ = sender.unbounded_send(current);
+        self.0
+            .lock()
+            .unwrap()
+            .entry(bucket_uuid)
+            .or_insert_with(Vec::new)
+            .push((user_uuid, sender));
+        receiver
+    }
+
+    /// Stops sending the bucket's session to the user, ending any streams they have open.
+    /// This should be called when the user leaves or is removed from the bucket.
+    pub fn unsubscribe(&self, bucket_uuid: BucketUuid, user_uuid: UserUuid) {
+        let mut sessions = self.0.lock().unwrap();
+        let is_empty = match sessions.get_mut(&bucket_uuid) {
+            Some(listeners) => {
+                listeners.retain(|(listener_uuid, _)| *listener_uuid != user_uuid);
+                listeners.is_empty()
+            }
+            None => false,
+        };
+        if is_empty {
+            sessions.remove(&bucket_uuid);
+        }
+    }
+
+    /// Sends the new state of the session to everyone listening to the bucket.
+    /// Listeners that have disconnected are forgotten.
+    pub fn publish(&self, bucket_uuid: BucketUuid, state: BucketSessionResponse) {
+        let mut sessions = self.0.lock().unwrap();
+        let is_empty = match sessions.get_mut(&bucket_uuid) {
+            Some(listeners) => {
+                listeners.retain(|(_, sender)| sender.unbounded_send(state.clone()).is_ok());
+                listeners.is_empty()
+            }
+            None => false,
+        };
+        if is_empty {
+            sessions.remove(&bucket_uuid);
+        }
+    }
+}
+
+pub fn bucket_sessions_filter(bucket_sessions: BucketSessions) -> BoxedFilter<(BucketSessions,)> {
+    warp::any().map(move || bucket_sessions.clone()).boxed()
+}
+
+/// How often timed turns are checked for having run out.
+const TURN_TIMER_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Skips timed turns as soon as they run out, letting everyone listening to the bucket know.
+/// Without this, a turn would only be skipped when someone happened to make a request to the bucket.
+pub fn spawn_turn_timer(pool: Pool, bucket_sessions: BucketSessions) {
+    thread::spawn(move || loop {
+        thread::sleep(TURN_TIMER_INTERVAL);
+        let conn: PooledConn = match pool.get() {
+            Ok(conn) => conn,
+            Err(e) => {
+                warn!("Could not get a connection to check turn timers: {:?}", e);
+                continue;
+            }
+        };
+        let skipped: Vec<BucketUuid> = match Bucket::skip_expired_turns(&conn) {
+            Ok(skipped) => skipped,
+            Err(e) => {
+                warn!("Could not skip expired turns: {:?}", e);
+                continue;
+            }
+        };
+        for bucket_uuid in skipped {
+            match Bucket::get_session(bucket_uuid, &conn) {
+                Ok(session) => bucket_sessions.publish(bucket_uuid, BucketSessionResponse::from(session)),
+                Err(e) => warn!("Could not publish the session for bucket {}: {:?}", bucket_uuid.0, e),
+            }
+        }
+    });
+}
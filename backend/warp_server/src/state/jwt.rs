@@ -3,7 +3,10 @@ use auth::{
     ServerJwt,
 };
 use identifiers::user::UserUuid;
-use std::result::Result::Err;
+use std::{
+    collections::HashMap,
+    result::Result::Err,
+};
 use warp::{
     self,
     filters::BoxedFilter,
@@ -36,9 +39,7 @@ pub fn jwt_filter(s: &State) -> BoxedFilter<(ServerJwt,)> {
 
         // Check if the user is banned, and therefore their jwt should be rejected.
         if let Ok(ref jwt) = &jwt {
-            if banned_list.is_banned(&jwt.0.sub) {
-                return Error::UserBanned.reject();
-            }
+            reject_if_banned(&jwt.0.sub, &banned_list)?;
         }
         jwt
     }
@@ -88,6 +89,34 @@ pub fn normal_user_filter(s: &State) -> BoxedFilter<(UserUuid,), > {
 }
 
 
+/// Gets the user from a `token` query parameter instead of the Authorization header.
+/// This is for endpoints consumed by browser APIs like EventSource, which can't set headers.
+pub fn query_token_user_filter(s: &State) -> BoxedFilter<(UserUuid,)> {
+    warp::query::query::<HashMap<String, String>>()
+        .or_else(|_| Error::MalformedToken.reject())
+        .and(s.secret.clone())
+        .and(s.banned_list.clone())
+        .and_then(|query: HashMap<String, String>, secret: Secret, banned_list: BannedList| {
+            let token = query.get("token").ok_or_else(|| Error::MalformedToken.simple_reject())?;
+            let jwt = ServerJwt::decode_jwt_string(token, &secret)
+                .map_err(|_| Error::IllegalToken.simple_reject())?;
+            reject_if_banned(&jwt.0.sub, &banned_list)?;
+            get_user_uuid_from_jwt(jwt)
+        })
+        .boxed()
+}
+
+/// Banned users are told why they were banned, and until when.
+fn reject_if_banned(user_uuid: &UserUuid, banned_list: &BannedList) -> Result<(), Rejection> {
+    match banned_list.get_ban(user_uuid) {
+        Some(ban) => Error::UserBanned {
+            reason: ban.reason,
+            banned_until: ban.banned_until,
+        }
+        .reject(),
+        None => Ok(()),
+    }
+}
 
 /// Gets an Option<UserUuid> from the request.
 /// Returns Some(user_uuid) if the user has a valid JWT, and None otherwise.
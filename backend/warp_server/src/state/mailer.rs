@@ -0,0 +1,73 @@
+use std::{
+    fmt::Debug,
+    fs::OpenOptions,
+    io::{
+        self,
+        Write,
+    },
+    path::PathBuf,
+    sync::{
+        Arc,
+        Mutex,
+    },
+};
+use warp::{
+    filters::BoxedFilter,
+    Filter,
+};
+
+/// An email addressed to a user.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Delivers mail to users, such as password reset tokens.
+///
+/// The server only ever holds one mailer, chosen at startup, so delivery can be swapped out without touching the routes.
+pub trait Mailer: Debug + Send + Sync {
+    fn send(&self, mail: Mail) -> io::Result<()>;
+}
+
+/// Writes mail to the log instead of sending it.
+/// This is used when no other mailer is configured, so that mail can be read during development.
+#[derive(Debug, Default, Clone)]
+pub struct LogMailer;
+
+impl Mailer for LogMailer {
+    fn send(&self, mail: Mail) -> io::Result<()> {
+        info!("Mail to: {}\nSubject: {}\n{}", mail.to, mail.subject, mail.body);
+        Ok(())
+    }
+}
+
+/// Appends mail to a file instead of sending it.
+#[derive(Debug)]
+pub struct FileMailer {
+    path: PathBuf,
+    /// Keeps mail sent by concurrent requests from being interleaved in the file.
+    lock: Mutex<()>,
+}
+
+impl FileMailer {
+    pub fn new(path: PathBuf) -> FileMailer {
+        FileMailer {
+            path,
+            lock: Mutex::new(()),
+        }
+    }
+}
+
+impl Mailer for FileMailer {
+    fn send(&self, mail: Mail) -> io::Result<()> {
+        let _guard = self.lock.lock().unwrap();
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "To: {}\nSubject: {}\n\n{}\n", mail.to, mail.subject, mail.body)
+    }
+}
+
+pub fn mailer_filter(mailer: Arc<dyn Mailer>) -> BoxedFilter<(Arc<dyn Mailer>,)> {
+    warp::any().map(move || mailer.clone()).boxed()
+}
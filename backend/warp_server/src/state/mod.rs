@@ -1,21 +1,46 @@
 pub mod banned_list;
+pub mod bucket_sessions;
 /// This module deals with anything in the server that requires some stateful interaction.
 /// This includes DB access, and secret management.
 pub mod db_integration;
 pub mod jwt;
+pub mod mailer;
 
 use self::{
     banned_list::{
         banned_list_filter,
+        spawn_ban_expiry,
         BannedList,
     },
+    bucket_sessions::{
+        bucket_sessions_filter,
+        spawn_turn_timer,
+        BucketSessions,
+    },
     jwt::secret_filter,
+    mailer::{
+        mailer_filter,
+        FileMailer,
+        LogMailer,
+        Mailer,
+    },
 };
 use auth::Secret;
+use db::{
+    article::DEFAULT_ARTICLE_COMMENTS_FORUM,
+    user_ban::UserBan,
+};
 #[cfg(test)]
 use pool::Pool;
 use pool::PooledConn;
-use warp::filters::BoxedFilter;
+use std::{
+    path::PathBuf,
+    sync::Arc,
+};
+use warp::{
+    filters::BoxedFilter,
+    Filter,
+};
 
 /// State object that should be accessable to most routes.
 /// This object will hold references to functions that will allow the production
@@ -24,22 +49,35 @@ pub struct State {
     pub db: BoxedFilter<(PooledConn,)>,
     pub secret: BoxedFilter<(Secret,)>,
     pub banned_list: BoxedFilter<(BannedList,)>,
+    /// Listeners waiting on changes to bucket sessions.
+    pub bucket_sessions: BoxedFilter<(BucketSessions,)>,
+    /// The title of the forum where article comment threads are created.
+    pub article_comments_forum: BoxedFilter<(String,)>,
+    /// Delivers mail, such as password reset tokens, to users.
+    pub mailer: BoxedFilter<(Arc<dyn Mailer>,)>,
 }
 
 /// Configuration struct used in constructing the State struct.
 pub struct StateConfig {
     pub specified_secret: Option<String>,
     pub database_url: String,
+    pub article_comments_forum: String,
+    /// If present, mail is appended to this file instead of being logged.
+    pub mail_file: Option<PathBuf>,
 }
 
 /// By default:
 /// * The secret will be randomly generated.
 /// * The database URL will point to the default database as defined by an environment variable.
+/// * Article comments will be placed in the "Article Comments" forum.
+/// * Mail will be written to the log.
 impl Default for StateConfig {
     fn default() -> Self {
         StateConfig {
             specified_secret: None,
             database_url: pool::DATABASE_URL.to_string(),
+            article_comments_forum: DEFAULT_ARTICLE_COMMENTS_FORUM.to_string(),
+            mail_file: None,
         }
     }
 }
@@ -56,12 +94,29 @@ impl State {
             Secret::generate()
         };
 
-        let banned_list: BannedList = BannedList::default();
+        // Bans that expired while the server was down are lifted before the rest are cached.
+        let banned_list: BannedList = {
+            let conn: PooledConn = pool.get().expect("Could not get a connection to load bans");
+            UserBan::lift_expired_bans(&conn).expect("Could not lift expired bans");
+            BannedList::from_bans(UserBan::get_active_bans(&conn).expect("Could not load bans"))
+        };
+        spawn_ban_expiry(pool.clone(), banned_list.clone());
+
+        let bucket_sessions = BucketSessions::default();
+        spawn_turn_timer(pool.clone(), bucket_sessions.clone());
+
+        let mailer: Arc<dyn Mailer> = match config.mail_file {
+            Some(path) => Arc::new(FileMailer::new(path)),
+            None => Arc::new(LogMailer),
+        };
 
         State {
             db: db_integration::db_filter(pool),
             secret: secret_filter(secret),
             banned_list: banned_list_filter(banned_list),
+            bucket_sessions: bucket_sessions_filter(bucket_sessions),
+            article_comments_forum: article_comments_forum_filter(config.article_comments_forum),
+            mailer: mailer_filter(mailer),
         }
     }
 }
@@ -76,8 +131,24 @@ impl State {
             db: db_integration::db_filter(pool),
             secret: secret_filter(secret),
             banned_list: banned_list_filter(BannedList::default()),
+            bucket_sessions: bucket_sessions_filter(BucketSessions::default()),
+            article_comments_forum: article_comments_forum_filter(DEFAULT_ARTICLE_COMMENTS_FORUM.to_string()),
+            mailer: mailer_filter(Arc::new(LogMailer)),
         }
     }
+
+    /// Replaces the mailer, so tests can read the mail that was sent.
+    pub fn with_mailer(self, mailer: Arc<dyn Mailer>) -> State {
+        State {
+            mailer: mailer_filter(mailer),
+            ..self
+        }
+    }
+}
+
+/// Brings the title of the article comments forum into scope.
+fn article_comments_forum_filter(forum_title: String) -> BoxedFilter<(String,)> {
+    warp::any().map(move || forum_title.clone()).boxed()
 }
 
 impl Default for State {
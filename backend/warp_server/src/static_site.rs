@@ -0,0 +1,339 @@
+//! Exports the published articles as a standalone static site,
+//! so that the blog can be mirrored or archived without running the server or the wasm app.
+//!
+//! The generated site has the following layout:
+//! * `index.html` - Every published article, newest first.
+//! * `articles/<slug>.html` - One page per article, with its Markdown rendered to HTML.
+//! * `tags/index.html` - Every tag, alongside the articles it is attached to.
+//! * `feed.xml` - An RSS feed of the articles.
+
+use chrono::{
+    DateTime,
+    NaiveDateTime,
+    Utc,
+};
+use db::article::{
+    Article,
+    ArticleData,
+};
+use error::Error;
+use pulldown_cmark::{
+    html,
+    Parser,
+    OPTION_ENABLE_TABLES,
+};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+/// Settings for a static export.
+#[derive(Clone, Debug)]
+pub struct ExportConfig {
+    /// The directory the site is written to. It is created if it doesn't exist.
+    pub out_dir: PathBuf,
+    /// The title used for the index page and the feed.
+    pub site_title: String,
+    /// The url the site will be hosted at, used for the absolute links required by the feed.
+    pub base_url: String,
+}
+
+#[derive(Debug)]
+pub enum ExportError {
+    Database(Error),
+    Io(io::Error),
+}
+
+impl From<Error> for ExportError {
+    fn from(e: Error) -> Self {
+        ExportError::Database(e)
+    }
+}
+
+impl From<io::Error> for ExportError {
+    fn from(e: io::Error) -> Self {
+        ExportError::Io(e)
+    }
+}
+
+/// Reads every published article and writes the static site.
+/// Returns the number of articles that were exported.
+pub fn export(database_url: &str, config: &ExportConfig) -> Result<usize, ExportError> {
+    let conn = pool::create_single_connection(database_url);
+    let articles: Vec<ArticleData> = Article::get_all_published(&conn)?;
+
+    let articles_dir = config.out_dir.join("articles");
+    let tags_dir = config.out_dir.join("tags");
+    fs::create_dir_all(&articles_dir)?;
+    fs::create_dir_all(&tags_dir)?;
+
+    for article_data in &articles {
+        let file_name = format!("{}.html", article_data.article.slug);
+        write_file(&articles_dir.join(file_name), &article_page(article_data, config))?;
+    }
+    write_file(&config.out_dir.join("index.html"), &index_page(&articles, config))?;
+    write_file(&tags_dir.join("index.html"), &tag_index_page(&articles, config))?;
+    write_file(&config.out_dir.join("feed.xml"), &feed(&articles, config))?;
+
+    info!("Exported {} articles to {:?}", articles.len(), config.out_dir);
+    Ok(articles.len())
+}
+
+fn write_file(path: &Path, contents: &str) -> io::Result<()> {
+    fs::write(path, contents.as_bytes())
+}
+
+/// Wraps the body in the markup shared by every page.
+/// `root` is the relative path from the page to the root of the site.
+fn page(title: &str, root: &str, body: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<link rel="alternate" type="application/rss+xml" href="{root}feed.xml">
+</head>
+<body>
+<nav><a href="{root}index.html">Articles</a> | <a href="{root}tags/index.html">Tags</a> | <a href="{root}feed.xml">Feed</a></nav>
+{body}
+</body>
+</html>
+"#,
+        title = escape(title),
+        root = root,
+        body = body
+    )
+}
+
+fn index_page(articles: &[ArticleData], config: &ExportConfig) -> String {
+    let body = format!(
+        "<h1>{}</h1>\n{}",
+        escape(&config.site_title),
+        article_list(articles.iter(), "")
+    );
+    page(&config.site_title, "", &body)
+}
+
+fn article_page(article_data: &ArticleData, config: &ExportConfig) -> String {
+    let article = &article_data.article;
+    let tags: Vec<String> = article_data
+        .tags
+        .iter()
+        .map(|tag| format!(r#"<a href="../tags/index.html#{0}">{0}</a>"#, escape(tag)))
+        .collect();
+
+    let body = format!(
+        "<article>\n<h1>{title}</h1>\n<p>By {author} - {date}</p>\n<p>{tags}</p>\n{content}</article>",
+        title = escape(&article.title),
+        author = escape(&article_data.user.display_name),
+        date = article.publish_date.map(display_date).unwrap_or_default(),
+        tags = tags.join(" "),
+        content = render_markdown(&article.body)
+    );
+    let title = format!("{} - {}", article.title, config.site_title);
+    page(&title, "../", &body)
+}
+
+fn tag_index_page(articles: &[ArticleData], config: &ExportConfig) -> String {
+    // A BTreeMap keeps the tags in alphabetical order.
+    let mut tagged: BTreeMap<&str, Vec<&ArticleData>> = BTreeMap::new();
+    for article_data in articles {
+        for tag in &article_data.tags {
+            tagged.entry(tag.as_str()).or_insert_with(Vec::new).push(article_data);
+        }
+    }
+
+    let sections: Vec<String> = tagged
+        .into_iter()
+        .map(|(tag, articles)| {
+            format!(
+                "<section id=\"{0}\">\n<h2>{0}</h2>\n{1}</section>",
+                escape(tag),
+                article_list(articles.into_iter(), "../")
+            )
+        })
+        .collect();
+
+    let body = format!("<h1>Tags</h1>\n{}", sections.join("\n"));
+    let title = format!("Tags - {}", config.site_title);
+    page(&title, "../", &body)
+}
+
+/// Lists links to the articles.
+/// `root` is the relative path from the page containing the list to the root of the site.
+fn article_list<'a>(articles: impl Iterator<Item = &'a ArticleData>, root: &str) -> String {
+    let items: Vec<String> = articles
+        .map(|article_data| {
+            let article = &article_data.article;
+            format!(
+                "<li><a href=\"{root}articles/{slug}.html\">{title}</a> - {author}, {date}</li>",
+                root = root,
+                slug = article.slug,
+                title = escape(&article.title),
+                author = escape(&article_data.user.display_name),
+                date = article.publish_date.map(display_date).unwrap_or_default()
+            )
+        })
+        .collect();
+    format!("<ul>\n{}\n</ul>\n", items.join("\n"))
+}
+
+fn feed(articles: &[ArticleData], config: &ExportConfig) -> String {
+    let base_url = config.base_url.trim_right_matches('/');
+    let items: Vec<String> = articles
+        .iter()
+        .map(|article_data| {
+            let article = &article_data.article;
+            let link = format!("{}/articles/{}.html", base_url, article.slug);
+            let categories: Vec<String> = article_data
+                .tags
+                .iter()
+                .map(|tag| format!("<category>{}</category>", escape(tag)))
+                .collect();
+            format!(
+                "<item>\n<title>{title}</title>\n<link>{link}</link>\n<guid isPermaLink=\"false\">{uuid}</guid>\n<pubDate>{date}</pubDate>\n<author>{author}</author>\n{categories}\n<description>{description}</description>\n</item>",
+                title = escape(&article.title),
+                link = escape(&link),
+                uuid = article.uuid,
+                date = article.publish_date.map(rfc_2822_date).unwrap_or_default(),
+                author = escape(&article_data.user.display_name),
+                categories = categories.join("\n"),
+                description = escape(&render_markdown(&article.body))
+            )
+        })
+        .collect();
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<rss version=\"2.0\">\n<channel>\n<title>{title}</title>\n<link>{link}</link>\n<description>{title}</description>\n{items}\n</channel>\n</rss>\n",
+        title = escape(&config.site_title),
+        link = escape(&format!("{}/index.html", base_url)),
+        items = items.join("\n")
+    )
+}
+
+fn render_markdown(src: &str) -> String {
+    let mut rendered = String::new();
+    html::push_html(&mut rendered, Parser::new_ext(src, OPTION_ENABLE_TABLES));
+    rendered
+}
+
+/// Escapes text so that it can be embedded in HTML or XML.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn display_date(date: NaiveDateTime) -> String {
+    date.format("%B %e, %Y").to_string()
+}
+
+fn rfc_2822_date(date: NaiveDateTime) -> String {
+    DateTime::<Utc>::from_utc(date, Utc).to_rfc2822()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::NaiveDate;
+    use db::user::User;
+    use uuid::Uuid;
+
+    fn config() -> ExportConfig {
+        ExportConfig {
+            out_dir: PathBuf::from("site"),
+            site_title: "Joe's <Blog>".to_string(),
+            base_url: "https://example.com/".to_string(),
+        }
+    }
+
+    fn article_data(title: &str, body: &str, tags: Vec<&str>) -> ArticleData {
+        let author_uuid = Uuid::new_v4();
+        ArticleData {
+            article: Article {
+                uuid: Uuid::new_v4(),
+                author_uuid,
+                title: title.to_string(),
+                slug: "an-article".to_string(),
+                body: body.to_string(),
+                publish_date: Some(NaiveDate::from_ymd(2019, 10, 22).and_hms(12, 30, 0)),
+                category_uuid: None,
+                comment_thread_uuid: None,
+            },
+            user: User {
+                uuid: author_uuid,
+                user_name: "joe".to_string(),
+                display_name: "Joe & Friends".to_string(),
+                password_hash: String::new(),
+                locked: None,
+                failed_login_count: 0,
+                banned: false,
+                roles: vec![],
+                reputation: 0,
+                email: None,
+            },
+            tags: tags.into_iter().map(String::from).collect(),
+            comment_count: 0,
+        }
+    }
+
+    #[test]
+    fn escape_markup() {
+        assert_eq!(escape(r#"<a href="x">Tom & Jerry's</a>"#), "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;");
+        assert_eq!(escape("plain text"), "plain text");
+    }
+
+    #[test]
+    fn render_markdown_with_tables() {
+        let rendered = render_markdown("# Heading\n\nSome *text*.\n\n| a | b |\n|---|---|\n| 1 | 2 |\n");
+        assert!(rendered.contains("<h1>Heading</h1>"));
+        assert!(rendered.contains("<em>text</em>"));
+        assert!(rendered.contains("<table>"));
+    }
+
+    #[test]
+    fn article_page_escapes_text_and_links_tags() {
+        let data = article_data("Cats <3 Dogs", "Hello *world*", vec!["pets & animals"]);
+
+        let page = article_page(&data, &config());
+
+        assert!(page.contains("<h1>Cats &lt;3 Dogs</h1>"));
+        assert!(page.contains("<title>Cats &lt;3 Dogs - Joe&#39;s &lt;Blog&gt;</title>"));
+        assert!(page.contains("By Joe &amp; Friends - October 22, 2019"));
+        assert!(page.contains(r#"<a href="../tags/index.html#pets &amp; animals">pets &amp; animals</a>"#));
+        assert!(page.contains("<em>world</em>"));
+        assert!(page.contains(r#"href="../index.html""#));
+    }
+
+    #[test]
+    fn feed_uses_absolute_links() {
+        let data = article_data("Cats <3 Dogs", "Hello *world*", vec!["pets"]);
+
+        let feed = feed(&[data], &config());
+
+        assert!(feed.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"));
+        assert!(feed.contains("<title>Joe&#39;s &lt;Blog&gt;</title>"));
+        assert!(feed.contains("<link>https://example.com/index.html</link>"));
+        assert!(feed.contains("<link>https://example.com/articles/an-article.html</link>"));
+        assert!(feed.contains("<title>Cats &lt;3 Dogs</title>"));
+        assert!(feed.contains("<category>pets</category>"));
+        assert!(feed.contains("<pubDate>Tue, 22 Oct 2019 12:30:00 +0000</pubDate>"));
+        // The rendered body is escaped, so it survives as text in the XML.
+        assert!(feed.contains("&lt;em&gt;world&lt;/em&gt;"));
+    }
+}
@@ -1,3 +1,4 @@
+use db::PaginatedData;
 use serde::{
     Deserialize,
     Serialize,
@@ -9,7 +10,10 @@ use warp::{
     Filter,
     Reply,
 };
-use wire::convert_vector;
+use wire::{
+    convert_vector,
+    Page,
+};
 
 /// Util function that makes replying easier
 pub fn convert_and_json<T, U>(source: T) -> impl Reply
@@ -31,6 +35,22 @@ where
     warp::reply::json(&target)
 }
 
+/// Converts the items of a page from T to U, then converts the page to a JSON reply.
+pub fn convert_page_and_json<T, U>(source: PaginatedData<T>) -> impl Reply
+where
+    U: From<T>,
+    U: Serialize,
+{
+    let target: Page<U> = Page {
+        page: source.page as i32,
+        page_size: source.page_size as i32,
+        total_items: source.total_items,
+        total_pages: source.total_pages,
+        items: convert_vector(source.items),
+    };
+    warp::reply::json(&target)
+}
+
 pub fn json_body_filter<T>(kb_limit: u64) -> BoxedFilter<(T,)>
 where
     T: for<'de> Deserialize<'de> + Send + Sync + 'static,
@@ -50,6 +70,22 @@ pub fn query_uuid(key: &'static str) -> BoxedFilter<(Uuid,)> {
         .boxed()
 }
 
+/// Gets the page size from the optional `page_size` query parameter,
+/// falling back to the provided default if it is absent or malformed.
+///
+/// The size is capped at `wire::page::MAX_PAGE_SIZE` when the page is loaded.
+pub fn page_size_filter(default_page_size: i32) -> BoxedFilter<(i32,)> {
+    warp::query::query::<HashMap<String, String>>()
+        .map(move |hm: HashMap<String, String>| {
+            hm.get("page_size")
+                .and_then(|value: &String| value.parse::<i32>().ok())
+                .unwrap_or(default_page_size)
+        })
+        .or(warp::any().map(move || default_page_size))
+        .unify()
+        .boxed()
+}
+
 #[cfg(test)]
 pub mod test {
     use bytes::Bytes;
@@ -67,12 +103,4 @@ pub mod test {
         println!("Body string: {}", body_string);
         from_str::<T>(body_string).expect("Should be able to deserialize body")
     }
-
-    pub fn deserialize_string(response: Response<Bytes>) -> String {
-        let body = response.into_body();
-        let bytes: &[u8] = body.deref();
-        let body_string = std::str::from_utf8(bytes).expect("valid utf8 string");
-        String::from(body_string)
-    }
-
 }
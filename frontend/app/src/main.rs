@@ -15,6 +15,7 @@ use yew_router::prelude::*;
 
 extern crate bucket;
 extern crate forum;
+extern crate article;
 extern crate auth;
 extern crate header;
 
@@ -25,6 +26,7 @@ extern crate web_logger;
 
 use header::Header;
 use forum::ForumModel;
+use article::ArticleModel;
 use bucket::BucketModel;
 use auth::{Login, CreateAccount};
 
@@ -53,7 +55,7 @@ impl Renderable<Model> for Model {
                     <Header: />
                 </div>
                 <div class="main-content", >
-                    <YewRouter: routes=routes![Login, CreateAccount, BucketModel, ForumModel], />
+                    <YewRouter: routes=routes![Login, CreateAccount, BucketModel, ForumModel, ArticleModel], />
                 </div>
             <div/>
         }
@@ -0,0 +1,314 @@
+use common::fetch::FetchResponse;
+use common::fetch::Networking;
+use common::datatypes::article::FullArticleData;
+use common::datatypes::article::MinimalArticleData;
+use common::datatypes::article::NewArticleData;
+use identifiers::article::ArticleUuid;
+use identifiers::user::UserUuid;
+use requests::ArticleRequest;
+use util::button::Button;
+use util::markdown::author_markdown_side_by_side::AuthorMarkdownSideBySide;
+use util::uploadable::Uploadable;
+use util::wrappers::empty_vdom_node;
+use wire::article::FullArticleResponse;
+use wire::article::MinimalArticleResponse;
+use yew::prelude::*;
+use yew_router::prelude::*;
+use yew::services::storage::{Area, StorageService};
+use yew_router::router_agent::RouterSenderBase;
+
+/// Creates new articles and edits existing ones.
+///
+/// Only publishers can author articles.
+/// Drafts must be saved before they can be published,
+/// so that what gets published is always what is stored on the server.
+pub struct ArticleEditor {
+    article: Uploadable<EditorData>,
+    /// None until the article has been saved for the first time.
+    article_uuid: Option<ArticleUuid>,
+    /// None if the user isn't logged in.
+    user_uuid: Option<UserUuid>,
+    is_publisher: bool,
+    router_sender: RouterSenderBase<()>,
+    networking: Networking,
+    link: ComponentLink<ArticleEditor>
+}
+
+#[derive(Default, Debug, PartialEq, Clone)]
+pub struct EditorData {
+    article: NewArticleData,
+    is_saved: bool,
+    has_unsaved_changes: bool,
+    is_published: bool,
+}
+
+impl ArticleEditor {
+    fn get_article(&mut self, article_uuid: ArticleUuid) {
+        info!("Getting Article: ArticleEditor Component");
+        self.networking.fetch(
+            &ArticleRequest::GetArticle{article_uuid},
+            |r: FetchResponse<FullArticleResponse>| Msg::HandleGetArticleResponse(r.map(FullArticleData::from)),
+            &self.link
+        );
+    }
+
+    fn send_save_request(&mut self) {
+        let request = match (self.article_uuid, self.user_uuid) {
+            (Some(article_uuid), _) => ArticleRequest::UpdateArticle(self.article.as_ref().article.to_update_request(article_uuid)),
+            (None, Some(user_uuid)) => ArticleRequest::CreateArticle(self.article.as_ref().article.attach_info(user_uuid)),
+            (None, None) => return
+        };
+        self.networking.fetch(
+            &request,
+            |r: FetchResponse<MinimalArticleResponse>| Msg::HandleSaveResponse(r.map(MinimalArticleData::from)),
+            &self.link
+        );
+    }
+}
+
+pub enum Msg {
+    HandleGetArticleResponse(FetchResponse<FullArticleData>),
+    UpdateTitle(String),
+    UpdateBody(String),
+    SaveDraft,
+    HandleSaveResponse(FetchResponse<MinimalArticleData>),
+    Publish,
+    HandlePublishResponse(FetchResponse<String>),
+    Unpublish,
+    HandleUnpublishResponse(FetchResponse<String>),
+    NoOp
+}
+
+impl Default for Msg {
+    fn default() -> Self {
+        Msg::NoOp
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct Props {
+    article_uuid: Option<ArticleUuid>,
+}
+
+
+impl Component for ArticleEditor {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut storage_service = StorageService::new(Area::Local);
+        let user_uuid: Option<UserUuid> = ::common::user::user_id(&mut storage_service).ok();
+        let is_publisher = ::common::user::user_is_publisher(&mut storage_service);
+
+        let route_cb = link.send_back(|_| Msg::NoOp);
+
+        let mut article_editor = ArticleEditor {
+            article: Uploadable::default(),
+            article_uuid: props.article_uuid,
+            user_uuid,
+            is_publisher,
+            router_sender: RouterSenderBase::new(route_cb),
+            networking: Networking::new(&link),
+            link
+        };
+
+        if let Some(article_uuid) = props.article_uuid {
+            article_editor.get_article(article_uuid);
+        }
+        article_editor
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::HandleGetArticleResponse(response) => {
+                let response = response.map(|article| EditorData {
+                    article: NewArticleData {
+                        title: article.title,
+                        body: article.body,
+                    },
+                    is_saved: true,
+                    has_unsaved_changes: false,
+                    is_published: article.publish_date.is_some(),
+                });
+                self.article.handle_fetch_response(response);
+                true
+            }
+            Msg::UpdateTitle(title) => {
+                let data = self.article.as_mut();
+                data.article.title = title;
+                data.has_unsaved_changes = true;
+                true
+            }
+            Msg::UpdateBody(body) => {
+                let data = self.article.as_mut();
+                data.article.body = body;
+                data.has_unsaved_changes = true;
+                true
+            }
+            Msg::SaveDraft => {
+                self.send_save_request();
+                false
+            }
+            Msg::HandleSaveResponse(response) => {
+                match response {
+                    FetchResponse::Success(saved) => {
+                        if self.article_uuid.is_none() {
+                            self.router_sender.send(RouterRequest::ChangeRoute(route!("article/{}/edit", saved.uuid)))
+                        }
+                        self.article_uuid = Some(saved.uuid);
+                        let mut data = self.article.cloned_inner();
+                        data.is_saved = true;
+                        data.has_unsaved_changes = false;
+                        self.article = Uploadable::NotUploaded(data);
+                    }
+                    FetchResponse::Error(_) => {
+                        self.article.set_failed("Couldn't save article.")
+                    }
+                    FetchResponse::Started => {
+                        self.article.set_uploading()
+                    }
+                }
+                true
+            }
+            Msg::Publish => {
+                if let Some(article_uuid) = self.article_uuid {
+                    self.networking.fetch_string(
+                        &ArticleRequest::Publish{article_uuid},
+                        Msg::HandlePublishResponse,
+                        &self.link
+                    );
+                }
+                false
+            }
+            Msg::HandlePublishResponse(response) => {
+                match response {
+                    FetchResponse::Success(_) => {
+                        if let Some(article_uuid) = self.article_uuid {
+                            self.router_sender.send(RouterRequest::ChangeRoute(route!("article/{}", article_uuid)))
+                        }
+                    }
+                    FetchResponse::Error(_) => {
+                        self.article.set_failed("Couldn't publish article.")
+                    }
+                    FetchResponse::Started => {
+                        self.article.set_uploading()
+                    }
+                }
+                true
+            }
+            Msg::Unpublish => {
+                if let Some(article_uuid) = self.article_uuid {
+                    self.networking.fetch_string(
+                        &ArticleRequest::Unpublish{article_uuid},
+                        Msg::HandleUnpublishResponse,
+                        &self.link
+                    );
+                }
+                false
+            }
+            Msg::HandleUnpublishResponse(response) => {
+                match response {
+                    FetchResponse::Success(_) => {
+                        let mut data = self.article.cloned_inner();
+                        data.is_published = false;
+                        self.article = Uploadable::NotUploaded(data);
+                    }
+                    FetchResponse::Error(_) => {
+                        self.article.set_failed("Couldn't unpublish article.")
+                    }
+                    FetchResponse::Started => {
+                        self.article.set_uploading()
+                    }
+                }
+                true
+            }
+            Msg::NoOp => false
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        // Saving a new article changes the route to its edit page, which shouldn't cause it to be reloaded.
+        if self.article_uuid != props.article_uuid {
+            self.article_uuid = props.article_uuid;
+            self.article = Uploadable::default();
+            if let Some(article_uuid) = props.article_uuid {
+                self.get_article(article_uuid);
+            }
+            return true
+        }
+        false
+    }
+}
+
+impl Renderable<ArticleEditor> for ArticleEditor {
+    fn view(&self) -> Html<Self> {
+        if !self.is_publisher || self.user_uuid.is_none() {
+            return html! {
+                <div>
+                    {"Only publishers can write articles."}
+                </div>
+            }
+        }
+        self.article.default_view(EditorData::view)
+    }
+}
+
+impl Renderable<ArticleEditor> for EditorData {
+    fn view(&self) -> Html<ArticleEditor> {
+        let publish_controls = if !self.is_saved {
+            empty_vdom_node()
+        } else if self.is_published {
+            html! {
+                <Button: title="Unpublish", onclick=|_| Msg::Unpublish, />
+            }
+        } else {
+            html! {
+                <Button: title="Publish", disabled=self.has_unsaved_changes, onclick=|_| Msg::Publish, />
+            }
+        };
+
+        html! {
+            <div class="article-editor",>
+                <input
+                    class="form-control",
+                    placeholder="Article Title",
+                    value=&self.article.title,
+                    oninput=|e| Msg::UpdateTitle(e.value),
+                 />
+                 <AuthorMarkdownSideBySide: text=&self.article.body, callback=|text| Msg::UpdateBody(text), />
+                 <div class="flexbox-horiz",>
+                     <Button: title="Save Draft", disabled=!self.has_unsaved_changes, onclick=|_| Msg::SaveDraft, />
+                     {publish_controls}
+                 </div>
+            </div>
+        }
+    }
+}
+
+impl Routable for ArticleEditor {
+    fn resolve_props(route: &Route) -> Option<<Self as Component>::Properties> {
+        // /article/create
+        // /article/<article_uuid>/edit
+        if let Some(seg_2) = route.path_segments.get(1) {
+            if seg_2.as_str() == "create" {
+                return Some(Props { article_uuid: None })
+            }
+            if let Ok(article_uuid) = ArticleUuid::parse_str(&seg_2) {
+                if let Some(seg_3) = route.path_segments.get(2) {
+                    if seg_3.as_str() == "edit" {
+                        return Some(Props { article_uuid: Some(article_uuid) })
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn will_try_to_route(route: &Route) -> bool {
+        if let Some(seg_1) = route.path_segments.get(0) {
+            seg_1.as_str() == "article" && route.path_segments.get(1).is_some()
+        } else {
+            false
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use yew::prelude::*;
+use yew_router::prelude::*;
+use yew_router::components::RouterLink;
+use yew::services::storage::{StorageService, Area};
+use common::fetch::Networking;
+use common::fetch::FetchResponse;
+use common::datatypes::article::ArticlePreviewData;
+use common::datatypes::article::MinimalArticleData;
+use util::loadable::Loadable;
+use util::wrappers::empty_vdom_node;
+use requests::ArticleRequest;
+use wire::article::ArticlePreviewResponse;
+use wire::article::MinimalArticleResponse;
+use wire::Page;
+
+/// The number of articles shown on each page of the index.
+const ARTICLES_PER_PAGE: usize = 10;
+
+pub struct ArticleIndex {
+    articles: Loadable<ArticlePage>,
+    /// Only publishers have drafts, so this is only loaded for them.
+    drafts: Loadable<Vec<MinimalArticleData>>,
+    page_index: usize,
+    is_publisher: bool,
+    networking: Networking,
+    link: ComponentLink<ArticleIndex>
+}
+
+/// A single page of the index, along with the information needed to link to its neighbours.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArticlePage {
+    articles: Vec<ArticlePreviewData>,
+    page_index: usize,
+    total_pages: usize,
+    is_publisher: bool,
+}
+
+#[derive(Clone, PartialEq, Default)]
+pub struct Props {
+    page_index: usize
+}
+
+pub enum Msg {
+    HandleGetArticlesResponse(FetchResponse<Page<ArticlePreviewData>>),
+    HandleGetDraftsResponse(FetchResponse<Vec<MinimalArticleData>>),
+    NoOp
+}
+
+impl Default for Msg {
+    fn default() -> Self {
+        Msg::NoOp
+    }
+}
+
+impl ArticleIndex {
+    fn get_articles(&mut self) {
+        info!("Getting Articles: ArticleIndex Component");
+        let page_index = self.page_index;
+        self.networking.fetch(
+            &ArticleRequest::GetPublishedArticles{page_index, page_size: ARTICLES_PER_PAGE},
+            |r: FetchResponse<Page<ArticlePreviewResponse>>| Msg::HandleGetArticlesResponse(r.map(
+                |x: Page<ArticlePreviewResponse>| {
+                    Page {
+                        items: x.items
+                            .into_iter()
+                            .map(ArticlePreviewData::from)
+                            .collect(),
+                        page: x.page,
+                        page_size: x.page_size,
+                        total_items: x.total_items,
+                        total_pages: x.total_pages,
+                    }
+                }
+            )),
+            &self.link
+        );
+    }
+
+    fn get_drafts(&mut self) {
+        self.networking.fetch(
+            &ArticleRequest::GetOwnedUnpublishedArticles,
+            |r: FetchResponse<Vec<MinimalArticleResponse>>| Msg::HandleGetDraftsResponse(r.map(
+                |x: Vec<MinimalArticleResponse>| {
+                    x.into_iter()
+                        .map(MinimalArticleData::from)
+                        .collect()
+                }
+            )),
+            &self.link
+        );
+    }
+}
+
+impl Component for ArticleIndex {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut storage_service = StorageService::new(Area::Local);
+        let is_publisher = ::common::user::user_is_publisher(&mut storage_service);
+
+        let mut article_index = ArticleIndex {
+            articles: Loadable::default(),
+            drafts: Loadable::default(),
+            page_index: props.page_index,
+            is_publisher,
+            networking: Networking::new(&link),
+            link
+        };
+
+        article_index.get_articles();
+        if is_publisher {
+            article_index.get_drafts();
+        }
+        article_index
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::HandleGetArticlesResponse(response) => {
+                let page_index = self.page_index;
+                let is_publisher = self.is_publisher;
+                let response = response.map(|page| {
+                    ArticlePage {
+                        articles: page.items,
+                        page_index,
+                        total_pages: page.total_pages as usize,
+                        is_publisher
+                    }
+                });
+                self.articles = Loadable::from_fetch_response(response);
+                true
+            }
+            Msg::HandleGetDraftsResponse(response) => {
+                self.drafts = Loadable::from_fetch_response(response);
+                true
+            }
+            Msg::NoOp => false
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if self.page_index != props.page_index {
+            self.page_index = props.page_index;
+            self.get_articles();
+        }
+        false
+    }
+}
+
+impl Renderable<ArticleIndex> for ArticleIndex {
+    fn view(&self) -> Html<ArticleIndex> {
+        fn drafts_view(drafts: &Vec<MinimalArticleData>) -> Html<ArticleIndex> {
+            if drafts.is_empty() {
+                return empty_vdom_node()
+            }
+            html! {
+                <div class="article-drafts",>
+                    <h3>{"Your Drafts"}</h3>
+                    <ul class="article-list",>
+                        { for drafts.iter().map(MinimalArticleData::view) }
+                    </ul>
+                </div>
+            }
+        }
+
+        let drafts = if self.is_publisher {
+            self.drafts.small_view(drafts_view)
+        } else {
+            empty_vdom_node()
+        };
+
+        html! {
+            <div class="article-index",>
+                {drafts}
+                {self.articles.default_view(ArticlePage::view)}
+            </div>
+        }
+    }
+}
+
+impl Renderable<ArticleIndex> for ArticlePage {
+    fn view(&self) -> Html<ArticleIndex> {
+        let new_article = if self.is_publisher {
+            html! {
+                <RouterLink: text="New Article", route=route!("article/create"), />
+            }
+        } else {
+            empty_vdom_node()
+        };
+
+        let previous_page = if self.page_index > 1 {
+            html! {
+                <RouterLink: text="Newer", route=route!("article/page/{}", self.page_index - 1), />
+            }
+        } else {
+            empty_vdom_node()
+        };
+
+        let next_page = if self.page_index < self.total_pages {
+            html! {
+                <RouterLink: text="Older", route=route!("article/page/{}", self.page_index + 1), />
+            }
+        } else {
+            empty_vdom_node()
+        };
+
+        let articles = if self.articles.is_empty() {
+            html! {
+                <div>{"There are no articles here yet."}</div>
+            }
+        } else {
+            html! {
+                <ul class="article-list",>
+                    { for self.articles.iter().map(ArticlePreviewData::view) }
+                </ul>
+            }
+        };
+
+        html! {
+            <div>
+                <div class="flexbox-horiz",>
+                    <h2>{"Articles"}</h2>
+                    {new_article}
+                </div>
+                {articles}
+                <div class="flexbox-horiz",>
+                    <span>{previous_page}</span>
+                    <span>{next_page}</span>
+                </div>
+            </div>
+        }
+    }
+}
+
+impl Renderable<ArticleIndex> for ArticlePreviewData {
+    fn view(&self) -> Html<ArticleIndex> {
+        let publish_date = self.publish_date
+            .map(|date| date.format("%B %e, %Y").to_string())
+            .unwrap_or_default();
+        html! {
+            <li class="article-list-element",>
+                <div>
+                    <RouterLink: text=&self.title, route=route!("article/{}", self.uuid), />
+                </div>
+                <div>
+                    {format!("By: {} - {}", self.author.display_name, publish_date)}
+                </div>
+                <div class="article-tags",>
+                    { for self.tags.iter().map(|tag| html! { <span class="article-tag",>{tag}</span> }) }
+                </div>
+            </li>
+        }
+    }
+}
+
+impl Renderable<ArticleIndex> for MinimalArticleData {
+    fn view(&self) -> Html<ArticleIndex> {
+        let status = match self.publish_date {
+            Some(date) => format!("Scheduled for {}", date.format("%B %e, %Y")),
+            None => "Draft".to_string()
+        };
+        html! {
+            <li class="article-list-element",>
+                <div>
+                    <RouterLink: text=&self.title, route=route!("article/{}/edit", self.uuid), />
+                </div>
+                <div>
+                    {status}
+                </div>
+            </li>
+        }
+    }
+}
+
+impl Routable for ArticleIndex {
+    fn resolve_props(route: &Route) -> Option<<Self as Component>::Properties> {
+        // /article
+        // /article/page/<page_index>
+        match route.path_segments.get(1) {
+            None => Some(Props { page_index: 1 }),
+            Some(seg_2) => {
+                if seg_2.as_str() == "page" {
+                    let page_index = route.path_segments
+                        .get(2)
+                        .and_then(|x| x.parse::<usize>().ok())
+                        .filter(|page_index| *page_index > 0)
+                        .unwrap_or(1);
+                    Some(Props { page_index })
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    fn will_try_to_route(route: &Route) -> bool {
+        if let Some(seg_1) = route.path_segments.get(0) {
+            seg_1.as_str() == "article"
+        } else {
+            false
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use yew::prelude::*;
+use yew_router::prelude::*;
+use yew_router::components::RouterLink;
+use yew::services::storage::{StorageService, Area};
+use common::fetch::Networking;
+use common::fetch::FetchResponse;
+use common::datatypes::article::FullArticleData;
+use identifiers::article::ArticleUuid;
+use identifiers::user::UserUuid;
+use util::loadable::Loadable;
+use util::markdown::render_markdown;
+use util::wrappers::empty_vdom_node;
+use requests::ArticleRequest;
+use wire::article::FullArticleResponse;
+
+pub struct ArticleReader {
+    article: Loadable<ReaderData>,
+    article_uuid: ArticleUuid,
+    networking: Networking,
+    link: ComponentLink<ArticleReader>
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReaderData {
+    article: FullArticleData,
+    /// Publishers may edit the articles they have written.
+    can_edit: bool,
+}
+
+#[derive(Clone, PartialEq, Default)]
+pub struct Props {
+    article_uuid: ArticleUuid
+}
+
+pub enum Msg {
+    HandleGetArticleResponse(FetchResponse<FullArticleData>),
+    NoOp
+}
+
+impl Default for Msg {
+    fn default() -> Self {
+        Msg::NoOp
+    }
+}
+
+impl ArticleReader {
+    fn get_article(&mut self) {
+        info!("Getting Article: ArticleReader Component");
+        let article_uuid = self.article_uuid;
+        self.networking.fetch(
+            &ArticleRequest::GetArticle{article_uuid},
+            |r: FetchResponse<FullArticleResponse>| Msg::HandleGetArticleResponse(r.map(FullArticleData::from)),
+            &self.link
+        );
+    }
+}
+
+impl Component for ArticleReader {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut article_reader = ArticleReader {
+            article: Loadable::default(),
+            article_uuid: props.article_uuid,
+            networking: Networking::new(&link),
+            link
+        };
+
+        article_reader.get_article();
+        article_reader
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::HandleGetArticleResponse(response) => {
+                let mut storage_service = StorageService::new(Area::Local);
+                let user_uuid: Option<UserUuid> = ::common::user::user_id(&mut storage_service).ok();
+                let is_publisher = ::common::user::user_is_publisher(&mut storage_service);
+
+                let response = response.map(|article| {
+                    let can_edit = is_publisher && user_uuid == Some(article.author.uuid);
+                    ReaderData {
+                        article,
+                        can_edit
+                    }
+                });
+                self.article = Loadable::from_fetch_response(response);
+                true
+            }
+            Msg::NoOp => false
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if self.article_uuid != props.article_uuid {
+            self.article_uuid = props.article_uuid;
+            self.get_article();
+        }
+        false
+    }
+}
+
+impl Renderable<ArticleReader> for ArticleReader {
+    fn view(&self) -> Html<ArticleReader> {
+        self.article.default_view(ReaderData::view)
+    }
+}
+
+impl Renderable<ArticleReader> for ReaderData {
+    fn view(&self) -> Html<ArticleReader> {
+        let article = &self.article;
+
+        let byline = match article.publish_date {
+            Some(date) => format!("By: {} - {}", article.author.display_name, date.format("%B %e, %Y")),
+            None => format!("By: {} - Unpublished", article.author.display_name)
+        };
+
+        let edit = if self.can_edit {
+            html! {
+                <RouterLink: text="Edit", route=route!("article/{}/edit", article.uuid), />
+            }
+        } else {
+            empty_vdom_node()
+        };
+
+        let comments = if article.comment_thread_uuid.is_some() {
+            html! {
+                <div class="article-comments",>
+                    {format!("{} comments", article.comment_count)}
+                </div>
+            }
+        } else {
+            empty_vdom_node()
+        };
+
+        html! {
+            <div class="article",>
+                <div class="flexbox-horiz",>
+                    <h1>{&article.title}</h1>
+                    {edit}
+                </div>
+                <div class="article-byline",>
+                    {byline}
+                </div>
+                <div class="article-tags",>
+                    { for article.tags.iter().map(|tag| html! { <span class="article-tag",>{tag}</span> }) }
+                </div>
+                <div class="article-body",>
+                    {render_markdown::<ArticleReader>(&article.body)}
+                </div>
+                {comments}
+            </div>
+        }
+    }
+}
+
+impl Routable for ArticleReader {
+    fn resolve_props(route: &Route) -> Option<<Self as Component>::Properties> {
+        // /article/<article_uuid>
+        if route.path_segments.get(2).is_some() {
+            return None
+        }
+        if let Some(seg_2) = route.path_segments.get(1) {
+            if let Ok(article_uuid) = ArticleUuid::parse_str(&seg_2) {
+                Some(
+                    Props{article_uuid}
+                )
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    }
+
+    fn will_try_to_route(route: &Route) -> bool {
+        if let Some(seg_1) = route.path_segments.get(0) {
+            seg_1.as_str() == "article" && route.path_segments.get(1).is_some()
+        } else {
+            false
+        }
+    }
+}
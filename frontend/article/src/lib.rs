@@ -0,0 +1,87 @@
+//! Articles are long-form Markdown posts written by users with the Publisher role.
+//! This crate provides the paginated index of published articles,
+//! a reader for individual articles, and an editor for drafting and publishing them.
+
+extern crate common;
+extern crate identifiers;
+#[macro_use]
+extern crate log;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate util;
+extern crate wire;
+#[macro_use]
+extern crate yew;
+#[macro_use]
+extern crate yew_router;
+
+pub use common::datatypes;
+use yew::html::Renderable;
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+mod requests;
+mod article_index;
+mod article_reader;
+mod article_editor;
+
+use article_index::ArticleIndex;
+use article_reader::ArticleReader;
+use article_editor::ArticleEditor;
+
+pub struct ArticleModel;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Props;
+
+
+pub enum Msg {
+}
+
+
+impl Component for ArticleModel {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(_props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+        ArticleModel
+    }
+
+    fn update(&mut self, _msg: Self::Message) -> ShouldRender {
+        false
+    }
+
+    fn change(&mut self, _props: Self::Properties) -> ShouldRender {
+        true
+    }
+}
+
+impl Renderable<ArticleModel> for ArticleModel {
+    fn view(&self) -> Html<ArticleModel> {
+        html! {
+            <div class=("flexbox-vert","full-height", "no-scroll"),>
+                <div class=("vertical-expand", "full-width", "scrollable"),>
+                    <YewRouter: routes=routes![ArticleEditor, ArticleReader, ArticleIndex], />
+                </div>
+            </div>
+        }
+    }
+}
+
+impl Routable for ArticleModel {
+    fn resolve_props(route: &Route) -> Option<<Self as Component>::Properties> {
+        if let Some(seg_1) = route.path_segments.get(0) {
+            if seg_1.as_str() == "article" {
+                Some(Props)
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    }
+    fn will_try_to_route(route: &Route) -> bool {
+       route.path_segments.get(0).is_some()
+    }
+}
@@ -0,0 +1,61 @@
+use common::fetch::Auth;
+use common::fetch::FetchRequest;
+use common::fetch::HttpMethod;
+use common::fetch::to_body;
+use identifiers::article::ArticleUuid;
+use wire::article::*;
+
+#[derive(Serialize, Deserialize)]
+pub enum ArticleRequest {
+    GetPublishedArticles { page_index: usize, page_size: usize },
+    GetOwnedUnpublishedArticles,
+    GetArticle { article_uuid: ArticleUuid },
+    CreateArticle(NewArticleRequest),
+    UpdateArticle(UpdateArticleRequest),
+    Publish { article_uuid: ArticleUuid },
+    Unpublish { article_uuid: ArticleUuid },
+}
+
+impl FetchRequest for ArticleRequest {
+    fn resolve_path(&self) -> String {
+        use self::ArticleRequest::*;
+        match *self {
+            GetPublishedArticles {
+                page_index,
+                page_size,
+            } => format!("article/{}/{}", page_index, page_size),
+            GetOwnedUnpublishedArticles => "article/owned_unpublished".into(),
+            GetArticle { article_uuid } => format!("article/{}", article_uuid),
+            CreateArticle(_) => "article".into(),
+            UpdateArticle(_) => "article".into(),
+            Publish { article_uuid } => format!("article/publish/{}", article_uuid),
+            Unpublish { article_uuid } => format!("article/unpublish/{}", article_uuid),
+        }
+    }
+    fn resolve_auth(&self) -> Auth {
+        use self::ArticleRequest::*;
+        use self::Auth::*;
+        match *self {
+            GetPublishedArticles {..} => NotRequired,
+            GetOwnedUnpublishedArticles => Required,
+            GetArticle {..} => NotRequired, // Authors can see their own unpublished articles if they are logged in.
+            CreateArticle(_) => Required,
+            UpdateArticle(_) => Required,
+            Publish {..} => Required,
+            Unpublish {..} => Required,
+        }
+    }
+    fn resolve_body_and_method(&self) -> HttpMethod {
+        use self::ArticleRequest::*;
+        use self::HttpMethod::*;
+        match self {
+            GetPublishedArticles {..} => Get,
+            GetOwnedUnpublishedArticles => Get,
+            GetArticle {..} => Get,
+            CreateArticle(r) => Post(to_body(r)),
+            UpdateArticle(r) => Put(to_body(r)),
+            Publish {..} => Put(String::new()),
+            Unpublish {..} => Put(String::new()),
+        }
+    }
+}
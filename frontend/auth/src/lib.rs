@@ -13,6 +13,7 @@ extern crate failure;
 //extern crate context;
 extern crate common;
 extern crate wire;
+extern crate identifiers;
 extern crate util;
 //extern crate routes;
 
@@ -25,7 +25,7 @@ pub enum Msg {
     UpdatePassword(String),
     UpdateUserName(String),
     Submit,
-    LoginRequestSuccess(String),
+    LoginRequestSuccess(TokensResponse),
     LoginRequestStarted,
     NoOp,
     LoginRequestError,
@@ -96,10 +96,10 @@ impl Component for Login {
     fn update(&mut self, msg: Msg) -> ShouldRender {
         match msg {
             Msg::Submit => {
-                fn response_mapper(fetch_response: FetchResponse<String>) -> Msg { // TODO figure out what response type.
+                fn response_mapper(fetch_response: FetchResponse<TokensResponse>) -> Msg {
                     match fetch_response {
                         FetchResponse::Started => Msg::LoginRequestStarted,
-                        FetchResponse::Success(jwt_string) => Msg::LoginRequestSuccess(jwt_string),
+                        FetchResponse::Success(tokens) => Msg::LoginRequestSuccess(tokens),
                         FetchResponse::Error(_) => Msg::LoginRequestError
                     }
                 };
@@ -112,7 +112,7 @@ impl Component for Login {
                     password: login_data.password,
                 };
 
-                self.networking.fetch_string(
+                self.networking.fetch(
                     &AuthRequest::Login(login_request),
                     response_mapper,
                     &self.link
@@ -128,13 +128,13 @@ impl Component for Login {
                 self.login_data.as_mut().user_name = u;
                 true
             }
-            Msg::LoginRequestSuccess(jwt_string) => {
+            Msg::LoginRequestSuccess(tokens) => {
 //                context.store_jwt(jwt.clone()); // store/upsert the local JWT.
 //                use common;
 //                common::user::store_jwt(&mut self.storage_service, jwt);
 
 //                context.log(&format!("Logged in. JWT received with payload: {:?}", ::context::user::extract_payload_from_jwt(jwt)));
-                self.login_agent.send(LoginStoreRequest::Login{jwt_string});
+                self.login_agent.send(LoginStoreRequest::Login{jwt_string: tokens.jwt, refresh_token: tokens.refresh_token});
 //                self.router.send(RouterRequest::ChangeRoute(Route::parse("forums/")));
                 self.router.send(RouterRequest::ChangeRoute(route!("forums")));
 
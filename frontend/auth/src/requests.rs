@@ -3,13 +3,25 @@ use common::fetch::Auth;
 use common::fetch::HttpMethod;
 use common::fetch::to_body;
 
+use identifiers::user_session::UserSessionUuid;
 use wire::user::NewUserRequest;
+use wire::user::UpdateEmailRequest;
 use wire::login::LoginRequest;
+use wire::login::ChangePasswordRequest;
+use wire::login::ForgotPasswordRequest;
+use wire::login::ResetPasswordRequest;
 
 #[derive(Serialize, Deserialize)]
 pub enum AuthRequest {
     Login(LoginRequest),
     CreateUser(NewUserRequest),
+    GetSessions,
+    EndSession{session_uuid: UserSessionUuid},
+    EndAllSessions,
+    ChangePassword(ChangePasswordRequest),
+    ForgotPassword(ForgotPasswordRequest),
+    ResetPassword(ResetPasswordRequest),
+    UpdateEmail(UpdateEmailRequest),
 }
 
 impl FetchRequest for AuthRequest {
@@ -18,10 +30,28 @@ impl FetchRequest for AuthRequest {
         match *self {
             Login(_) => "auth/login".into(),
             CreateUser(_) => "user/".into(),
+            GetSessions => "auth/sessions".into(),
+            EndSession{session_uuid} => format!("auth/sessions/{}", session_uuid),
+            EndAllSessions => "auth/sessions".into(),
+            ChangePassword(_) => "auth/password".into(),
+            ForgotPassword(_) => "auth/forgot_password".into(),
+            ResetPassword(_) => "auth/reset_password".into(),
+            UpdateEmail(_) => "user/email".into(),
         }
     }
     fn resolve_auth(&self) -> Auth {
-        Auth::NotRequired
+        use self::AuthRequest::*;
+        match self {
+            Login(_) => Auth::NotRequired,
+            CreateUser(_) => Auth::NotRequired,
+            GetSessions => Auth::Required,
+            EndSession{..} => Auth::Required,
+            EndAllSessions => Auth::Required,
+            ChangePassword(_) => Auth::Required,
+            ForgotPassword(_) => Auth::NotRequired,
+            ResetPassword(_) => Auth::NotRequired,
+            UpdateEmail(_) => Auth::Required,
+        }
     }
     fn resolve_body_and_method(&self) -> HttpMethod {
         use self::AuthRequest::*;
@@ -29,6 +59,13 @@ impl FetchRequest for AuthRequest {
         match self {
             Login(r) => Post(to_body(r)),
             CreateUser(r) => Post(to_body(r)),
+            GetSessions => Get,
+            EndSession{..} => Delete,
+            EndAllSessions => Delete,
+            ChangePassword(r) => Put(to_body(r)),
+            ForgotPassword(r) => Post(to_body(r)),
+            ResetPassword(r) => Post(to_body(r)),
+            UpdateEmail(r) => Put(to_body(r)),
         }
     }
 }
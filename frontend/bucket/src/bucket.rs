@@ -14,8 +14,13 @@ use datatypes::answer::AnswerData;
 
 
 use wire::question::QuestionResponse;
+use wire::bucket::BucketSessionResponse;
+use wire::bucket::TurnMode;
+use wire::user::UserResponse;
 use wire::answer::AnswerResponse;
 use wire::question::NewQuestionRequest;
+use wire::question::NewQuestionResponse;
+use wire::question::SimilarQuestionResponse;
 use wire::answer::NewAnswerRequest;
 
 use util::input::InputValidator;
@@ -23,10 +28,21 @@ use util::link::Link;
 
 use identifiers::question::QuestionUuid;
 use identifiers::bucket::BucketUuid;
+use identifiers::user::UserUuid;
+use common::datatypes::user::UserData;
 
 use requests::BucketRequest;
+use session_events::{SessionEventsService, SessionEventsTask};
 use common::fetch::Networking;
 use common::fetch::FetchResponse;
+use common::fetch::FetchRequest;
+use common::user::get_token_if_valid;
+use common::user::get_now;
+use common::user::user_id;
+use yew::services::storage::{StorageService, Area};
+use yew::services::TimeoutService;
+use yew::services::timeout::TimeoutTask;
+use chrono::NaiveDateTime;
 
 #[derive(Debug, Default, Clone)]
 pub struct QuestionPackage {
@@ -40,9 +56,21 @@ pub struct QuestionList {
     filter: QuestionLocation //show either questions in the bucket or on the floor in the righthand pane.
 }
 
+/// Who is taking their turn in the bucket's session.
+#[derive(Debug, Default, Clone)]
+pub struct Turns {
+    turn_mode: TurnMode,
+    turn_user: Option<UserData>,
+    hot_seat_user: Option<UserData>,
+    turn_ends_at: Option<NaiveDateTime>,
+}
+
 #[derive(Debug, Default, Clone)]
 struct NewQuestion {
-    question_text: InputState
+    question_text: InputState,
+    /// Questions already in the bucket that are worded like this one.
+    /// While there are any, the question is only added if the user confirms it isn't a duplicate.
+    similar_questions: Vec<SimilarQuestionResponse>
 }
 impl NewQuestion {
     fn validator(text: String) -> Result<String, String> {
@@ -59,7 +87,17 @@ pub struct BucketLobby {
     active_question: Loadable<Uploadable<QuestionPackage>>,
     new_question: Uploadable<NewQuestion>,
     prior_questions_and_answers: Loadable<QuestionList>,
+    turns: Turns,
+    /// The other participants, who can be chosen to answer questions in hot seat mode.
+    participants: Loadable<Vec<UserData>>,
+    user_uuid: Option<UserUuid>,
     networking: Networking,
+    timeout_service: TimeoutService,
+    /// Refreshes the session once the current turn runs out of time.
+    turn_timeout_task: Option<TimeoutTask>,
+    session_events: SessionEventsService,
+    /// Keeps the session's event stream open while the lobby is shown.
+    session_events_task: Option<SessionEventsTask>,
     link: ComponentLink<BucketLobby>
 }
 
@@ -81,32 +119,119 @@ impl BucketLobby {
             &self.link
         );
     }
-    fn get_random_question(&mut self, bucket_uuid: BucketUuid) {
+    /// Listens for changes to the bucket's session made by any participant.
+    /// The current state of the session is sent as soon as the connection opens.
+    fn subscribe_to_session(&mut self, bucket_uuid: BucketUuid) {
+        self.session_events_task = get_token_if_valid(&mut StorageService::new(Area::Local))
+            .map(|token| {
+                let url = BucketRequest::GetSessionEvents{bucket_uuid, token}.resolve_url();
+                let callback = self.link.send_back(Msg::HandleSessionEvent);
+                self.session_events.connect(&url, callback)
+            });
+    }
+
+    fn get_session(&mut self, bucket_uuid: BucketUuid) {
         self.networking.fetch(
-            &BucketRequest::GetRandomQuestion{bucket_uuid},
-            |r: FetchResponse<QuestionResponse>| {
-                // Convert the question response to question data,
-                // move it to a quesiton package,
-                // Wrap it in an uploadable wrapper
-                let r = r
-                    .map(QuestionData::from)
-                    .map(|question_data| {
-                        QuestionPackage {
-                            question_data,
-                            answer: InputState::default(),
-                        }
-                    })
-                    .map(Uploadable::NotUploaded);
-                Msg::HandleDrawRandomQuestionResponse(r)
-            },
+            &BucketRequest::GetSession{bucket_uuid},
+            Msg::HandleSessionResponse,
+            &self.link
+        );
+    }
+
+    fn get_participants(&mut self, bucket_uuid: BucketUuid) {
+        self.networking.fetch(
+            &BucketRequest::GetUsersInBucket{bucket_uuid},
+            |r: FetchResponse<Vec<UserResponse>>| Msg::HandleParticipantsResponse(r.map(::wire::convert_vector)),
+            &self.link
+        );
+    }
+
+    fn choose_answerer(&mut self, bucket_uuid: BucketUuid, user_uuid: UserUuid) {
+        self.networking.fetch(
+            &BucketRequest::ChooseAnswerer{bucket_uuid, user_uuid},
+            Msg::HandleSessionResponse,
+            &self.link
+        );
+    }
+
+    fn draw_question(&mut self, bucket_uuid: BucketUuid) {
+        self.networking.fetch(
+            &BucketRequest::DrawQuestion{bucket_uuid},
+            Msg::HandleSessionResponse,
             &self.link
         );
+    }
+
+    fn discard_question(&mut self, bucket_uuid: BucketUuid) {
+        self.networking.fetch(
+            &BucketRequest::DiscardQuestion{bucket_uuid},
+            Msg::HandleSessionResponse,
+            &self.link
+        );
+    }
 
+    /// Shows the session's current question, keeping any answer already typed if it hasn't changed.
+    fn apply_session(&mut self, session: BucketSessionResponse) {
+        self.turns = Turns {
+            turn_mode: session.turn_mode,
+            turn_user: session.turn_user.map(UserData::from),
+            hot_seat_user: session.hot_seat_user.map(UserData::from),
+            turn_ends_at: session.turn_ends_at,
+        };
+        // The server skips turns that have run out of time the next time the session is requested.
+        self.turn_timeout_task = session.turn_ends_at
+            .and_then(|turn_ends_at| (turn_ends_at - get_now()).to_std().ok())
+            .map(|remaining| {
+                let callback = self.link.send_back(|_| Msg::RefreshSession);
+                self.timeout_service.spawn(remaining + ::std::time::Duration::from_secs(1), callback)
+            });
+
+        let previous_question_uuid: Option<QuestionUuid> = self.active_question
+            .as_option()
+            .map(|question_package| question_package.as_ref().question_data.uuid);
+
+        self.active_question = match session.current_question.map(QuestionData::from) {
+            Some(question_data) => {
+                if previous_question_uuid == Some(question_data.uuid) {
+                    let mut active_question = ::std::mem::replace(&mut self.active_question, Loadable::Unloaded);
+                    if let Some(question_package) = active_question.as_mut_option() {
+                        question_package.as_mut().question_data = question_data;
+                    }
+                    active_question
+                } else {
+                    Loadable::Loaded(Uploadable::NotUploaded(QuestionPackage {
+                        question_data,
+                        answer: InputState::default(),
+                    }))
+                }
+            }
+            None => Loadable::Unloaded
+        };
+
+        // The old question was answered or discarded, so the list of prior questions is out of date.
+        let current_question_uuid: Option<QuestionUuid> = self.active_question
+            .as_option()
+            .map(|question_package| question_package.as_ref().question_data.uuid);
+        if previous_question_uuid.is_some() && previous_question_uuid != current_question_uuid {
+            let bucket_uuid = self.bucket_data.uuid;
+            self.get_prior_questions_and_answers(bucket_uuid);
+        }
+    }
+    fn submit_new_question(&mut self, ignore_similar: bool) {
+        let question_text = self.new_question.as_ref().question_text.inner_text();
+        let bucket_uuid = self.bucket_data.uuid;
+        let new_question_request = NewQuestionRequest {
+            bucket_uuid,
+            question_text,
+            ignore_similar
+        };
+        self.post_new_question(new_question_request);
     }
+
     fn post_new_question(&mut self, new_question_request: NewQuestionRequest, /* new_question: &mut Uploadable<NewQuestion>,*/) {
         self.networking.fetch(
             &BucketRequest::CreateQuestion(new_question_request),
-            |r: FetchResponse<QuestionResponse>| Msg::HandleSubmitNewQuestionResponse(r.map(|_|())),
+            Msg::HandleSubmitNewQuestionResponse,
             &self.link
         );
 
@@ -130,14 +255,6 @@ impl BucketLobby {
 
 
     }
-
-    fn delete_question(&mut self, question_uuid: QuestionUuid, ) {
-        self.networking.fetch(
-            &BucketRequest::DeleteQuestion{question_uuid},
-            Msg::HandleDiscardQuestionResponse,
-            &self.link
-         );
-    }
 }
 
 #[derive(Default, PartialEq, Debug, Clone)]
@@ -147,19 +264,23 @@ pub struct Props {
 
 pub enum Msg {
     DrawRandomQuestion,
-    HandleDrawRandomQuestionResponse(FetchResponse<Uploadable<QuestionPackage>>),
+    HandleSessionResponse(FetchResponse<BucketSessionResponse>),
+    HandleSessionEvent(String),
     UpdateAnswer(InputState),
     SubmitAnswer,
     HandleSubmitAnswerResponse(FetchResponse<()>),
     UpdateNewQuestion(InputState),
     SubmitNewQuestion,
-    HandleSubmitNewQuestionResponse(FetchResponse<()>),
+    SubmitNewQuestionIgnoringSimilar,
+    HandleSubmitNewQuestionResponse(FetchResponse<NewQuestionResponse>),
     ResetCreateQuestionText,
     HandlePriorQuestionResponse(FetchResponse<Vec<QuestionData>>),
     PutOldQuestionBackInBucket{question_uuid: QuestionUuid},
     HandlePutOldQuestionBackInBucketResponse(FetchResponse<QuestionUuid>),
     DiscardQuestion,
-    HandleDiscardQuestionResponse(FetchResponse<QuestionUuid>),
+    ChooseAnswerer(UserUuid),
+    HandleParticipantsResponse(FetchResponse<Vec<UserData>>),
+    RefreshSession,
     SetListFilter(QuestionLocation),
     NoOp
 }
@@ -180,12 +301,21 @@ impl Component for BucketLobby {
             active_question: Loadable::default(),
             new_question: Uploadable::default(),
             prior_questions_and_answers: Loadable::default(),
+            turns: Turns::default(),
+            participants: Loadable::default(),
+            user_uuid: user_id(&mut StorageService::new(Area::Local)).ok(),
             networking: Networking::new(&link),
+            timeout_service: TimeoutService::new(),
+            turn_timeout_task: None,
+            session_events: SessionEventsService::new(),
+            session_events_task: None,
             link
         };
 
         let bucket_uuid = bucket.bucket_data.uuid;
         bucket.get_prior_questions_and_answers(bucket_uuid);
+        bucket.get_participants(bucket_uuid);
+        bucket.subscribe_to_session(bucket_uuid);
 
         bucket
     }
@@ -195,10 +325,24 @@ impl Component for BucketLobby {
         match msg {
             DrawRandomQuestion => {
                 let bucket_uuid = self.bucket_data.uuid;
-                self.get_random_question(bucket_uuid)
+                self.draw_question(bucket_uuid)
             },
-            HandleDrawRandomQuestionResponse(response) => {
-                self.active_question = Loadable::from_fetch_response(response);
+            HandleSessionResponse(response) => {
+                match response {
+                    FetchResponse::Success(session) => self.apply_session(session),
+                    FetchResponse::Error(_) => self.active_question = Loadable::Failed(Some(String::from("Could not draw a question"))),
+                    FetchResponse::Started => {
+                        if self.active_question.as_option().is_none() {
+                            self.active_question = Loadable::Loading
+                        }
+                    }
+                }
+            }
+            HandleSessionEvent(data) => {
+                match ::serde_json::from_str::<BucketSessionResponse>(&data) {
+                    Ok(session) => self.apply_session(session),
+                    Err(e) => warn!("Could not parse session event: {}", e)
+                }
             }
             UpdateAnswer(input) => {
                 if let Loadable::Loaded(ref mut question_package) = self.active_question {
@@ -247,22 +391,24 @@ impl Component for BucketLobby {
                     },
                 }
             }
-            UpdateNewQuestion(input) => self.new_question.as_mut().question_text = input,
-            SubmitNewQuestion => {
-                let question_text = self.new_question.as_ref().question_text.inner_text();
-                let bucket_uuid = self.bucket_data.uuid;
-                let new_question_request = NewQuestionRequest {
-                    bucket_uuid,
-                    question_text
-                };
-                self.post_new_question(new_question_request);
-            },
+            UpdateNewQuestion(input) => {
+                let new_question = self.new_question.as_mut();
+                new_question.question_text = input;
+                new_question.similar_questions = vec![];
+            }
+            SubmitNewQuestion => self.submit_new_question(false),
+            SubmitNewQuestionIgnoringSimilar => self.submit_new_question(true),
             HandleSubmitNewQuestionResponse(response) => {
                 use self::FetchResponse::*;
                 match response {
-                    Success(_) => {
+                    Success(NewQuestionResponse {question: Some(_), ..}) => {
                         self.update(ResetCreateQuestionText);
                     },
+                    Success(NewQuestionResponse {question: None, similar_questions}) => {
+                        let mut new_question = self.new_question.cloned_inner();
+                        new_question.similar_questions = similar_questions;
+                        self.new_question = Uploadable::NotUploaded(new_question);
+                    },
                     Error(_) => self.new_question.set_failed("failed to submit question"),
                     Started => self.new_question.set_uploading(),
                 };
@@ -322,21 +468,17 @@ impl Component for BucketLobby {
 //                }
 //            },
             DiscardQuestion => {
-                if let Some(question_uuid) = self.active_question.as_option().map(|x|x.as_ref().question_data.uuid) {
-                    self.delete_question(question_uuid)
-                }
+                let bucket_uuid = self.bucket_data.uuid;
+                self.discard_question(bucket_uuid)
             }
-            HandleDiscardQuestionResponse(response) => {
-                match response {
-                    FetchResponse::Success(question_uuid) => {
-                        if let Loadable::Loaded(ref mut old_list) = self.prior_questions_and_answers {
-                            old_list.list.retain(|x| x.uuid != question_uuid) // Remove the question from the local list of questions.
-                        }
-                    }
-                    FetchResponse::Error(_) => warn!("Failed to discard question"),
-                    FetchResponse::Started => {}
-                }
-
+            ChooseAnswerer(user_uuid) => {
+                let bucket_uuid = self.bucket_data.uuid;
+                self.choose_answerer(bucket_uuid, user_uuid)
+            }
+            HandleParticipantsResponse(response) => self.participants = Loadable::from_fetch_response(response),
+            RefreshSession => {
+                let bucket_uuid = self.bucket_data.uuid;
+                self.get_session(bucket_uuid)
             }
             SetListFilter(location) => {
                 if let Loadable::Loaded(ref mut old_list) = self.prior_questions_and_answers {
@@ -354,9 +496,14 @@ impl Component for BucketLobby {
         self.active_question = Loadable::default();
         self.new_question = Uploadable::default();
         self.prior_questions_and_answers = Loadable::default();
+        self.turns = Turns::default();
+        self.participants = Loadable::default();
+        self.turn_timeout_task = None;
 
         let bucket_uuid = self.bucket_data.uuid;
         self.get_prior_questions_and_answers( bucket_uuid);
+        self.get_participants(bucket_uuid);
+        self.subscribe_to_session(bucket_uuid);
         true
     }
 }
@@ -396,6 +543,8 @@ impl Renderable<BucketLobby> for BucketLobby {
                 <div class=("flexbox-horiz", "full-height", "no-scroll"),> // (Question container and answers container) container
                     <div class=("flexbox-vert", "questions-container", "scrollable", "flexbox-test"),> // Answer question and new question container
 
+                        {self.turns_view()}
+
                         <div class=("full-height", "full-width", "flexbox-center"),>
                             <div class=("question-card", "active-question-card"),> // Answer question card
                                 {self.active_question.restricted_custom_view(
@@ -427,6 +576,81 @@ impl Renderable<BucketLobby> for BucketLobby {
     }
 }
 
+impl BucketLobby {
+    /// Shows whose turn it is, and lets the drawer choose who answers in hot seat mode.
+    fn turns_view(&self) -> Html<BucketLobby> {
+        fn name(user: &Option<UserData>, user_uuid: Option<UserUuid>) -> String {
+            match user {
+                Some(ref user) if Some(user.uuid) == user_uuid => "You".to_string(),
+                Some(ref user) => user.display_name.clone(),
+                None => "Nobody".to_string(),
+            }
+        }
+
+        let turns = &self.turns;
+        if turns.turn_mode == TurnMode::FreeForAll {
+            return ::util::wrappers::empty_vdom_node()
+        }
+
+        let ends_at = match turns.turn_ends_at {
+            Some(turn_ends_at) => format!(" until {}", turn_ends_at.format("%H:%M:%S")),
+            None => String::new()
+        };
+        let is_users_turn = turns.turn_user.as_ref().map(|user| user.uuid) == self.user_uuid && self.user_uuid.is_some();
+        let choosing_answerer = turns.turn_mode == TurnMode::HotSeat
+            && turns.hot_seat_user.is_none()
+            && is_users_turn
+            && self.active_question.as_option().is_some();
+
+        let hot_seat = if turns.turn_mode == TurnMode::HotSeat && turns.hot_seat_user.is_some() {
+            html! {
+                <div>
+                    {format!("In the hot seat: {}", name(&turns.hot_seat_user, self.user_uuid))}
+                </div>
+            }
+        } else {
+            ::util::wrappers::empty_vdom_node()
+        };
+
+        let choose_answerer = if choosing_answerer {
+            fn participant_view(user: &UserData) -> Html<BucketLobby> {
+                let user_uuid = user.uuid;
+                html! {
+                    <Button: title=&user.display_name, onclick=move |_| Msg::ChooseAnswerer(user_uuid), />
+                }
+            }
+            html! {
+                <div class=("flexbox-horiz"),>
+                    {"Choose who answers: "}
+                    {
+                        if let Some(participants) = self.participants.as_option() {
+                            html! {
+                                <>
+                                    {for participants.iter().map(participant_view)}
+                                </>
+                            }
+                        } else {
+                            ::util::wrappers::empty_vdom_node()
+                        }
+                    }
+                </div>
+            }
+        } else {
+            ::util::wrappers::empty_vdom_node()
+        };
+
+        html! {
+            <div class=("flexbox-vert", "padding-default"),>
+                <div class="bolded",>
+                    {format!("Turn: {}{}", name(&turns.turn_user, self.user_uuid), ends_at)}
+                </div>
+                {hot_seat}
+                {choose_answerer}
+            </div>
+        }
+    }
+}
+
 impl Renderable<BucketLobby> for QuestionPackage {
     fn view(&self) -> Html<BucketLobby> {
         html! {
@@ -483,6 +707,7 @@ impl Renderable<BucketLobby> for NewQuestion {
                         validator=Box::new(NewQuestion::validator as InputValidator),
                     />
                 </div>
+                {self.similar_questions_view()}
                 <div class=("flexbox-horiz-reverse"),>
                     <Button: title="Add Question To Bucket", onclick=|_| Msg::SubmitNewQuestion, />
                 </div>
@@ -491,6 +716,25 @@ impl Renderable<BucketLobby> for NewQuestion {
     }
 }
 
+impl NewQuestion {
+    fn similar_questions_view(&self) -> Html<BucketLobby> {
+        if self.similar_questions.is_empty() {
+            return ::util::wrappers::empty_vdom_node()
+        }
+        html! {
+            <div class=("padding-left", "padding-right"),>
+                {"The bucket already has questions like this one:"}
+                <ul>
+                    {for self.similar_questions.iter().map(|similar| html! {
+                        <li>{&similar.question_text}</li>
+                    })}
+                </ul>
+                <Button: title="Add It Anyway", onclick=|_| Msg::SubmitNewQuestionIgnoringSimilar, />
+            </div>
+        }
+    }
+}
+
 impl Renderable<BucketLobby> for AnswerData {
     fn view(&self) -> Html<BucketLobby> {
         html! {
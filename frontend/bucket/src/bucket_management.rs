@@ -1,15 +1,17 @@
 use common::datatypes::bucket::BucketUsersData;
-//use Context;
 use util::button::Button;
 use yew::prelude::*;
 
 use wire::bucket::BucketUsersResponse;
+use wire::bucket::BucketResponse;
+use wire::bucket::BucketSessionResponse;
+use wire::bucket::DrawMode;
+use wire::bucket::TurnMode;
+use wire::bucket::TurnSettingsRequest;
 
 use util::loadable::Loadable;
 use util::uploadable::Uploadable;
 
-//use context::networking::RequestWrapper;
-
 use identifiers::bucket::BucketUuid;
 use identifiers::user::UserUuid;
 use common::datatypes::user::UserData;
@@ -18,12 +20,20 @@ use requests::BucketRequest;
 use common::fetch::Networking;
 use common::fetch::FetchResponse;
 
+/// How long an owner opens a bucket to the public for.
+const PUBLIC_MINUTES: i64 = 60;
+/// How long each turn lasts when an owner chooses timed turns.
+const TIMED_TURN_SECONDS: i32 = 60;
+
 /// A component for approving and rejecting requests to join buckets.
 pub struct BucketManagement {
     bucket_users:  Loadable<Vec<BucketUsersData>>,
     remove_user_action: Uploadable<()>,
     approve_user_action: Uploadable<()>,
     set_public_or_private_action: Uploadable<()>,
+    set_template_action: Uploadable<()>,
+    set_turn_settings_action: Uploadable<()>,
+    set_draw_mode_action: Uploadable<()>,
     networking: Networking,
     link: ComponentLink<BucketManagement>,
 }
@@ -43,6 +53,12 @@ pub enum Msg {
     HandleDenyUserAccessResponse(FetchResponse<()>),
     SetPublicOrPrivate{bucket_uuid: BucketUuid, publicity: Publicity },
     HandleSetPublicityResponse(FetchResponse<()>),
+    SetTemplate{bucket_uuid: BucketUuid, is_template: bool},
+    HandleSetTemplateResponse(FetchResponse<()>),
+    SetTurnSettings{bucket_uuid: BucketUuid, settings: TurnSettingsRequest},
+    HandleSetTurnSettingsResponse(FetchResponse<()>),
+    SetDrawMode{bucket_uuid: BucketUuid, draw_mode: DrawMode},
+    HandleSetDrawModeResponse(FetchResponse<()>),
     NoOp
 }
 
@@ -65,33 +81,56 @@ impl BucketManagement {
     }
 
     fn grant_access_to_user_for_bucket(&mut self, bucket_uuid: BucketUuid, user_uuid: UserUuid) {
-        self.networking.fetch(
+        self.networking.fetch_string(
             &BucketRequest::ApproveUserForBucket{bucket_uuid, user_uuid},
-            Msg::HandleGrantUserAccessResponse,
+            |r: FetchResponse<String>| Msg::HandleGrantUserAccessResponse(r.map(|_| ())),
             &self.link
         );
     }
     fn remove_user_from_bucket(&mut self, bucket_uuid: BucketUuid, user_uuid: UserUuid) {
-        self.networking.fetch(
+        self.networking.fetch_string(
             &BucketRequest::RemoveUserFromBucket{bucket_uuid, user_uuid},
-            Msg::HandleDenyUserAccessResponse,
+            |r: FetchResponse<String>| Msg::HandleDenyUserAccessResponse(r.map(|_| ())),
             &self.link
         );
     }
 
     fn set_public_or_private(&mut self, bucket_uuid: BucketUuid, publicity: Publicity) {
-        let is_public: bool = match publicity {
-            Publicity::Public => true,
-            Publicity::Private => false
+        let public_for_minutes: Option<i64> = match publicity {
+            Publicity::Public => Some(PUBLIC_MINUTES),
+            Publicity::Private => None
         };
         self.networking.fetch(
-            &BucketRequest::SetBucketPublicStatus{bucket_uuid, is_public},
-            Msg::HandleSetPublicityResponse,
+            &BucketRequest::SetBucketPublicStatus{bucket_uuid, public_for_minutes},
+            |r: FetchResponse<BucketResponse>| Msg::HandleSetPublicityResponse(r.map(|_| ())),
+            &self.link
+        );
+    }
+
+    fn set_template(&mut self, bucket_uuid: BucketUuid, is_template: bool) {
+        self.networking.fetch(
+            &BucketRequest::SetBucketTemplateStatus{bucket_uuid, is_template},
+            |r: FetchResponse<BucketResponse>| Msg::HandleSetTemplateResponse(r.map(|_| ())),
+            &self.link
+        );
+    }
+
+    fn set_turn_settings(&mut self, bucket_uuid: BucketUuid, settings: TurnSettingsRequest) {
+        self.networking.fetch(
+            &BucketRequest::SetTurnSettings{bucket_uuid, settings},
+            |r: FetchResponse<BucketSessionResponse>| Msg::HandleSetTurnSettingsResponse(r.map(|_| ())),
             &self.link
         );
     }
-}
 
+    fn set_draw_mode(&mut self, bucket_uuid: BucketUuid, draw_mode: DrawMode) {
+        self.networking.fetch(
+            &BucketRequest::SetDrawMode{bucket_uuid, draw_mode},
+            |r: FetchResponse<BucketSessionResponse>| Msg::HandleSetDrawModeResponse(r.map(|_| ())),
+            &self.link
+        );
+    }
+}
 
 impl Component for BucketManagement {
     type Message = Msg;
@@ -104,6 +143,9 @@ impl Component for BucketManagement {
             remove_user_action: Uploadable::default(),
             approve_user_action: Uploadable::default(),
             set_public_or_private_action: Uploadable::default(),
+            set_template_action: Uploadable::default(),
+            set_turn_settings_action: Uploadable::default(),
+            set_draw_mode_action: Uploadable::default(),
             networking: Networking::new(&link),
             link,
         };
@@ -149,6 +191,27 @@ impl Component for BucketManagement {
                    self.update(GetBucketUsersData);
                 }
             }
+            SetTemplate {bucket_uuid, is_template} => {
+                self.set_template(bucket_uuid, is_template)
+            }
+            HandleSetTemplateResponse(response) => {
+                self.set_template_action.handle_fetch_response(response.clone());
+                if let FetchResponse::Success(_) = response {
+                   self.update(GetBucketUsersData);
+                }
+            }
+            SetTurnSettings {bucket_uuid, settings} => {
+                self.set_turn_settings(bucket_uuid, settings)
+            }
+            HandleSetTurnSettingsResponse(response) => {
+                self.set_turn_settings_action.handle_fetch_response(response);
+            }
+            SetDrawMode {bucket_uuid, draw_mode} => {
+                self.set_draw_mode(bucket_uuid, draw_mode)
+            }
+            HandleSetDrawModeResponse(response) => {
+                self.set_draw_mode_action.handle_fetch_response(response);
+            }
             NoOp => return false
         }
         true
@@ -178,13 +241,26 @@ impl BucketManagement {
 
         fn bucket_view(bucket_user_data: &BucketUsersData) -> Html<BucketManagement> {
             let bucket_uuid = bucket_user_data.bucket.uuid;
-            let button = if bucket_user_data.bucket.is_public {
+            let publicity = match bucket_user_data.bucket.public_until {
+                Some(public_until) if bucket_user_data.bucket.is_public => html! {
+                    <>
+                        <span>{format!("Public until {}", public_until.format("%H:%M %b %e"))}</span>
+                        <Button: title="Close", onclick= move |_| Msg::SetPublicOrPrivate{bucket_uuid, publicity: Publicity::Private}, />
+                    </>
+                },
+                _ => html! {
+                    <Button: title="Open for an hour", onclick= move |_| Msg::SetPublicOrPrivate{bucket_uuid, publicity: Publicity::Public}, />
+                }
+            };
+
+            let is_template = bucket_user_data.bucket.is_template;
+            let template = if is_template {
                 html! {
-                    <Button: title="Lock", onclick= move |_| Msg::SetPublicOrPrivate{bucket_uuid, publicity: Publicity::Private}, />
+                    <Button: title="Withdraw template", onclick= move |_| Msg::SetTemplate{bucket_uuid, is_template: false}, />
                 }
             } else {
                 html! {
-                    <Button: title="Unlock", onclick= move |_| Msg::SetPublicOrPrivate{bucket_uuid, publicity: Publicity::Public}, />
+                    <Button: title="Publish as template", onclick= move |_| Msg::SetTemplate{bucket_uuid, is_template: true}, />
                 }
             };
 
@@ -194,8 +270,11 @@ impl BucketManagement {
                         <div class=("flexbox-expand"),>
                             {&bucket_user_data.bucket.bucket_name}
                         </div>
-                        {button}
+                        {publicity}
+                        {template}
                     </div>
+                    {BucketManagement::turn_settings_view(bucket_uuid)}
+                    {BucketManagement::draw_mode_view(bucket_uuid)}
                     {BucketManagement::users_view(&bucket_user_data.users, bucket_user_data.bucket.uuid)}
                 </div>
             }
@@ -207,6 +286,39 @@ impl BucketManagement {
         }
     }
 
+    fn turn_settings_view(bucket_uuid: BucketUuid) -> Html<BucketManagement> {
+        fn settings(turn_mode: TurnMode, turn_seconds: Option<i32>) -> TurnSettingsRequest {
+            TurnSettingsRequest {
+                turn_mode,
+                turn_seconds
+            }
+        }
+
+        html! {
+            <div class=("flexbox-horiz", "full-width"),>
+                <div class=("flexbox-expand"),>
+                    {"Turns"}
+                </div>
+                <Button: title="Free for all", onclick= move |_| Msg::SetTurnSettings{bucket_uuid, settings: settings(TurnMode::FreeForAll, None)}, />
+                <Button: title="Round robin", onclick= move |_| Msg::SetTurnSettings{bucket_uuid, settings: settings(TurnMode::RoundRobin, None)}, />
+                <Button: title="Timed round robin", onclick= move |_| Msg::SetTurnSettings{bucket_uuid, settings: settings(TurnMode::RoundRobin, Some(TIMED_TURN_SECONDS))}, />
+                <Button: title="Hot seat", onclick= move |_| Msg::SetTurnSettings{bucket_uuid, settings: settings(TurnMode::HotSeat, None)}, />
+            </div>
+        }
+    }
+
+    fn draw_mode_view(bucket_uuid: BucketUuid) -> Html<BucketManagement> {
+        html! {
+            <div class=("flexbox-horiz", "full-width"),>
+                <div class=("flexbox-expand"),>
+                    {"Draws"}
+                </div>
+                <Button: title="Fair draws", onclick= move |_| Msg::SetDrawMode{bucket_uuid, draw_mode: DrawMode::Fair}, />
+                <Button: title="Shuffle deck", onclick= move |_| Msg::SetDrawMode{bucket_uuid, draw_mode: DrawMode::ShuffleDeck}, />
+            </div>
+        }
+    }
+
     fn users_view(users: &[UserData], bucket_uuid: BucketUuid) -> Html<BucketManagement> {
 
         fn user_view(user: &UserData, bucket_uuid: BucketUuid) -> Html<BucketManagement> {
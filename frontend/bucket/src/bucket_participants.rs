@@ -67,9 +67,9 @@ impl BucketParticipants {
     }
 
     fn remove_user_from_bucket(&mut self, bucket_uuid: BucketUuid, user_uuid: UserUuid) {
-        self.networking.fetch(
+        self.networking.fetch_string(
             &BucketRequest::RemoveUserFromBucket{bucket_uuid, user_uuid},
-            Msg::HandleRemoveUserResponse,
+            |r: FetchResponse<String>| Msg::HandleRemoveUserResponse(r.map(|_| ())),
             &self.link
         );
     }
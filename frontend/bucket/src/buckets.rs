@@ -119,7 +119,9 @@ use util::input::InputState;
 
 #[derive(Default)]
 pub struct BucketFinder {
-    pub bucket_name: InputState
+    pub bucket_name: InputState,
+    /// Buckets that are currently open to the public, which the user can join without being approved.
+    pub public_buckets: Loadable<Vec<BucketData>>,
 }
 impl Renderable<BucketModel> for BucketFinder {
     fn view(&self) -> Html<BucketModel> {
@@ -132,7 +134,37 @@ impl Renderable<BucketModel> for BucketFinder {
                     on_enter=|_| Msg::SearchForBucket,
                 />
                 <Button: title="Search", onclick= |_| Msg::SearchForBucket, />
+                <div class=("full-width","light-gray"),>
+                    {"Public Buckets"}
+                </div>
+                {self.public_buckets.default_view(public_buckets_view)}
             </div>
         }
     }
+}
+
+fn public_buckets_view(buckets: &Vec<BucketData>) -> Html<BucketModel> {
+    fn public_bucket_view(bucket: &BucketData) -> Html<BucketModel> {
+        let bucket_uuid = bucket.uuid;
+        let open_until = bucket.public_until
+            .map(|until| format!("Open until {}", until.format("%H:%M %b %e")))
+            .unwrap_or_default();
+        html! {
+            <div class="public-bucket-card", onclick=move |_| Msg::NavigateToBucket{bucket_uuid}, >
+                <div class=("flexbox-vert", "full-height"),>
+                    <div class="flexbox-expand",>
+                        {&bucket.bucket_name}
+                    </div>
+                    <div>
+                        {open_until}
+                    </div>
+                </div>
+            </div>
+        }
+    }
+    html! {
+        <div class=("flexbox-horiz-wrap"),>
+            {for buckets.iter().map(public_bucket_view)}
+        </div>
+    }
 }
\ No newline at end of file
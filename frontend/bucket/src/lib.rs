@@ -7,9 +7,13 @@ extern crate wire;
 extern crate identifiers;
 extern crate util;
 extern crate common;
+extern crate chrono;
 #[macro_use]
 extern crate serde_derive;
 extern crate serde;
+extern crate serde_json;
+#[macro_use]
+extern crate stdweb;
 
 #[macro_use]
 extern crate log;
@@ -17,19 +21,23 @@ extern crate log;
 pub use common::datatypes;
 
 use yew::prelude::*;
+use yew::services::storage::{StorageService, Area};
 use yew_router::prelude::*;
 use yew_router::router_agent::RouterSenderBase;
 
 mod bucket;
 mod buckets;
 mod new_bucket;
-//mod bucket_participants;
-//mod bucket_management;
+mod bucket_participants;
+mod bucket_management;
 mod requests;
+mod session_events;
 
 
 use util::button::Button;
 use bucket::BucketLobby;
+use bucket_management::BucketManagement;
+use bucket_participants::BucketParticipants;
 use util::loadable::Loadable;
 use wire::bucket::BucketResponse;
 use util::input::InputState;
@@ -116,6 +124,8 @@ impl Routable for BucketModel {
 pub struct BucketModel {
     bucket_page: BucketPage,
     drop_down_state: DropDownPaneVariant,
+    /// The state of the user's request to join the bucket they are viewing.
+    join_request: Loadable<bool>,
     networking: Networking,
     link: ComponentLink<BucketModel>,
     router: RouterSenderBase<()>,
@@ -136,14 +146,14 @@ pub enum Msg {
     NavigateToCreateBucket,
     SearchForBucket,
     UpdateSearchedBucketName(InputState),
-//    HandleGetPublicBucketsResponse(FetchResponse<Vec<BucketResponse>>),
+    HandleGetPublicBucketsResponse(FetchResponse<Vec<BucketData>>),
 //    HandleGetApprovedBucketsResponse(FetchResponse<Vec<BucketResponse>>),
     HandleGetBucketResponse(FetchResponse<BucketData>),
-//    HandleJoinBucketResponse(FetchResponse<()>),
+    HandleJoinBucketResponse(FetchResponse<bool>),
     CreateBucket,
     UpdateBucketName(InputState),
-//    ChangeDropDownState(DropDownPaneVariant),
-//    RequestToJoinBucket { bucket_uuid: BucketUuid },
+    ChangeDropDownState(DropDownPaneVariant),
+    RequestToJoinBucket { bucket_uuid: BucketUuid },
     NoOp, // TODO remove me
 }
 
@@ -154,14 +164,17 @@ impl Default for Msg {
 }
 
 impl BucketModel {
-    /// Gets the list of buckets the user can request to join.
-//    fn get_public_buckets(networking: &mut Networking, link: &ComponentLink<Self>) {
-//        networking.fetch(
-//            &BucketRequest::GetPublicBuckets,
-//            Msg::HandleGetPublicBucketsResponse,
-//            link,
-//        );
-//    }
+    /// Gets the list of buckets the user can join without approval.
+    fn get_public_buckets(networking: &mut Networking, link: &ComponentLink<Self>) {
+        if !::common::user::is_logged_in(&mut StorageService::new(Area::Local)) {
+            return
+        }
+        networking.fetch(
+            &BucketRequest::GetPublicBuckets,
+            |r: FetchResponse<Vec<BucketResponse>>| Msg::HandleGetPublicBucketsResponse(r.map(::wire::convert_vector)),
+            link,
+        );
+    }
 
     /// Gets the list of buckets the user can join.
 //    fn get_approved_buckets(networking: &mut Networking, link: &ComponentLink<Self>) {
@@ -196,13 +209,13 @@ impl BucketModel {
             }
         }
     }
-//    fn request_to_join_bucket(&mut self, bucket_uuid: BucketUuid) {
-//        self.networking.fetch(
-//            &BucketRequest::CreateJoinBucketRequest { bucket_uuid },
-//            Msg::HandleJoinBucketResponse,
-//            &self.link,
-//        );
-//    }
+    fn request_to_join_bucket(&mut self, bucket_uuid: BucketUuid) {
+        self.networking.fetch(
+            &BucketRequest::CreateJoinBucketRequest { bucket_uuid },
+            Msg::HandleJoinBucketResponse,
+            &self.link,
+        );
+    }
 }
 
 impl Component for BucketModel {
@@ -214,7 +227,7 @@ impl Component for BucketModel {
 
         let bucket_page: BucketPage = match props {
             BucketRoute::BucketList => {
-//                Self::get_public_buckets(&mut networking, &link);
+                Self::get_public_buckets(&mut networking, &link);
 //                Self::get_approved_buckets(&mut networking, &link);
 //                BucketPage::BucketList(BucketLists::default())
                 BucketPage::BucketFinder(BucketFinder::default())
@@ -231,6 +244,7 @@ impl Component for BucketModel {
         BucketModel {
             bucket_page,
             drop_down_state: DropDownPaneVariant::Closed,
+            join_request: Loadable::default(),
             networking,
             router: RouterSenderBase::<()>::new(router_cb),
             link,
@@ -267,24 +281,6 @@ impl Component for BucketModel {
             SearchForBucket => {
                 unimplemented!()
             }
-//            HandleGetPublicBucketsResponse(buckets_response) => {
-//
-//                let public_buckets_response: FetchResponse<Vec<PublicBucket>> = buckets_response.map(
-//                    |x: Vec<BucketResponse>| {
-//                        x.into_iter()
-//                            .map(BucketData::from)
-//                            .map(PublicBucket)
-//                            .collect()
-//                    },
-//                );
-//                if let BucketPage::BucketList(ref mut bucket_list) = self.bucket_page {
-//                    bucket_list.public_buckets = Loadable::from_fetch_response(public_buckets_response);
-//                } else {
-//                    let mut bucket_lists = BucketLists::default();
-//                    bucket_lists.public_buckets = Loadable::from_fetch_response(public_buckets_response);
-//                    self.bucket_page = BucketPage::BucketList(bucket_lists)
-//                }
-//            }
 //            HandleGetApprovedBucketsResponse(buckets_response) => {
 //                let approved_buckets_response: FetchResponse<Vec<ApprovedBucket>> = buckets_response.map(
 //                    |x: Vec<BucketResponse>| {
@@ -326,23 +322,30 @@ impl Component for BucketModel {
                     return false;
                 }
             }
-//            ChangeDropDownState(drop_down_state) => {
-//                if self.drop_down_state == drop_down_state {
-//                    self.drop_down_state = DropDownPaneVariant::Closed // close the drop down pane if the current one is already selected
-//                } else {
-//                    self.drop_down_state = drop_down_state
-//                }
-//            }
-//            RequestToJoinBucket { bucket_uuid } => {
-//                self.request_to_join_bucket(bucket_uuid)
-//                //                self.networking.fetch(BucketRequest::CreateJoinBucketRequest{bucket_uuid}, |r: FetchResponse<()>| Msg::HandleJoinBucketResponse(r) , &self.link);
-//            }
-//            HandleJoinBucketResponse(_response) => {
-//                //                if let BucketPage::BucketList(ref mut bucket_lists) = self.bucket_page {
-//                //                    bucket_lists
-//                //                }
-//                //TODO this used to be a "noop" but it should probably do something
-//            }
+            ChangeDropDownState(drop_down_state) => {
+                if self.drop_down_state == drop_down_state {
+                    self.drop_down_state = DropDownPaneVariant::Closed // close the drop down pane if the current one is already selected
+                } else {
+                    self.drop_down_state = drop_down_state
+                }
+            }
+            RequestToJoinBucket { bucket_uuid } => {
+                self.request_to_join_bucket(bucket_uuid)
+            }
+            HandleJoinBucketResponse(response) => {
+                self.join_request = Loadable::from_fetch_response(response);
+                // If the bucket let the user in straight away, reload it so its questions can be fetched.
+                if let Loadable::Loaded(true) = self.join_request {
+                    if let BucketPage::Bucket(Loadable::Loaded(ref bucket_data)) = self.bucket_page {
+                        Self::get_bucket(bucket_data.uuid, &mut self.networking, &self.link);
+                    }
+                }
+            }
+            HandleGetPublicBucketsResponse(response) => {
+                if let BucketPage::BucketFinder(ref mut find_bucket) = self.bucket_page {
+                    find_bucket.public_buckets = Loadable::from_fetch_response(response);
+                }
+            }
             NoOp => {}
         }
         true
@@ -350,12 +353,13 @@ impl Component for BucketModel {
     fn change(&mut self, props: Self::Properties) -> ShouldRender {
         let bucket_page: BucketPage = match props {
             BucketRoute::BucketList => {
-//                Self::get_public_buckets(&mut self.networking, &self.link);
+                Self::get_public_buckets(&mut self.networking, &self.link);
 //                Self::get_approved_buckets(&mut self.networking, &self.link);
 //                BucketPage::BucketList(BucketLists::default())
                 BucketPage::BucketFinder(BucketFinder::default())
             }
             BucketRoute::Bucket { bucket_uuid } => {
+                self.join_request = Loadable::default();
                 Self::get_bucket(bucket_uuid, &mut self.networking, &self.link);
                 BucketPage::Bucket(Loadable::default())
             }
@@ -396,13 +400,13 @@ impl Renderable<BucketModel> for BucketModel {
             DropDownPaneVariant::Closed => ::util::wrappers::empty_vdom_node(),
             DropDownPaneVariant::ManageBuckets => {
                 html! {
-//                <BucketManagement: />
+                <BucketManagement: />
             }
             }
             DropDownPaneVariant::ViewParticipants => {
                 if let Bucket(ref bucket) = self.bucket_page {
                     html! {
-//                        <BucketParticipants: bucket_data=bucket,/>
+                        <BucketParticipants: bucket_data=bucket,/>
                     }
                 } else {
                     ::util::wrappers::empty_vdom_node()
@@ -422,7 +426,7 @@ impl Renderable<BucketModel> for BucketModel {
                         <RouterButton: text="Create Bucket", route=route!("bucket/create"), />
                     </div>
                     <div style="position: relative",>
-//                        <Button: title="Manage", onclick=|_| Msg::ChangeDropDownState(DropDownPaneVariant::ManageBuckets), />
+                        <Button: title="Manage", onclick=|_| Msg::ChangeDropDownState(DropDownPaneVariant::ManageBuckets), />
                         {pane}
                     </div>
                 </div>
@@ -441,9 +445,9 @@ impl Renderable<BucketModel> for BucketModel {
                     }
                     </div>
                     <div style="position: relative",>
-
-//                        <Button: title="Manage", onclick=|_| Msg::ChangeDropDownState(DropDownPaneVariant::ManageBuckets), />
-//                        <Button: title="Participants", onclick=|_| Msg::ChangeDropDownState(DropDownPaneVariant::ViewParticipants), />
+                        {self.join_button_view()}
+                        <Button: title="Manage", onclick=|_| Msg::ChangeDropDownState(DropDownPaneVariant::ManageBuckets), />
+                        <Button: title="Participants", onclick=|_| Msg::ChangeDropDownState(DropDownPaneVariant::ViewParticipants), />
                         {pane}
                     </div>
                 </div>
@@ -474,3 +478,27 @@ impl Renderable<BucketModel> for BucketModel {
 
     }
 }
+
+impl BucketModel {
+    /// Lets users who aren't participating in the bucket ask its owners to let them in.
+    fn join_button_view(&self) -> Html<BucketModel> {
+        let bucket_uuid: BucketUuid = match self.bucket_page {
+            BucketPage::Bucket(Loadable::Loaded(ref bucket_data)) => bucket_data.uuid,
+            _ => return ::util::wrappers::empty_vdom_node(),
+        };
+        match self.join_request {
+            Loadable::Loading => html! {
+                <span>{"Requesting..."}</span>
+            },
+            Loadable::Loaded(true) => html! {
+                <span>{"Joined"}</span>
+            },
+            Loadable::Loaded(false) => html! {
+                <span>{"Join request sent"}</span>
+            },
+            _ => html! {
+                <Button: title="Request to Join", onclick=move |_| Msg::RequestToJoinBucket{bucket_uuid}, />
+            },
+        }
+    }
+}
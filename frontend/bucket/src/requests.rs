@@ -4,8 +4,10 @@ use common::fetch::HttpMethod;
 use common::fetch::to_body;
 
 use identifiers::bucket::BucketUuid;
+use identifiers::bucket_invite::BucketInviteUuid;
 use identifiers::user::UserUuid;
 use identifiers::question::QuestionUuid;
+use identifiers::answer::AnswerUuid;
 use wire::bucket::*;
 use wire::question::*;
 use wire::answer::*;
@@ -13,7 +15,7 @@ use wire::answer::*;
 
 #[derive(Serialize, Deserialize)]
 pub enum BucketRequest {
-//    GetPublicBuckets,
+    GetPublicBuckets,
 //    GetBucketsForUser,
     GetBucket{bucket_uuid: BucketUuid},
     GetBucketByName{bucket_name: String},
@@ -22,69 +24,146 @@ pub enum BucketRequest {
     GetRandomQuestion { bucket_uuid: BucketUuid },
     GetQuestions { bucket_uuid: BucketUuid},
     AnswerQuestion(NewAnswerRequest),
+    EditAnswer{answer_uuid: AnswerUuid, request: EditAnswerRequest},
+    DeleteAnswer{answer_uuid: AnswerUuid},
+    GetAnswersByUser{user_uuid: UserUuid},
     CreateQuestion(NewQuestionRequest),
+    GetDuplicateQuestions{bucket_uuid: BucketUuid},
+    MergeQuestions(MergeQuestionsRequest),
     DeleteQuestion{question_uuid: QuestionUuid},
     PutQuestionBackInBucket{question_uuid: QuestionUuid},
-//    SetBucketPublicStatus{bucket_uuid: BucketUuid, is_public: bool},
-//    ApproveUserForBucket {bucket_uuid: BucketUuid, user_uuid: UserUuid},
-//    RemoveUserFromBucket {bucket_uuid: BucketUuid, user_uuid: UserUuid},
-//    GetUnapprovedUsersForOwnedBuckets,
-//    GetUsersInBucket{bucket_uuid: BucketUuid},
-//    GetIsUserOwnerOfBucket{bucket_uuid: BucketUuid},
-//    CreateJoinBucketRequest {bucket_uuid: BucketUuid},
-    GetNumberOfQuestionsInBucket {bucket_uuid: BucketUuid}
+    SetBucketPublicStatus{bucket_uuid: BucketUuid, public_for_minutes: Option<i64>},
+    SetBucketTemplateStatus{bucket_uuid: BucketUuid, is_template: bool},
+    GetBucketTemplates,
+    CloneBucket{bucket_uuid: BucketUuid, request: CloneBucketRequest},
+    CreateInvite{bucket_uuid: BucketUuid, request: NewInviteRequest},
+    GetInvites{bucket_uuid: BucketUuid},
+    RevokeInvite{invite_uuid: BucketInviteUuid},
+    RedeemInvite{token: String},
+    ApproveUserForBucket {bucket_uuid: BucketUuid, user_uuid: UserUuid},
+    RemoveUserFromBucket {bucket_uuid: BucketUuid, user_uuid: UserUuid},
+    GetUnapprovedUsersForOwnedBuckets,
+    GetUsersInBucket{bucket_uuid: BucketUuid},
+    GetBucketStats{bucket_uuid: BucketUuid},
+    GetIsUserOwnerOfBucket{bucket_uuid: BucketUuid},
+    CreateJoinBucketRequest {bucket_uuid: BucketUuid},
+    GetNumberOfQuestionsInBucket {bucket_uuid: BucketUuid},
+    GetSession {bucket_uuid: BucketUuid},
+    /// Only used to build the url for the session's EventSource, which can't send an Authorization header.
+    GetSessionEvents {bucket_uuid: BucketUuid, token: String},
+    DrawQuestion {bucket_uuid: BucketUuid},
+    DiscardQuestion {bucket_uuid: BucketUuid},
+    ChooseAnswerer {bucket_uuid: BucketUuid, user_uuid: UserUuid},
+    SetTurnSettings {bucket_uuid: BucketUuid, settings: TurnSettingsRequest},
+    SetDrawMode {bucket_uuid: BucketUuid, draw_mode: DrawMode},
+    GetDrawHistory {bucket_uuid: BucketUuid},
+    ImportQuestions {bucket_uuid: BucketUuid, request: ImportQuestionsRequest},
+    /// Responds with the contents of the file, which should be fetched as a string.
+    ExportQuestions {bucket_uuid: BucketUuid, format: QuestionFileFormat}
 }
 
 impl FetchRequest for BucketRequest {
     fn resolve_path(&self) -> String {
         use self::BucketRequest::*;
         match *self {
-//            GetPublicBuckets => "buckets/public".into(),
+            GetPublicBuckets => "bucket/public".into(),
 //            GetBucketsForUser => "buckets/approved".into(),
-            GetBucket{bucket_uuid} => format!("buckets/{}", bucket_uuid),
-            GetBucketByName {ref bucket_name} => format!("buckets/{}", bucket_name),
-            GetBucketsUserOwns => format!("buckets/owned"),
-            CreateBucket(_) => "buckets".into(),
+            GetBucket{bucket_uuid} => format!("bucket/{}", bucket_uuid),
+            GetBucketByName {ref bucket_name} => format!("bucket/{}", bucket_name),
+            GetBucketsUserOwns => format!("bucket/owned"),
+            CreateBucket(_) => "bucket".into(),
             GetRandomQuestion { bucket_uuid } => format!("question/random_question?bucket_uuid={}", bucket_uuid),
             GetQuestions { bucket_uuid } => format!("question?bucket_uuid={}", bucket_uuid),
-            AnswerQuestion(_) => "answer/create".into(),
-            CreateQuestion(_) => "question/create".into(),
+            AnswerQuestion(_) => "answer".into(),
+            EditAnswer {answer_uuid, ..} => format!("answer/{}", answer_uuid),
+            DeleteAnswer {answer_uuid} => format!("answer/{}", answer_uuid),
+            GetAnswersByUser {user_uuid} => format!("answer/user/{}", user_uuid),
+            CreateQuestion(_) => "question".into(),
+            GetDuplicateQuestions {bucket_uuid} => format!("question/duplicates/{}", bucket_uuid),
+            MergeQuestions(_) => "question/merge".into(),
             DeleteQuestion {question_uuid} => format!("question/{}", question_uuid),
             PutQuestionBackInBucket {question_uuid} => format!("question/{}/into_bucket", question_uuid),
-//            SetBucketPublicStatus {bucket_uuid, is_public} => format!("buckets/{}/publicity?is_public={}", bucket_uuid, is_public),
-//            ApproveUserForBucket {bucket_uuid, user_uuid} => format!("buckets/{}/approval?user_id={}",bucket_uuid, user_uuid),
-//            RemoveUserFromBucket {bucket_uuid, user_uuid} => format!("buckets/{}?user_id={}",bucket_uuid, user_uuid),
-//            GetUnapprovedUsersForOwnedBuckets => "buckets/unapproved_users_for_owned_buckets".into(),
-//            GetUsersInBucket {bucket_uuid} => format!("buckets/{}/users", bucket_uuid),
-//            GetIsUserOwnerOfBucket {bucket_uuid}  => format!{"buckets/{}/user_owner_status", bucket_uuid},
-//            CreateJoinBucketRequest {bucket_uuid} => format!{"buckets/{}/user_join_request", bucket_uuid},
-            GetNumberOfQuestionsInBucket {bucket_uuid} => format!("/api/question/quantity_in_bucket?bucket_uuid={}", bucket_uuid)
+            SetBucketPublicStatus {bucket_uuid, ..} => format!("bucket/publicity/{}", bucket_uuid),
+            SetBucketTemplateStatus {bucket_uuid, ..} => format!("bucket/template/{}", bucket_uuid),
+            GetBucketTemplates => "bucket/templates".into(),
+            CloneBucket {bucket_uuid, ..} => format!("bucket/clone/{}", bucket_uuid),
+            CreateInvite {bucket_uuid, ..} => format!("bucket_invite/{}", bucket_uuid),
+            GetInvites {bucket_uuid} => format!("bucket_invite/{}", bucket_uuid),
+            RevokeInvite {invite_uuid} => format!("bucket_invite/{}", invite_uuid),
+            RedeemInvite {token} => format!("bucket_invite/redeem/{}", token),
+            ApproveUserForBucket {bucket_uuid, user_uuid} => format!("bucket/approve/{}?user_uuid={}", bucket_uuid, user_uuid),
+            RemoveUserFromBucket {bucket_uuid, user_uuid} => format!("bucket/users/{}?user_uuid={}", bucket_uuid, user_uuid),
+            GetUnapprovedUsersForOwnedBuckets => "bucket/join_requests".into(),
+            GetUsersInBucket {bucket_uuid} => format!("bucket/users/{}", bucket_uuid),
+            GetBucketStats {bucket_uuid} => format!("bucket/stats/{}", bucket_uuid),
+            GetIsUserOwnerOfBucket {bucket_uuid}  => format!("bucket/owner_status/{}", bucket_uuid),
+            CreateJoinBucketRequest {bucket_uuid} => format!("bucket/join_request/{}", bucket_uuid),
+            GetNumberOfQuestionsInBucket {bucket_uuid} => format!("/api/question/quantity_in_bucket?bucket_uuid={}", bucket_uuid),
+            GetSession {bucket_uuid} => format!("bucket_session/{}", bucket_uuid),
+            GetSessionEvents {bucket_uuid, ref token} => format!("bucket_session/events/{}?token={}", bucket_uuid, token),
+            DrawQuestion {bucket_uuid} => format!("bucket_session/draw/{}", bucket_uuid),
+            DiscardQuestion {bucket_uuid} => format!("bucket_session/discard/{}", bucket_uuid),
+            ChooseAnswerer {bucket_uuid, user_uuid} => format!("bucket_session/hot_seat/{}?user_uuid={}", bucket_uuid, user_uuid),
+            SetTurnSettings {bucket_uuid, ..} => format!("bucket_session/turns/{}", bucket_uuid),
+            SetDrawMode {bucket_uuid, ..} => format!("bucket_session/draw_mode/{}", bucket_uuid),
+            GetDrawHistory {bucket_uuid} => format!("bucket_session/draws/{}", bucket_uuid),
+            ImportQuestions {bucket_uuid, ..} => format!("question/import/{}", bucket_uuid),
+            ExportQuestions {bucket_uuid, format} => {
+                let format = match format {
+                    QuestionFileFormat::Json => "json",
+                    QuestionFileFormat::Csv => "csv"
+                };
+                format!("question/export/{}?format={}", bucket_uuid, format)
+            }
         }
     }
     fn resolve_auth(&self) -> Auth {
         use self::BucketRequest::*;
         use self::Auth::*;
         match *self {
-//            GetPublicBuckets => Required,
+            GetPublicBuckets => Required,
 //            GetBucketsForUser => Required,
             GetBucket{..} => NotRequired,
             GetBucketByName {..} => NotRequired,
             GetBucketsUserOwns => Required,
             CreateBucket(_) => Required,
-            GetRandomQuestion {..} => NotRequired,
-            GetQuestions {..} => NotRequired,
+            GetRandomQuestion {..} => Required,
+            GetQuestions {..} => Required,
             AnswerQuestion(_) => Required,
+            EditAnswer {..} => Required,
+            DeleteAnswer {..} => Required,
+            GetAnswersByUser {..} => Required,
             CreateQuestion(_) => Required,
+            GetDuplicateQuestions {..} => Required,
+            MergeQuestions(_) => Required,
             DeleteQuestion {..} => Required,
             PutQuestionBackInBucket {..} => Required,
-//            SetBucketPublicStatus {..} => Required,
-//            ApproveUserForBucket {..} => Required,
-//            RemoveUserFromBucket {..} => Required,
-//            GetUnapprovedUsersForOwnedBuckets => Required,
-//            GetUsersInBucket {..} => Required,
-//            GetIsUserOwnerOfBucket {..} => Required,
-//            CreateJoinBucketRequest {..} => Required,
-            GetNumberOfQuestionsInBucket {..} => Required
+            SetBucketPublicStatus {..} => Required,
+            SetBucketTemplateStatus {..} => Required,
+            GetBucketTemplates => Required,
+            CloneBucket {..} => Required,
+            CreateInvite {..} => Required,
+            GetInvites {..} => Required,
+            RevokeInvite {..} => Required,
+            RedeemInvite {..} => Required,
+            ApproveUserForBucket {..} => Required,
+            RemoveUserFromBucket {..} => Required,
+            GetUnapprovedUsersForOwnedBuckets => Required,
+            GetUsersInBucket {..} => Required,
+            GetBucketStats {..} => Required,
+            GetIsUserOwnerOfBucket {..} => Required,
+            CreateJoinBucketRequest {..} => Required,
+            GetNumberOfQuestionsInBucket {..} => Required,
+            GetSession {..} => Required,
+            GetSessionEvents {..} => NotRequired,
+            DrawQuestion {..} => Required,
+            DiscardQuestion {..} => Required,
+            ChooseAnswerer {..} => Required,
+            SetTurnSettings {..} => Required,
+            SetDrawMode {..} => Required,
+            GetDrawHistory {..} => Required,
+            ImportQuestions {..} => Required,
+            ExportQuestions {..} => Required
         }
     }
     fn resolve_body_and_method(&self) -> HttpMethod {
@@ -93,7 +172,7 @@ impl FetchRequest for BucketRequest {
 
         let empty: String = "".to_string();
         match self {
-//            GetPublicBuckets => Get,
+            GetPublicBuckets => Get,
 //            GetBucketsForUser => Get,
             GetBucket {..} => Get,
             GetBucketByName {..} => Get,
@@ -102,17 +181,40 @@ impl FetchRequest for BucketRequest {
             GetRandomQuestion {..} => Get,
             GetQuestions {..} => Get,
             AnswerQuestion(r) => Post(to_body(r)),
+            EditAnswer {request, ..} => Put(to_body(request)),
+            DeleteAnswer {..} => Delete,
+            GetAnswersByUser {..} => Get,
             CreateQuestion(r) => Post(to_body(r)),
+            GetDuplicateQuestions {..} => Get,
+            MergeQuestions(r) => Post(to_body(r)),
             DeleteQuestion {..} => Delete,
             PutQuestionBackInBucket {..} => Put(empty), // no body
-//            SetBucketPublicStatus {..} => Put(empty),
-//            ApproveUserForBucket {..} => Put(empty),
-//            RemoveUserFromBucket {..} => Delete,
-//            GetUnapprovedUsersForOwnedBuckets => Get,
-//            GetUsersInBucket {..} => Get,
-//            GetIsUserOwnerOfBucket {..} => Get,
-//            CreateJoinBucketRequest {..} => Post(empty),
-            GetNumberOfQuestionsInBucket {..} => Get
+            SetBucketPublicStatus {public_for_minutes, ..} => Put(to_body(&BucketPublicityRequest{public_for_minutes: *public_for_minutes})),
+            SetBucketTemplateStatus {is_template, ..} => Put(to_body(&BucketTemplateRequest{is_template: *is_template})),
+            GetBucketTemplates => Get,
+            CloneBucket {request, ..} => Post(to_body(request)),
+            CreateInvite {request, ..} => Post(to_body(request)),
+            GetInvites {..} => Get,
+            RevokeInvite {..} => Delete,
+            RedeemInvite {..} => Post(empty),
+            ApproveUserForBucket {..} => Put(empty),
+            RemoveUserFromBucket {..} => Delete,
+            GetUnapprovedUsersForOwnedBuckets => Get,
+            GetUsersInBucket {..} => Get,
+            GetBucketStats {..} => Get,
+            GetIsUserOwnerOfBucket {..} => Get,
+            CreateJoinBucketRequest {..} => Post(empty),
+            GetNumberOfQuestionsInBucket {..} => Get,
+            GetSession {..} => Get,
+            GetSessionEvents {..} => Get,
+            DrawQuestion {..} => Post(empty),
+            DiscardQuestion {..} => Post(empty),
+            ChooseAnswerer {..} => Put(empty),
+            SetTurnSettings {settings, ..} => Put(to_body(settings)),
+            SetDrawMode {draw_mode, ..} => Put(to_body(&DrawModeRequest{draw_mode: *draw_mode})),
+            GetDrawHistory {..} => Get,
+            ImportQuestions {request, ..} => Post(to_body(request)),
+            ExportQuestions {..} => Get
         }
     }
 }
@@ -0,0 +1,45 @@
+use stdweb::Value;
+use yew::callback::Callback;
+
+/// Listens to the server sent events describing the state of a bucket's session.
+pub struct SessionEventsService {}
+
+/// Closes the EventSource when dropped.
+pub struct SessionEventsTask(Option<Value>);
+
+impl SessionEventsService {
+    pub fn new() -> SessionEventsService {
+        SessionEventsService {}
+    }
+
+    /// Opens an EventSource at the url, emitting the data of every message it receives.
+    pub fn connect(&mut self, url: &str, callback: Callback<String>) -> SessionEventsTask {
+        let callback = move |data: String| {
+            callback.emit(data);
+        };
+        let handle = js! {
+            var callback = @{callback};
+            var source = new EventSource(@{url});
+            source.onmessage = function(event) {
+                callback(event.data);
+            };
+            return {
+                source: source,
+                callback: callback,
+            };
+        };
+        SessionEventsTask(Some(handle))
+    }
+}
+
+impl Drop for SessionEventsTask {
+    fn drop(&mut self) {
+        let handle = self.0.take().expect("Session events task already empty.");
+        js! {
+            @(no_return)
+            var handle = @{handle};
+            handle.source.close();
+            handle.callback.drop();
+        }
+    }
+}
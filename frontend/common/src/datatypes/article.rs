@@ -0,0 +1,109 @@
+use wire::article::ArticlePreviewResponse;
+use wire::article::FullArticleResponse;
+use wire::article::MinimalArticleResponse;
+use wire::article::NewArticleRequest;
+use wire::article::UpdateArticleRequest;
+use datatypes::user::UserData;
+use chrono::NaiveDateTime;
+use identifiers::article::ArticleUuid;
+use identifiers::thread::ThreadUuid;
+use identifiers::user::UserUuid;
+
+
+/// An article without its body, used when listing many articles.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArticlePreviewData {
+    pub uuid: ArticleUuid,
+    pub author: UserData,
+    pub title: String,
+    pub slug: String,
+    pub publish_date: Option<NaiveDateTime>,
+    pub tags: Vec<String>,
+}
+
+impl From<ArticlePreviewResponse> for ArticlePreviewData {
+    fn from(response: ArticlePreviewResponse) -> Self {
+        ArticlePreviewData {
+            uuid: response.uuid,
+            author: UserData::from(response.author),
+            title: response.title,
+            slug: response.slug,
+            publish_date: response.publish_date,
+            tags: response.tags,
+        }
+    }
+}
+
+/// An article as seen by its author, without the author attached.
+/// Used for listing drafts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MinimalArticleData {
+    pub uuid: ArticleUuid,
+    pub title: String,
+    pub body: String,
+    pub publish_date: Option<NaiveDateTime>,
+}
+
+impl From<MinimalArticleResponse> for MinimalArticleData {
+    fn from(response: MinimalArticleResponse) -> Self {
+        MinimalArticleData {
+            uuid: response.uuid,
+            title: response.title,
+            body: response.body,
+            publish_date: response.publish_date,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FullArticleData {
+    pub uuid: ArticleUuid,
+    pub author: UserData,
+    pub title: String,
+    pub slug: String,
+    pub body: String,
+    pub publish_date: Option<NaiveDateTime>,
+    pub tags: Vec<String>,
+    pub comment_thread_uuid: Option<ThreadUuid>,
+    pub comment_count: i64,
+}
+
+impl From<FullArticleResponse> for FullArticleData {
+    fn from(response: FullArticleResponse) -> Self {
+        FullArticleData {
+            uuid: response.id,
+            author: UserData::from(response.author),
+            title: response.title,
+            slug: response.slug,
+            body: response.body,
+            publish_date: response.publish_date,
+            tags: response.tags,
+            comment_thread_uuid: response.comment_thread_uuid,
+            comment_count: response.comment_count,
+        }
+    }
+}
+
+#[derive(Default, Debug, PartialEq, Clone)]
+pub struct NewArticleData {
+    pub title: String,
+    pub body: String,
+}
+
+impl NewArticleData {
+    pub fn attach_info(&self, user_uuid: UserUuid) -> NewArticleRequest {
+        NewArticleRequest {
+            title: self.title.clone(),
+            body: self.body.clone(),
+            author_id: user_uuid,
+        }
+    }
+
+    pub fn to_update_request(&self, article_uuid: ArticleUuid) -> UpdateArticleRequest {
+        UpdateArticleRequest {
+            uuid: article_uuid,
+            title: Some(self.title.clone()),
+            body: Some(self.body.clone()),
+        }
+    }
+}
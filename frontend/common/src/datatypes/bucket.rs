@@ -2,12 +2,15 @@ use wire::bucket::*;
 use datatypes::user::UserData;
 //use util::input::InputState;
 use identifiers::bucket::BucketUuid;
+use chrono::NaiveDateTime;
 
 #[derive(Clone, Debug, PartialEq, Default)]
 pub struct BucketData {
     pub uuid: BucketUuid,
     pub bucket_name: String,
-    pub is_public: bool
+    pub is_public: bool,
+    pub public_until: Option<NaiveDateTime>,
+    pub is_template: bool,
 }
 
 impl From<BucketResponse> for BucketData {
@@ -15,7 +18,9 @@ impl From<BucketResponse> for BucketData {
         BucketData {
             uuid: response.uuid,
             bucket_name: response.bucket_name,
-            is_public: response.is_public
+            is_public: response.is_public,
+            public_until: response.public_until,
+            is_template: response.is_template,
         }
     }
 }
@@ -3,6 +3,7 @@ pub mod thread;
 pub mod user;
 pub mod post;
 pub mod bucket;
+pub mod article;
 
 pub mod answer;
 pub mod question;
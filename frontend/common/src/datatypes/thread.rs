@@ -3,6 +3,7 @@
 use wire::thread::MinimalThreadResponse;
 use wire::thread::NewThreadRequest;
 use wire::thread::ThreadResponse;
+use wire::poll::PollResponse;
 use datatypes::user::UserData;
 use datatypes::post::PostData;
 use chrono::NaiveDateTime;
@@ -59,6 +60,7 @@ impl NewThreadData {
             author_uuid: user_uuid,
             title: self.title.clone(),
             post_content: self.post_content.clone(),
+            poll: None,
         }
     }
 }
@@ -72,6 +74,7 @@ pub struct ThreadData {
     pub posts: PostData,
     pub created_date: NaiveDateTime,
     pub locked: bool,
+    pub poll: Option<PollResponse>,
 }
 
 impl Default for ThreadData {
@@ -83,7 +86,8 @@ impl Default for ThreadData {
             author: UserData::default(),
             posts: PostData::default(),
             created_date: NaiveDateTime::from_timestamp(0,0),
-            locked: bool::default()
+            locked: bool::default(),
+            poll: None
         }
     }
 }
@@ -97,7 +101,8 @@ impl From<ThreadResponse> for ThreadData {
             author: UserData::from(response.author),
             posts: PostData::from(response.posts),
             created_date: response.created_date,
-            locked: response.locked
+            locked: response.locked,
+            poll: response.poll
         }
     }
 }
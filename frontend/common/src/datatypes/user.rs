@@ -7,6 +7,7 @@ pub struct UserData {
     pub user_name: String,
     pub display_name: String,
     pub uuid: UserUuid,
+    pub reputation: i32,
 }
 
 impl From<UserResponse> for UserData {
@@ -15,6 +16,7 @@ impl From<UserResponse> for UserData {
             user_name: response.user_name,
             display_name: response.display_name,
             uuid: response.uuid,
+            reputation: response.reputation,
         }
     }
 }
@@ -13,8 +13,9 @@ use serde_json;
 use yew_router::router_agent::{Router, RouterRequest};
 use yew_router::Route;
 use wire::user::Jwt;
+use wire::login::{RefreshTokenRequest, TokensResponse};
 use super::user;
-use chrono::{Duration};
+use std::cell::Cell;
 
 use yew::services::fetch::Request;
 use yew::services::storage::{StorageService, Area};
@@ -271,41 +272,73 @@ impl <T, W> Agent for FetchAgent<T, W>
 }
 
 #[derive(Serialize, Deserialize)]
-/// Simple reauth request
-struct Reauth;
+/// Exchanges the refresh token for new tokens.
+struct Refresh(RefreshTokenRequest);
 
-impl FetchRequest for Reauth {
+impl FetchRequest for Refresh {
     fn resolve_path(&self) -> String {
-        "/auth/reauth".to_string()
+        "/auth/refresh".to_string()
     }
 
     fn resolve_auth(&self) -> Auth {
-        Auth::Required
+        Auth::NotRequired
     }
 
     fn resolve_body_and_method(&self) -> HttpMethod {
-        HttpMethod::Get
+        HttpMethod::Post(to_body(&self.0))
     }
 }
 
-fn refresh_jwt(jwt_string: &str, fetch_service: &mut FetchService, fetch_task_collection: &mut Vec<FetchTask>) {
+#[derive(Serialize, Deserialize)]
+/// Ends the session the refresh token belongs to.
+struct Logout(RefreshTokenRequest);
+
+impl FetchRequest for Logout {
+    fn resolve_path(&self) -> String {
+        "/auth/logout".to_string()
+    }
+
+    fn resolve_auth(&self) -> Auth {
+        Auth::NotRequired
+    }
+
+    fn resolve_body_and_method(&self) -> HttpMethod {
+        HttpMethod::Post(to_body(&self.0))
+    }
+}
+
+thread_local! {
+    /// Refresh tokens only work once, so only one refresh can be in flight at a time.
+    static REFRESH_IN_PROGRESS: Cell<bool> = Cell::new(false);
+}
+
+fn refresh_jwt(fetch_service: &mut FetchService, fetch_task_collection: &mut Vec<FetchTask>) {
+    let mut storage_service = StorageService::new(Area::Local);
+    let refresh_token: String = match user::restore_refresh_token(&mut storage_service) {
+        Ok(refresh_token) => refresh_token,
+        Err(_) => return, // The JWT will expire, and the user will have to log in again.
+    };
+    if REFRESH_IN_PROGRESS.with(|in_progress| in_progress.replace(true)) {
+        return;
+    }
     info!("JWT is being refreshed, this will extend the length of login session's validity.");
 
-    let closure = move |response: Response<Result<String, Error>>| {
+    let closure = move |response: Response<Text>| {
+        REFRESH_IN_PROGRESS.with(|in_progress| in_progress.set(false));
         let (meta, data) = response.into_parts();
-        println!("META: {:?}, {:?}", meta, data);
-        if meta.status.is_success() {
-            info!("New JWT retrieved.");
-            let jwt = data;
-            if jwt.is_err() {
-                error!("New JWT encountered an error")
-            }
-            let mut storage_service = StorageService::new(Area::Local);
-            info!("Storing JWT");
-            storage_service.store("JWT", jwt);
+        let mut storage_service = StorageService::new(Area::Local);
+        let tokens: Option<TokensResponse> = if meta.status.is_success() {
+            data.ok().and_then(|data| serde_json::from_str(&data).ok())
+        } else {
+            None
+        };
+        if let Some(tokens) = tokens {
+            info!("Storing new JWT and refresh token");
+            user::store_jwt(&mut storage_service, tokens.jwt);
+            user::store_refresh_token(&mut storage_service, tokens.refresh_token);
         } else {
-            let mut storage_service = StorageService::new(Area::Local);
-            storage_service.remove("JWT");
+            error!("Could not refresh the JWT, logging out");
+            user::remove_jwt(&mut storage_service);
             let callback = Callback::from(|_| ());// NOOP
             let mut router = Router::new(callback);
             let route = Route::parse("/auth/login");
@@ -314,11 +347,11 @@ fn refresh_jwt(jwt_string: &str, fetch_service: &mut FetchService, fetch_task_co
     };
     let callback = Callback::from(closure);
 
-    let url = Reauth.resolve_url();
-    let request = Request::get(url.as_str())
+    let request = Refresh(RefreshTokenRequest { refresh_token });
+    let url = request.resolve_url();
+    let request = Request::post(url.as_str())
         .header("Content-Type", "application/json")
-        .header("Authorization", format!("{} {}", BEARER, jwt_string).as_str())
-        .body(Nothing)
+        .body(Ok::<String, Error>(to_body(&request.0)))
         .unwrap();
 
     // This is never culled, as the FetchTasks themselves are so small,
@@ -327,19 +360,27 @@ fn refresh_jwt(jwt_string: &str, fetch_service: &mut FetchService, fetch_task_co
     fetch_task_collection.push(fetch_service.fetch(request, callback));
 }
 
-/// If a specific amount of time has elapsed since the jwt has been issued, then refresh the jwt.
+/// Ends the session on the server, so its refresh token can't be used again.
+/// The response is ignored, because the user is logged out locally either way.
+pub fn end_session(refresh_token: String, fetch_service: &mut FetchService) -> FetchTask {
+    let request = Logout(RefreshTokenRequest { refresh_token });
+    let url = request.resolve_url();
+    let request = Request::post(url.as_str())
+        .header("Content-Type", "application/json")
+        .body(Ok::<String, Error>(to_body(&request.0)))
+        .unwrap();
+    fetch_service.fetch(request, Callback::from(|_: Response<Text>| ()))
+}
+
+/// Once half of the jwt's lifetime has elapsed, refresh the jwt.
 fn refresh_jwt_if_needed(fetch_service: &mut FetchService, jwt_string: &str, fetch_task_collection: &mut Vec<FetchTask>)  {
-        // The stored jwt may be malformed 
-        // By sending a default jwt, it will fail the reauth, logging the user out. This path should never be needed, but would fail in a safe manner.
+        // The stored jwt may be malformed
+        // By using a default jwt, it will be refreshed immediately. If the refresh token is bad too, the user will be logged out.
         let jwt: Jwt = user::extract_payload_from_jwt(jwt_string).unwrap_or_default();
 
-
         let current_date = user::get_now();
-//        self.log(&format!("current: {:?}, iat: {:?}", current_date, jwt.iat) );
-        // If current time > iat + 1 day, then refresh.
-        if current_date > jwt.iat + Duration::days(1) {
-//            self.log("Refreshing JWT");
-            refresh_jwt(jwt_string, fetch_service, fetch_task_collection);
+        if current_date > jwt.iat + (jwt.exp - jwt.iat) / 2 {
+            refresh_jwt(fetch_service, fetch_task_collection);
         }
 }
 
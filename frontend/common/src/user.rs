@@ -1,6 +1,9 @@
 
 use yew::services::storage::{StorageService, Area};
 use yew::prelude::worker::*;
+use yew::services::FetchService;
+use yew::services::fetch::FetchTask;
+use fetch::end_session;
 
 use base64::decode_config as b64_dec;
 use serde_json::Value as JsonValue;
@@ -144,6 +147,16 @@ pub fn is_logged_in(storage_service: &mut StorageService) -> bool {
 /// Functionally logs the user out
 pub fn remove_jwt(storage_service: &mut StorageService) {
     storage_service.remove("JWT");
+    storage_service.remove("REFRESH_TOKEN");
+}
+
+pub fn store_refresh_token(storage_service: &mut StorageService, refresh_token: String) {
+    let refresh_token: Result<String, Error> = Ok(refresh_token);
+    storage_service.store("REFRESH_TOKEN", refresh_token)
+}
+
+pub fn restore_refresh_token(storage_service: &mut StorageService) -> Result<String, Error> {
+    storage_service.restore("REFRESH_TOKEN")
 }
 
 
@@ -152,7 +165,8 @@ pub fn remove_jwt(storage_service: &mut StorageService) {
 pub enum LoginRequest {
     Logout,
     Login {
-        jwt_string: String
+        jwt_string: String,
+        refresh_token: String
     },
     Query
 }
@@ -165,6 +179,9 @@ pub enum LoginResponse {
 
 pub struct LoginAgent {
     storage_service: StorageService,
+    /// Used to end the session on the server when logging out.
+    fetch_service: FetchService,
+    logout_task: Option<FetchTask>,
     subscribers: HashSet<HandlerId>,
     link: AgentLink<LoginAgent>
 }
@@ -183,6 +200,8 @@ impl Agent for LoginAgent
     fn create(link: AgentLink<Self>) -> Self {
         LoginAgent {
             storage_service: StorageService::new(Area::Local),
+            fetch_service: FetchService::new(),
+            logout_task: None,
             subscribers: HashSet::new(),
             link
         }
@@ -198,10 +217,11 @@ impl Agent for LoginAgent
 
     fn handle(&mut self, request: Self::Input, who: HandlerId) {
         match request {
-            LoginRequest::Login{jwt_string} => {
+            LoginRequest::Login{jwt_string, refresh_token} => {
                 if let Ok(jwt) = extract_payload_from_jwt(&jwt_string) {
                     // Only store the jwt if it is valid
                     store_jwt(&mut self.storage_service, jwt_string);
+                    store_refresh_token(&mut self.storage_service, refresh_token);
                     for sub in self.subscribers.iter().filter(|s| *s != &who) {
                         self.link.response(*sub, LoginResponse::LoggedIn(jwt.clone()));
                     }
@@ -211,6 +231,9 @@ impl Agent for LoginAgent
                 }
             }
             LoginRequest::Logout => {
+                if let Ok(refresh_token) = restore_refresh_token(&mut self.storage_service) {
+                    self.logout_task = Some(end_session(refresh_token, &mut self.fetch_service));
+                }
                 remove_jwt(&mut self.storage_service);
                 for sub in self.subscribers.iter().filter(|s| *s != &who) {
                     self.link.response(*sub, LoginResponse::LoggedOut);
@@ -280,7 +280,7 @@ impl Renderable<PostTree> for PostTree {
                     </div>
                     <div class=("post-info","flexbox-horiz"),>
                         <div>
-                            {"By "}{&self.post.author.display_name}
+                            {"By "}{&self.post.author.display_name}{format!(" ({})", self.post.author.reputation)}
                         </div>
                         <div>
                             {reply_button_fn(self)}
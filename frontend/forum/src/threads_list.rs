@@ -11,6 +11,7 @@ use identifiers::thread::ThreadUuid;
 use common::datatypes::thread::SelectableMinimalThreadData;
 use common::datatypes::thread::MinimalThreadData;
 use wire::thread::MinimalThreadResponse;
+use wire::Page;
 
 pub struct ThreadsList {
     threads: Loadable<Vec<SelectableMinimalThreadData>>,
@@ -59,9 +60,10 @@ impl Component for ThreadsList {
 
         threads_list.networking.fetch(
             &ForumRequest::GetThreads{forum_uuid, page_index},
-            |r: FetchResponse<Vec<MinimalThreadResponse>>| Msg::HandleGetThreadsResponse(r.map(
-                |x: Vec<MinimalThreadResponse>| {
-                    x.into_iter()
+            |r: FetchResponse<Page<MinimalThreadResponse>>| Msg::HandleGetThreadsResponse(r.map(
+                |x: Page<MinimalThreadResponse>| {
+                    x.items
+                        .into_iter()
                         .map(MinimalThreadData::from)
                         .collect()
                 }
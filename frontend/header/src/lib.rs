@@ -125,6 +125,9 @@ impl Renderable<Header> for Header {
                     <span>
                         <RouterLink: text="Forums", route=route!("forum"), />
                     </span>
+                    <span>
+                        <RouterLink: text="Articles", route=route!("article"), />
+                    </span>
                     <span>
                         <RouterLink: text="Bucket Questions", route=route!("bucket"), />
                     </span>
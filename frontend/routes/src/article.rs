@@ -0,0 +1,55 @@
+use identifiers::article::ArticleUuid;
+
+use routing::*;
+
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ArticleRoute {
+    ArticleList { page_index: usize },
+    Article { article_uuid: ArticleUuid },
+    Edit { article_uuid: ArticleUuid },
+    Create,
+}
+
+impl Default for ArticleRoute {
+    fn default() -> Self {
+        ArticleRoute::ArticleList { page_index: 1 }
+    }
+}
+
+impl Router for ArticleRoute {
+    fn to_route(&self) -> RouteInfo {
+        use self::ArticleRoute::*;
+        match *self {
+            ArticleList { page_index } => RouteInfo::parse(&format!("/page/{}", page_index)).unwrap(),
+            Article { article_uuid } => RouteInfo::parse(&format!("/{}", article_uuid)).unwrap(),
+            Edit { article_uuid } => RouteInfo::parse(&format!("/{}/edit", article_uuid)).unwrap(),
+            Create => RouteInfo::parse("/create").unwrap(),
+        }
+    }
+    fn from_route(route: &mut RouteInfo) -> Option<Self> {
+        use self::ArticleRoute::*;
+        if let Some(RouteSection::Node { segment }) = route.next() {
+            if let Ok(article_uuid) = ArticleUuid::parse_str(&segment) {
+                if let Some(RouteSection::Node { segment }) = route.next() {
+                    if segment == "edit" {
+                        return Some(Edit { article_uuid });
+                    }
+                }
+                Some(Article { article_uuid })
+            } else if segment == "create" {
+                Some(Create)
+            } else if segment == "page" {
+                let page_index = match route.next() {
+                    Some(RouteSection::Node { segment }) => segment.parse::<usize>().unwrap_or(1),
+                    _ => 1,
+                };
+                Some(ArticleList { page_index })
+            } else {
+                Some(ArticleList { page_index: 1 })
+            }
+        } else {
+            Some(ArticleList { page_index: 1 })
+        }
+    }
+}
@@ -7,6 +7,7 @@ extern crate identifiers;
 
 pub mod routing;
 
+pub mod article;
 pub mod auth;
 pub mod bucket;
 pub mod forum;
@@ -16,6 +17,7 @@ pub mod forum;
 //pub use bucket;
 //pub use forum;
 
+use article::ArticleRoute;
 use auth::AuthRoute;
 use bucket::BucketRoute;
 use forum::ForumRoute;
@@ -30,7 +32,7 @@ use routing::*;
 #[derive(Clone, PartialEq, Debug)]
 pub enum Route {
     Forums(ForumRoute),
-    //    ArticleView,
+    Articles(ArticleRoute),
     Auth(AuthRoute),
     Bucket(BucketRoute),
     PageNotFound,
@@ -41,6 +43,7 @@ impl Router for Route {
     fn to_route(&self) -> RouteInfo {
         match *self {
             Route::Forums(ref forum_list_route) => RouteInfo::parse("/forum").unwrap() + forum_list_route.to_route(),
+            Route::Articles(ref article_route) => RouteInfo::parse("/article").unwrap() + article_route.to_route(),
             Route::Auth(ref auth_route) => RouteInfo::parse("/auth").unwrap() + auth_route.to_route(),
             Route::Bucket(ref bucket_route) => RouteInfo::parse("/bucket").unwrap() + bucket_route.to_route(),
             Route::PageNotFound => {
@@ -65,6 +68,13 @@ impl MainRouter for Route {
                         Route::PageNotFound
                     }
                 }
+                "article" => {
+                    if let Some(child) = ArticleRoute::from_route(route) {
+                        Route::Articles(child)
+                    } else {
+                        Route::PageNotFound
+                    }
+                }
                 "auth" => {
                     if let Some(child) = AuthRoute::from_route(route) {
                         Route::Auth(child)
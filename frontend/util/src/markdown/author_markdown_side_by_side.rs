@@ -49,8 +49,11 @@ impl Component for AuthorMarkdownSideBySide {
         }
     }
 
-    fn change(&mut self, _props: Self::Properties) -> ShouldRender {
-        false
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        // The parent may load the text after this has been created, as is the case when editing existing content.
+        self.text = props.text;
+        self.callback = props.callback;
+        true
     }
 }
 
@@ -0,0 +1,67 @@
+use uuid::{
+    Uuid,
+    ParseError
+};
+use std::{
+    fmt::{
+        Display,
+        Formatter,
+        Result as FormatResult
+    }
+};
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Default, Hash, Eq)]
+pub struct CategoryUuid(pub Uuid);
+
+impl CategoryUuid {
+    pub fn to_query_parameter(self) -> String {
+        format!("{}={}", PARAM_NAME, self.0)
+    }
+    pub fn parse_str(input: &str) -> Result<Self, ParseError> {
+        Uuid::parse_str(input).map(CategoryUuid)
+    }
+}
+
+const PARAM_NAME: &str = "category_uuid";
+
+impl Display for CategoryUuid {
+    fn fmt(&self, f: &mut Formatter) -> FormatResult {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<Uuid> for CategoryUuid {
+    fn from(uuid: Uuid) -> CategoryUuid {
+        CategoryUuid(uuid)
+    }
+}
+
+#[cfg(feature = "rocket_support")]
+mod rocket {
+    use super::*;
+    use ::rocket::http::RawStr;
+    use ::rocket::request::FromParam;
+    use crate::uuid_from_param;
+    use crate::uuid_from_form;
+    use ::rocket::request::{FromForm, FormItems};
+
+    impl<'a> FromParam<'a> for CategoryUuid {
+        type Error = &'a RawStr;
+
+        #[inline]
+        fn from_param(param: &'a RawStr) -> Result<Self, Self::Error> {
+            uuid_from_param(param).map(CategoryUuid)
+        }
+    }
+
+
+    impl<'f> FromForm<'f> for CategoryUuid {
+        type Error = ();
+
+        #[inline]
+        fn from_form(items: &mut FormItems<'f>, strict: bool) -> Result<Self, ()> {
+            uuid_from_form(items, strict, PARAM_NAME)
+                .map(CategoryUuid)
+        }
+    }
+}
@@ -12,15 +12,21 @@ extern crate uuid;
 extern crate serde_derive;
 
 pub mod user;
+pub mod user_session;
+pub mod password_reset;
 pub mod article;
+pub mod category;
 pub mod forum;
 pub mod post;
 pub mod thread;
 pub mod bucket;
+pub mod bucket_invite;
 pub mod question;
 pub mod answer;
 pub mod chat;
 pub mod message;
+pub mod poll;
+pub mod poll_option;
 
 
 
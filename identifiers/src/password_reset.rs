@@ -0,0 +1,33 @@
+use std::{
+    fmt::{
+        Display,
+        Formatter,
+        Result as FormatResult
+    }
+};
+use uuid::{
+    Uuid,
+    ParseError
+};
+
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Default, Hash, Eq)]
+pub struct PasswordResetUuid(pub Uuid);
+
+impl PasswordResetUuid {
+    pub fn parse_str(input: &str) -> Result<Self, ParseError> {
+        Uuid::parse_str(input).map(PasswordResetUuid)
+    }
+}
+
+impl Display for PasswordResetUuid {
+    fn fmt(&self, f: &mut Formatter) -> FormatResult {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<Uuid> for PasswordResetUuid {
+    fn from(uuid: Uuid) -> PasswordResetUuid {
+        PasswordResetUuid(uuid)
+    }
+}
@@ -1,6 +1,7 @@
 use crate::user::UserResponse;
 use identifiers::{
     answer::AnswerUuid,
+    bucket::BucketUuid,
     question::QuestionUuid
 };
 
@@ -17,3 +18,18 @@ pub struct NewAnswerRequest {
     pub question_uuid: QuestionUuid,
     pub answer_text: Option<String>,
 }
+
+/// Replaces the text of an answer. Only the answer's author can do this.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct EditAnswerRequest {
+    pub answer_text: Option<String>,
+}
+
+/// An answer given by a particular user, along with the question it answers.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct UserAnswerResponse {
+    pub answer: AnswerResponse,
+    pub question_uuid: QuestionUuid,
+    pub question_text: String,
+    pub bucket_uuid: BucketUuid,
+}
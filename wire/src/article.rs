@@ -2,6 +2,8 @@ use chrono::NaiveDateTime;
 use crate::user::UserResponse;
 use identifiers::{
     article::ArticleUuid,
+    category::CategoryUuid,
+    thread::ThreadUuid,
     user::UserUuid
 };
 
@@ -20,14 +22,22 @@ pub struct UpdateArticleRequest {
     pub body: Option<String>,
 }
 
+/// Schedules an article to be published at a future time.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct SchedulePublishRequest {
+    pub publish_date: NaiveDateTime,
+}
+
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct MinimalArticleResponse {
     pub uuid: ArticleUuid,
     pub author_uuid: UserUuid,
     pub title: String,
+    pub slug: String,
     pub body: String,
     pub publish_date: Option<NaiveDateTime>,
+    pub category_uuid: Option<CategoryUuid>,
 }
 
 /// Doesn't have the body attached.
@@ -37,7 +47,10 @@ pub struct ArticlePreviewResponse {
     pub uuid: ArticleUuid,
     pub author: UserResponse,
     pub title: String,
+    pub slug: String,
     pub publish_date: Option<NaiveDateTime>,
+    pub tags: Vec<String>,
+    pub category_uuid: Option<CategoryUuid>,
 }
 
 /// All relevant information is attached.
@@ -46,6 +59,65 @@ pub struct FullArticleResponse {
     pub id: ArticleUuid,
     pub author: UserResponse,
     pub title: String,
+    pub slug: String,
     pub body: String,
     pub publish_date: Option<NaiveDateTime>,
+    pub tags: Vec<String>,
+    pub category_uuid: Option<CategoryUuid>,
+    /// The forum thread where readers discuss the article.
+    pub comment_thread_uuid: Option<ThreadUuid>,
+    pub comment_count: i64,
+}
+
+/// Replaces the tags attached to an article.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct SetArticleTagsRequest {
+    pub tags: Vec<String>,
+}
+
+/// Files an article under a category, or removes it from its category if None.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct SetArticleCategoryRequest {
+    pub category_uuid: Option<CategoryUuid>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct NewCategoryRequest {
+    pub name: String,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct CategoryResponse {
+    pub uuid: CategoryUuid,
+    pub name: String,
+}
+
+/// A snapshot of an article's content, taken every time it is created or updated.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ArticleRevisionResponse {
+    pub article_uuid: ArticleUuid,
+    pub revision_number: i32,
+    pub editor: UserResponse,
+    pub title: String,
+    pub body: String,
+    pub created_date: NaiveDateTime,
+}
+
+/// A single line in the difference between two revisions of an article body.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub enum DiffLine {
+    Unchanged(String),
+    Added(String),
+    Removed(String),
+}
+
+/// The line-based difference between two revisions of an article.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ArticleDiffResponse {
+    pub article_uuid: ArticleUuid,
+    pub from_revision: i32,
+    pub to_revision: i32,
+    pub from_title: String,
+    pub to_title: String,
+    pub lines: Vec<DiffLine>,
 }
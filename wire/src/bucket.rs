@@ -1,12 +1,32 @@
-use crate::user::UserResponse;
-use identifiers::bucket::BucketUuid;
+use crate::{
+    question::QuestionResponse,
+    user::UserResponse,
+};
+use chrono::NaiveDateTime;
+use identifiers::{
+    bucket::BucketUuid,
+    bucket_invite::BucketInviteUuid,
+    question::QuestionUuid,
+};
+
+/// The longest a bucket can be opened to the public for at once.
+pub const MAX_PUBLIC_MINUTES: i64 = 7 * 24 * 60;
+/// The longest a timed turn can last.
+pub const MAX_TURN_SECONDS: i32 = 60 * 60;
+/// The longest an invite link can last before it expires.
+pub const MAX_INVITE_MINUTES: i64 = 30 * 24 * 60;
 
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct BucketResponse {
     pub uuid: BucketUuid,
     pub bucket_name: String,
+    /// While the bucket is public, anyone can find it and join it without being approved.
     pub is_public: bool,
+    /// When the bucket stops being public. None if the bucket is private.
+    pub public_until: Option<NaiveDateTime>,
+    /// Templates can be browsed and cloned by anyone.
+    pub is_template: bool,
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -21,3 +41,195 @@ pub struct BucketUsersResponse {
     pub bucket: BucketResponse,
     pub users: Vec<UserResponse>,
 }
+
+/// Opens the bucket to the public for the given number of minutes, or closes it if None.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct BucketPublicityRequest {
+    pub public_for_minutes: Option<i64>,
+}
+
+/// Publishes the bucket as a template, or withdraws it.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct BucketTemplateRequest {
+    pub is_template: bool,
+}
+
+/// Creates a new bucket with the same questions as an existing one, owned by whoever cloned it.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct CloneBucketRequest {
+    pub bucket_name: String,
+    /// Copies the answers to each question as well. Only owners of the original bucket can do this.
+    #[serde(default)]
+    pub include_answers: bool,
+}
+
+/// Creates a link that adds whoever follows it to the bucket, without the owners having to approve them.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct NewInviteRequest {
+    /// The invite can be used any number of times if None.
+    pub max_uses: Option<i32>,
+    /// The invite never expires if None.
+    pub expires_in_minutes: Option<i64>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct InviteLinkResponse {
+    pub uuid: BucketInviteUuid,
+    pub bucket_uuid: BucketUuid,
+    /// The token that is redeemed to join the bucket.
+    /// It is only given when the invite is created, because only a hash of it is kept.
+    pub token: Option<String>,
+    pub max_uses: Option<i32>,
+    pub uses: i32,
+    pub expires_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+/// The state of the game being played in a bucket, shared by all of its participants.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct BucketSessionResponse {
+    pub bucket_uuid: BucketUuid,
+    /// The question every participant is looking at. None until a question is drawn.
+    pub current_question: Option<QuestionResponse>,
+    /// The number of questions left to draw.
+    pub questions_in_bucket: i64,
+    pub turn_mode: TurnMode,
+    /// How long each turn lasts, if turns are timed.
+    pub turn_seconds: Option<i32>,
+    /// The participant whose turn it is to draw. None when the session is free for all.
+    pub turn_user: Option<UserResponse>,
+    /// In hot seat mode, the participant the drawer chose to answer the current question.
+    pub hot_seat_user: Option<UserResponse>,
+    /// If turns are timed, the current turn is skipped if it hasn't finished by this time.
+    pub turn_ends_at: Option<NaiveDateTime>,
+    pub draw_mode: DrawMode,
+}
+
+/// How questions are drawn from a bucket.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+pub enum DrawMode {
+    /// Questions that haven't been drawn recently, or that have few answers, are more likely to be drawn.
+    Fair,
+    /// Every question in the bucket is drawn once, in a random order, before any are drawn again.
+    ShuffleDeck,
+}
+
+impl Default for DrawMode {
+    fn default() -> Self {
+        DrawMode::Fair
+    }
+}
+
+impl From<DrawMode> for i32 {
+    fn from(draw_mode: DrawMode) -> i32 {
+        match draw_mode {
+            DrawMode::Fair => 0,
+            DrawMode::ShuffleDeck => 1,
+        }
+    }
+}
+
+impl From<i32> for DrawMode {
+    fn from(number: i32) -> DrawMode {
+        match number {
+            0 => DrawMode::Fair,
+            1 => DrawMode::ShuffleDeck,
+            _ => {
+                panic!("Tried to convert an unsupported number into a draw mode");
+            }
+        }
+    }
+}
+
+/// Changes how questions are drawn from the bucket.
+/// Choosing the shuffle deck mode always starts a new deck.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct DrawModeRequest {
+    pub draw_mode: DrawMode,
+}
+
+/// How the participants of a bucket's session take turns.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+pub enum TurnMode {
+    /// Anyone can draw, discard, or answer questions at any time.
+    FreeForAll,
+    /// Participants take turns drawing and answering questions, in order of their user names.
+    RoundRobin,
+    /// Participants take turns drawing questions, and the drawer chooses who answers each one.
+    HotSeat,
+}
+
+impl Default for TurnMode {
+    fn default() -> Self {
+        TurnMode::FreeForAll
+    }
+}
+
+impl From<TurnMode> for i32 {
+    fn from(turn_mode: TurnMode) -> i32 {
+        match turn_mode {
+            TurnMode::FreeForAll => 0,
+            TurnMode::RoundRobin => 1,
+            TurnMode::HotSeat => 2,
+        }
+    }
+}
+
+impl From<i32> for TurnMode {
+    fn from(number: i32) -> TurnMode {
+        match number {
+            0 => TurnMode::FreeForAll,
+            1 => TurnMode::RoundRobin,
+            2 => TurnMode::HotSeat,
+            _ => {
+                panic!("Tried to convert an unsupported number into a turn mode");
+            }
+        }
+    }
+}
+
+/// Changes how the participants of a bucket's session take turns, starting the turns over.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct TurnSettingsRequest {
+    pub turn_mode: TurnMode,
+    /// Limits each turn to this many seconds. Turns are untimed if None, and this is ignored when the session is free for all.
+    pub turn_seconds: Option<i32>,
+}
+
+/// Statistics about how a bucket's questions have been used.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct BucketStatsResponse {
+    pub bucket: BucketResponse,
+    pub total_questions: i64,
+    pub questions_on_floor: i64,
+    pub questions_in_bucket: i64,
+    /// Participants who have answered questions, most answers first.
+    pub participants: Vec<ParticipantStatsResponse>,
+    /// Answers given anonymously are counted here, and not attributed to any participant.
+    pub anonymous_answers: i64,
+    pub most_favorited: Vec<FavoritedQuestionResponse>,
+    /// The bucket's past sessions, oldest first.
+    pub sessions: Vec<SessionSummaryResponse>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ParticipantStatsResponse {
+    pub user: UserResponse,
+    pub answers: i64,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct FavoritedQuestionResponse {
+    pub question_uuid: QuestionUuid,
+    pub question_text: String,
+    pub favorites: i64,
+}
+
+/// A stretch of activity in a bucket, which ended when no questions were drawn or answered for a while.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct SessionSummaryResponse {
+    pub started_at: NaiveDateTime,
+    pub ended_at: NaiveDateTime,
+    pub draws: i64,
+    pub answers: i64,
+}
@@ -22,6 +22,11 @@ pub mod answer;
 pub mod chat;
 pub mod message;
 pub mod login;
+pub mod reputation;
+pub mod poll;
+pub mod page;
+
+pub use crate::page::Page;
 
 
 
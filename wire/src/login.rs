@@ -1,4 +1,7 @@
 use crate::user::*;
+use chrono::NaiveDateTime;
+use identifiers::user_session::UserSessionUuid;
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct LoginRequest {
     pub user_name: String,
@@ -11,3 +14,46 @@ pub struct LoginResponse {
     pub jwt: String,
     pub user: UserResponse,
 }
+
+/// The tokens handed out when logging in, or when a refresh token is used.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TokensResponse {
+    /// A short lived JWT, to be included in the Authorization header.
+    pub jwt: String,
+    /// Used once to get new tokens when the JWT expires.
+    pub refresh_token: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
+/// A device or browser the user is logged in on.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UserSessionResponse {
+    pub uuid: UserSessionUuid,
+    pub user_agent: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub last_used_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+/// Asks for a password reset token to be mailed to the user.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ForgotPasswordRequest {
+    pub user_name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResetPasswordRequest {
+    /// The token from the password reset email.
+    pub reset_token: String,
+    pub new_password: String,
+}
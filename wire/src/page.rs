@@ -0,0 +1,15 @@
+/// The largest page that clients can request.
+/// Requests for larger pages are served pages of this size instead.
+pub const MAX_PAGE_SIZE: i32 = 100;
+
+/// A single page of a paginated listing, along with the totals across every page.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// The index of this page, starting at 1.
+    pub page: i32,
+    /// The maximum number of items on each page.
+    pub page_size: i32,
+    pub total_items: i64,
+    pub total_pages: i64,
+}
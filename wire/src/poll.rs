@@ -0,0 +1,46 @@
+use chrono::NaiveDateTime;
+use identifiers::{
+    poll::PollUuid,
+    poll_option::PollOptionUuid,
+};
+
+/// Attached to a NewThreadRequest when the thread should open with a poll.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NewPollRequest {
+    pub question: String,
+    /// The text of each option, in the order they should be displayed.
+    pub options: Vec<String>,
+    /// If false, users may only select a single option.
+    pub multiple_choice: bool,
+    /// If set, no more votes will be accepted after this time.
+    pub closes_at: Option<NaiveDateTime>,
+    /// If true, tallies are withheld until the poll closes.
+    pub hide_results_until_closed: bool,
+}
+
+/// Casts or replaces the user's vote on a poll.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PollVoteRequest {
+    pub poll_uuid: PollUuid,
+    pub option_uuids: Vec<PollOptionUuid>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PollOptionResponse {
+    pub uuid: PollOptionUuid,
+    pub content: String,
+    /// None if the results are hidden until the poll closes.
+    pub votes: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PollResponse {
+    pub uuid: PollUuid,
+    pub question: String,
+    pub multiple_choice: bool,
+    pub closes_at: Option<NaiveDateTime>,
+    pub closed: bool,
+    pub options: Vec<PollOptionResponse>,
+    /// The options the requesting user has voted for.
+    pub user_votes: Vec<PollOptionUuid>,
+}
@@ -2,6 +2,7 @@ use crate::{
     user::UserResponse,
     answer::AnswerResponse
 };
+use chrono::NaiveDateTime;
 use identifiers::{
     question::QuestionUuid,
     bucket::BucketUuid
@@ -18,8 +19,104 @@ pub struct QuestionResponse {
     pub on_floor: bool,
 }
 
+/// A question having been drawn in a bucket's session.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct QuestionDrawResponse {
+    pub question_uuid: QuestionUuid,
+    pub question_text: String,
+    pub drawn_at: NaiveDateTime,
+}
+
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct NewQuestionRequest {
     pub bucket_uuid: BucketUuid,
     pub question_text: String,
+    /// Adds the question even if the bucket already has questions similar to it.
+    #[serde(default)]
+    pub ignore_similar: bool,
+}
+
+/// The result of asking to add a question to a bucket.
+///
+/// If the bucket already has similar questions, the question isn't added unless the request said to ignore them.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct NewQuestionResponse {
+    /// The question that was added, if it was.
+    pub question: Option<QuestionResponse>,
+    pub similar_questions: Vec<SimilarQuestionResponse>,
+}
+
+/// A question that is likely a duplicate of another.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct SimilarQuestionResponse {
+    pub question_uuid: QuestionUuid,
+    pub question_text: String,
+    /// How alike the questions are, from 0 to 1.
+    pub similarity: f64,
+}
+
+/// A group of questions in a bucket that are likely duplicates of each other.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct DuplicateClusterResponse {
+    pub questions: Vec<QuestionResponse>,
+}
+
+/// Merges questions into another question in the same bucket.
+/// Their answers and favorites are moved to the kept question, and then they are deleted.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct MergeQuestionsRequest {
+    pub keep: QuestionUuid,
+    pub merge: Vec<QuestionUuid>,
+}
+
+/// The file formats that questions can be imported from and exported to.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+pub enum QuestionFileFormat {
+    Json,
+    /// A header row naming the columns is required.
+    /// Files have `question_text`, `answer_text` and `author` columns, with a row for every answer,
+    /// and the answer columns are optional when importing.
+    /// Consecutive rows with the same question are read as one question with several answers.
+    Csv,
+}
+
+/// A question as it appears in an imported or exported file.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct QuestionFileEntry {
+    pub question_text: String,
+    /// Imported answers are anonymous, because the authors are only named.
+    #[serde(default)]
+    pub answers: Vec<AnswerFileEntry>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct AnswerFileEntry {
+    pub answer_text: Option<String>,
+    /// The display name of the author, which is absent for anonymous answers.
+    pub author: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ImportQuestionsRequest {
+    pub format: QuestionFileFormat,
+    /// The contents of the uploaded file.
+    pub contents: String,
+}
+
+/// A row of an imported file that didn't become a question.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct SkippedQuestionRow {
+    /// The position of the row among the questions in the file, starting at 1.
+    /// Questions that span several CSV rows are reported at their first row.
+    pub row: usize,
+    pub reason: String,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ImportQuestionsResponse {
+    pub imported: Vec<QuestionResponse>,
+    /// Rows with a question that is already in the bucket, or that appeared earlier in the file.
+    pub duplicates: Vec<SkippedQuestionRow>,
+    /// Rows that couldn't be read or failed validation.
+    pub errors: Vec<SkippedQuestionRow>,
 }
@@ -0,0 +1,40 @@
+/// Actions that can be gated behind a minimum reputation.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ReputationAction {
+    CreateThread,
+    CreatePost,
+}
+
+impl From<ReputationAction> for i32 {
+    fn from(action: ReputationAction) -> i32 {
+        match action {
+            ReputationAction::CreateThread => 1,
+            ReputationAction::CreatePost => 2,
+        }
+    }
+}
+
+impl From<i32> for ReputationAction {
+    fn from(number: i32) -> ReputationAction {
+        match number {
+            1 => ReputationAction::CreateThread,
+            2 => ReputationAction::CreatePost,
+            _ => {
+                panic!("Tried to convert an unsupported number into a reputation action");
+            }
+        }
+    }
+}
+
+/// Sets the minimum reputation a user needs before they can perform the action.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReputationThresholdRequest {
+    pub action: ReputationAction,
+    pub minimum_reputation: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReputationThresholdResponse {
+    pub action: ReputationAction,
+    pub minimum_reputation: i32,
+}
@@ -1,6 +1,10 @@
 use crate::{
     user::UserResponse,
-    post::PostResponse
+    post::PostResponse,
+    poll::{
+        NewPollRequest,
+        PollResponse
+    }
 };
 use chrono::NaiveDateTime;
 use identifiers::{
@@ -17,6 +21,7 @@ pub struct NewThreadRequest {
     pub author_uuid: UserUuid, // TODO, this should be removed, JWT provides this anyway
     pub title: String,
     pub post_content: String,
+    pub poll: Option<NewPollRequest>,
 }
 
 /// Used when viewing an individual thread.
@@ -29,6 +34,7 @@ pub struct ThreadResponse {
     pub posts: PostResponse,
     pub created_date: NaiveDateTime,
     pub locked: bool,
+    pub poll: Option<PollResponse>,
 }
 
 /// Used when returning a list of threads for perusing
@@ -7,6 +7,8 @@ pub struct UserResponse {
     pub user_name: String,
     pub display_name: String,
     pub uuid: UserUuid,
+    /// Earned from votes on the user's posts.
+    pub reputation: i32,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -14,6 +16,7 @@ pub struct FullUserResponse {
     pub user_name: String,
     pub display_name: String,
     pub uuid: UserUuid,
+    pub reputation: i32,
     pub locked: bool,
     pub banned: bool,
     // pub roles: UserRoleResponse
@@ -33,12 +36,38 @@ pub struct UpdateDisplayNameRequest {
     pub new_display_name: String,
 }
 
+/// Sets the email that password reset tokens are sent to, or removes it if None.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UpdateEmailRequest {
+    pub email: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct UserRoleRequest {
     pub uuid: UserUuid,
     pub user_role: i32,
 }
 
+/// Bans the user, either permanently or for a number of minutes.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BanUserRequest {
+    /// Shown to the user whenever they try to use the site.
+    pub reason: String,
+    /// The ban is permanent if None.
+    pub banned_for_minutes: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UserBanResponse {
+    pub user_uuid: UserUuid,
+    /// The admin who issued the ban, if their account still exists.
+    pub admin_uuid: Option<UserUuid>,
+    pub reason: String,
+    pub created_at: NaiveDateTime,
+    /// The ban is permanent if None.
+    pub expires_at: Option<NaiveDateTime>,
+}
+
 /// This is the word that should proceeded the JWT when attaching it to the Authorization header.
 pub const BEARER: &str = "Bearer";
 